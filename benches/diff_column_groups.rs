@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dms_cdc_operator::dataframe::dataframe_diff::DataframeDiffer;
+use dms_cdc_operator::dataframe::value_comparator::ValueComparator;
+use polars::prelude::*;
+
+/// Creates a DataFrame with an `id` column and the given number of integer columns,
+/// where every 7th column of the changed row differs.
+fn wide_dataframe(columns: usize, rows: i64, changed_row: Option<i64>) -> DataFrame {
+    let mut series = vec![Series::new("id", (0..rows).collect::<Vec<_>>())];
+    for column in 0..columns {
+        let values = (0..rows)
+            .map(|row| {
+                if Some(row) == changed_row && column % 7 == 0 {
+                    -1
+                } else {
+                    row * column as i64
+                }
+            })
+            .collect::<Vec<_>>();
+        series.push(Series::new(&format!("column_{column}"), values));
+    }
+    DataFrame::new(series).unwrap()
+}
+
+fn bench_diff_column_groups(c: &mut Criterion) {
+    let source = wide_dataframe(200, 20_000, None);
+    let target = wide_dataframe(200, 20_000, Some(42));
+    let primary_key = vec!["id".to_string()];
+
+    let mut group = c.benchmark_group("diff_200_columns_20k_rows");
+    group.sample_size(10);
+    for column_group_size in [None, Some(16)] {
+        let differ = DataframeDiffer::new(ValueComparator::new(), column_group_size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{column_group_size:?}")),
+            &differ,
+            |b, differ| b.iter(|| differ.diff(&source, &target, &primary_key).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_diff_column_groups);
+criterion_main!(benches);
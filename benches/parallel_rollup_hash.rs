@@ -0,0 +1,25 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dms_cdc_operator::dataframe::rollup_hash::{parallel_rollup_hash, rollup_hash};
+use polars::prelude::*;
+
+fn bench_parallel_rollup_hash(c: &mut Criterion) {
+    let df = df!(
+        "id" => (0..1_000_000i64).collect::<Vec<_>>(),
+        "name" => (0..1_000_000).map(|id| format!("name_{id}")).collect::<Vec<_>>()
+    )
+    .unwrap();
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+
+    let mut group = c.benchmark_group("rollup_hash_1m_rows");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| b.iter(|| rollup_hash(&df).unwrap()));
+    group.bench_with_input(
+        BenchmarkId::new("parallel", threads),
+        &threads,
+        |b, &threads| b.iter(|| parallel_rollup_hash(&df, threads).unwrap()),
+    );
+    group.finish();
+}
+
+criterion_group!(benches, bench_parallel_rollup_hash);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use dms_cdc_operator::dataframe::dataframe_ops::{
+    read_parquet_from_file, RowCountMismatchSeverity,
+};
+use polars::prelude::*;
+
+const ROWS: i64 = 1_000_000;
+const ROW_GROUP_SIZE: usize = 100_000;
+
+/// Writes a Parquet file with the given number of rows, in row groups of `ROW_GROUP_SIZE` rows.
+fn write_parquet_file(rows: i64) -> tempfile::NamedTempFile {
+    let mut df = df!(
+        "id" => (0..rows).collect::<Vec<_>>(),
+        "name" => (0..rows).map(|i| format!("name {i}")).collect::<Vec<_>>()
+    )
+    .unwrap();
+    let file = tempfile::Builder::new()
+        .suffix(".parquet")
+        .tempfile()
+        .unwrap();
+    ParquetWriter::new(file.reopen().unwrap())
+        .with_row_group_size(Some(ROW_GROUP_SIZE))
+        .finish(&mut df)
+        .unwrap();
+    file
+}
+
+fn bench_read_parquet_from_file_chunk_sizes(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let file = write_parquet_file(ROWS);
+
+    let mut group = c.benchmark_group("read_parquet_from_file");
+    group.sample_size(10);
+    for chunk_size in [None, Some(10_000), Some(ROW_GROUP_SIZE), Some(1_000_000)] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{chunk_size:?}")),
+            &chunk_size,
+            |b, &chunk_size| {
+                b.iter(|| {
+                    let df = runtime
+                        .block_on(read_parquet_from_file(
+                            file.path(),
+                            "key",
+                            None,
+                            chunk_size,
+                            RowCountMismatchSeverity::Error,
+                        ))
+                        .unwrap();
+                    assert_eq!(df.height(), ROWS as usize);
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_parquet_from_file_chunk_sizes);
+criterion_main!(benches);
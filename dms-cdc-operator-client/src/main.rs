@@ -1,4 +1,7 @@
-use anyhow::{Ok, Result};
+use anyhow::{anyhow, Ok, Result};
+use aws_config::provider_config::ProviderConfig;
+use aws_config::web_identity_token::{StaticConfiguration, WebIdentityTokenCredentialsProvider};
+use aws_config::SdkConfig;
 use aws_sdk_s3::Client as S3Client;
 use colored::Colorize;
 
@@ -20,10 +23,19 @@ use dms_cdc_operator::{
 };
 use tracing::info;
 
+/// The help on the environment variables that select the credentials of the S3 client.
+#[cfg(feature = "with-clap")]
+const S3_CREDENTIALS_HELP: &str = "Environment variables:
+  S3_ROLE_ARN                 ARN of the role to assume through a web identity token, e.g. on EKS with IRSA
+  S3_WEB_IDENTITY_TOKEN_FILE  Path of the web identity token file
+When both are set, they take precedence over the whole default credentials chain of the AWS SDK.
+Otherwise, the default credentials chain is used.";
+
 #[cfg(feature = "with-clap")]
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(after_help = S3_CREDENTIALS_HELP)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -326,7 +338,13 @@ async fn main() -> Result<()> {
 
     // Create an S3 client
     info!("{}", "Creating S3 client".bold().green());
-    let client = create_s3_client().await;
+    let client = match S3Credentials::from_env() {
+        S3Credentials::WebIdentity {
+            role_arn,
+            token_file,
+        } => create_s3_client_with_web_identity(&role_arn, &token_file).await,
+        S3Credentials::DefaultChain => create_s3_client().await,
+    };
 
     let cdc_operator_snapshot_payload = CDCOperatorSnapshotPayload::new(
         cdc_operator_payload.bucket_name(),
@@ -344,13 +362,19 @@ async fn main() -> Result<()> {
 
     if !cdc_operator_payload.only_datadiff() {
         info!("{}", "Running snapshot...".bold().blue());
-        let _ = CDCOperator::snapshot(
+        let report = CDCOperator::snapshot(
             &cdc_operator_snapshot_payload,
             &postgres_operator,
             &target_postgres_operator,
             &client,
         )
         .await;
+
+        if !report.passed() {
+            postgres_operator.close_connection_pool().await;
+            target_postgres_operator.close_connection_pool().await;
+            return Err(anyhow!("Snapshot failed: {}", report.to_json()?));
+        }
     }
 
     if cdc_operator_payload.only_snapshot() {
@@ -380,7 +404,201 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Represents the credentials of the S3 client.
+///
+/// The credentials can be one of the following:
+///
+/// * WebIdentity - A role assumed through a web identity token, set with the `S3_ROLE_ARN` and
+///   `S3_WEB_IDENTITY_TOKEN_FILE` environment variables, which takes precedence when both are set.
+/// * DefaultChain - The default credentials chain of the AWS SDK, otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum S3Credentials {
+    WebIdentity {
+        role_arn: String,
+        token_file: String,
+    },
+    DefaultChain,
+}
+
+impl S3Credentials {
+    /// Selects the credentials of the S3 client from the environment variables.
+    fn from_env() -> Self {
+        Self::from_vars(
+            std::env::var("S3_ROLE_ARN").ok(),
+            std::env::var("S3_WEB_IDENTITY_TOKEN_FILE").ok(),
+        )
+    }
+
+    /// Selects the credentials of the S3 client from the values of the environment variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `role_arn` - The value of `S3_ROLE_ARN`, if set.
+    /// * `token_file` - The value of `S3_WEB_IDENTITY_TOKEN_FILE`, if set.
+    ///
+    /// # Returns
+    ///
+    /// The credentials of the S3 client.
+    fn from_vars(role_arn: Option<String>, token_file: Option<String>) -> Self {
+        match (role_arn, token_file) {
+            (Some(role_arn), Some(token_file)) => Self::WebIdentity {
+                role_arn,
+                token_file,
+            },
+            _ => Self::DefaultChain,
+        }
+    }
+}
+
 async fn create_s3_client() -> S3Client {
     let config = aws_config::load_from_env().await;
     S3Client::new(&config)
 }
+
+/// Creates an S3 client with the credentials of a role assumed through a web identity token,
+/// e.g. the projected service account token of IRSA, instead of the default credentials chain.
+///
+/// The default chain already uses the `AWS_ROLE_ARN` and `AWS_WEB_IDENTITY_TOKEN_FILE`
+/// environment variables, but only after the static credentials of the environment and the
+/// shared profile, and only for a single role per pod. The given role and token file take
+/// precedence over the whole default chain, while the region is still resolved by the
+/// default region chain.
+///
+/// # Arguments
+///
+/// * `role_arn` - The ARN of the role to assume.
+/// * `token_file` - The path of the web identity token file.
+///
+/// # Returns
+///
+/// The S3 client.
+async fn create_s3_client_with_web_identity(role_arn: &str, token_file: &str) -> S3Client {
+    S3Client::new(&web_identity_config(role_arn, token_file).await)
+}
+
+/// Loads the SDK config with the credentials of a role assumed through a web identity token.
+///
+/// # Arguments
+///
+/// * `role_arn` - The ARN of the role to assume.
+/// * `token_file` - The path of the web identity token file.
+///
+/// # Returns
+///
+/// The SDK config.
+async fn web_identity_config(role_arn: &str, token_file: &str) -> SdkConfig {
+    let provider_config = ProviderConfig::with_default_region().await;
+    let credentials_provider = WebIdentityTokenCredentialsProvider::builder()
+        .configure(&provider_config)
+        .static_configuration(StaticConfiguration {
+            web_identity_token_file: token_file.into(),
+            role_arn: role_arn.to_string(),
+            session_name: "dms-cdc-operator".to_string(),
+        })
+        .build();
+
+    aws_config::from_env()
+        .credentials_provider(credentials_provider)
+        .load()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::config::ProvideCredentials;
+    use std::error::Error;
+
+    /// Checks if an error was caused by a missing file, i.e. the web identity token file,
+    /// which is only read by the web identity provider before assuming the role.
+    fn is_caused_by_missing_file(error: &(dyn Error + 'static)) -> bool {
+        let mut source = Some(error);
+        while let Some(error) = source {
+            if error
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|error| error.kind() == std::io::ErrorKind::NotFound)
+            {
+                return true;
+            }
+            source = error.source();
+        }
+        false
+    }
+
+    fn missing_token_file() -> String {
+        std::env::temp_dir()
+            .join(format!("dms-cdc-operator-token-{}", std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_web_identity_config() {
+        let config = web_identity_config(
+            "arn:aws:iam::123456789012:role/dms-cdc-operator",
+            &missing_token_file(),
+        )
+        .await;
+
+        let credentials_provider = config.credentials_provider().unwrap();
+        assert!(format!("{credentials_provider:?}").contains("WebIdentityTokenCredentialsProvider"));
+
+        let error = credentials_provider
+            .provide_credentials()
+            .await
+            .unwrap_err();
+        assert!(is_caused_by_missing_file(&error));
+    }
+
+    #[tokio::test]
+    async fn test_create_s3_client_with_web_identity() {
+        // A region is required to build the request, before the credentials are resolved
+        std::env::set_var("AWS_REGION", "us-east-1");
+        let client = create_s3_client_with_web_identity(
+            "arn:aws:iam::123456789012:role/dms-cdc-operator",
+            &missing_token_file(),
+        )
+        .await;
+
+        let error = client.list_buckets().send().await.unwrap_err();
+        assert!(is_caused_by_missing_file(&error));
+    }
+
+    #[test]
+    fn test_s3_credentials_with_both_vars_set() {
+        let credentials = S3Credentials::from_vars(
+            Some("arn:aws:iam::123456789012:role/dms-cdc-operator".to_string()),
+            Some("/var/run/secrets/eks.amazonaws.com/serviceaccount/token".to_string()),
+        );
+
+        assert_eq!(
+            credentials,
+            S3Credentials::WebIdentity {
+                role_arn: "arn:aws:iam::123456789012:role/dms-cdc-operator".to_string(),
+                token_file: "/var/run/secrets/eks.amazonaws.com/serviceaccount/token".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_s3_credentials_without_both_vars_set() {
+        assert_eq!(
+            S3Credentials::from_vars(None, None),
+            S3Credentials::DefaultChain
+        );
+        assert_eq!(
+            S3Credentials::from_vars(
+                Some("arn:aws:iam::123456789012:role/dms-cdc-operator".to_string()),
+                None
+            ),
+            S3Credentials::DefaultChain
+        );
+        assert_eq!(
+            S3Credentials::from_vars(
+                None,
+                Some("/var/run/secrets/eks.amazonaws.com/serviceaccount/token".to_string())
+            ),
+            S3Credentials::DefaultChain
+        );
+    }
+}
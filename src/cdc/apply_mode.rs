@@ -0,0 +1,138 @@
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Represents the order in which the operations of the CDC files are applied.
+///
+/// The mode can be one of the following:
+///
+/// * InOrder - The operations are applied in the order of the files and of the rows in each file.
+/// * DeletesLast - The inserts and updates are applied in order, while the deletes are buffered
+///   and applied after all the files are processed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApplyMode {
+    #[default]
+    InOrder,
+    DeletesLast,
+}
+
+/// Splits the DataFrames of the CDC files into the rows to apply immediately
+/// and the rows to apply after all the files are processed, based on the apply mode.
+#[derive(Debug)]
+pub struct CDCApplyBuffer {
+    mode: ApplyMode,
+    deferred_deletes: Option<DataFrame>,
+}
+
+impl CDCApplyBuffer {
+    pub fn new(mode: ApplyMode) -> Self {
+        CDCApplyBuffer {
+            mode,
+            deferred_deletes: None,
+        }
+    }
+
+    /// Stages the DataFrame of a CDC file.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame of the CDC file, including the Op column.
+    ///
+    /// # Returns
+    ///
+    /// The rows of the DataFrame to apply immediately.
+    pub fn stage(&mut self, df: DataFrame) -> Result<DataFrame> {
+        match self.mode {
+            ApplyMode::InOrder => Ok(df),
+            ApplyMode::DeletesLast => {
+                let is_delete = df.column("Op")?.str()?.equal("D");
+                let deletes = df.filter(&is_delete)?;
+                let rest = df.filter(&!&is_delete)?;
+
+                if deletes.height() > 0 {
+                    match self.deferred_deletes.as_mut() {
+                        Some(deferred_deletes) => {
+                            deferred_deletes.vstack_mut(&deletes)?;
+                        }
+                        None => self.deferred_deletes = Some(deletes),
+                    }
+                }
+
+                Ok(rest)
+            }
+        }
+    }
+
+    /// Consumes the buffer.
+    ///
+    /// # Returns
+    ///
+    /// The deferred delete rows to apply after all the files are processed, if any.
+    pub fn finish(self) -> Option<DataFrame> {
+        self.deferred_deletes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Applies the rows of a DataFrame to a set of keys, the way an upsert does.
+    fn apply(keys: &mut HashSet<i64>, df: &DataFrame) {
+        let ops = df.column("Op").unwrap().str().unwrap();
+        let ids = df.column("id").unwrap().i64().unwrap();
+        for (op, id) in ops.into_iter().zip(ids) {
+            let id = id.unwrap();
+            if op == Some("D") {
+                keys.remove(&id);
+            } else {
+                keys.insert(id);
+            }
+        }
+    }
+
+    fn run(mode: ApplyMode) -> HashSet<i64> {
+        let files = vec![
+            df!("Op" => &["I", "I"], "id" => &[1i64, 2]).unwrap(),
+            df!("Op" => &["D"], "id" => &[1i64]).unwrap(),
+            df!("Op" => &["I"], "id" => &[1i64]).unwrap(),
+        ];
+
+        let mut keys = HashSet::new();
+        let mut buffer = CDCApplyBuffer::new(mode);
+        for file in files {
+            let df = buffer.stage(file).unwrap();
+            apply(&mut keys, &df);
+        }
+        if let Some(deferred_deletes) = buffer.finish() {
+            apply(&mut keys, &deferred_deletes);
+        }
+
+        keys
+    }
+
+    #[test]
+    fn test_insert_then_delete_of_same_key_in_order() {
+        assert_eq!(run(ApplyMode::InOrder), HashSet::from([1, 2]));
+    }
+
+    #[test]
+    fn test_insert_then_delete_of_same_key_deletes_last() {
+        assert_eq!(run(ApplyMode::DeletesLast), HashSet::from([2]));
+    }
+
+    #[test]
+    fn test_deletes_are_buffered_across_files() {
+        let mut buffer = CDCApplyBuffer::new(ApplyMode::DeletesLast);
+        let staged = buffer
+            .stage(df!("Op" => &["D", "U"], "id" => &[1i64, 2]).unwrap())
+            .unwrap();
+        assert_eq!(staged.height(), 1);
+        buffer
+            .stage(df!("Op" => &["D"], "id" => &[3i64]).unwrap())
+            .unwrap();
+
+        let deferred_deletes = buffer.finish().unwrap();
+        assert_eq!(deferred_deletes.height(), 2);
+    }
+}
@@ -1,58 +1,102 @@
+use anyhow::{anyhow, Result};
 use aws_sdk_s3::Client as S3Client;
 use colored::Colorize;
-use log::info;
+use indexmap::IndexMap;
+use log::{error, info, warn};
 use rust_pgdatadiff::diff::diff_ops::Differ;
 use rust_pgdatadiff::diff::diff_payload::DiffPayload;
 use std::env;
 use std::sync::Arc;
 use std::time::Instant;
+use tracing::Instrument;
 
+use super::apply_mode::CDCApplyBuffer;
+use super::file_pipeline::FilePipeline;
+use super::load_metrics::LoadMetrics;
+use super::operation_filter::SkippedOperations;
+use super::report_upload::{report_key, upload_report};
+use super::row_count_expectation::RowCountExpectation;
+use super::run_budget::RunBudget;
 use super::snapshot_payload::CDCOperatorSnapshotPayload;
 use super::validate_payload::CDCOperatorValidatePayload;
+use super::validation_report::{TableReport, TableStatus, ValidationReport};
 
-use crate::dataframe::dataframe_ops::{
-    CreateDataframePayload, DataframeOperator, DataframeOperatorImpl,
-};
+use crate::dataframe::clock_skew::ClockSkewDetector;
+use crate::dataframe::dataframe_ops::{DataframeOperator, DataframeOperatorImpl};
+use crate::dataframe::decimal::activate_decimals;
+use crate::dataframe::duplicate_sequence::DuplicateSequenceDetector;
+use crate::dataframe::generated_column::GeneratedColumnExpression;
+use crate::postgres::data_type_mapping::postgres_data_type;
+use crate::postgres::default_verification::verify_defaults;
 use crate::postgres::postgres_operator::{
     InsertDataframePayload, PostgresOperator, UpsertDataframePayload,
 };
-use crate::s3::s3_operator::{LoadParquetFilesPayload, S3Operator, S3OperatorImpl};
+use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+use crate::postgres::schema_diff::{decimal_scale_mismatches, SchemaDiff, SchemaStrictness};
+use crate::postgres::table_query::TableQuery;
+use crate::postgres::temp_table_session::{CheckConstraintViolation, TempTableSession};
+use crate::s3::s3_operator::{S3Operator, S3OperatorImpl};
+use crate::telemetry::operation_span;
 
 /// Represents a CDC Operator that validates the data between S3 and a target database.
 pub struct CDCOperator;
 
 impl CDCOperator {
     /// Takes a snpashot of the data stored in S3 and replicates them in a target database.
+    ///
+    /// If a maximum duration is set, no new files are processed once it is exceeded, and the
+    /// tables that were not fully processed are reported as timed out.
+    ///
+    /// # Returns
+    ///
+    /// The report of the run, tagged with its run id and labels.
     pub async fn snapshot(
         cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
         source_postgres_operator: &(impl PostgresOperator + Sync),
         target_postgres_operator: &(impl PostgresOperator + Sync),
         s3_client: &S3Client,
-    ) {
+    ) -> ValidationReport {
+        let run_context = cdc_operator_snapshot_payload.run_context.clone();
+        let run_budget = RunBudget::start(cdc_operator_snapshot_payload.max_duration);
+        info!("Starting run: {}", run_context);
+        if cdc_operator_snapshot_payload.preserve_decimals {
+            activate_decimals();
+        }
+
         info!("{}", "Creating schema in the target DB".bold().green());
-        let _ = target_postgres_operator
-            .create_schema(cdc_operator_snapshot_payload.schema_name().as_str())
-            .await;
+        cdc_operator_snapshot_payload
+            .on_missing_schema
+            .ensure_schema(
+                target_postgres_operator,
+                cdc_operator_snapshot_payload.schema_name().as_str(),
+            )
+            .await
+            .unwrap_or_else(|e| panic!("{:?}", e));
 
         // Check if only_datadiff is true
         info!("{}", "Starting snapshotting...".bold().blue());
 
         // Find the tables for snapshotting
-        let table_list = source_postgres_operator
-            .get_tables_in_schema(
-                cdc_operator_snapshot_payload.schema_name().as_str(),
-                cdc_operator_snapshot_payload.included_tables().as_slice(),
-                cdc_operator_snapshot_payload.excluded_tables().as_slice(),
-                &cdc_operator_snapshot_payload.table_mode(),
-            )
-            .await
-            .unwrap();
+        let table_list =
+            Self::tables_to_validate(cdc_operator_snapshot_payload, source_postgres_operator)
+                .await
+                .unwrap();
 
         let cdc_operator_snapshot_payload: Arc<&CDCOperatorSnapshotPayload> =
             Arc::new(cdc_operator_snapshot_payload);
         let client = s3_client.clone();
-        let s3_operator = Arc::new(S3OperatorImpl::new(&client));
-        let dataframe_operator = Arc::new(DataframeOperatorImpl::new(s3_client));
+        let requester_pays = cdc_operator_snapshot_payload.requester_pays;
+        let s3_operator = Arc::new(
+            S3OperatorImpl::new(&client)
+                .with_requester_pays(requester_pays)
+                .with_object_versions(cdc_operator_snapshot_payload.object_versions)
+                .with_start_after(cdc_operator_snapshot_payload.list_start_after),
+        );
+        let dataframe_operator = Arc::new(
+            DataframeOperatorImpl::new(s3_client)
+                .with_requester_pays(requester_pays)
+                .with_server_side_encryption(cdc_operator_snapshot_payload.server_side_encryption),
+        );
 
         let tables = table_list
             .iter()
@@ -60,10 +104,25 @@ impl CDCOperator {
                 let payload = Arc::clone(&cdc_operator_snapshot_payload);
                 let s3_operator = Arc::clone(&s3_operator);
                 let dataframe_operator = Arc::clone(&dataframe_operator);
+                let table_span = operation_span!(
+                    "snapshot_table",
+                    schema = payload.schema_name.as_str(),
+                    table = table_name.as_str()
+                );
 
                 async move {
                     let payload = Arc::clone(&payload);
 
+                    if run_budget.is_exhausted() {
+                        warn!("Maximum duration exceeded, skipping table {}", table_name);
+                        let table_report = TableReport::timed_out(table_name);
+                        payload
+                            .table_result_sender
+                            .send(payload.schema_name.as_str(), &table_report)
+                            .await;
+                        return table_report;
+                    }
+
                     let start = Instant::now();
                     info!(
                         "{}",
@@ -98,65 +157,78 @@ impl CDCOperator {
                         .unwrap();
                     info!("Primary key(s): {:?}", primary_key_list);
 
-                    // Create the table in the target database
-                    info!("{}", "Creating table in the target DB".bold().green());
-                    let _ = target_postgres_operator
-                        .create_table(
-                            &source_table_columns,
-                            primary_key_list.as_slice(),
-                            payload.schema_name.clone().as_str(),
-                            table_name,
-                        )
-                        .await;
+                    // The table name in the target DB may differ from the one in the S3 path
+                    let target_table_name = payload.target_table_name(table_name);
+
+                    // The table in the target DB is created before applying the first DataFrame,
+                    // so that it follows the schema of the transformed DataFrame if a transform is set
+                    let mut pipeline = FilePipeline::new(
+                        &payload,
+                        table_name,
+                        &source_table_columns,
+                        primary_key_list.as_slice(),
+                    );
+                    let mut table_created = false;
 
                     // Get the list of Parquet files from S3
                     info!("{}", "Getting list of Parquet files from S3".bold().green());
 
-                    // Check if mode is DateAware and start_date is not None
-                    if payload.mode_is_date_aware() && payload.start_date.is_none() {
-                        panic!("start_date is required for DateAware mode");
-                    }
-
-                    let load_parquet_files_payload
-                    = if payload.mode_is_date_aware(){
-                            LoadParquetFilesPayload::DateAware {
-                                bucket_name: payload.bucket_name.clone(),
-                                s3_prefix: payload.key.clone(),
-                                database_name: payload.database_name.clone(),
-                                schema_name: payload.schema_name.clone(),
-                                table_name: table_name.to_string(),
-                                start_date: payload.start_date.clone().unwrap(),
-                                stop_date: payload
-                                    .stop_date.clone(),
-                            }
-                        }
-                    else if payload.mode_is_full_load_only() {
-                        LoadParquetFilesPayload::FullLoadOnly {
-                            bucket_name: payload.bucket_name.clone(),
-                            s3_prefix: payload.key.clone(),
-                                database_name: payload.database_name.clone(),
-                                schema_name: payload.schema_name.clone(),
-                                table_name: table_name.to_string(),
-                        }
-                    } else {
-                        LoadParquetFilesPayload::AbsolutePath(payload.key.clone())
-                    };
+                    let load_parquet_files_payload = payload.load_parquet_files_payload(table_name);
 
                     let parquet_files = s3_operator
                         .get_list_of_parquet_files_from_s3(&load_parquet_files_payload)
-                        .await;
+                        .await
+                        .unwrap();
+                    payload
+                        .on_no_files
+                        .check(table_name, &parquet_files)
+                        .unwrap_or_else(|e| panic!("{:?}", e));
+
+                    let load_metrics = LoadMetrics::from_files(table_name, &parquet_files);
+                    info!(
+                        "[{}] Files to process: {}, oldest last modified: {:?}, newest last modified: {:?}",
+                        payload.run_context,
+                        load_metrics.files_processed,
+                        load_metrics.min_last_modified,
+                        load_metrics.max_last_modified
+                    );
 
                     // Read the Parquet files from S3
                     info!("{}", "Reading Parquet files from S3".bold().green());
 
-                    for file in &parquet_files.unwrap() {
-                        let create_dataframe_payload = CreateDataframePayload {
-                            bucket_name: payload.bucket_name.clone(),
-                            key: file.file_name.to_string(),
-                            database_name: payload.database_name.clone(),
-                            schema_name: payload.schema_name.clone(),
-                            table_name: table_name.clone(),
-                        };
+                    let primary_keys = primary_key_list.clone().as_slice().join(",");
+                    let upsert_dataframe_payload = UpsertDataframePayload {
+                        database_name: payload.database_name.clone(),
+                        schema_name: payload.schema_name.clone(),
+                        table_name: target_table_name.clone(),
+                        primary_key: primary_keys,
+                        conflict_behavior: payload.conflict_behavior,
+                        unmatched_delete_policy: payload.unmatched_delete_policy.clone(),
+                        incomplete_delete_policy: payload.on_incomplete_delete,
+                        delete_batch_size: payload.delete_batch_size,
+                        deferred_constraints: payload.deferred_constraints,
+                    };
+                    let mut apply_buffer = CDCApplyBuffer::new(payload.apply_mode);
+                    let mut files_processed = 0;
+                    let mut row_count_expectation = RowCountExpectation::default();
+                    let mut unapplied_updates = Vec::new();
+                    let mut skipped_operations = SkippedOperations::default();
+                    let mut foreign_rows = 0;
+                    let mut duplicate_sequence_detector = payload
+                        .detect_duplicate_sequences
+                        .then(|| DuplicateSequenceDetector::new(&primary_key_list, payload.sequence_column.as_str()));
+                    let mut clock_skew_detector = payload.detect_clock_skew.then(|| {
+                        ClockSkewDetector::new(
+                            &primary_key_list,
+                            payload.sequence_column.as_str(),
+                            chrono::Utc::now().timestamp_micros(),
+                        )
+                    });
+
+                    for file in run_budget.within(&parquet_files) {
+                        files_processed += 1;
+                        let create_dataframe_payload =
+                            payload.create_dataframe_payload(table_name, file);
 
                         let current_df = dataframe_operator
                             .create_dataframe_from_parquet_file(&create_dataframe_payload)
@@ -167,6 +239,64 @@ impl CDCOperator {
                             .unwrap()
                             .unwrap();
 
+                        let transformed_file = pipeline
+                            .transform(file, current_df)
+                            .unwrap_or_else(|e| panic!("Failed to transform file {:?}: {:?}", file, e));
+                        foreign_rows += transformed_file.foreign_rows;
+
+                        if !table_created {
+                            let table_columns = pipeline.resolve_table_columns(&transformed_file);
+                            Self::create_target_table(
+                                target_postgres_operator,
+                                table_columns,
+                                primary_key_list.as_slice(),
+                                payload.schema_name.as_str(),
+                                target_table_name.as_str(),
+                                payload.skip_create_table_if_schema_matches,
+                                payload.check_column_order,
+                                payload.schema_strictness,
+                                &payload.storage_parameters,
+                            )
+                            .await
+                            .unwrap_or_else(|e| panic!("Invalid schema of table {}: {:?}", target_table_name, e));
+                            table_created = true;
+
+                            let parquet_columns = transformed_file
+                                .df
+                                .get_columns()
+                                .iter()
+                                .map(|column| (column.name().to_string(), postgres_data_type(column.dtype())))
+                                .collect::<IndexMap<_, _>>();
+                            for mismatch in decimal_scale_mismatches(&parquet_columns, pipeline.table_columns()) {
+                                warn!(
+                                    "Column {} of table {} is {} in the Parquet files but {} in the target, so its values may be rounded",
+                                    mismatch.column, target_table_name, mismatch.parquet_data_type, mismatch.target_data_type
+                                );
+                            }
+                        }
+
+                        let prepared_file = pipeline
+                            .prepare(file, transformed_file.df)
+                            .unwrap_or_else(|e| panic!("Failed to prepare file {:?}: {:?}", file, e));
+                        for (column_name, data_type) in &prepared_file.added_columns {
+                            info!("Adding column {} {} to table {}", column_name, data_type, target_table_name);
+                            target_postgres_operator
+                                .add_column(
+                                    payload.schema_name.as_str(),
+                                    target_table_name.as_str(),
+                                    column_name.as_str(),
+                                    data_type.as_str(),
+                                )
+                                .await
+                                .unwrap_or_else(|e| panic!("Failed to add column {}: {:?}", column_name, e));
+                        }
+                        skipped_operations.add(&prepared_file.skipped_operations);
+                        let current_df = prepared_file.df;
+
+                        row_count_expectation
+                            .record(file, &current_df)
+                            .unwrap_or_else(|e| panic!("Failed to count operations of file {:?}: {:?}", file, e));
+
                         if file.is_load_file() {
                             info!("Processing LOAD file: {:?}", file);
                             // Check if the schema of the table is the same as the schema of the Parquet file
@@ -177,7 +307,7 @@ impl CDCOperator {
                                 .filter(|field| {
                                     field.name() != "Op" && field.name() != "_dms_ingestion_timestamp"
                                 })
-                                .any(|field| !source_table_columns.contains_key(field.name()));
+                                .any(|field| !pipeline.table_columns().contains_key(field.name()));
 
                             if has_schema_diff {
                                 panic!("Schema of table is not the same as the schema of the Parquet file");
@@ -186,35 +316,190 @@ impl CDCOperator {
                             let insert_dataframe_payload = InsertDataframePayload {
                                 database_name: payload.database_name.clone(),
                                 schema_name: payload.schema_name.clone(),
-                                table_name: table_name.clone(),
+                                table_name: target_table_name.clone(),
+                                insert_method: payload.insert_method,
+                                load_mode: payload.load_mode,
+                                copy_commit_rows: payload.copy_commit_rows,
                             };
 
                             target_postgres_operator
                                 .insert_dataframe_in_target_db(&current_df, &insert_dataframe_payload)
+                                .instrument(operation_span!(
+                                    "apply_file",
+                                    key = file.file_name.as_str(),
+                                    table = target_table_name.as_str(),
+                                    rows = current_df.height()
+                                ))
                                 .await
                                 .unwrap_or_else(|_| {
                                     panic!("Failed to insert LOAD file {:?} into table", file)
                                 })
                         } else {
                             info!("Processing CDC file: {:?}", file);
-                            let primary_keys = primary_key_list.clone().as_slice().join(",");
-
-                            let upsert_dataframe_payload = UpsertDataframePayload {
-                                database_name: payload.database_name.clone(),
-                                schema_name: payload.schema_name.clone(),
-                                table_name: table_name.clone(),
-                                primary_key: primary_keys.clone(),
-                            };
+                            if let Some(detector) = duplicate_sequence_detector.as_mut() {
+                                detector
+                                    .observe(&file.file_name, &current_df)
+                                    .unwrap_or_else(|e| panic!("Failed to check sequences of CDC file {:?}: {:?}", file, e));
+                            }
+                            if let Some(detector) = clock_skew_detector.as_mut() {
+                                detector
+                                    .observe(&file.file_name, &current_df)
+                                    .unwrap_or_else(|e| panic!("Failed to check timestamps of CDC file {:?}: {:?}", file, e));
+                            }
+                            let current_df = apply_buffer
+                                .stage(current_df)
+                                .unwrap_or_else(|e| panic!("Failed to stage CDC file {:?}: {:?}", file, e));
 
                             target_postgres_operator
                                 .upsert_dataframe_in_target_db(&current_df, &upsert_dataframe_payload)
+                                .instrument(operation_span!(
+                                    "apply_file",
+                                    key = file.file_name.as_str(),
+                                    table = target_table_name.as_str(),
+                                    rows = current_df.height()
+                                ))
                                 .await
                                 .unwrap_or_else(|_| {
                                     panic!("Failed to upsert CDC file {:?} into table", file)
-                                })
+                                });
+
+                            if let Some(upsert_verification) = payload.upsert_verification {
+                                unapplied_updates.extend(
+                                    upsert_verification
+                                        .verify(target_postgres_operator, &current_df, &upsert_dataframe_payload)
+                                        .await
+                                        .unwrap_or_else(|e| panic!("Failed to verify upsert of CDC file {:?}: {:?}", file, e)),
+                                );
+                            }
                         }
                     }
 
+                    let duplicate_sequences = duplicate_sequence_detector
+                        .map(DuplicateSequenceDetector::finish)
+                        .unwrap_or_default();
+                    for duplicate_sequence in &duplicate_sequences {
+                        warn!(
+                            "Key {} with sequence {} of table {} is claimed by both {} and {}",
+                            duplicate_sequence.primary_key,
+                            duplicate_sequence.sequence,
+                            table_name,
+                            duplicate_sequence.first_file,
+                            duplicate_sequence.duplicate_file
+                        );
+                    }
+                    if payload.detect_duplicate_sequences {
+                        info!("Duplicate sequences of table {}: {}", table_name, duplicate_sequences.len());
+                    }
+
+                    let clock_skew_anomalies = clock_skew_detector.map(ClockSkewDetector::finish).unwrap_or_default();
+                    for anomaly in &clock_skew_anomalies {
+                        warn!(
+                            "Key {} of table {} has a {:?} commit timestamp {} in {}",
+                            anomaly.primary_key, table_name, anomaly.kind, anomaly.timestamp, anomaly.file
+                        );
+                    }
+
+                    let timed_out = files_processed < parquet_files.len();
+                    if timed_out {
+                        warn!(
+                            "Maximum duration exceeded, processed {} of {} files for table {}",
+                            files_processed,
+                            parquet_files.len(),
+                            table_name
+                        );
+                    }
+
+                    // The deferred deletes are only applied if all the files were processed
+                    let mut dropped_deletes = None;
+                    let mut failure = None;
+                    match apply_buffer.finish() {
+                        Some(deferred_deletes) if timed_out => {
+                            warn!(
+                                "Maximum duration exceeded, dropping {} deferred deletes of table {}",
+                                deferred_deletes.height(),
+                                table_name
+                            );
+                            dropped_deletes = Some(deferred_deletes.height());
+                        }
+                        Some(deferred_deletes) => {
+                            info!("Applying {} deferred deletes", deferred_deletes.height());
+                            if let Err(e) = target_postgres_operator
+                                .upsert_dataframe_in_target_db(&deferred_deletes, &upsert_dataframe_payload)
+                                .await
+                            {
+                                error!("Failed to apply deferred deletes into table {}: {:?}", target_table_name, e);
+                                failure = Some(format!("Failed to apply deferred deletes: {:?}", e));
+                            }
+                        }
+                        None => {}
+                    }
+
+                    let mut row_count_discrepancy = None;
+                    if payload.check_final_row_count && table_created && !timed_out {
+                        let row_count = target_postgres_operator
+                            .count_rows(payload.schema_name.as_str(), target_table_name.as_str())
+                            .await
+                            .unwrap_or_else(|e| panic!("Failed to count rows of table {}: {:?}", target_table_name, e));
+                        let discrepancy = row_count_expectation.discrepancy(row_count);
+                        if discrepancy != 0 {
+                            warn!(
+                                "Table {} has {} rows, expected {} from {:?}",
+                                target_table_name,
+                                row_count,
+                                row_count_expectation.expected_row_count(),
+                                row_count_expectation
+                            );
+                        }
+                        row_count_discrepancy = Some(discrepancy);
+                    }
+
+                    let mut missing_defaults = Vec::new();
+                    if let Some(columns) = payload.expect_default_columns.get(table_name).filter(|_| table_created) {
+                        missing_defaults = verify_defaults(
+                            target_postgres_operator,
+                            payload.schema_name.as_str(),
+                            target_table_name.as_str(),
+                            columns,
+                        )
+                        .await
+                        .unwrap_or_else(|e| panic!("Failed to verify defaults of table {}: {:?}", target_table_name, e));
+                    }
+
+                    if payload.advance_sequences && table_created {
+                        for column_name in &primary_key_list {
+                            match target_postgres_operator
+                                .advance_sequence(payload.schema_name.as_str(), target_table_name.as_str(), column_name)
+                                .await
+                            {
+                                Ok(Some(sequence_value)) => info!(
+                                    "Advanced sequence of column {} of table {} to {}",
+                                    column_name, target_table_name, sequence_value
+                                ),
+                                Ok(None) => {}
+                                Err(e) => warn!(
+                                    "Failed to advance sequence of column {} of table {}: {:?}",
+                                    column_name, target_table_name, e
+                                ),
+                            }
+                        }
+                    }
+
+                    if !table_created {
+                        Self::create_target_table(
+                            target_postgres_operator,
+                            pipeline.table_columns(),
+                            primary_key_list.as_slice(),
+                            payload.schema_name.as_str(),
+                            target_table_name.as_str(),
+                            payload.skip_create_table_if_schema_matches,
+                            payload.check_column_order,
+                            payload.schema_strictness,
+                            &payload.storage_parameters,
+                        )
+                        .await
+                        .unwrap_or_else(|e| panic!("Invalid schema of table {}: {:?}", target_table_name, e));
+                    }
+
                     let elapsed = start.elapsed();
                     info!(
                         "{}",
@@ -226,7 +511,33 @@ impl CDCOperator {
                         .yellow()
                         .bold(),
                     );
+
+                    let mut table_report = TableReport::from(&LoadMetrics::from_files(
+                        table_name,
+                        &parquet_files[..files_processed],
+                    ));
+                    if timed_out {
+                        table_report.status = TableStatus::TimedOut;
+                    }
+                    table_report.row_count_discrepancy = row_count_discrepancy;
+                    table_report.unapplied_updates = unapplied_updates;
+                    table_report.missing_defaults = missing_defaults;
+                    table_report.skipped_operations = skipped_operations;
+                    table_report.clock_skew_anomalies = clock_skew_anomalies;
+                    table_report.duplicate_sequences = duplicate_sequences;
+                    table_report.foreign_rows = payload.table_identifier.as_ref().map(|_| foreign_rows);
+                    table_report.dropped_deletes = dropped_deletes;
+                    if let Some(error) = failure {
+                        table_report.fail(error);
+                    }
+                    payload
+                        .table_result_sender
+                        .send(payload.schema_name.as_str(), &table_report)
+                        .await;
+
+                    table_report
                 }
+                .instrument(table_span)
             })
             .collect::<Vec<_>>();
 
@@ -234,10 +545,15 @@ impl CDCOperator {
         use futures::FutureExt;
         use futures::StreamExt;
 
-        let num_of_buffers = env::var("NUM_OF_BUFFERS")
-            .unwrap_or_else(|_| "80".to_string())
-            .parse::<usize>()
-            .unwrap();
+        // The tables of an explicit table order are processed one at a time, in that order
+        let num_of_buffers = if cdc_operator_snapshot_payload.table_order.is_some() {
+            1
+        } else {
+            env::var("NUM_OF_BUFFERS")
+                .unwrap_or_else(|_| "80".to_string())
+                .parse::<usize>()
+                .unwrap()
+        };
 
         // Convert the Vec into a stream
         let stream = stream::iter(tables)
@@ -245,9 +561,603 @@ impl CDCOperator {
             .buffer_unordered(num_of_buffers);
 
         // Collect results, ensuring at most 80 futures run concurrently
-        stream.for_each(|_| async {}).await;
+        let table_reports = stream.collect::<Vec<_>>().await;
+
+        let mut report = ValidationReport::new(run_context);
+        table_reports.into_iter().for_each(|table_report| {
+            report.add_table(
+                cdc_operator_snapshot_payload.schema_name.as_str(),
+                table_report,
+            )
+        });
 
         info!("{}", "Snapshotting completed...".bold().blue());
+
+        if let Err(e) = Self::upload_report_if_configured(
+            &cdc_operator_snapshot_payload,
+            &*s3_operator,
+            &report,
+        )
+        .await
+        {
+            warn!("Failed to upload report: {:?}", e);
+        }
+
+        report
+    }
+
+    /// Uploads the report of a run to S3, if a report prefix is configured.
+    ///
+    /// The report is uploaded to the bucket of the payload, under the report prefix,
+    /// and its key includes the run id and the time of the upload.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    async fn upload_report_if_configured(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        s3_operator: &(impl S3Operator + Sync),
+        report: &ValidationReport,
+    ) -> Result<()> {
+        let Some(report_prefix) = &cdc_operator_snapshot_payload.report_prefix else {
+            return Ok(());
+        };
+        let key = report_key(report_prefix, &report.run_context, chrono::Utc::now());
+
+        upload_report(
+            s3_operator,
+            report,
+            cdc_operator_snapshot_payload.bucket_name.as_str(),
+            key.as_str(),
+        )
+        .await
+    }
+
+    /// Finds the tables to validate, either the explicit table order of the payload, which
+    /// skips the discovery of the tables, or the tables of the schema selected by the mode.
+    ///
+    /// # Returns
+    ///
+    /// The tables to validate, in the order they must be validated.
+    async fn tables_to_validate(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+    ) -> Result<Vec<String>> {
+        if let Some(table_order) = &cdc_operator_snapshot_payload.table_order {
+            return Ok(table_order.clone());
+        }
+
+        source_postgres_operator
+            .get_tables_in_schema(
+                cdc_operator_snapshot_payload.schema_name().as_str(),
+                cdc_operator_snapshot_payload.included_tables().as_slice(),
+                cdc_operator_snapshot_payload.excluded_tables().as_slice(),
+                &cdc_operator_snapshot_payload.table_mode(),
+            )
+            .await
+    }
+
+    /// Creates a table in the target database.
+    ///
+    /// If `skip_if_schema_matches` is set and the table already exists with the expected schema,
+    /// the creation is skipped. If `check_column_order` is set, the columns must also be in the
+    /// same order for the schemas to match. An existing table with a different schema is
+    /// handled based on the schema strictness.
+    ///
+    /// # Returns
+    ///
+    /// Whether the table was created, or an error if the schemas differ in strict mode.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_target_table(
+        target_postgres_operator: &(impl PostgresOperator + Sync),
+        table_columns: &IndexMap<String, String>,
+        primary_key_list: &[String],
+        schema_name: &str,
+        table_name: &str,
+        skip_if_schema_matches: bool,
+        check_column_order: bool,
+        schema_strictness: SchemaStrictness,
+        storage_parameters: &IndexMap<String, String>,
+    ) -> Result<bool> {
+        if (skip_if_schema_matches || schema_strictness == SchemaStrictness::Strict)
+            && target_postgres_operator
+                .table_exists(schema_name, table_name)
+                .await
+                .unwrap_or(false)
+        {
+            let target_table_columns = target_postgres_operator
+                .get_table_columns(schema_name, table_name)
+                .await
+                .unwrap_or_default();
+            let schema_diff = if check_column_order {
+                SchemaDiff::with_column_order(table_columns, &target_table_columns)
+            } else {
+                SchemaDiff::new(table_columns, &target_table_columns)
+            };
+
+            if schema_diff.is_empty() && skip_if_schema_matches {
+                info!("Table {schema_name}.{table_name} already exists with the same schema, skipping creation");
+                return Ok(false);
+            }
+            schema_strictness.check(&schema_diff, schema_name, table_name)?;
+        }
+
+        info!("{}", "Creating table in the target DB".bold().green());
+        let _ = target_postgres_operator
+            .create_table(
+                table_columns,
+                primary_key_list,
+                schema_name,
+                table_name,
+                storage_parameters,
+            )
+            .await;
+
+        Ok(true)
+    }
+
+    /// Loads the data stored in S3 in temporary tables and compares them with the tables of a target database.
+    ///
+    /// Since temporary tables are visible only to the connection that created them, all the tables
+    /// are loaded and compared through a single client, and nothing is persisted in the target database.
+    /// The rollup hashes of the tables are compared first, and the differing rows are only counted
+    /// for the tables whose rollups differ. When the files are sampled, only the rows of the
+    /// sampled keys are compared, and the differing rows are scaled by the sample ratio.
+    ///
+    /// # Returns
+    ///
+    /// The number of differing rows per table.
+    pub async fn compare_with_temp_tables(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+        target_postgres_operator: &PostgresOperatorImpl,
+        s3_client: &S3Client,
+    ) -> Result<IndexMap<String, i64>> {
+        info!(
+            "{}",
+            "Starting comparison with temporary tables...".bold().blue()
+        );
+
+        let payload = cdc_operator_snapshot_payload;
+        if payload.preserve_decimals {
+            activate_decimals();
+        }
+        let table_list = Self::tables_to_validate(payload, source_postgres_operator).await?;
+
+        payload
+            .on_missing_schema
+            .ensure_schema(target_postgres_operator, payload.schema_name.as_str())
+            .await?;
+
+        let session = target_postgres_operator.temp_table_session().await?;
+        // The session is closed even if a table fails, so that no temporary table is left behind
+        let result: Result<IndexMap<String, i64>> = async {
+            let mut differences = IndexMap::new();
+
+            for table_name in &table_list {
+                let table_columns = Self::load_temp_table(
+                    &session,
+                    payload,
+                    source_postgres_operator,
+                    s3_client,
+                    table_name,
+                )
+                .await?;
+
+                let columns = table_columns.keys().cloned().collect::<Vec<_>>();
+                let target_table_name = payload.target_table_name(table_name);
+                // Only the rows of the sampled keys are compared in the target table
+                let sampled_key = match payload.sampling {
+                    Some(_) => Some(
+                        source_postgres_operator
+                            .get_primary_key(table_name, payload.schema_name.as_str())
+                            .await?,
+                    ),
+                    None => None,
+                };
+                let rollup_hashes_match = session
+                    .rollup_hashes_match(
+                        payload.schema_name.as_str(),
+                        target_table_name.as_str(),
+                        columns.as_slice(),
+                        payload.key_range.as_ref(),
+                        sampled_key.as_deref(),
+                    )
+                    .await?;
+                let difference_count = if rollup_hashes_match {
+                    0
+                } else {
+                    session
+                        .count_differences(
+                            payload.schema_name.as_str(),
+                            target_table_name.as_str(),
+                            columns.as_slice(),
+                            payload.key_range.as_ref(),
+                            sampled_key.as_deref(),
+                        )
+                        .await?
+                };
+                let difference_count = match &payload.sampling {
+                    Some(sampling) => sampling.scale(difference_count),
+                    None => difference_count,
+                };
+                payload
+                    .table_result_sender
+                    .send(
+                        payload.schema_name.as_str(),
+                        &TableReport::from_differing_rows(table_name, difference_count),
+                    )
+                    .await;
+                differences.insert(table_name.clone(), difference_count);
+            }
+
+            Ok(differences)
+        }
+        .await;
+        let differences = session.close_with(result).await?;
+
+        info!(
+            "{}",
+            "Comparison with temporary tables completed..."
+                .bold()
+                .blue()
+        );
+        Ok(differences)
+    }
+
+    /// Validates the tables of a schema against the data stored in S3, by comparing them with
+    /// temporary tables.
+    ///
+    /// # Returns
+    ///
+    /// The report of the schema, tagged with the run id and labels of the payload.
+    pub async fn validate_schema(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+        target_postgres_operator: &PostgresOperatorImpl,
+        s3_client: &S3Client,
+    ) -> Result<ValidationReport> {
+        let differences = Self::compare_with_temp_tables(
+            cdc_operator_snapshot_payload,
+            source_postgres_operator,
+            target_postgres_operator,
+            s3_client,
+        )
+        .await?;
+
+        let mut report = ValidationReport::new(cdc_operator_snapshot_payload.run_context.clone());
+        for (table_name, differing_rows) in differences {
+            report.add_table(
+                cdc_operator_snapshot_payload.schema_name.as_str(),
+                TableReport::from_differing_rows(table_name, differing_rows),
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Validates the tables of multiple schemas against the data stored in S3.
+    ///
+    /// # Returns
+    ///
+    /// A single report, grouped by schema, which passes only if all the schemas pass.
+    pub async fn validate_database(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        schemas: Vec<String>,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+        target_postgres_operator: &PostgresOperatorImpl,
+        s3_client: &S3Client,
+    ) -> Result<ValidationReport> {
+        let mut report = ValidationReport::new(cdc_operator_snapshot_payload.run_context.clone());
+
+        for schema_name in schemas {
+            info!(
+                "{}",
+                format!("Validating schema: {}", schema_name)
+                    .bold()
+                    .magenta()
+            );
+
+            let mut schema_payload = cdc_operator_snapshot_payload.clone();
+            schema_payload.schema_name = schema_name;
+
+            let schema_report = Self::validate_schema(
+                &schema_payload,
+                source_postgres_operator,
+                target_postgres_operator,
+                s3_client,
+            )
+            .await?;
+            report.merge(schema_report);
+        }
+
+        info!(
+            "Validation of {} schemas {}",
+            report.schemas.len(),
+            if report.passed() { "passed" } else { "failed" }
+        );
+
+        let s3_operator = S3OperatorImpl::new(s3_client)
+            .with_requester_pays(cdc_operator_snapshot_payload.requester_pays);
+        Self::upload_report_if_configured(cdc_operator_snapshot_payload, &s3_operator, &report)
+            .await?;
+
+        Ok(report)
+    }
+
+    /// Validates that the data stored in S3 would be accepted by the check constraints of the
+    /// tables of a target database, without inserting anything in them.
+    ///
+    /// The data are loaded in temporary tables, and the check constraints of the target tables
+    /// are evaluated against the loaded rows.
+    ///
+    /// # Returns
+    ///
+    /// The violated check constraints per table.
+    pub async fn validate_check_constraints(
+        cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+        target_postgres_operator: &PostgresOperatorImpl,
+        s3_client: &S3Client,
+    ) -> Result<IndexMap<String, Vec<CheckConstraintViolation>>> {
+        info!(
+            "{}",
+            "Starting check constraint validation...".bold().blue()
+        );
+
+        let payload = cdc_operator_snapshot_payload;
+        let table_list = Self::tables_to_validate(payload, source_postgres_operator).await?;
+
+        let session = target_postgres_operator.temp_table_session().await?;
+        // The session is closed even if a table fails, so that no temporary table is left behind
+        let result: Result<IndexMap<String, Vec<CheckConstraintViolation>>> = async {
+            let mut violations = IndexMap::new();
+
+            for table_name in &table_list {
+                Self::load_temp_table(
+                    &session,
+                    payload,
+                    source_postgres_operator,
+                    s3_client,
+                    table_name,
+                )
+                .await?;
+
+                let table_violations = session
+                    .find_check_constraint_violations(
+                        payload.schema_name.as_str(),
+                        payload.target_table_name(table_name).as_str(),
+                    )
+                    .await?;
+                for violation in &table_violations {
+                    warn!(
+                        "{} rows of table {} violate the check constraint {}: {}",
+                        violation.violating_rows,
+                        table_name,
+                        violation.constraint_name,
+                        violation.definition
+                    );
+                }
+                violations.insert(table_name.clone(), table_violations);
+            }
+
+            Ok(violations)
+        }
+        .await;
+        let violations = session.close_with(result).await?;
+
+        info!(
+            "{}",
+            "Check constraint validation completed...".bold().blue()
+        );
+        Ok(violations)
+    }
+
+    /// Validates that the stored generated columns of a table in the target database have the
+    /// values of their expression, recomputed with Polars from the other columns of the table.
+    ///
+    /// This is a self-check of the target: the columns of the expression are read from the
+    /// target table, so it confirms that the target computed its generated columns from the
+    /// applied rows, while the applied rows themselves are validated against the source by the
+    /// comparison of the tables.
+    ///
+    /// Only simple arithmetic expressions are supported, and the generated columns with other
+    /// expressions are skipped.
+    ///
+    /// # Returns
+    ///
+    /// The number of mismatching rows per generated column.
+    pub async fn validate_generated_columns(
+        schema_name: &str,
+        table_name: &str,
+        target_postgres_operator: &(impl PostgresOperator + Sync),
+    ) -> Result<IndexMap<String, usize>> {
+        let query =
+            TableQuery::FindGeneratedColumns(schema_name.to_string(), table_name.to_string());
+        let generated_columns = target_postgres_operator
+            .query_to_dataframe(&query.to_string(), &[])
+            .await?;
+
+        let mut mismatches = IndexMap::new();
+        if generated_columns.height() == 0 {
+            return Ok(mismatches);
+        }
+
+        let column_names = generated_columns.column("column_name")?.str()?;
+        let expressions = generated_columns.column("generation_expression")?.str()?;
+        for (column_name, expression) in column_names.into_iter().zip(expressions) {
+            let (Some(column_name), Some(expression)) = (column_name, expression) else {
+                continue;
+            };
+            let generated_column_expression = match GeneratedColumnExpression::parse(expression) {
+                Ok(generated_column_expression) => generated_column_expression,
+                Err(e) => {
+                    warn!("Skipping generated column {column_name} of table {table_name}: {e}");
+                    continue;
+                }
+            };
+
+            let columns = generated_column_expression
+                .columns
+                .iter()
+                .map(String::as_str)
+                .chain([column_name])
+                .map(|column| format!(r#""{}""#, column.replace('"', r#""""#)))
+                .collect::<Vec<_>>();
+            let query = format!(
+                r#"SELECT {} FROM "{}"."{}""#,
+                columns.join(", "),
+                schema_name.replace('"', r#""""#),
+                table_name.replace('"', r#""""#)
+            );
+            let df = target_postgres_operator
+                .query_to_dataframe(&query, &[])
+                .await?;
+
+            let mismatching_rows = if df.height() == 0 {
+                0
+            } else {
+                generated_column_expression.count_mismatches(&df, column_name)?
+            };
+            if mismatching_rows > 0 {
+                warn!(
+                    "{} rows of table {} have an unexpected value in generated column {}: {}",
+                    mismatching_rows, table_name, column_name, expression
+                );
+            }
+            mismatches.insert(column_name.to_string(), mismatching_rows);
+        }
+
+        Ok(mismatches)
+    }
+
+    /// Loads the Parquet files of a table from S3 in its temporary table.
+    ///
+    /// The files go through the same pipeline as in the snapshot, so that the temporary table
+    /// holds the rows that the snapshot writes to the target table.
+    ///
+    /// # Returns
+    ///
+    /// The columns of the temporary table.
+    async fn load_temp_table(
+        session: &TempTableSession,
+        payload: &CDCOperatorSnapshotPayload,
+        source_postgres_operator: &(impl PostgresOperator + Sync),
+        s3_client: &S3Client,
+        table_name: &str,
+    ) -> Result<IndexMap<String, String>> {
+        info!(
+            "{}",
+            format!("Running for table: {}", table_name)
+                .bold()
+                .magenta()
+        );
+
+        let s3_operator = S3OperatorImpl::new(s3_client)
+            .with_requester_pays(payload.requester_pays)
+            .with_object_versions(payload.object_versions)
+            .with_start_after(payload.list_start_after);
+        let dataframe_operator = DataframeOperatorImpl::new(s3_client)
+            .with_requester_pays(payload.requester_pays)
+            .with_server_side_encryption(payload.server_side_encryption);
+
+        let source_table_columns = source_postgres_operator
+            .get_table_columns(payload.schema_name.as_str(), table_name)
+            .await?;
+        let primary_key_list = source_postgres_operator
+            .get_primary_key(table_name, payload.schema_name.as_str())
+            .await?;
+        let target_table_name = payload.target_table_name(table_name);
+
+        let parquet_files = s3_operator
+            .get_list_of_parquet_files_from_s3(&payload.load_parquet_files_payload(table_name))
+            .await?;
+
+        let insert_dataframe_payload = InsertDataframePayload {
+            database_name: payload.database_name.clone(),
+            schema_name: payload.schema_name.clone(),
+            table_name: target_table_name.clone(),
+            insert_method: payload.insert_method,
+            load_mode: payload.load_mode,
+            copy_commit_rows: payload.copy_commit_rows,
+        };
+        let upsert_dataframe_payload = UpsertDataframePayload {
+            database_name: payload.database_name.clone(),
+            schema_name: payload.schema_name.clone(),
+            table_name: target_table_name.clone(),
+            primary_key: primary_key_list.join(","),
+            conflict_behavior: payload.conflict_behavior,
+            unmatched_delete_policy: payload.unmatched_delete_policy.clone(),
+            incomplete_delete_policy: payload.on_incomplete_delete,
+            delete_batch_size: payload.delete_batch_size,
+            deferred_constraints: false,
+        };
+        let mut pipeline = FilePipeline::new(
+            payload,
+            table_name,
+            &source_table_columns,
+            primary_key_list.as_slice(),
+        );
+        let mut apply_buffer = CDCApplyBuffer::new(payload.apply_mode);
+        let mut temp_table_created = false;
+
+        for file in &parquet_files {
+            let current_df = dataframe_operator
+                .create_dataframe_from_parquet_file(
+                    &payload.create_dataframe_payload(table_name, file),
+                )
+                .await?
+                .ok_or_else(|| anyhow!("No DataFrame was read from file {:?}", file))?;
+
+            let mut transformed_file = pipeline.transform(file, current_df)?;
+            transformed_file.df = payload.sample(transformed_file.df, &primary_key_list)?;
+
+            if !temp_table_created {
+                session
+                    .create_temp_table(
+                        pipeline.resolve_table_columns(&transformed_file),
+                        primary_key_list.as_slice(),
+                        target_table_name.as_str(),
+                    )
+                    .await?;
+                temp_table_created = true;
+            }
+
+            let prepared_file = pipeline.prepare(file, transformed_file.df)?;
+            for (column_name, data_type) in &prepared_file.added_columns {
+                session
+                    .add_column_to_temp_table(target_table_name.as_str(), column_name, data_type)
+                    .await?;
+            }
+
+            if file.is_load_file() {
+                session
+                    .insert_dataframe_in_temp_table(&prepared_file.df, &insert_dataframe_payload)
+                    .await?;
+            } else {
+                let current_df = apply_buffer.stage(prepared_file.df)?;
+                session
+                    .upsert_dataframe_in_temp_table(&current_df, &upsert_dataframe_payload)
+                    .await?;
+            }
+        }
+
+        if !temp_table_created {
+            session
+                .create_temp_table(
+                    pipeline.table_columns(),
+                    primary_key_list.as_slice(),
+                    target_table_name.as_str(),
+                )
+                .await?;
+        }
+        if let Some(deferred_deletes) = apply_buffer.finish() {
+            session
+                .upsert_dataframe_in_temp_table(&deferred_deletes, &upsert_dataframe_payload)
+                .await?;
+        }
+
+        Ok(pipeline.table_columns().clone())
     }
 
     /// Validates the data between S3 and a target database.
@@ -287,3 +1197,300 @@ impl CDCOperator {
         info!("{}", "Pgdatadiff completed!".bold().blue());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use mockall::predicate::*;
+
+    use aws_sdk_s3::config::{BehaviorVersion, Region};
+    use aws_sdk_s3::Client as S3Client;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    use super::CDCOperator;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::cdc::snapshot_payload::CDCOperatorSnapshotPayload;
+    use crate::cdc::validation_report::TableResultSender;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use crate::postgres::schema_diff::SchemaStrictness;
+    use polars::prelude::*;
+
+    fn table_columns() -> IndexMap<String, String> {
+        let mut columns = IndexMap::new();
+        columns.insert("id".to_string(), "integer".to_string());
+        columns.insert("name".to_string(), "text".to_string());
+        columns
+    }
+
+    fn snapshot_payload() -> CDCOperatorSnapshotPayload {
+        CDCOperatorSnapshotPayload::new(
+            "bucket",
+            "prefix",
+            "mydb",
+            "public",
+            Vec::<String>::new(),
+            Vec::<String>::new(),
+            ModeValueEnum::FullLoadOnly,
+            None,
+            None,
+            "postgres://source".to_string(),
+            "postgres://target".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tables_to_validate_in_explicit_order() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator.expect_get_tables_in_schema().times(0);
+
+        let mut payload = snapshot_payload();
+        payload.table_order = Some(vec!["orders".to_string(), "users".to_string()]);
+        let tables = CDCOperator::tables_to_validate(&payload, &postgres_operator)
+            .await
+            .unwrap();
+
+        assert_eq!(tables, vec!["orders", "users"]);
+    }
+
+    #[tokio::test]
+    async fn test_tables_to_validate_discovered_without_explicit_order() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_get_tables_in_schema()
+            .with(eq("public"), always(), always(), always())
+            .times(1)
+            .returning(|_, _, _, _| Ok(vec!["users".to_string(), "orders".to_string()]));
+
+        let tables = CDCOperator::tables_to_validate(&snapshot_payload(), &postgres_operator)
+            .await
+            .unwrap();
+
+        assert_eq!(tables, vec!["users", "orders"]);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_follows_explicit_table_order() {
+        let mut source_postgres_operator = MockPostgresOperator::new();
+        source_postgres_operator
+            .expect_get_tables_in_schema()
+            .times(0);
+        let mut target_postgres_operator = MockPostgresOperator::new();
+        target_postgres_operator
+            .expect_schema_exists()
+            .returning(|_| Ok(true));
+        let s3_client = S3Client::from_conf(
+            aws_sdk_s3::Config::builder()
+                .behavior_version(BehaviorVersion::latest())
+                .region(Region::new("us-east-1"))
+                .build(),
+        );
+
+        let (sender, mut receiver) = mpsc::channel(8);
+        let mut payload = snapshot_payload();
+        payload.table_order = Some(vec!["orders".to_string(), "users".to_string()]);
+        payload.table_result_sender = TableResultSender::new(sender);
+        // The run budget is exhausted at once, so each table is reported without being loaded
+        payload.max_duration = Some(Duration::ZERO);
+        let report = CDCOperator::snapshot(
+            &payload,
+            &source_postgres_operator,
+            &target_postgres_operator,
+            &s3_client,
+        )
+        .await;
+
+        let mut processed_tables = Vec::new();
+        while let Ok(table_result) = receiver.try_recv() {
+            processed_tables.push(table_result.table_report.table_name);
+        }
+        let reported_tables = report.schemas["public"]
+            .tables
+            .iter()
+            .map(|table_report| table_report.table_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(processed_tables, vec!["orders", "users"]);
+        assert_eq!(reported_tables, vec!["orders", "users"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_target_table_skips_matching_existing_table() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_table_exists()
+            .with(eq("schema"), eq("table"))
+            .returning(|_, _| Ok(true));
+        postgres_operator
+            .expect_get_table_columns()
+            .returning(|_, _| Ok(table_columns()));
+        postgres_operator.expect_create_table().times(0);
+
+        let created = CDCOperator::create_target_table(
+            &postgres_operator,
+            &table_columns(),
+            &["id".to_string()],
+            "schema",
+            "table",
+            true,
+            false,
+            SchemaStrictness::Lenient,
+            &IndexMap::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!created);
+    }
+
+    #[tokio::test]
+    async fn test_create_target_table_checks_column_order() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_table_exists()
+            .returning(|_, _| Ok(true));
+        postgres_operator
+            .expect_get_table_columns()
+            .returning(|_, _| {
+                let mut columns = IndexMap::new();
+                columns.insert("name".to_string(), "text".to_string());
+                columns.insert("id".to_string(), "integer".to_string());
+                Ok(columns)
+            });
+        postgres_operator
+            .expect_create_table()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+
+        let created = CDCOperator::create_target_table(
+            &postgres_operator,
+            &table_columns(),
+            &["id".to_string()],
+            "schema",
+            "table",
+            true,
+            true,
+            SchemaStrictness::Lenient,
+            &IndexMap::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(created);
+    }
+
+    #[tokio::test]
+    async fn test_create_target_table_with_type_mismatch() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_table_exists()
+            .returning(|_, _| Ok(true));
+        postgres_operator
+            .expect_get_table_columns()
+            .returning(|_, _| {
+                let mut columns = table_columns();
+                columns.insert("id".to_string(), "bigint".to_string());
+                Ok(columns)
+            });
+        postgres_operator
+            .expect_create_table()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+
+        let result = CDCOperator::create_target_table(
+            &postgres_operator,
+            &table_columns(),
+            &["id".to_string()],
+            "schema",
+            "table",
+            false,
+            false,
+            SchemaStrictness::Strict,
+            &IndexMap::new(),
+        )
+        .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("column id is integer but bigint in the target"));
+
+        let created = CDCOperator::create_target_table(
+            &postgres_operator,
+            &table_columns(),
+            &["id".to_string()],
+            "schema",
+            "table",
+            true,
+            false,
+            SchemaStrictness::Lenient,
+            &IndexMap::new(),
+        )
+        .await
+        .unwrap();
+        assert!(created);
+    }
+
+    #[tokio::test]
+    async fn test_create_target_table_creates_missing_table() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_table_exists()
+            .returning(|_, _| Ok(false));
+        postgres_operator
+            .expect_create_table()
+            .times(1)
+            .returning(|_, _, _, _, _| Ok(()));
+
+        let created = CDCOperator::create_target_table(
+            &postgres_operator,
+            &table_columns(),
+            &["id".to_string()],
+            "schema",
+            "table",
+            true,
+            false,
+            SchemaStrictness::Lenient,
+            &IndexMap::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(created);
+    }
+
+    #[tokio::test]
+    async fn test_validate_generated_columns_with_arithmetic_expression() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_query_to_dataframe()
+            .returning(|sql, _| {
+                let df = if sql.contains("information_schema.columns") {
+                    df!(
+                        "column_name" => &["total", "label"],
+                        "generation_expression" => &[
+                            "(price * (quantity)::numeric)",
+                            "((name || ' ') || code)",
+                        ]
+                    )
+                } else {
+                    assert_eq!(
+                        sql,
+                        r#"SELECT "price", "quantity", "total" FROM "schema"."orders""#
+                    );
+                    df!(
+                        "price" => &[2.5, 4.0],
+                        "quantity" => &[2i32, 5],
+                        "total" => &[5.0, 21.0]
+                    )
+                };
+                Ok(df.unwrap())
+            });
+
+        let mismatches =
+            CDCOperator::validate_generated_columns("schema", "orders", &postgres_operator)
+                .await
+                .unwrap();
+
+        // The concatenation is not supported, so only the arithmetic expression is validated
+        assert_eq!(mismatches, IndexMap::from([("total".to_string(), 1)]));
+    }
+}
@@ -111,7 +111,7 @@ impl CDCOperatorPayload {
     pub fn database_name(&self) -> String {
         self.source_postgres_url
             .split('/')
-            .last()
+            .next_back()
             .unwrap()
             .to_string()
     }
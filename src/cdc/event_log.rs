@@ -0,0 +1,276 @@
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use super::prefix_comparison::read_cdc_rows_from_s3;
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+
+use crate::dataframe::dataframe_ops::DataframeOperator;
+use crate::s3::s3_operator::S3Operator;
+
+/// Represents an operation of the CDC stream of a table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CdcEvent {
+    pub primary_key_value: String,
+    pub op: String,
+    pub sequence: String,
+}
+
+impl CdcEvent {
+    pub fn new(
+        primary_key_value: impl Into<String>,
+        op: impl Into<String>,
+        sequence: impl Into<String>,
+    ) -> Self {
+        Self {
+            primary_key_value: primary_key_value.into(),
+            op: op.into(),
+            sequence: sequence.into(),
+        }
+    }
+}
+
+/// Represents the differences between the expected event log of a table and its parsed operations.
+///
+/// * `missing` - The expected events that were not parsed.
+/// * `extra` - The parsed events that were not expected.
+/// * `reordered` - The events of a key that were parsed in a different order than expected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventLogDiff {
+    pub missing: Vec<CdcEvent>,
+    pub extra: Vec<CdcEvent>,
+    pub reordered: Vec<CdcEvent>,
+}
+
+impl EventLogDiff {
+    /// Checks if the parsed operations match the expected event log.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.reordered.is_empty()
+    }
+}
+
+/// Parses the operations of the rows of the Parquet files of a table.
+///
+/// The rows without an `Op` column, i.e. of the LOAD files, are parsed as inserts.
+///
+/// # Arguments
+///
+/// * `df` - The rows of the Parquet files, in the order they were written.
+/// * `primary_key` - The primary key columns of the table.
+/// * `sequence_column` - The column ordering the operations, e.g. `_dms_ingestion_timestamp`.
+///
+/// # Returns
+///
+/// The operations, in the order they were written.
+pub fn parse_events(
+    df: &DataFrame,
+    primary_key: &[String],
+    sequence_column: &str,
+) -> Result<Vec<CdcEvent>> {
+    let key_columns = df.columns(primary_key)?;
+    let op_column = df.column("Op").ok();
+    let sequence_column = df.column(sequence_column).ok();
+    let text = |column: Option<&Series>, row: usize| -> Result<Option<String>> {
+        let Some(column) = column else {
+            return Ok(None);
+        };
+        Ok(match column.get(row)? {
+            AnyValue::Null => None,
+            AnyValue::String(value) => Some(value.to_string()),
+            AnyValue::StringOwned(value) => Some(value.to_string()),
+            value => Some(value.to_string()),
+        })
+    };
+
+    (0..df.height())
+        .map(|row| {
+            let primary_key_value = key_columns
+                .iter()
+                .map(|column| Ok(text(Some(*column), row)?.unwrap_or_default()))
+                .collect::<Result<Vec<_>>>()?
+                .join(",");
+            Ok(CdcEvent {
+                primary_key_value,
+                op: text(op_column, row)?.unwrap_or_else(|| "I".to_string()),
+                sequence: text(sequence_column, row)?.unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Reads the operations of a table from the Parquet files in S3.
+///
+/// # Arguments
+///
+/// * `payload` - The payload describing the S3 prefix.
+/// * `table_name` - The name of the table in the S3 path.
+/// * `primary_key` - The primary key columns of the table.
+/// * `s3_operator` - The operator to list the Parquet files.
+/// * `dataframe_operator` - The operator to read the Parquet files.
+///
+/// # Returns
+///
+/// The operations, in the order they were written.
+pub async fn read_events_from_s3(
+    payload: &CDCOperatorSnapshotPayload,
+    table_name: &str,
+    primary_key: &[String],
+    s3_operator: &(impl S3Operator + Sync),
+    dataframe_operator: &(impl DataframeOperator + Sync),
+) -> Result<Vec<CdcEvent>> {
+    match read_cdc_rows_from_s3(payload, table_name, s3_operator, dataframe_operator).await? {
+        Some(df) => parse_events(&df, primary_key, payload.sequence_column.as_str()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Compares the parsed operations of a table with an independent log of the expected events,
+/// treating the CDC files as an event stream.
+///
+/// Only the order of the events of the same key is checked, as the events of different keys
+/// may be written in any order.
+///
+/// # Arguments
+///
+/// * `expected` - The expected events, in order.
+/// * `parsed` - The parsed operations, in order.
+///
+/// # Returns
+///
+/// The missing, extra and reordered events.
+pub fn compare_event_logs(expected: &[CdcEvent], parsed: &[CdcEvent]) -> EventLogDiff {
+    let expected_events = expected.iter().collect::<HashSet<_>>();
+    let parsed_events = parsed.iter().collect::<HashSet<_>>();
+
+    let missing = expected
+        .iter()
+        .filter(|event| !parsed_events.contains(event))
+        .cloned()
+        .collect();
+    let extra = parsed
+        .iter()
+        .filter(|event| !expected_events.contains(event))
+        .cloned()
+        .collect();
+
+    // The events present in both logs, by key, in the order of each log
+    let events_by_key = |events: &[CdcEvent], other_events: &HashSet<&CdcEvent>| {
+        let mut events_by_key: HashMap<String, Vec<CdcEvent>> = HashMap::new();
+        for event in events.iter().filter(|event| other_events.contains(event)) {
+            events_by_key
+                .entry(event.primary_key_value.clone())
+                .or_default()
+                .push(event.clone());
+        }
+        events_by_key
+    };
+    let expected_by_key = events_by_key(expected, &parsed_events);
+    let mut parsed_by_key = events_by_key(parsed, &expected_events);
+
+    let mut reordered = Vec::new();
+    for event in expected {
+        let Some(expected_events) = expected_by_key.get(&event.primary_key_value) else {
+            continue;
+        };
+        let Some(parsed_events) = parsed_by_key.remove(&event.primary_key_value) else {
+            continue;
+        };
+        reordered.extend(
+            expected_events
+                .iter()
+                .zip(parsed_events)
+                .filter(|(expected_event, parsed_event)| *expected_event != parsed_event)
+                .map(|(_, parsed_event)| parsed_event),
+        );
+    }
+
+    EventLogDiff {
+        missing,
+        extra,
+        reordered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected_events() -> Vec<CdcEvent> {
+        vec![
+            CdcEvent::new("1", "I", "2024-01-01 00:00:01"),
+            CdcEvent::new("2", "I", "2024-01-01 00:00:02"),
+            CdcEvent::new("1", "U", "2024-01-01 00:00:03"),
+            CdcEvent::new("2", "D", "2024-01-01 00:00:04"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let df = df!(
+            "Op" => &["I", "U"],
+            "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01", "2024-01-01 00:00:03"],
+            "tenant_id" => &[7i64, 7],
+            "id" => &[1i64, 1],
+            "name" => &["a", "b"]
+        )
+        .unwrap();
+
+        let events = parse_events(
+            &df,
+            &["tenant_id".to_string(), "id".to_string()],
+            "_dms_ingestion_timestamp",
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                CdcEvent::new("7,1", "I", "2024-01-01 00:00:01"),
+                CdcEvent::new("7,1", "U", "2024-01-01 00:00:03"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_event_log_with_missing_event() {
+        let mut parsed = expected_events();
+        parsed.remove(2);
+
+        let diff = compare_event_logs(&expected_events(), &parsed);
+
+        assert_eq!(
+            diff,
+            EventLogDiff {
+                missing: vec![CdcEvent::new("1", "U", "2024-01-01 00:00:03")],
+                ..Default::default()
+            }
+        );
+        assert!(compare_event_logs(&expected_events(), &expected_events()).is_empty());
+    }
+
+    #[test]
+    fn test_compare_event_log_with_extra_and_reordered_events() {
+        let parsed = vec![
+            CdcEvent::new("2", "I", "2024-01-01 00:00:02"),
+            CdcEvent::new("1", "U", "2024-01-01 00:00:03"),
+            CdcEvent::new("1", "I", "2024-01-01 00:00:01"),
+            CdcEvent::new("2", "D", "2024-01-01 00:00:04"),
+            CdcEvent::new("3", "I", "2024-01-01 00:00:05"),
+        ];
+
+        let diff = compare_event_logs(&expected_events(), &parsed);
+
+        assert!(diff.missing.is_empty());
+        assert_eq!(
+            diff.extra,
+            vec![CdcEvent::new("3", "I", "2024-01-01 00:00:05")]
+        );
+        assert_eq!(
+            diff.reordered,
+            vec![
+                CdcEvent::new("1", "U", "2024-01-01 00:00:03"),
+                CdcEvent::new("1", "I", "2024-01-01 00:00:01"),
+            ]
+        );
+    }
+}
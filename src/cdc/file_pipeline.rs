@@ -0,0 +1,326 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use super::operation_filter::{filter_operations, SkippedOperations};
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+use crate::dataframe::dataframe_transform::transformed_table_columns;
+use crate::dataframe::required_columns::assert_required_columns;
+use crate::dataframe::schema_alignment::SchemaAligner;
+use crate::dataframe::uuid_coercion::coerce_uuid_columns;
+use crate::s3::s3_operator::S3ParquetFile;
+
+/// Represents the DataFrame of a Parquet file, transformed the way it is applied to its table.
+#[derive(Debug)]
+pub struct TransformedFile {
+    pub df: DataFrame,
+    /// The number of rows of the file that belong to another table.
+    pub foreign_rows: usize,
+    /// The columns of the file with a data type that is not supported.
+    pub unsupported_columns: Vec<String>,
+}
+
+/// Represents the DataFrame of a Parquet file, ready to be applied to its table.
+#[derive(Debug)]
+pub struct PreparedFile {
+    pub df: DataFrame,
+    /// The columns to add to the table, along with their data type.
+    pub added_columns: Vec<(String, String)>,
+    /// The operations of a CDC file that were skipped as they are not allowed.
+    pub skipped_operations: SkippedOperations,
+}
+
+/// Represents the processing of the Parquet files of a table before they are applied,
+/// shared by the snapshot and the comparison with temporary tables, so that a temporary
+/// table holds the same rows as the table written by the snapshot.
+///
+/// Each file is first transformed, then the columns of the table are resolved from the first
+/// transformed file, and each file is prepared against the columns of the table.
+pub struct FilePipeline<'a> {
+    payload: &'a CDCOperatorSnapshotPayload,
+    table_name: &'a str,
+    source_table_columns: &'a IndexMap<String, String>,
+    primary_key: &'a [String],
+    table_columns: IndexMap<String, String>,
+    required_columns: Vec<String>,
+    schema_aligner: SchemaAligner,
+}
+
+impl<'a> FilePipeline<'a> {
+    /// Creates a new file pipeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the run.
+    /// * `table_name` - The name of the table in the S3 path.
+    /// * `source_table_columns` - The columns of the table in the source database.
+    /// * `primary_key` - The primary key of the table.
+    ///
+    /// # Returns
+    ///
+    /// A new file pipeline instance, with the columns of the source table.
+    pub fn new(
+        payload: &'a CDCOperatorSnapshotPayload,
+        table_name: &'a str,
+        source_table_columns: &'a IndexMap<String, String>,
+        primary_key: &'a [String],
+    ) -> Self {
+        Self {
+            payload,
+            table_name,
+            source_table_columns,
+            primary_key,
+            table_columns: source_table_columns.clone(),
+            required_columns: Vec::new(),
+            schema_aligner: SchemaAligner::new(),
+        }
+    }
+
+    /// Gets the columns of the table, along with their data type.
+    pub fn table_columns(&self) -> &IndexMap<String, String> {
+        &self.table_columns
+    }
+
+    /// Transforms a DataFrame read from S3, up to the point where the columns of the table are known.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The Parquet file the DataFrame was read from.
+    /// * `df` - The DataFrame read from S3.
+    ///
+    /// # Returns
+    ///
+    /// The transformed file.
+    pub fn transform(&self, file: &S3ParquetFile, df: DataFrame) -> Result<TransformedFile> {
+        let payload = self.payload;
+
+        payload
+            .column_limit
+            .check(&df, &file.file_name)
+            .context("Invalid file")?;
+        let df = payload
+            .transform_dataframe(df)
+            .context("Failed to transform DataFrame")?;
+        let df = payload
+            .filter_key_range(df)
+            .context("Failed to filter key range")?;
+        let df = payload
+            .filter_as_of(file, df)
+            .context("Failed to filter as-of point")?;
+        let df = payload
+            .normalize_keys(self.table_name, df, self.primary_key)
+            .context("Failed to normalize keys")?;
+        let (mut df, foreign_rows) = payload
+            .check_table_identifier(self.table_name, file, df)
+            .context("Misrouted file")?;
+        // The table identifier column is not applied, unless it is a column of the table
+        if let Some(table_identifier) = &payload.table_identifier {
+            if !self
+                .source_table_columns
+                .contains_key(&table_identifier.column)
+            {
+                _ = df.drop_in_place(&table_identifier.column);
+            }
+        }
+
+        let (df, unsupported_columns) = payload
+            .unsupported_column
+            .apply(df)
+            .context("Unsupported column")?;
+        let df = match &payload.invalid_utf8 {
+            Some(invalid_utf8_policy) => invalid_utf8_policy
+                .apply(df, self.primary_key, &self.table_columns)
+                .context("Invalid UTF-8")?,
+            None => df,
+        };
+
+        Ok(TransformedFile {
+            df,
+            foreign_rows,
+            unsupported_columns,
+        })
+    }
+
+    /// Resolves the columns of the table from the first transformed file, before the table is created,
+    /// so that the table follows the schema of the transformed DataFrame if a transform is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The first transformed file of the table.
+    ///
+    /// # Returns
+    ///
+    /// The columns of the table, along with their data type.
+    pub fn resolve_table_columns(&mut self, file: &TransformedFile) -> &IndexMap<String, String> {
+        if self.payload.has_dataframe_transform() {
+            self.table_columns = transformed_table_columns(self.source_table_columns, &file.df);
+        }
+        self.payload
+            .unsupported_column
+            .apply_to_table_columns(&mut self.table_columns, &file.unsupported_columns);
+        self.required_columns = self.table_columns.keys().cloned().collect();
+
+        &self.table_columns
+    }
+
+    /// Prepares a transformed DataFrame against the columns of the table.
+    ///
+    /// The columns added by the file are added to the columns of the table, and must be added
+    /// to the table before the DataFrame is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The Parquet file the DataFrame was read from.
+    /// * `df` - The transformed DataFrame.
+    ///
+    /// # Returns
+    ///
+    /// The prepared file.
+    pub fn prepare(&mut self, file: &S3ParquetFile, df: DataFrame) -> Result<PreparedFile> {
+        // Columns added to the table by later files are not required
+        assert_required_columns(&df, &self.required_columns).context("Invalid file")?;
+
+        // Align the DataFrame to the columns seen so far, since the CDC files
+        // may have columns added after the LOAD files were written
+        let (df, _) = self
+            .schema_aligner
+            .align(df)
+            .context("Failed to align schema")?;
+        let (df, added_columns) = self
+            .payload
+            .on_extra_source_column
+            .apply(df, &self.table_columns)
+            .context("Invalid file")?;
+        self.table_columns.extend(added_columns.iter().cloned());
+
+        let df = coerce_uuid_columns(df, &self.table_columns).context("Invalid UUID")?;
+
+        let (df, skipped_operations) = if file.is_load_file() {
+            (df, SkippedOperations::default())
+        } else {
+            filter_operations(df, &self.payload.allowed_operations)
+                .context("Failed to filter operations of CDC file")?
+        };
+
+        Ok(PreparedFile {
+            df,
+            added_columns,
+            skipped_operations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::cdc::operation_filter::Operation;
+    use crate::dataframe::extra_source_column::ExtraSourceColumn;
+    use std::collections::HashSet;
+
+    fn snapshot_payload() -> CDCOperatorSnapshotPayload {
+        CDCOperatorSnapshotPayload::new(
+            "bucket",
+            "prefix",
+            "mydb",
+            "public",
+            Vec::<String>::new(),
+            Vec::<String>::new(),
+            ModeValueEnum::FullLoadOnly,
+            None,
+            None,
+            "postgres://source".to_string(),
+            "postgres://target".to_string(),
+        )
+    }
+
+    fn source_table_columns() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("id".to_string(), "integer".to_string()),
+            ("uid".to_string(), "uuid".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_prepare_cdc_file_coerces_uuids_and_filters_operations() {
+        let mut payload = snapshot_payload();
+        payload.allowed_operations = HashSet::from([Operation::Insert, Operation::Update]);
+        let source_table_columns = source_table_columns();
+        let primary_key = vec!["id".to_string()];
+        let mut pipeline =
+            FilePipeline::new(&payload, "users", &source_table_columns, &primary_key);
+        let file = S3ParquetFile::new("prefix/mydb/public/users/20240101-000000000.parquet");
+        let df = df!(
+            "Op" => &["I", "D"],
+            "id" => &[1i32, 2],
+            "uid" => &[
+                "550E8400E29B41D4A716446655440000",
+                "{6ba7b810-9dad-11d1-80b4-00c04fd430c8}",
+            ]
+        )
+        .unwrap();
+
+        let transformed_file = pipeline.transform(&file, df).unwrap();
+        pipeline.resolve_table_columns(&transformed_file);
+        let prepared_file = pipeline.prepare(&file, transformed_file.df).unwrap();
+
+        assert_eq!(prepared_file.df.height(), 1);
+        assert_eq!(
+            prepared_file
+                .df
+                .column("uid")
+                .unwrap()
+                .str()
+                .unwrap()
+                .get(0),
+            Some("550e8400-e29b-41d4-a716-446655440000")
+        );
+        assert_eq!(prepared_file.skipped_operations.deletes, 1);
+        assert!(prepared_file.added_columns.is_empty());
+    }
+
+    #[test]
+    fn test_prepare_file_with_added_column() {
+        let mut payload = snapshot_payload();
+        payload.on_extra_source_column = ExtraSourceColumn::AddColumn;
+        let source_table_columns = source_table_columns();
+        let primary_key = vec!["id".to_string()];
+        let mut pipeline =
+            FilePipeline::new(&payload, "users", &source_table_columns, &primary_key);
+        let load_file = S3ParquetFile::new("prefix/mydb/public/users/LOAD00000001.parquet");
+        let cdc_file = S3ParquetFile::new("prefix/mydb/public/users/20240101-000000000.parquet");
+
+        let transformed_file = pipeline
+            .transform(
+                &load_file,
+                df!("id" => &[1i32], "uid" => &["550e8400-e29b-41d4-a716-446655440000"]).unwrap(),
+            )
+            .unwrap();
+        pipeline.resolve_table_columns(&transformed_file);
+        pipeline.prepare(&load_file, transformed_file.df).unwrap();
+
+        let transformed_file = pipeline
+            .transform(
+                &cdc_file,
+                df!(
+                    "Op" => &["U"],
+                    "id" => &[1i32],
+                    "uid" => &["550e8400-e29b-41d4-a716-446655440000"],
+                    "email" => &["user@example.com"]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        let prepared_file = pipeline.prepare(&cdc_file, transformed_file.df).unwrap();
+
+        assert_eq!(
+            prepared_file
+                .added_columns
+                .iter()
+                .map(|(column, _)| column.as_str())
+                .collect::<Vec<_>>(),
+            vec!["email"]
+        );
+        assert!(pipeline.table_columns().contains_key("email"));
+    }
+}
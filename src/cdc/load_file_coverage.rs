@@ -0,0 +1,99 @@
+use anyhow::Result;
+use log::warn;
+
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+
+use crate::s3::s3_operator::S3Operator;
+
+/// Finds the expected tables that have no LOAD file in S3,
+/// e.g. because a misconfigured DMS task did a full load of only a subset of the tables.
+///
+/// # Arguments
+///
+/// * `cdc_operator_snapshot_payload` - The payload describing the Parquet files in S3.
+/// * `expected_tables` - The tables that are expected to have a full load.
+/// * `s3_operator` - The operator to list the Parquet files.
+///
+/// # Returns
+///
+/// The expected tables without any LOAD file.
+pub async fn find_tables_without_load_files(
+    cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+    expected_tables: &[String],
+    s3_operator: &(impl S3Operator + Sync),
+) -> Result<Vec<String>> {
+    let mut tables_without_load_files = Vec::new();
+
+    for table_name in expected_tables {
+        let load_parquet_files_payload =
+            cdc_operator_snapshot_payload.load_parquet_files_payload(table_name);
+        let parquet_files = s3_operator
+            .get_list_of_parquet_files_from_s3(&load_parquet_files_payload)
+            .await?;
+
+        if !parquet_files.iter().any(|file| file.is_load_file()) {
+            warn!("No LOAD file found for table: {}", table_name);
+            tables_without_load_files.push(table_name.clone());
+        }
+    }
+
+    Ok(tables_without_load_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::s3::s3_operator::{LoadParquetFilesPayload, MockS3Operator, S3ParquetFile};
+
+    #[tokio::test]
+    async fn test_table_without_load_file_is_flagged() {
+        let payload = CDCOperatorSnapshotPayload::new(
+            "bucket_name",
+            "prefix",
+            "database_name",
+            "public",
+            Vec::<String>::new(),
+            Vec::<String>::new(),
+            ModeValueEnum::DateAware,
+            Some("2024-02-14T10:00:00Z".to_string()),
+            None,
+            "source_postgres_url".to_string(),
+            "target_postgres_url".to_string(),
+        );
+
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .times(3)
+            .returning(|payload| match payload {
+                LoadParquetFilesPayload::DateAware { table_name, .. } if table_name == "users" => {
+                    Ok(vec![S3ParquetFile::new(
+                        "prefix/database_name/public/users/2024/02/14/20240214-100000000.parquet",
+                    )])
+                }
+                LoadParquetFilesPayload::DateAware { table_name, .. } => Ok(vec![
+                    S3ParquetFile::new(format!(
+                        "prefix/database_name/public/{table_name}/LOAD00000001.parquet"
+                    )),
+                    S3ParquetFile::new(format!(
+                        "prefix/database_name/public/{table_name}/2024/02/14/20240214-100000000.parquet"
+                    )),
+                ]),
+                _ => unreachable!(),
+            });
+
+        let expected_tables = vec![
+            "orders".to_string(),
+            "users".to_string(),
+            "products".to_string(),
+        ];
+
+        let tables_without_load_files =
+            find_tables_without_load_files(&payload, &expected_tables, &s3_operator)
+                .await
+                .unwrap();
+
+        assert_eq!(tables_without_load_files, vec!["users"]);
+    }
+}
@@ -0,0 +1,81 @@
+use aws_sdk_s3::primitives::DateTime;
+
+use crate::s3::s3_operator::S3ParquetFile;
+
+/// Represents the metrics of the files loaded for a table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadMetrics {
+    pub table_name: String,
+    pub files_processed: usize,
+    pub min_last_modified: Option<DateTime>,
+    pub max_last_modified: Option<DateTime>,
+}
+
+impl LoadMetrics {
+    /// Creates the load metrics from the files processed for a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table.
+    /// * `files` - The files processed for the table.
+    ///
+    /// # Returns
+    ///
+    /// The load metrics, with the oldest and newest last modified date among the files.
+    pub fn from_files(table_name: impl Into<String>, files: &[S3ParquetFile]) -> Self {
+        let mut min_last_modified: Option<DateTime> = None;
+        let mut max_last_modified: Option<DateTime> = None;
+
+        for last_modified in files.iter().filter_map(|file| file.last_modified) {
+            min_last_modified = match min_last_modified {
+                Some(min) if min <= last_modified => Some(min),
+                _ => Some(last_modified),
+            };
+            max_last_modified = match max_last_modified {
+                Some(max) if max >= last_modified => Some(max),
+                _ => Some(last_modified),
+            };
+        }
+
+        Self {
+            table_name: table_name.into(),
+            files_processed: files.len(),
+            min_last_modified,
+            max_last_modified,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::primitives::DateTimeFormat;
+
+    fn date(value: &str) -> Option<DateTime> {
+        Some(DateTime::from_str(value, DateTimeFormat::DateTimeWithOffset).unwrap())
+    }
+
+    #[test]
+    fn test_from_files_reports_last_modified_window() {
+        let files = vec![
+            S3ParquetFile::new_with_last_modified("LOAD00000001", date("2024-02-10T00:00:00Z")),
+            S3ParquetFile::new_with_last_modified("cdc1", date("2024-02-14T10:00:00Z")),
+            S3ParquetFile::new_with_last_modified("cdc2", date("2024-02-12T08:30:00Z")),
+            S3ParquetFile::new("no_metadata"),
+        ];
+
+        let metrics = LoadMetrics::from_files("table", &files);
+
+        assert_eq!(metrics.files_processed, 4);
+        assert_eq!(metrics.min_last_modified, date("2024-02-10T00:00:00Z"));
+        assert_eq!(metrics.max_last_modified, date("2024-02-14T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_from_files_without_last_modified() {
+        let metrics = LoadMetrics::from_files("table", &[S3ParquetFile::new("file")]);
+
+        assert_eq!(metrics.min_last_modified, None);
+        assert_eq!(metrics.max_last_modified, None);
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use log::info;
+
+use crate::postgres::postgres_operator::PostgresOperator;
+
+/// Represents what to do when the schema does not exist in the target database,
+/// which otherwise makes the queries against its tables fail with confusing errors.
+///
+/// The policy can be one of the following:
+///
+/// * Create - The schema is created.
+/// * Error - The run fails with an error naming the schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingSchemaPolicy {
+    #[default]
+    Create,
+    Error,
+}
+
+impl MissingSchemaPolicy {
+    /// Checks that a schema exists in the target database, applying the policy if it does not.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_postgres_operator` - The operator of the target database.
+    /// * `schema_name` - The name of the schema.
+    ///
+    /// # Returns
+    ///
+    /// An error if the schema does not exist and the policy is Error, or if it cannot be created.
+    pub async fn ensure_schema(
+        &self,
+        target_postgres_operator: &(impl PostgresOperator + Sync),
+        schema_name: &str,
+    ) -> Result<()> {
+        if target_postgres_operator.schema_exists(schema_name).await? {
+            return Ok(());
+        }
+
+        match self {
+            MissingSchemaPolicy::Create => {
+                info!(
+                    "Schema {} does not exist in the target DB, creating it",
+                    schema_name
+                );
+                target_postgres_operator.create_schema(schema_name).await
+            }
+            MissingSchemaPolicy::Error => Err(anyhow!(
+                "Schema {} does not exist in the target DB",
+                schema_name
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use mockall::predicate::*;
+
+    fn missing_schema_operator() -> MockPostgresOperator {
+        let mut target_postgres_operator = MockPostgresOperator::new();
+        target_postgres_operator
+            .expect_schema_exists()
+            .with(eq("scratch"))
+            .returning(|_| Ok(false));
+        target_postgres_operator
+    }
+
+    #[tokio::test]
+    async fn test_create_missing_schema() {
+        let mut target_postgres_operator = missing_schema_operator();
+        target_postgres_operator
+            .expect_create_schema()
+            .with(eq("scratch"))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        MissingSchemaPolicy::Create
+            .ensure_schema(&target_postgres_operator, "scratch")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_error_with_missing_schema() {
+        let mut target_postgres_operator = missing_schema_operator();
+        target_postgres_operator.expect_create_schema().times(0);
+
+        let result = MissingSchemaPolicy::Error
+            .ensure_schema(&target_postgres_operator, "scratch")
+            .await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Schema scratch does not exist in the target DB"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_existing_schema_is_left_as_is() {
+        let mut target_postgres_operator = MockPostgresOperator::new();
+        target_postgres_operator
+            .expect_schema_exists()
+            .returning(|_| Ok(true));
+        target_postgres_operator.expect_create_schema().times(0);
+
+        MissingSchemaPolicy::Error
+            .ensure_schema(&target_postgres_operator, "public")
+            .await
+            .unwrap();
+    }
+}
@@ -1,5 +1,22 @@
+pub mod apply_mode;
 pub mod cdc_operator;
 pub mod cdc_operator_mode;
 pub mod cdc_operator_payload;
+pub mod event_log;
+pub mod file_pipeline;
+pub mod load_file_coverage;
+pub mod load_metrics;
+pub mod missing_schema_policy;
+pub mod multi_source;
+pub mod no_files_policy;
+pub mod operation_filter;
+pub mod prefix_comparison;
+pub mod report_upload;
+pub mod row_count_expectation;
+pub mod run_budget;
+pub mod run_context;
 pub mod snapshot_payload;
+pub mod table_name_transform;
 pub mod validate_payload;
+pub mod validation_plan;
+pub mod validation_report;
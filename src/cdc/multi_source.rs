@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Result};
+use log::info;
+use polars::prelude::*;
+
+use super::prefix_comparison::{read_cdc_rows_from_s3, replay_cdc_rows};
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+
+use crate::dataframe::dataframe_ops::DataframeOperator;
+use crate::s3::s3_operator::S3Operator;
+
+/// Represents a source of the Parquet files of a table, e.g. the output of DMS in one region,
+/// along with the operators reading it, which may use a client of a different region.
+pub struct ParquetSource<'a, S, D> {
+    pub payload: &'a CDCOperatorSnapshotPayload,
+    pub s3_operator: &'a S,
+    pub dataframe_operator: &'a D,
+}
+
+/// Reads the Parquet files of a table from multiple sources, e.g. DMS running in two regions
+/// for resilience, and replays their union into the final state of the table.
+///
+/// # Arguments
+///
+/// * `sources` - The sources of the Parquet files.
+/// * `table_name` - The name of the table in the S3 paths.
+/// * `primary_key` - The primary key columns of the table.
+///
+/// # Returns
+///
+/// A DataFrame with the final state of the table.
+pub async fn read_table_from_sources<S, D>(
+    sources: &[ParquetSource<'_, S, D>],
+    table_name: &str,
+    primary_key: &[String],
+) -> Result<DataFrame>
+where
+    S: S3Operator + Sync,
+    D: DataframeOperator + Sync,
+{
+    let Some(first_source) = sources.first() else {
+        return Err(anyhow!("At least one source is required"));
+    };
+
+    let mut source_rows = Vec::new();
+    for source in sources {
+        if let Some(rows) = read_cdc_rows_from_s3(
+            source.payload,
+            table_name,
+            source.s3_operator,
+            source.dataframe_operator,
+        )
+        .await?
+        {
+            source_rows.push(rows);
+        }
+    }
+    if source_rows.is_empty() {
+        return Err(anyhow!("No Parquet files found for table {}", table_name));
+    }
+
+    let rows = merge_cdc_rows(
+        source_rows,
+        primary_key,
+        first_source.payload.sequence_column.as_str(),
+    )?;
+    replay_cdc_rows(rows, primary_key)
+}
+
+/// Merges the rows of the Parquet files of multiple sources, keeping a single copy of the
+/// operations present in more than one source, i.e. with the same key and sequence, and
+/// ordering the operations by sequence.
+///
+/// # Arguments
+///
+/// * `source_rows` - The rows of each source, in the order they were written.
+/// * `primary_key` - The primary key columns of the table.
+/// * `sequence_column` - The column ordering the operations, e.g. `_dms_ingestion_timestamp`.
+///
+/// # Returns
+///
+/// The deduplicated rows of all the sources.
+pub fn merge_cdc_rows(
+    source_rows: Vec<DataFrame>,
+    primary_key: &[String],
+    sequence_column: &str,
+) -> Result<DataFrame> {
+    let mut source_rows = source_rows.into_iter();
+    let Some(mut rows) = source_rows.next() else {
+        return Err(anyhow!("At least one source is required"));
+    };
+    for other_rows in source_rows {
+        rows.vstack_mut(&other_rows)?;
+    }
+
+    let operation_key = primary_key
+        .iter()
+        .cloned()
+        .chain([sequence_column.to_string()])
+        .collect::<Vec<_>>();
+    let row_count = rows.height();
+    let rows = rows.unique_stable(Some(&operation_key), UniqueKeepStrategy::First, None)?;
+    info!(
+        "Removed {} operations present in more than one source",
+        row_count - rows.height()
+    );
+
+    // The sort is stable, so the operations with the same sequence keep the order of their files
+    Ok(rows.sort(
+        [sequence_column],
+        SortMultipleOptions::default().with_maintain_order(true),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::dataframe::dataframe_ops::MockDataframeOperator;
+    use crate::s3::s3_operator::{MockS3Operator, S3ParquetFile};
+
+    fn payload(key: &str) -> CDCOperatorSnapshotPayload {
+        CDCOperatorSnapshotPayload::new(
+            "bucket_name",
+            key,
+            "database_name",
+            "public",
+            vec!["table_name"],
+            Vec::<String>::new(),
+            ModeValueEnum::AbsolutePath,
+            None,
+            None,
+            "source_postgres_url".to_string(),
+            "target_postgres_url".to_string(),
+        )
+    }
+
+    fn region_operators(rows: DataFrame) -> (MockS3Operator, MockDataframeOperator) {
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .returning(|_| Ok(vec![S3ParquetFile::new("20240101-000000000.parquet")]));
+
+        let mut dataframe_operator = MockDataframeOperator::new();
+        dataframe_operator
+            .expect_create_dataframe_from_parquet_file()
+            .returning(move |_| Ok(Some(rows.clone())));
+
+        (s3_operator, dataframe_operator)
+    }
+
+    #[test]
+    fn test_merge_cdc_rows_deduplicates_operations() {
+        let region_a = df!(
+            "Op" => &["I", "U"],
+            "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01", "2024-01-01 00:00:03"],
+            "id" => &[1i64, 1],
+            "name" => &["a", "b"]
+        )
+        .unwrap();
+        let region_b = df!(
+            "Op" => &["I", "U"],
+            "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01", "2024-01-01 00:00:02"],
+            "id" => &[1i64, 1],
+            "name" => &["a", "c"]
+        )
+        .unwrap();
+
+        let rows = merge_cdc_rows(
+            vec![region_a, region_b],
+            &["id".to_string()],
+            "_dms_ingestion_timestamp",
+        )
+        .unwrap();
+
+        let names = rows.column("name").unwrap().str().unwrap();
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![Some("a"), Some("c"), Some("b")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_table_from_two_regions_with_overlapping_operation() {
+        let (s3_operator_a, dataframe_operator_a) = region_operators(
+            df!(
+                "Op" => &["I", "I"],
+                "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01", "2024-01-01 00:00:02"],
+                "id" => &[1i64, 2],
+                "name" => &["a", "b"]
+            )
+            .unwrap(),
+        );
+        let (s3_operator_b, dataframe_operator_b) = region_operators(
+            df!(
+                "Op" => &["I", "D"],
+                "_dms_ingestion_timestamp" => &["2024-01-01 00:00:02", "2024-01-01 00:00:03"],
+                "id" => &[2i64, 1],
+                "name" => &["b", "a"]
+            )
+            .unwrap(),
+        );
+        let payload_a = payload("region_a");
+        let payload_b = payload("region_b");
+        let sources = [
+            ParquetSource {
+                payload: &payload_a,
+                s3_operator: &s3_operator_a,
+                dataframe_operator: &dataframe_operator_a,
+            },
+            ParquetSource {
+                payload: &payload_b,
+                s3_operator: &s3_operator_b,
+                dataframe_operator: &dataframe_operator_b,
+            },
+        ];
+
+        let df = read_table_from_sources(&sources, "table_name", &["id".to_string()])
+            .await
+            .unwrap();
+
+        let expected = df!("id" => &[2i64], "name" => &["b"]).unwrap();
+        assert!(df.equals(&expected));
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+
+use crate::s3::s3_operator::S3ParquetFile;
+
+/// Represents what to do when no Parquet files are found for a table,
+/// which usually signals a wrong prefix or date range.
+///
+/// The policy can be one of the following:
+///
+/// * Warn - A warning is logged and the table is processed without files.
+/// * Error - The run fails.
+/// * Ok - The table is processed without files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NoFilesPolicy {
+    #[default]
+    Warn,
+    Error,
+    Ok,
+}
+
+impl NoFilesPolicy {
+    /// Checks the Parquet files found for a table against the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table.
+    /// * `parquet_files` - The Parquet files found for the table.
+    ///
+    /// # Returns
+    ///
+    /// An error if no files were found and the policy is Error.
+    pub fn check(&self, table_name: &str, parquet_files: &[S3ParquetFile]) -> Result<()> {
+        if !parquet_files.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            NoFilesPolicy::Warn => {
+                warn!("No Parquet files found for table {}", table_name);
+                Ok(())
+            }
+            NoFilesPolicy::Error => Err(anyhow!("No Parquet files found for table {}", table_name)),
+            NoFilesPolicy::Ok => {
+                debug!("No Parquet files found for table {}", table_name);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warn_with_no_files() {
+        assert!(NoFilesPolicy::Warn.check("table_name", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_error_with_no_files() {
+        let result = NoFilesPolicy::Error.check("table_name", &[]);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No Parquet files found for table table_name"
+        );
+    }
+
+    #[test]
+    fn test_ok_with_no_files() {
+        assert!(NoFilesPolicy::Ok.check("table_name", &[]).is_ok());
+    }
+
+    #[test]
+    fn test_error_with_files() {
+        let parquet_files = vec![S3ParquetFile::new("table_name/LOAD00000001.parquet")];
+
+        assert!(NoFilesPolicy::Error
+            .check("table_name", &parquet_files)
+            .is_ok());
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Represents the type of an operation of the CDC files, as written in their `Op` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    /// Gets all the operation types.
+    pub fn all() -> HashSet<Operation> {
+        HashSet::from([Operation::Insert, Operation::Update, Operation::Delete])
+    }
+
+    /// Gets the value of the `Op` column of the operation type.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Operation::Insert => "I",
+            Operation::Update => "U",
+            Operation::Delete => "D",
+        }
+    }
+}
+
+/// Represents the number of operations of each type skipped as they are not allowed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedOperations {
+    pub inserts: usize,
+    pub updates: usize,
+    pub deletes: usize,
+}
+
+impl SkippedOperations {
+    /// Checks if no operation was skipped.
+    pub fn is_empty(&self) -> bool {
+        self.inserts == 0 && self.updates == 0 && self.deletes == 0
+    }
+
+    /// Adds the operations skipped from another CDC file.
+    pub fn add(&mut self, other: &SkippedOperations) {
+        self.inserts += other.inserts;
+        self.updates += other.updates;
+        self.deletes += other.deletes;
+    }
+}
+
+/// Filters the rows of a CDC file down to the allowed operation types, e.g. to only apply
+/// the inserts of the CDC files for an append-only validation.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of the CDC file, including the Op column.
+/// * `allowed_operations` - The operation types to apply.
+///
+/// # Returns
+///
+/// The rows of the allowed operation types, and the number of skipped operations of each type.
+pub fn filter_operations(
+    df: DataFrame,
+    allowed_operations: &HashSet<Operation>,
+) -> Result<(DataFrame, SkippedOperations)> {
+    if allowed_operations.len() == Operation::all().len() {
+        return Ok((df, SkippedOperations::default()));
+    }
+
+    let ops = df.column("Op")?.str()?;
+    let mut skipped_operations = SkippedOperations::default();
+    let mut is_allowed = BooleanChunked::full("", false, df.height());
+    for operation in Operation::all() {
+        let is_operation = ops.equal(operation.code());
+        if allowed_operations.contains(&operation) {
+            is_allowed = &is_allowed | &is_operation;
+            continue;
+        }
+
+        let skipped = is_operation
+            .into_iter()
+            .filter(|is_operation| *is_operation == Some(true))
+            .count();
+        match operation {
+            Operation::Insert => skipped_operations.inserts = skipped,
+            Operation::Update => skipped_operations.updates = skipped,
+            Operation::Delete => skipped_operations.deletes = skipped,
+        }
+    }
+
+    Ok((df.filter(&is_allowed)?, skipped_operations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cdc_rows() -> DataFrame {
+        df!(
+            "Op" => &["I", "U", "I", "D", "U"],
+            "id" => &[1i64, 1, 2, 2, 3]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_inserts_are_applied() {
+        let (df, skipped_operations) =
+            filter_operations(cdc_rows(), &HashSet::from([Operation::Insert])).unwrap();
+
+        let ids = df.column("id").unwrap().i64().unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+        assert_eq!(
+            skipped_operations,
+            SkippedOperations {
+                inserts: 0,
+                updates: 2,
+                deletes: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_all_operations_are_applied_by_default() {
+        let (df, skipped_operations) = filter_operations(cdc_rows(), &Operation::all()).unwrap();
+
+        assert_eq!(df.height(), 5);
+        assert!(skipped_operations.is_empty());
+    }
+}
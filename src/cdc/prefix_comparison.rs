@@ -0,0 +1,397 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use indexmap::IndexMap;
+use log::info;
+use polars::prelude::*;
+
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+
+use crate::dataframe::dataframe_diff::{DataFrameDiff, DataframeDiffer};
+use crate::dataframe::dataframe_ops::DataframeOperator;
+use crate::dataframe::rollup_hash::{parallel_rollup_hash, rollup_hash, RollupHash};
+use crate::dataframe::schema_alignment::SchemaAligner;
+use crate::s3::s3_operator::S3Operator;
+
+/// Compares the data of two S3 prefixes, e.g. the outputs of two DMS tasks for the same source,
+/// table by table and without involving a database.
+///
+/// The rollup hashes of the two sides are compared first, and only the tables whose rollups
+/// differ are compared row by row.
+///
+/// # Arguments
+///
+/// * `left_payload` - The payload describing the first S3 prefix.
+/// * `right_payload` - The payload describing the second S3 prefix.
+/// * `primary_keys` - The tables to compare, along with their primary key columns.
+/// * `s3_operator` - The operator to list the Parquet files.
+/// * `dataframe_operator` - The operator to read the Parquet files.
+/// * `differ` - The differ of the resulting DataFrames.
+///
+/// # Returns
+///
+/// The differences per table.
+pub async fn compare_s3_prefixes(
+    left_payload: &CDCOperatorSnapshotPayload,
+    right_payload: &CDCOperatorSnapshotPayload,
+    primary_keys: &IndexMap<String, Vec<String>>,
+    s3_operator: &(impl S3Operator + Sync),
+    dataframe_operator: &(impl DataframeOperator + Sync),
+    differ: &DataframeDiffer,
+) -> Result<IndexMap<String, DataFrameDiff>> {
+    info!("{}", "Starting comparison of S3 prefixes...".bold().blue());
+
+    let mut diffs = IndexMap::new();
+
+    for (table_name, primary_key) in primary_keys {
+        info!(
+            "{}",
+            format!("Comparing table: {}", table_name).bold().magenta()
+        );
+
+        let left_df = read_table_from_s3(
+            left_payload,
+            table_name,
+            primary_key,
+            s3_operator,
+            dataframe_operator,
+        )
+        .await?;
+        let right_df = read_table_from_s3(
+            right_payload,
+            table_name,
+            primary_key,
+            s3_operator,
+            dataframe_operator,
+        )
+        .await?;
+
+        // Equal rollup hashes imply equal tables, so the detailed comparison is skipped
+        let table_hash =
+            |payload: &CDCOperatorSnapshotPayload, df: &DataFrame| -> Result<RollupHash> {
+                match payload.hash_threads {
+                    Some(threads) => parallel_rollup_hash(df, threads),
+                    None => rollup_hash(df),
+                }
+            };
+        let diff = if table_hash(left_payload, &left_df)? == table_hash(right_payload, &right_df)? {
+            DataFrameDiff::default()
+        } else {
+            differ.diff(&left_df, &right_df, primary_key)?
+        };
+        info!("Differences for table {}: {}", table_name, diff.len());
+
+        diffs.insert(table_name.clone(), diff);
+    }
+
+    info!("{}", "Comparison of S3 prefixes completed...".bold().blue());
+
+    Ok(diffs)
+}
+
+/// Reads the Parquet files of a table from S3 and replays them into the final state of the table.
+///
+/// # Arguments
+///
+/// * `payload` - The payload describing the S3 prefix.
+/// * `table_name` - The name of the table in the S3 path.
+/// * `primary_key` - The primary key columns of the table.
+/// * `s3_operator` - The operator to list the Parquet files.
+/// * `dataframe_operator` - The operator to read the Parquet files.
+///
+/// # Returns
+///
+/// A DataFrame with the final state of the table.
+pub async fn read_table_from_s3(
+    payload: &CDCOperatorSnapshotPayload,
+    table_name: &str,
+    primary_key: &[String],
+    s3_operator: &(impl S3Operator + Sync),
+    dataframe_operator: &(impl DataframeOperator + Sync),
+) -> Result<DataFrame> {
+    match read_cdc_rows_from_s3(payload, table_name, s3_operator, dataframe_operator).await? {
+        Some(table_df) => replay_cdc_rows(table_df, primary_key),
+        None => Err(anyhow!("No Parquet files found for table {}", table_name)),
+    }
+}
+
+/// Reads the rows of all the Parquet files of a table from S3, in the order they were written,
+/// without replaying them.
+///
+/// The rows of the LOAD files, which DMS writes without the `Op` column, are read as inserts,
+/// and the files are aligned to the union of their columns, so that they can be stacked.
+///
+/// # Arguments
+///
+/// * `payload` - The payload describing the S3 prefix.
+/// * `table_name` - The name of the table in the S3 path.
+/// * `s3_operator` - The operator to list the Parquet files.
+/// * `dataframe_operator` - The operator to read the Parquet files.
+///
+/// # Returns
+///
+/// The rows of the Parquet files, or `None` if no file was found.
+pub async fn read_cdc_rows_from_s3(
+    payload: &CDCOperatorSnapshotPayload,
+    table_name: &str,
+    s3_operator: &(impl S3Operator + Sync),
+    dataframe_operator: &(impl DataframeOperator + Sync),
+) -> Result<Option<DataFrame>> {
+    let load_parquet_files_payload = payload.load_parquet_files_payload(table_name);
+    let parquet_files = s3_operator
+        .get_list_of_parquet_files_from_s3(&load_parquet_files_payload)
+        .await?;
+
+    let mut schema_aligner = SchemaAligner::new();
+    let mut file_dfs = Vec::new();
+    for file in &parquet_files {
+        let create_dataframe_payload = payload.create_dataframe_payload(table_name, file);
+
+        let Some(current_df) = dataframe_operator
+            .create_dataframe_from_parquet_file(&create_dataframe_payload)
+            .await?
+        else {
+            continue;
+        };
+        let current_df = payload.transform_dataframe(current_df)?;
+        let current_df = payload.filter_key_range(current_df)?;
+        let current_df = payload.filter_as_of(file, current_df)?;
+        let (current_df, _) = schema_aligner.align(with_dms_op_column(current_df)?)?;
+
+        file_dfs.push(current_df);
+    }
+
+    // The files read before a column was added lack it, so they are aligned again to the full union
+    let mut table_df: Option<DataFrame> = None;
+    for current_df in file_dfs {
+        let (current_df, _) = schema_aligner.align(current_df)?;
+
+        match table_df.as_mut() {
+            Some(table_df) => {
+                table_df.vstack_mut(&current_df)?;
+            }
+            None => table_df = Some(current_df),
+        }
+    }
+
+    Ok(table_df)
+}
+
+/// Adds the `Op` column to the rows of a LOAD file as inserts, and drops the ingestion timestamp,
+/// which not all the files have, so that the rows of the LOAD and CDC files have the same DMS columns.
+fn with_dms_op_column(mut df: DataFrame) -> Result<DataFrame> {
+    if df.column("Op").is_err() {
+        df.insert_column(0, Series::new("Op", vec!["I"; df.height()]))?;
+    }
+    if df.column("_dms_ingestion_timestamp").is_ok() {
+        _ = df.drop_in_place("_dms_ingestion_timestamp")?;
+    }
+
+    Ok(df)
+}
+
+/// Replays the rows of the Parquet files of a table, in the order they were written,
+/// keeping the latest row of each primary key unless it was deleted.
+///
+/// # Arguments
+///
+/// * `df` - The rows of all the Parquet files of a table.
+/// * `primary_key` - The primary key columns of the table.
+///
+/// # Returns
+///
+/// A DataFrame with the final state of the table, without the columns added by DMS.
+pub fn replay_cdc_rows(df: DataFrame, primary_key: &[String]) -> Result<DataFrame> {
+    let mut df = df.unique_stable(Some(primary_key), UniqueKeepStrategy::Last, None)?;
+
+    if let Ok(op) = df.column("Op") {
+        let is_not_delete = op.str()?.not_equal("D");
+        df = df.filter(&is_not_delete)?;
+        _ = df.drop_in_place("Op")?;
+    }
+    if df.column("_dms_ingestion_timestamp").is_ok() {
+        _ = df.drop_in_place("_dms_ingestion_timestamp")?;
+    }
+
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::dataframe::dataframe_diff::DiffType;
+    use crate::dataframe::dataframe_ops::MockDataframeOperator;
+    use crate::s3::s3_operator::{LoadParquetFilesPayload, MockS3Operator, S3ParquetFile};
+
+    fn payload(key: &str) -> CDCOperatorSnapshotPayload {
+        CDCOperatorSnapshotPayload::new(
+            "bucket_name",
+            key,
+            "database_name",
+            "public",
+            vec!["table_name"],
+            Vec::<String>::new(),
+            ModeValueEnum::AbsolutePath,
+            None,
+            None,
+            "source_postgres_url".to_string(),
+            "target_postgres_url".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_replay_cdc_rows() {
+        let df = df!(
+            "Op" => &["I", "I", "U", "D"],
+            "id" => &[1i64, 2, 1, 2],
+            "name" => &["a", "b", "c", "b"]
+        )
+        .unwrap();
+
+        let replayed = replay_cdc_rows(df, &["id".to_string()]).unwrap();
+
+        let expected = df!("id" => &[1i64], "name" => &["c"]).unwrap();
+        assert!(replayed.equals(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_read_table_from_s3_reads_pinned_version() {
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .returning(|_| {
+                Ok(vec![S3ParquetFile::new("task_a/LOAD00000001.parquet")
+                    .with_version_id(Some("3HL4kqtJlcpXroDTDmJ".to_string()))])
+            });
+
+        let mut dataframe_operator = MockDataframeOperator::new();
+        dataframe_operator
+            .expect_create_dataframe_from_parquet_file()
+            .withf(|payload| {
+                payload.key == "task_a/LOAD00000001.parquet"
+                    && payload.version_id.as_deref() == Some("3HL4kqtJlcpXroDTDmJ")
+            })
+            .times(1)
+            .returning(|_| Ok(Some(df!("Op" => &["I"], "id" => &[1i64]).unwrap())));
+
+        let df = read_table_from_s3(
+            &payload("task_a"),
+            "table_name",
+            &["id".to_string()],
+            &s3_operator,
+            &dataframe_operator,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(df.height(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_table_from_s3_with_load_file_without_op_column() {
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .returning(|_| {
+                Ok(vec![
+                    S3ParquetFile::new("task_a/LOAD00000001.parquet"),
+                    S3ParquetFile::new("task_a/20240101-000000000.parquet"),
+                ])
+            });
+
+        let mut dataframe_operator = MockDataframeOperator::new();
+        dataframe_operator
+            .expect_create_dataframe_from_parquet_file()
+            .returning(|payload| {
+                let df = match payload.key.as_str() {
+                    "task_a/LOAD00000001.parquet" => df!(
+                        "id" => &[1i64, 2],
+                        "name" => &["a", "b"]
+                    ),
+                    _ => df!(
+                        "Op" => &["U", "D"],
+                        "_dms_ingestion_timestamp" => &["2024-01-01 00:00:00", "2024-01-01 00:00:01"],
+                        "id" => &[1i64, 2],
+                        "name" => &["c", "b"],
+                        "email" => &["c@example.com", "b@example.com"]
+                    ),
+                };
+                Ok(Some(df.unwrap()))
+            });
+
+        let df = read_table_from_s3(
+            &payload("task_a"),
+            "table_name",
+            &["id".to_string()],
+            &s3_operator,
+            &dataframe_operator,
+        )
+        .await
+        .unwrap();
+
+        let expected = df!(
+            "id" => &[1i64],
+            "name" => &["c"],
+            "email" => &["c@example.com"]
+        )
+        .unwrap();
+        assert!(df.equals(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_compare_s3_prefixes_differ_by_one_row() {
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .returning(|payload| match payload {
+                LoadParquetFilesPayload::AbsolutePath(key) => Ok(vec![
+                    S3ParquetFile::new(format!("{key}/LOAD00000001.parquet")),
+                    S3ParquetFile::new(format!("{key}/20240101-000000000.parquet")),
+                ]),
+                _ => unreachable!(),
+            });
+
+        let mut dataframe_operator = MockDataframeOperator::new();
+        dataframe_operator
+            .expect_create_dataframe_from_parquet_file()
+            .returning(|payload| {
+                let df = match payload.key.as_str() {
+                    "task_a/LOAD00000001.parquet" | "task_b/LOAD00000001.parquet" => df!(
+                        "Op" => &["I", "I"],
+                        "id" => &[1i64, 2],
+                        "name" => &["a", "b"]
+                    ),
+                    "task_a/20240101-000000000.parquet" => df!(
+                        "Op" => &["U"],
+                        "id" => &[2i64],
+                        "name" => &["c"]
+                    ),
+                    _ => df!(
+                        "Op" => &["U"],
+                        "id" => &[2i64],
+                        "name" => &["d"]
+                    ),
+                };
+                Ok(Some(df.unwrap()))
+            });
+
+        let primary_keys = IndexMap::from([("table_name".to_string(), vec!["id".to_string()])]);
+
+        let diffs = compare_s3_prefixes(
+            &payload("task_a"),
+            &payload("task_b"),
+            &primary_keys,
+            &s3_operator,
+            &dataframe_operator,
+            &DataframeDiffer::default(),
+        )
+        .await
+        .unwrap();
+
+        let diff = diffs.get("table_name").unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.rows[0].primary_key, "2");
+        assert_eq!(diff.rows[0].column.as_deref(), Some("name"));
+        assert_eq!(diff.rows[0].diff_type, DiffType::ValueMismatch);
+    }
+}
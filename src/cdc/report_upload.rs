@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::info;
+
+use super::run_context::RunContext;
+use super::validation_report::ValidationReport;
+use crate::s3::s3_operator::S3Operator;
+
+/// Builds the S3 key of the report of a run, so that the reports of different runs, and of
+/// different attempts of the same run, do not overwrite each other.
+///
+/// # Arguments
+///
+/// * `prefix` - The prefix under which the reports are archived.
+/// * `run_context` - The identity of the run.
+/// * `uploaded_at` - The time of the upload.
+///
+/// # Returns
+///
+/// The key of the report, e.g. `reports/nightly-42/20240101T000000Z.json`.
+pub fn report_key(prefix: &str, run_context: &RunContext, uploaded_at: DateTime<Utc>) -> String {
+    format!(
+        "{}/{}/{}.json",
+        prefix.trim_end_matches('/'),
+        run_context.run_id(),
+        uploaded_at.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+/// Uploads the JSON report of a run to S3, for archival.
+///
+/// # Arguments
+///
+/// * `s3_operator` - The operator to upload the report.
+/// * `report` - The report of the run.
+/// * `bucket_name` - The name of the S3 bucket.
+/// * `key` - The key of the report.
+///
+/// # Returns
+///
+/// A Result indicating success or failure.
+pub async fn upload_report(
+    s3_operator: &(impl S3Operator + Sync),
+    report: &ValidationReport,
+    bucket_name: &str,
+    key: &str,
+) -> Result<()> {
+    let body = report.to_json()?;
+    s3_operator
+        .put_object(bucket_name, key, body.into_bytes(), "application/json")
+        .await?;
+    info!(
+        "Uploaded report of run {} to s3://{bucket_name}/{key}",
+        report.run_context
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::validation_report::TableReport;
+    use crate::s3::s3_operator::MockS3Operator;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn run_context() -> RunContext {
+        RunContext::new(Some("nightly-42".to_string()), HashMap::new())
+    }
+
+    #[test]
+    fn test_report_key() {
+        let uploaded_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        assert_eq!(
+            report_key("reports/", &run_context(), uploaded_at),
+            "reports/nightly-42/20240102T030405Z.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_report() {
+        let mut report = ValidationReport::new(run_context());
+        report.add_table("public", TableReport::from_differing_rows("users", 2));
+        let expected_body = report.to_json().unwrap();
+
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_put_object()
+            .withf(move |bucket_name, key, body, content_type| {
+                bucket_name == "bucket_name"
+                    && key == "reports/nightly-42/20240102T030405Z.json"
+                    && body.as_slice() == expected_body.as_bytes()
+                    && content_type == "application/json"
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+
+        upload_report(
+            &s3_operator,
+            &report,
+            "bucket_name",
+            "reports/nightly-42/20240102T030405Z.json",
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use polars::prelude::*;
+
+use crate::s3::s3_operator::S3ParquetFile;
+
+/// Represents the operations applied to a table, to compute the number of rows it is expected
+/// to have once the LOAD and CDC files are applied, as an end-to-end check.
+///
+/// The expected row count is the number of rows of the LOAD files, plus the inserts of the CDC
+/// files, minus their deletes. The updates do not change the row count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowCountExpectation {
+    pub load_inserts: i64,
+    pub cdc_inserts: i64,
+    pub cdc_updates: i64,
+    pub cdc_deletes: i64,
+}
+
+impl RowCountExpectation {
+    /// Records the operations of a file applied to the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The Parquet file.
+    /// * `df` - The DataFrame of the file, as applied to the table.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub fn record(&mut self, file: &S3ParquetFile, df: &DataFrame) -> Result<()> {
+        if file.is_load_file() {
+            self.load_inserts += df.height() as i64;
+            return Ok(());
+        }
+
+        for op in df.column("Op")?.str()?.into_iter().flatten() {
+            match op {
+                "I" => self.cdc_inserts += 1,
+                "U" => self.cdc_updates += 1,
+                "D" => self.cdc_deletes += 1,
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the number of rows the table is expected to have.
+    pub fn expected_row_count(&self) -> i64 {
+        self.load_inserts + self.cdc_inserts - self.cdc_deletes
+    }
+
+    /// Gets the difference between the actual and the expected number of rows of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_count` - The actual number of rows of the table.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows the table has in excess, or in deficit if negative.
+    pub fn discrepancy(&self, row_count: i64) -> i64 {
+        row_count - self.expected_row_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_row_count_of_loads_and_cdc() {
+        let mut row_count_expectation = RowCountExpectation::default();
+        row_count_expectation
+            .record(
+                &S3ParquetFile::new("users/LOAD00000001.parquet"),
+                &df!("id" => &[1i64, 2, 3]).unwrap(),
+            )
+            .unwrap();
+        row_count_expectation
+            .record(
+                &S3ParquetFile::new("users/LOAD00000002.parquet"),
+                &df!("id" => &[4i64]).unwrap(),
+            )
+            .unwrap();
+        row_count_expectation
+            .record(
+                &S3ParquetFile::new("users/20240101-000000000.parquet"),
+                &df!(
+                    "Op" => &["I", "U", "D", "I"],
+                    "id" => &[5i64, 1, 2, 6]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        row_count_expectation
+            .record(
+                &S3ParquetFile::new("users/20240102-000000000.parquet"),
+                &df!(
+                    "Op" => &["U", "D"],
+                    "id" => &[5i64, 3]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            row_count_expectation,
+            RowCountExpectation {
+                load_inserts: 4,
+                cdc_inserts: 2,
+                cdc_updates: 2,
+                cdc_deletes: 2,
+            }
+        );
+        assert_eq!(row_count_expectation.expected_row_count(), 4);
+        assert_eq!(row_count_expectation.discrepancy(4), 0);
+        assert_eq!(row_count_expectation.discrepancy(3), -1);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+/// Represents the wall-clock budget of a run, e.g. for time-boxed CI checks.
+///
+/// The budget is only checked before a new file is processed, so the statements in flight
+/// complete and each file is either fully applied or not applied at all.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunBudget {
+    deadline: Option<Instant>,
+}
+
+impl RunBudget {
+    /// Starts the budget of a run.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_duration` - The maximum duration of the run, or None for no limit.
+    ///
+    /// # Returns
+    ///
+    /// A new run budget, starting now.
+    pub fn start(max_duration: Option<Duration>) -> Self {
+        Self {
+            deadline: max_duration.map(|max_duration| Instant::now() + max_duration),
+        }
+    }
+
+    /// Checks if the maximum duration of the run has been exceeded.
+    pub fn is_exhausted(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Iterates over the items to process, stopping before the next item once the budget is exhausted.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to process, e.g. the Parquet files of a table.
+    ///
+    /// # Returns
+    ///
+    /// An iterator over the items processed within the budget.
+    pub fn within<'a, T>(&'a self, items: &'a [T]) -> impl Iterator<Item = &'a T> + 'a {
+        items.iter().take_while(move |_| !self.is_exhausted())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_unlimited_budget_processes_all_files() {
+        let run_budget = RunBudget::start(None);
+
+        assert!(!run_budget.is_exhausted());
+        assert_eq!(run_budget.within(&["file1", "file2", "file3"]).count(), 3);
+    }
+
+    #[test]
+    fn test_exceeded_budget_stops_further_file_processing() {
+        let run_budget = RunBudget::start(Some(Duration::from_millis(20)));
+        let files = ["file1", "file2", "file3"];
+
+        let mut processed = Vec::new();
+        for file in run_budget.within(&files) {
+            // Processing the first file exceeds the budget
+            sleep(Duration::from_millis(50));
+            processed.push(*file);
+        }
+
+        assert!(run_budget.is_exhausted());
+        assert_eq!(processed, vec!["file1"]);
+    }
+
+    #[test]
+    fn test_zero_budget_processes_no_files() {
+        let run_budget = RunBudget::start(Some(Duration::ZERO));
+
+        assert_eq!(run_budget.within(&["file1"]).count(), 0);
+    }
+}
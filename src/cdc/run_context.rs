@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the identity of a run of the CDC Operator, used to correlate its logs and report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunContext {
+    run_id: String,
+    labels: HashMap<String, String>,
+}
+
+impl RunContext {
+    /// Creates a new run context.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_id` - The id of the run. A random UUID is generated if absent.
+    /// * `labels` - Arbitrary labels attached to the run.
+    ///
+    /// # Returns
+    ///
+    /// A new run context instance, whose run id stays the same for the duration of the run.
+    pub fn new(run_id: Option<String>, labels: HashMap<String, String>) -> Self {
+        Self {
+            run_id: run_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            labels,
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+}
+
+impl Default for RunContext {
+    fn default() -> Self {
+        Self::new(None, HashMap::new())
+    }
+}
+
+impl Display for RunContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut labels = self
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>();
+        labels.sort();
+
+        if labels.is_empty() {
+            write!(f, "run_id={}", self.run_id)
+        } else {
+            write!(f, "run_id={} {}", self.run_id, labels.join(" "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_id_is_generated_if_absent() {
+        let run_context = RunContext::default();
+
+        assert!(uuid::Uuid::parse_str(run_context.run_id()).is_ok());
+        assert_eq!(run_context.clone().run_id(), run_context.run_id());
+    }
+
+    #[test]
+    fn test_display_with_labels() {
+        let run_context = RunContext::new(
+            Some("run".to_string()),
+            HashMap::from([
+                ("team".to_string(), "data".to_string()),
+                ("env".to_string(), "prod".to_string()),
+            ]),
+        );
+
+        assert_eq!(run_context.to_string(), "run_id=run env=prod team=data");
+    }
+}
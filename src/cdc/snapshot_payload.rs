@@ -1,9 +1,45 @@
+use anyhow::Result;
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use indexmap::IndexMap;
+use polars::prelude::DataFrame;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use crate::dataframe::as_of::filter_as_of;
+use crate::dataframe::column_limit::ColumnLimit;
+use crate::dataframe::dataframe_ops::{CreateDataframePayload, RowCountMismatchSeverity};
+use crate::dataframe::dataframe_transform::DataframeTransform;
+use crate::dataframe::extra_source_column::ExtraSourceColumn;
+use crate::dataframe::invalid_utf8::InvalidUtf8Policy;
+use crate::dataframe::key_normalization::KeyNormalization;
+use crate::dataframe::key_range::KeyRange;
+use crate::dataframe::sampling::Sampling;
+use crate::dataframe::table_identifier::TableIdentifier;
+use crate::dataframe::unsupported_column::UnsupportedColumn;
+use crate::postgres::conflict_behavior::ConflictBehavior;
+use crate::postgres::copy_loader::InsertMethod;
+use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+use crate::postgres::schema_diff::SchemaStrictness;
+use crate::postgres::staging_load::LoadMode;
 use crate::postgres::table_mode::TableMode;
+use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+use crate::postgres::upsert_verification::UpsertVerification;
+use crate::s3::partition_listing::DEFAULT_LIST_CONCURRENCY;
+use crate::s3::path_style::PathStyle;
+use crate::s3::s3_operator::{LoadParquetFilesPayload, S3ParquetFile};
+use crate::s3::server_side_encryption::ServerSideEncryptionMode;
 
+use super::apply_mode::ApplyMode;
 use super::cdc_operator_mode::ModeValueEnum;
+use super::missing_schema_policy::MissingSchemaPolicy;
+use super::no_files_policy::NoFilesPolicy;
+use super::operation_filter::Operation;
+use super::run_context::RunContext;
+use super::table_name_transform::TableNameTransform;
+use super::validation_report::TableResultSender;
 
 #[allow(clippy::too_many_arguments)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CDCOperatorSnapshotPayload {
     pub bucket_name: String,
     pub key: String,
@@ -16,6 +52,71 @@ pub struct CDCOperatorSnapshotPayload {
     pub stop_date: Option<String>,
     pub source_postgres_url: String,
     pub target_postgres_url: String,
+    pub row_count_mismatch_severity: RowCountMismatchSeverity,
+    pub table_name_transform: TableNameTransform,
+    pub dataframe_transform: Option<DataframeTransform>,
+    pub skip_create_table_if_schema_matches: bool,
+    pub check_column_order: bool,
+    /// The size above which a Parquet file is streamed to disk before being decoded. Unset by
+    /// default, so that every file is read in memory, which is the fastest path as long as the
+    /// largest file fits in memory.
+    pub streaming_threshold_bytes: Option<i64>,
+    pub apply_mode: ApplyMode,
+    pub run_context: RunContext,
+    pub n_rows: Option<usize>,
+    pub unsupported_column: UnsupportedColumn,
+    pub conflict_behavior: ConflictBehavior,
+    pub insert_method: InsertMethod,
+    pub load_mode: LoadMode,
+    pub on_extra_source_column: ExtraSourceColumn,
+    pub on_no_files: NoFilesPolicy,
+    pub table_result_sender: TableResultSender,
+    pub requester_pays: bool,
+    pub key_range: Option<KeyRange>,
+    pub unmatched_delete_policy: UnmatchedDeletePolicy,
+    pub on_incomplete_delete: IncompleteDeletePolicy,
+    pub delete_batch_size: Option<usize>,
+    /// The size of the buffer used when writing the S3 body of a streamed file to disk. Unset by
+    /// default, which uses the 8 KiB buffer of `BufWriter`.
+    pub read_buffer_size: Option<usize>,
+    /// The number of rows decoded per batch from a streamed file. Unset by default, so that the file
+    /// is decoded in one pass, which avoids stacking the batches. A chunk size close to the row
+    /// group size of the files keeps the throughput of a single pass, while much smaller chunks add
+    /// an overhead per batch, as measured by the `read_parquet_chunk_sizes` benchmark.
+    pub chunk_size: Option<usize>,
+    pub include_load_outside_window: bool,
+    pub max_duration: Option<Duration>,
+    pub server_side_encryption: ServerSideEncryptionMode,
+    pub partitioned_listing: bool,
+    pub list_concurrency: usize,
+    pub sequence_column: String,
+    pub detect_duplicate_sequences: bool,
+    pub detect_clock_skew: bool,
+    pub path_style: PathStyle,
+    pub object_versions: bool,
+    pub report_prefix: Option<String>,
+    pub on_missing_schema: MissingSchemaPolicy,
+    pub as_of: Option<DateTime>,
+    pub column_limit: ColumnLimit,
+    pub advance_sequences: bool,
+    pub manifest_key: Option<String>,
+    pub include_stop_date: bool,
+    pub preserve_decimals: bool,
+    pub table_order: Option<Vec<String>>,
+    pub deferred_constraints: bool,
+    pub invalid_utf8: Option<InvalidUtf8Policy>,
+    pub hash_threads: Option<usize>,
+    pub schema_strictness: SchemaStrictness,
+    pub check_final_row_count: bool,
+    pub key_normalization: HashMap<String, KeyNormalization>,
+    pub upsert_verification: Option<UpsertVerification>,
+    pub list_start_after: bool,
+    pub copy_commit_rows: Option<usize>,
+    pub expect_default_columns: HashMap<String, Vec<String>>,
+    pub allowed_operations: HashSet<Operation>,
+    pub table_identifier: Option<TableIdentifier>,
+    pub storage_parameters: IndexMap<String, String>,
+    pub sampling: Option<Sampling>,
 }
 
 impl CDCOperatorSnapshotPayload {
@@ -45,6 +146,100 @@ impl CDCOperatorSnapshotPayload {
             stop_date,
             source_postgres_url,
             target_postgres_url,
+            row_count_mismatch_severity: RowCountMismatchSeverity::default(),
+            table_name_transform: TableNameTransform::default(),
+            dataframe_transform: None,
+            skip_create_table_if_schema_matches: false,
+            check_column_order: false,
+            streaming_threshold_bytes: None,
+            apply_mode: ApplyMode::default(),
+            run_context: RunContext::default(),
+            n_rows: None,
+            unsupported_column: UnsupportedColumn::default(),
+            conflict_behavior: ConflictBehavior::default(),
+            insert_method: InsertMethod::default(),
+            load_mode: LoadMode::default(),
+            on_extra_source_column: ExtraSourceColumn::default(),
+            on_no_files: NoFilesPolicy::default(),
+            table_result_sender: TableResultSender::default(),
+            requester_pays: false,
+            key_range: None,
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            on_incomplete_delete: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            read_buffer_size: None,
+            chunk_size: None,
+            include_load_outside_window: true,
+            max_duration: None,
+            server_side_encryption: ServerSideEncryptionMode::default(),
+            partitioned_listing: false,
+            list_concurrency: DEFAULT_LIST_CONCURRENCY,
+            sequence_column: "_dms_ingestion_timestamp".to_string(),
+            detect_duplicate_sequences: false,
+            detect_clock_skew: false,
+            path_style: PathStyle::default(),
+            object_versions: false,
+            report_prefix: None,
+            on_missing_schema: MissingSchemaPolicy::default(),
+            as_of: None,
+            column_limit: ColumnLimit::default(),
+            advance_sequences: false,
+            manifest_key: None,
+            include_stop_date: false,
+            preserve_decimals: false,
+            table_order: None,
+            deferred_constraints: false,
+            invalid_utf8: None,
+            hash_threads: None,
+            schema_strictness: SchemaStrictness::default(),
+            check_final_row_count: false,
+            key_normalization: HashMap::new(),
+            upsert_verification: None,
+            list_start_after: true,
+            copy_commit_rows: None,
+            expect_default_columns: HashMap::new(),
+            allowed_operations: Operation::all(),
+            table_identifier: None,
+            storage_parameters: IndexMap::new(),
+            sampling: None,
+        }
+    }
+
+    /// Restricts the run to the CDC files that appeared between the stop date of the previous
+    /// run and the current stop date, i.e. within `(previous_stop, current_stop]`, for an
+    /// incremental validation. The LOAD files are excluded, as they were applied by an earlier run.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_stop` - The stop date of the previous run, e.g. `2024-02-14T10:00:00Z`, exclusive.
+    /// * `current_stop` - The stop date of the current run, inclusive.
+    ///
+    /// # Returns
+    ///
+    /// The payload restricted to the delta window.
+    pub fn with_delta_window(
+        mut self,
+        previous_stop: impl Into<String>,
+        current_stop: impl Into<String>,
+    ) -> Self {
+        self.mode = ModeValueEnum::DateAware;
+        self.start_date = Some(previous_stop.into());
+        self.stop_date = Some(current_stop.into());
+        self.include_stop_date = true;
+        self.include_load_outside_window = false;
+        self
+    }
+
+    /// Gets the stop date of the listing. The listing excludes the stop date, so an inclusive
+    /// stop date is moved one second later, the precision of the last modified date of S3.
+    fn listed_stop_date(&self) -> Result<Option<String>> {
+        match &self.stop_date {
+            Some(stop_date) if self.include_stop_date => {
+                let stop_date = DateTime::from_str(stop_date, DateTimeFormat::DateTimeWithOffset)?;
+                let stop_date = DateTime::from_secs(stop_date.secs() + 1);
+                Ok(Some(stop_date.fmt(DateTimeFormat::DateTimeWithOffset)?))
+            }
+            stop_date => Ok(stop_date.clone()),
         }
     }
 
@@ -109,4 +304,270 @@ impl CDCOperatorSnapshotPayload {
     pub fn target_postgres_url(&self) -> String {
         self.target_postgres_url.clone()
     }
+
+    pub fn row_count_mismatch_severity(&self) -> RowCountMismatchSeverity {
+        self.row_count_mismatch_severity
+    }
+
+    pub fn target_table_name(&self, table_name: &str) -> String {
+        self.table_name_transform
+            .s3_table_to_target_table(table_name)
+    }
+
+    pub fn has_dataframe_transform(&self) -> bool {
+        self.dataframe_transform.is_some()
+    }
+
+    /// Applies the DataFrame transform, if any, on a DataFrame read from S3.
+    pub fn transform_dataframe(&self, df: DataFrame) -> Result<DataFrame> {
+        match &self.dataframe_transform {
+            Some(transform) => transform.apply(df),
+            None => Ok(df),
+        }
+    }
+
+    /// Keeps the rows of a DataFrame read from S3 within the key range, if any.
+    pub fn filter_key_range(&self, df: DataFrame) -> Result<DataFrame> {
+        match &self.key_range {
+            Some(key_range) => key_range.filter(&df),
+            None => Ok(df),
+        }
+    }
+
+    /// Keeps the rows of a DataFrame read from S3 whose primary key is sampled, if a sampling is set.
+    pub fn sample(&self, df: DataFrame, primary_key: &[String]) -> Result<DataFrame> {
+        match &self.sampling {
+            Some(sampling) => sampling.sample(df, primary_key),
+            None => Ok(df),
+        }
+    }
+
+    /// Checks that the rows of a DataFrame read from S3 belong to the table, if a table identifier
+    /// column is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table in the S3 path.
+    /// * `file` - The file of the DataFrame.
+    /// * `df` - The DataFrame.
+    ///
+    /// # Returns
+    ///
+    /// The rows of the table, and the number of skipped rows of other tables.
+    pub fn check_table_identifier(
+        &self,
+        table_name: &str,
+        file: &S3ParquetFile,
+        df: DataFrame,
+    ) -> Result<(DataFrame, usize)> {
+        match &self.table_identifier {
+            Some(table_identifier) => table_identifier.check(df, table_name, &file.file_name),
+            None => Ok((df, 0)),
+        }
+    }
+
+    /// Normalizes the primary key values of a DataFrame read from S3, if a key normalization
+    /// is set for the table.
+    pub fn normalize_keys(
+        &self,
+        table_name: &str,
+        df: DataFrame,
+        primary_key: &[String],
+    ) -> Result<DataFrame> {
+        match self.key_normalization.get(table_name) {
+            Some(key_normalization) => key_normalization.apply(df, primary_key),
+            None => Ok(df),
+        }
+    }
+
+    /// Keeps the rows of a CDC file committed at or before the as-of point, if any,
+    /// based on the sequence column. LOAD files are kept as is.
+    pub fn filter_as_of(&self, file: &S3ParquetFile, df: DataFrame) -> Result<DataFrame> {
+        match &self.as_of {
+            Some(as_of) if !file.is_load_file() => filter_as_of(&df, &self.sequence_column, as_of),
+            _ => Ok(df),
+        }
+    }
+
+    /// Builds the payload to list the Parquet files of a table from S3, based on the mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table in the S3 path.
+    ///
+    /// # Returns
+    ///
+    /// The payload to list the Parquet files.
+    pub fn load_parquet_files_payload(&self, table_name: &str) -> LoadParquetFilesPayload {
+        if let Some(manifest_key) = &self.manifest_key {
+            return LoadParquetFilesPayload::Manifest {
+                bucket_name: self.bucket_name.clone(),
+                manifest_key: manifest_key.clone(),
+                table_prefix: format!(
+                    "{}/{}/{}/{}/",
+                    self.key, self.database_name, self.schema_name, table_name
+                ),
+            };
+        }
+
+        // Check if mode is DateAware and start_date is not None
+        if self.mode_is_date_aware() && self.start_date.is_none() {
+            panic!("start_date is required for DateAware mode");
+        }
+
+        if self.mode_is_date_aware() {
+            LoadParquetFilesPayload::DateAware {
+                bucket_name: self.bucket_name.clone(),
+                s3_prefix: self.key.clone(),
+                database_name: self.database_name.clone(),
+                schema_name: self.schema_name.clone(),
+                table_name: table_name.to_string(),
+                start_date: self.start_date.clone().unwrap(),
+                stop_date: self
+                    .listed_stop_date()
+                    .unwrap_or_else(|e| panic!("Invalid stop_date: {:?}", e)),
+                include_load_outside_window: self.include_load_outside_window,
+                partitioned_listing: self.partitioned_listing,
+                list_concurrency: self.list_concurrency,
+                path_style: self.path_style,
+            }
+        } else if self.mode_is_full_load_only() {
+            LoadParquetFilesPayload::FullLoadOnly {
+                bucket_name: self.bucket_name.clone(),
+                s3_prefix: self.key.clone(),
+                database_name: self.database_name.clone(),
+                schema_name: self.schema_name.clone(),
+                table_name: table_name.to_string(),
+            }
+        } else {
+            LoadParquetFilesPayload::AbsolutePath(self.key.clone())
+        }
+    }
+
+    /// Builds the payload to read a Parquet file of a table from S3.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table in the S3 path.
+    /// * `file` - The Parquet file, along with the version of its object, if any.
+    ///
+    /// # Returns
+    ///
+    /// The payload to create a DataFrame from the Parquet file.
+    pub fn create_dataframe_payload(
+        &self,
+        table_name: &str,
+        file: &S3ParquetFile,
+    ) -> CreateDataframePayload {
+        CreateDataframePayload {
+            bucket_name: self.bucket_name.clone(),
+            key: file.file_name.clone(),
+            database_name: self.database_name.clone(),
+            schema_name: self.schema_name.clone(),
+            table_name: table_name.to_string(),
+            row_count_mismatch_severity: self.row_count_mismatch_severity,
+            streaming_threshold_bytes: self.streaming_threshold_bytes,
+            n_rows: self.n_rows,
+            read_buffer_size: self.read_buffer_size,
+            chunk_size: self.chunk_size,
+            version_id: file.version_id.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::s3_operator::is_file_in_date_window;
+
+    fn date_time(date: &str) -> DateTime {
+        DateTime::from_str(date, DateTimeFormat::DateTimeWithOffset).unwrap()
+    }
+
+    #[test]
+    fn test_manifest_payload_is_restricted_to_table() {
+        let mut payload = CDCOperatorSnapshotPayload::new(
+            "bucket",
+            "prefix",
+            "mydb",
+            "public",
+            vec!["users", "orders"],
+            Vec::<String>::new(),
+            ModeValueEnum::FullLoadOnly,
+            None,
+            None,
+            "postgres://source".to_string(),
+            "postgres://target".to_string(),
+        );
+        payload.manifest_key = Some("manifests/run.json".to_string());
+
+        for table_name in ["users", "orders"] {
+            let LoadParquetFilesPayload::Manifest { table_prefix, .. } =
+                payload.load_parquet_files_payload(table_name)
+            else {
+                panic!("Expected a manifest payload");
+            };
+            assert_eq!(table_prefix, format!("prefix/mydb/public/{table_name}/"));
+        }
+    }
+
+    #[test]
+    fn test_delta_window_excludes_files_of_earlier_window() {
+        let payload = CDCOperatorSnapshotPayload::new(
+            "bucket",
+            "prefix",
+            "mydb",
+            "public",
+            vec!["users"],
+            Vec::<String>::new(),
+            ModeValueEnum::FullLoadOnly,
+            None,
+            None,
+            "postgres://source".to_string(),
+            "postgres://target".to_string(),
+        )
+        .with_delta_window("2024-02-14T10:00:00Z", "2024-02-15T10:00:00Z");
+
+        let LoadParquetFilesPayload::DateAware {
+            start_date,
+            stop_date,
+            include_load_outside_window,
+            ..
+        } = payload.load_parquet_files_payload("users")
+        else {
+            panic!("Expected a DateAware listing");
+        };
+        let start_date = date_time(&start_date);
+        let stop_date = date_time(&stop_date.unwrap());
+
+        let files = [
+            ("users/LOAD00000001.parquet", "2024-02-01T00:00:00Z"),
+            ("users/20240214-090000000.parquet", "2024-02-14T09:00:00Z"),
+            ("users/20240214-100000000.parquet", "2024-02-14T10:00:00Z"),
+            ("users/20240214-120000000.parquet", "2024-02-14T12:00:00Z"),
+            ("users/20240215-100000000.parquet", "2024-02-15T10:00:00Z"),
+            ("users/20240215-100001000.parquet", "2024-02-15T10:00:01Z"),
+        ];
+        let included = files
+            .iter()
+            .filter(|(file, last_modified)| {
+                is_file_in_date_window(
+                    file,
+                    &date_time(last_modified),
+                    &start_date,
+                    Some(&stop_date),
+                    include_load_outside_window,
+                )
+            })
+            .map(|(file, _)| *file)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            included,
+            vec![
+                "users/20240214-120000000.parquet",
+                "users/20240215-100000000.parquet"
+            ]
+        );
+    }
 }
@@ -0,0 +1,88 @@
+/// Represents a transform from the table name used in the S3 path to the table name in the target database.
+///
+/// The S3 path will always use the original table name, while the queries in the target database
+/// will use the transformed one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableNameTransform {
+    strip_prefix: Option<String>,
+    strip_suffix: Option<String>,
+}
+
+impl TableNameTransform {
+    /// Creates a new table name transform.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip_prefix` - The prefix to strip from the table name in the S3 path.
+    /// * `strip_suffix` - The suffix to strip from the table name in the S3 path.
+    ///
+    /// # Returns
+    ///
+    /// A new table name transform instance.
+    pub fn new(
+        strip_prefix: impl Into<Option<String>>,
+        strip_suffix: impl Into<Option<String>>,
+    ) -> Self {
+        Self {
+            strip_prefix: strip_prefix.into(),
+            strip_suffix: strip_suffix.into(),
+        }
+    }
+
+    pub fn strip_prefix(&self) -> Option<&str> {
+        self.strip_prefix.as_deref()
+    }
+
+    pub fn strip_suffix(&self) -> Option<&str> {
+        self.strip_suffix.as_deref()
+    }
+
+    /// Maps the table name used in the S3 path to the table name in the target database.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The table name used in the S3 path.
+    ///
+    /// # Returns
+    ///
+    /// The table name in the target database.
+    pub fn s3_table_to_target_table(&self, name: &str) -> String {
+        let mut target_table = name;
+
+        if let Some(prefix) = self.strip_prefix.as_deref() {
+            target_table = target_table.strip_prefix(prefix).unwrap_or(target_table);
+        }
+        if let Some(suffix) = self.strip_suffix.as_deref() {
+            target_table = target_table.strip_suffix(suffix).unwrap_or(target_table);
+        }
+
+        target_table.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s3_table_to_target_table_strips_suffix() {
+        let transform = TableNameTransform::new(None, Some("_v2".to_string()));
+
+        assert_eq!(transform.s3_table_to_target_table("orders_v2"), "orders");
+        assert_eq!(transform.s3_table_to_target_table("customers"), "customers");
+    }
+
+    #[test]
+    fn test_s3_table_to_target_table_strips_prefix() {
+        let transform = TableNameTransform::new(Some("dms_".to_string()), None);
+
+        assert_eq!(transform.s3_table_to_target_table("dms_orders"), "orders");
+    }
+
+    #[test]
+    fn test_s3_table_to_target_table_default_is_identity() {
+        let transform = TableNameTransform::default();
+
+        assert_eq!(transform.s3_table_to_target_table("orders_v2"), "orders_v2");
+    }
+}
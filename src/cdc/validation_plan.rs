@@ -0,0 +1,233 @@
+use anyhow::Result;
+use colored::Colorize;
+use log::info;
+use serde::Serialize;
+
+use super::snapshot_payload::CDCOperatorSnapshotPayload;
+
+use crate::dataframe::dataframe_ops::ParquetFileReader;
+use crate::postgres::postgres_operator::PostgresOperator;
+use crate::postgres::table_query::TableQuery;
+use crate::s3::s3_operator::S3Operator;
+
+/// Represents what a snapshot would do for a table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TablePlan {
+    pub table_name: String,
+    pub target_table_name: String,
+    pub load_files: usize,
+    pub cdc_files: usize,
+    pub total_bytes: i64,
+    pub estimated_rows: usize,
+    pub ddl: String,
+}
+
+/// Represents what a snapshot would do, without executing it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationPlan {
+    pub tables: Vec<TablePlan>,
+}
+
+impl ValidationPlan {
+    /// Gets the number of files to process across all tables.
+    pub fn files(&self) -> usize {
+        self.tables
+            .iter()
+            .map(|table| table.load_files + table.cdc_files)
+            .sum()
+    }
+
+    /// Serializes the plan.
+    ///
+    /// # Returns
+    ///
+    /// The plan as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Plans a snapshot without executing it.
+///
+/// Only the metadata of the source tables is queried, the Parquet files are listed, and their size
+/// and footer row count are read. No data is downloaded and no DDL is executed.
+///
+/// # Arguments
+///
+/// * `cdc_operator_snapshot_payload` - The payload of the snapshot.
+/// * `source_postgres_operator` - The operator of the source database.
+/// * `s3_operator` - The operator to list the Parquet files.
+/// * `parquet_file_reader` - The reader of the Parquet file metadata.
+///
+/// # Returns
+///
+/// The plan of the snapshot.
+pub async fn plan(
+    cdc_operator_snapshot_payload: &CDCOperatorSnapshotPayload,
+    source_postgres_operator: &(impl PostgresOperator + Sync),
+    s3_operator: &(impl S3Operator + Sync),
+    parquet_file_reader: &(impl ParquetFileReader + Sync),
+) -> Result<ValidationPlan> {
+    info!("{}", "Planning snapshot...".bold().blue());
+
+    let payload = cdc_operator_snapshot_payload;
+    let table_list = source_postgres_operator
+        .get_tables_in_schema(
+            payload.schema_name().as_str(),
+            payload.included_tables().as_slice(),
+            payload.excluded_tables().as_slice(),
+            &payload.table_mode(),
+        )
+        .await?;
+
+    let mut validation_plan = ValidationPlan::default();
+
+    for table_name in &table_list {
+        let table_columns = source_postgres_operator
+            .get_table_columns(payload.schema_name.as_str(), table_name)
+            .await?;
+        let primary_key_list = source_postgres_operator
+            .get_primary_key(table_name, payload.schema_name.as_str())
+            .await?;
+        let target_table_name = payload.target_table_name(table_name);
+
+        let parquet_files = s3_operator
+            .get_list_of_parquet_files_from_s3(&payload.load_parquet_files_payload(table_name))
+            .await?;
+
+        let mut total_bytes = 0;
+        let mut estimated_rows = 0;
+        for file in &parquet_files {
+            let create_dataframe_payload = payload.create_dataframe_payload(table_name, file);
+            total_bytes += parquet_file_reader
+                .content_length(&create_dataframe_payload)
+                .await?;
+            estimated_rows += parquet_file_reader
+                .footer_row_count(&create_dataframe_payload)
+                .await?;
+        }
+
+        let load_files = parquet_files
+            .iter()
+            .filter(|file| file.is_load_file())
+            .count();
+
+        let ddl = TableQuery::CreateTable(
+            payload.schema_name.clone(),
+            target_table_name.clone(),
+            table_columns,
+            primary_key_list,
+            payload.storage_parameters.clone(),
+        )
+        .to_string();
+
+        validation_plan.tables.push(TablePlan {
+            table_name: table_name.clone(),
+            target_table_name,
+            load_files,
+            cdc_files: parquet_files.len() - load_files,
+            total_bytes,
+            estimated_rows,
+            ddl,
+        });
+    }
+
+    info!(
+        "Planned {} tables with {} files",
+        validation_plan.tables.len(),
+        validation_plan.files()
+    );
+
+    Ok(validation_plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdc::cdc_operator_mode::ModeValueEnum;
+    use crate::dataframe::dataframe_ops::MockParquetFileReader;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use crate::s3::s3_operator::{LoadParquetFilesPayload, MockS3Operator, S3ParquetFile};
+    use indexmap::IndexMap;
+
+    #[tokio::test]
+    async fn test_plan_enumerates_tables_and_files() {
+        let payload = CDCOperatorSnapshotPayload::new(
+            "bucket_name",
+            "prefix",
+            "database_name",
+            "public",
+            Vec::<String>::new(),
+            Vec::<String>::new(),
+            ModeValueEnum::FullLoadOnly,
+            None,
+            None,
+            "source_postgres_url".to_string(),
+            "target_postgres_url".to_string(),
+        );
+
+        let mut source_postgres_operator = MockPostgresOperator::new();
+        source_postgres_operator
+            .expect_get_tables_in_schema()
+            .returning(|_, _, _, _| Ok(vec!["orders".to_string(), "users".to_string()]));
+        source_postgres_operator
+            .expect_get_table_columns()
+            .returning(|_, _| Ok(IndexMap::from([("id".to_string(), "bigint".to_string())])));
+        source_postgres_operator
+            .expect_get_primary_key()
+            .returning(|_, _| Ok(vec!["id".to_string()]));
+
+        let mut s3_operator = MockS3Operator::new();
+        s3_operator
+            .expect_get_list_of_parquet_files_from_s3()
+            .returning(|payload| match payload {
+                LoadParquetFilesPayload::FullLoadOnly { table_name, .. }
+                    if table_name == "orders" =>
+                {
+                    Ok(vec![
+                        S3ParquetFile::new("orders/LOAD00000001.parquet"),
+                        S3ParquetFile::new("orders/LOAD00000002.parquet"),
+                        S3ParquetFile::new("orders/20240101-000000000.parquet"),
+                    ])
+                }
+                _ => Ok(vec![S3ParquetFile::new("users/LOAD00000001.parquet")]),
+            });
+
+        let mut parquet_file_reader = MockParquetFileReader::new();
+        parquet_file_reader
+            .expect_content_length()
+            .returning(|_| Ok(1024));
+        parquet_file_reader
+            .expect_footer_row_count()
+            .returning(|_| Ok(100));
+        parquet_file_reader.expect_read_eagerly().times(0);
+        parquet_file_reader.expect_read_streaming().times(0);
+
+        let validation_plan = plan(
+            &payload,
+            &source_postgres_operator,
+            &s3_operator,
+            &parquet_file_reader,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(validation_plan.tables.len(), 2);
+        assert_eq!(validation_plan.files(), 4);
+
+        let orders = &validation_plan.tables[0];
+        assert_eq!(orders.table_name, "orders");
+        assert_eq!(orders.load_files, 2);
+        assert_eq!(orders.cdc_files, 1);
+        assert_eq!(orders.total_bytes, 3072);
+        assert_eq!(orders.estimated_rows, 300);
+        assert!(orders
+            .ddl
+            .starts_with("CREATE TABLE IF NOT EXISTS public.orders ("));
+
+        let users = &validation_plan.tables[1];
+        assert_eq!(users.table_name, "users");
+        assert_eq!(users.load_files, 1);
+        assert_eq!(users.estimated_rows, 100);
+    }
+}
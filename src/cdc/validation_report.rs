@@ -0,0 +1,605 @@
+use anyhow::Result;
+use aws_sdk_s3::primitives::DateTimeFormat;
+use indexmap::IndexMap;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+
+use super::load_metrics::LoadMetrics;
+use super::operation_filter::SkippedOperations;
+use super::run_context::RunContext;
+
+use crate::dataframe::clock_skew::ClockSkewAnomaly;
+use crate::dataframe::duplicate_sequence::DuplicateSequence;
+use crate::postgres::default_verification::MissingDefault;
+use crate::postgres::upsert_verification::UnappliedUpdate;
+
+/// Represents the status of a table in a run of the CDC Operator.
+///
+/// The status can be one of the following:
+///
+/// * Completed - All the files of the table were processed.
+/// * TimedOut - The maximum duration of the run was exceeded before all the files were processed.
+/// * Failed - The table failed to apply, with the error in the report of the table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TableStatus {
+    #[default]
+    Completed,
+    TimedOut,
+    Failed,
+}
+
+/// Represents the report of a table in a run of the CDC Operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableReport {
+    pub table_name: String,
+    pub status: TableStatus,
+    pub files_processed: usize,
+    pub min_last_modified: Option<String>,
+    pub max_last_modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub differing_rows: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_count_discrepancy: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unapplied_updates: Vec<UnappliedUpdate>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_defaults: Vec<MissingDefault>,
+    #[serde(skip_serializing_if = "SkippedOperations::is_empty")]
+    pub skipped_operations: SkippedOperations,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub clock_skew_anomalies: Vec<ClockSkewAnomaly>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_sequences: Vec<DuplicateSequence>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreign_rows: Option<usize>,
+    /// The number of deferred deletes that were not applied, as the run timed out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dropped_deletes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TableReport {
+    /// Creates the report of a table compared with the target database.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table.
+    /// * `differing_rows` - The number of rows that differ between S3 and the target database.
+    ///
+    /// # Returns
+    ///
+    /// The report of the table.
+    pub fn from_differing_rows(table_name: impl Into<String>, differing_rows: i64) -> Self {
+        Self {
+            table_name: table_name.into(),
+            differing_rows: Some(differing_rows),
+            ..Default::default()
+        }
+    }
+
+    /// Marks the table as failed, along with the error.
+    pub fn fail(&mut self, error: impl Into<String>) {
+        self.status = TableStatus::Failed;
+        self.error = Some(error.into());
+    }
+
+    /// Creates the report of a table that was not processed within the maximum duration of the run.
+    pub fn timed_out(table_name: impl Into<String>) -> Self {
+        Self {
+            table_name: table_name.into(),
+            status: TableStatus::TimedOut,
+            ..Default::default()
+        }
+    }
+
+    /// Checks if the table passed the validation, i.e. that it was completed, no differing rows
+    /// were found, its final row count was the expected one, its verified upserts were applied
+    /// and its columns expected to be filled by their default hold no NULL values.
+    pub fn passed(&self) -> bool {
+        self.status == TableStatus::Completed
+            && self.differing_rows.unwrap_or_default() == 0
+            && self.row_count_discrepancy.unwrap_or_default() == 0
+            && self.unapplied_updates.is_empty()
+            && self.missing_defaults.is_empty()
+    }
+}
+
+impl From<&LoadMetrics> for TableReport {
+    fn from(load_metrics: &LoadMetrics) -> Self {
+        let format = |date: &aws_sdk_s3::primitives::DateTime| {
+            date.fmt(DateTimeFormat::DateTimeWithOffset).ok()
+        };
+
+        Self {
+            table_name: load_metrics.table_name.clone(),
+            status: TableStatus::Completed,
+            files_processed: load_metrics.files_processed,
+            min_last_modified: load_metrics.min_last_modified.as_ref().and_then(format),
+            max_last_modified: load_metrics.max_last_modified.as_ref().and_then(format),
+            differing_rows: None,
+            row_count_discrepancy: None,
+            unapplied_updates: Vec::new(),
+            missing_defaults: Vec::new(),
+            skipped_operations: SkippedOperations::default(),
+            clock_skew_anomalies: Vec::new(),
+            duplicate_sequences: Vec::new(),
+            foreign_rows: None,
+            dropped_deletes: None,
+            error: None,
+        }
+    }
+}
+
+/// Represents the report of the tables of a schema in a run of the CDC Operator.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaReport {
+    pub tables: Vec<TableReport>,
+}
+
+impl SchemaReport {
+    /// Checks if all the tables of the schema passed the validation.
+    pub fn passed(&self) -> bool {
+        self.tables.iter().all(TableReport::passed)
+    }
+}
+
+/// Represents the result of a table, emitted as soon as the table is completed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableResult {
+    pub schema_name: String,
+    pub table_report: TableReport,
+}
+
+/// Represents an optional channel where the result of each table is sent as soon as the table
+/// is completed, e.g. to show the progress of a long run, in addition to the final report.
+#[derive(Debug, Clone, Default)]
+pub struct TableResultSender(Option<Sender<TableResult>>);
+
+impl TableResultSender {
+    /// Creates a new table result sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the channel of the table results.
+    ///
+    /// # Returns
+    ///
+    /// A new table result sender instance.
+    pub fn new(sender: Sender<TableResult>) -> Self {
+        Self(Some(sender))
+    }
+
+    /// Sends the result of a table, if a channel is set.
+    ///
+    /// A closed channel is logged and otherwise ignored, so that the run is not affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the table.
+    /// * `table_report` - The report of the table.
+    pub async fn send(&self, schema_name: &str, table_report: &TableReport) {
+        let Some(sender) = &self.0 else {
+            return;
+        };
+
+        let table_result = TableResult {
+            schema_name: schema_name.to_string(),
+            table_report: table_report.clone(),
+        };
+        if sender.send(table_result).await.is_err() {
+            warn!(
+                "Failed to send the result of table {}, the channel is closed",
+                table_report.table_name
+            );
+        }
+    }
+}
+
+/// Represents the change of the outcome of a table between a baseline run and a run.
+///
+/// The transition can be one of the following:
+///
+/// * Unchanged - The table passed, or failed, in both runs.
+/// * Regressed - The table passed in the baseline run but failed in the run.
+/// * Improved - The table failed in the baseline run but passed in the run.
+/// * Added - The table is only in the run.
+/// * Removed - The table is only in the baseline run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StatusTransition {
+    Unchanged,
+    Regressed,
+    Improved,
+    Added,
+    Removed,
+}
+
+/// Represents the change of a table between a baseline run and a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableDelta {
+    pub schema_name: String,
+    pub table_name: String,
+    pub baseline_differing_rows: Option<i64>,
+    pub differing_rows: Option<i64>,
+    pub transition: StatusTransition,
+}
+
+impl TableDelta {
+    /// Gets the change of the number of differing rows, positive if the mismatches increased.
+    pub fn differing_rows_change(&self) -> i64 {
+        self.differing_rows.unwrap_or_default() - self.baseline_differing_rows.unwrap_or_default()
+    }
+}
+
+/// Represents the changes of the tables between a baseline run and a run, e.g. of two CI runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReportDelta {
+    pub tables: Vec<TableDelta>,
+}
+
+impl ReportDelta {
+    /// Gets the tables that passed in the baseline run but failed in the run.
+    pub fn regressed(&self) -> Vec<&TableDelta> {
+        self.tables
+            .iter()
+            .filter(|table| table.transition == StatusTransition::Regressed)
+            .collect()
+    }
+
+    /// Gets the tables that failed in the baseline run but passed in the run.
+    pub fn improved(&self) -> Vec<&TableDelta> {
+        self.tables
+            .iter()
+            .filter(|table| table.transition == StatusTransition::Improved)
+            .collect()
+    }
+}
+
+/// Represents the report of a run of the CDC Operator, grouped by schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    #[serde(flatten)]
+    pub run_context: RunContext,
+    pub schemas: IndexMap<String, SchemaReport>,
+}
+
+impl ValidationReport {
+    pub fn new(run_context: RunContext) -> Self {
+        Self {
+            run_context,
+            schemas: IndexMap::new(),
+        }
+    }
+
+    pub fn add_table(&mut self, schema_name: &str, table_report: TableReport) {
+        self.schemas
+            .entry(schema_name.to_string())
+            .or_default()
+            .tables
+            .push(table_report);
+    }
+
+    /// Merges the schemas of another report of the same run into this report.
+    pub fn merge(&mut self, other: ValidationReport) {
+        for (schema_name, schema_report) in other.schemas {
+            self.schemas
+                .entry(schema_name)
+                .or_default()
+                .tables
+                .extend(schema_report.tables);
+        }
+    }
+
+    /// Checks if all the tables of all the schemas passed the validation.
+    pub fn passed(&self) -> bool {
+        self.schemas.values().all(SchemaReport::passed)
+    }
+
+    /// Compares the report with the report of a baseline run, to show whether the mismatches
+    /// of the tables increased or decreased.
+    ///
+    /// # Arguments
+    ///
+    /// * `baseline` - The report of the baseline run.
+    ///
+    /// # Returns
+    ///
+    /// The changes of the tables of both reports, in the order of this report followed by
+    /// the tables only in the baseline report.
+    pub fn diff(&self, baseline: &ValidationReport) -> ReportDelta {
+        let find = |report: &'_ ValidationReport, schema_name: &str, table_name: &str| {
+            report.schemas.get(schema_name).and_then(|schema_report| {
+                schema_report
+                    .tables
+                    .iter()
+                    .find(|table| table.table_name == table_name)
+                    .cloned()
+            })
+        };
+        let table_delta = |schema_name: &str,
+                           table_name: &str,
+                           baseline_table: Option<&TableReport>,
+                           table: Option<&TableReport>| {
+            let transition = match (baseline_table, table) {
+                (Some(baseline_table), Some(table)) => {
+                    match (baseline_table.passed(), table.passed()) {
+                        (true, false) => StatusTransition::Regressed,
+                        (false, true) => StatusTransition::Improved,
+                        _ => StatusTransition::Unchanged,
+                    }
+                }
+                (None, _) => StatusTransition::Added,
+                (_, None) => StatusTransition::Removed,
+            };
+            TableDelta {
+                schema_name: schema_name.to_string(),
+                table_name: table_name.to_string(),
+                baseline_differing_rows: baseline_table.and_then(|table| table.differing_rows),
+                differing_rows: table.and_then(|table| table.differing_rows),
+                transition,
+            }
+        };
+
+        let mut tables = Vec::new();
+        for (schema_name, schema_report) in &self.schemas {
+            for table in &schema_report.tables {
+                let baseline_table = find(baseline, schema_name, &table.table_name);
+                tables.push(table_delta(
+                    schema_name,
+                    &table.table_name,
+                    baseline_table.as_ref(),
+                    Some(table),
+                ));
+            }
+        }
+        for (schema_name, schema_report) in &baseline.schemas {
+            for baseline_table in &schema_report.tables {
+                if find(self, schema_name, &baseline_table.table_name).is_none() {
+                    tables.push(table_delta(
+                        schema_name,
+                        &baseline_table.table_name,
+                        Some(baseline_table),
+                        None,
+                    ));
+                }
+            }
+        }
+
+        ReportDelta { tables }
+    }
+
+    /// Serializes the report.
+    ///
+    /// # Returns
+    ///
+    /// The report as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Deserializes a report, e.g. the report of a baseline run uploaded by a previous CI run.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The report as JSON.
+    ///
+    /// # Returns
+    ///
+    /// The report.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_serialized_report_contains_run_id_and_labels() {
+        let run_context = RunContext::new(
+            Some("nightly-42".to_string()),
+            HashMap::from([("env".to_string(), "staging".to_string())]),
+        );
+        let mut report = ValidationReport::new(run_context);
+        report.add_table(
+            "public",
+            TableReport::from(&LoadMetrics::from_files("table", &[])),
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["run_id"], "nightly-42");
+        assert_eq!(json["labels"]["env"], "staging");
+        let table = &json["schemas"]["public"]["tables"][0];
+        assert_eq!(table["table_name"], "table");
+        assert_eq!(table["files_processed"], 0);
+        assert_eq!(table["status"], "Completed");
+    }
+
+    #[test]
+    fn test_timed_out_table_does_not_pass() {
+        let mut report = ValidationReport::new(RunContext::default());
+        report.add_table("public", TableReport::from_differing_rows("orders", 0));
+        assert!(report.passed());
+
+        report.add_table("public", TableReport::timed_out("users"));
+
+        assert!(!report.passed());
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["schemas"]["public"]["tables"][1]["status"], "TimedOut");
+    }
+
+    #[test]
+    fn test_failed_table_does_not_pass() {
+        let mut table_report = TableReport::from_differing_rows("users", 0);
+        table_report.fail("Failed to apply deferred deletes");
+
+        assert!(!table_report.passed());
+        let json = serde_json::to_value(&table_report).unwrap();
+        assert_eq!(json["status"], "Failed");
+        assert_eq!(json["error"], "Failed to apply deferred deletes");
+    }
+
+    #[test]
+    fn test_table_with_row_count_discrepancy_does_not_pass() {
+        let mut table_report = TableReport::from_differing_rows("users", 0);
+        table_report.row_count_discrepancy = Some(0);
+        assert!(table_report.passed());
+
+        table_report.row_count_discrepancy = Some(-1);
+        assert!(!table_report.passed());
+    }
+
+    #[test]
+    fn test_merge_reports_of_two_schemas() {
+        let run_context = RunContext::new(Some("run".to_string()), HashMap::new());
+
+        let mut report = ValidationReport::new(run_context.clone());
+        report.add_table("sales", TableReport::from_differing_rows("orders", 0));
+
+        let mut other = ValidationReport::new(run_context);
+        other.add_table("inventory", TableReport::from_differing_rows("products", 0));
+        other.add_table("inventory", TableReport::from_differing_rows("stock", 3));
+        assert!(!other.passed());
+
+        report.merge(other);
+
+        assert_eq!(
+            report.schemas.keys().collect::<Vec<_>>(),
+            vec!["sales", "inventory"]
+        );
+        assert!(report.schemas["sales"].passed());
+        assert!(!report.schemas["inventory"].passed());
+        assert_eq!(report.schemas["inventory"].tables.len(), 2);
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_diff_with_regressed_and_improved_tables() {
+        let mut baseline = ValidationReport::new(RunContext::default());
+        baseline.add_table("public", TableReport::from_differing_rows("orders", 0));
+        baseline.add_table("public", TableReport::from_differing_rows("users", 5));
+        baseline.add_table("public", TableReport::from_differing_rows("invoices", 1));
+
+        let mut report = ValidationReport::new(RunContext::default());
+        report.add_table("public", TableReport::from_differing_rows("orders", 3));
+        report.add_table("public", TableReport::from_differing_rows("users", 0));
+        report.add_table("public", TableReport::from_differing_rows("invoices", 2));
+
+        let delta = report.diff(&baseline);
+
+        let regressed = delta.regressed();
+        assert_eq!(regressed.len(), 1);
+        assert_eq!(regressed[0].table_name, "orders");
+        assert_eq!(regressed[0].differing_rows_change(), 3);
+
+        let improved = delta.improved();
+        assert_eq!(improved.len(), 1);
+        assert_eq!(improved[0].table_name, "users");
+        assert_eq!(improved[0].differing_rows_change(), -5);
+
+        assert_eq!(delta.tables[2].transition, StatusTransition::Unchanged);
+        assert_eq!(delta.tables[2].differing_rows_change(), 1);
+    }
+
+    #[test]
+    fn test_diff_with_baseline_from_json() {
+        let mut baseline = ValidationReport::new(RunContext::new(
+            Some("nightly-41".to_string()),
+            HashMap::from([("env".to_string(), "staging".to_string())]),
+        ));
+        baseline.add_table("public", TableReport::from_differing_rows("orders", 0));
+        let mut failed = TableReport::from_differing_rows("users", 5);
+        failed.fail("Failed to upsert CDC file");
+        baseline.add_table("public", failed);
+
+        let loaded_baseline = ValidationReport::from_json(&baseline.to_json().unwrap()).unwrap();
+        assert_eq!(loaded_baseline, baseline);
+
+        let mut report = ValidationReport::new(RunContext::default());
+        report.add_table("public", TableReport::from_differing_rows("orders", 2));
+        report.add_table("public", TableReport::from_differing_rows("users", 0));
+
+        let delta = report.diff(&loaded_baseline);
+
+        assert_eq!(
+            delta
+                .tables
+                .iter()
+                .map(|table| (table.table_name.as_str(), table.transition))
+                .collect::<Vec<_>>(),
+            vec![
+                ("orders", StatusTransition::Regressed),
+                ("users", StatusTransition::Improved)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_with_added_and_removed_tables() {
+        let mut baseline = ValidationReport::new(RunContext::default());
+        baseline.add_table("public", TableReport::from_differing_rows("orders", 0));
+
+        let mut report = ValidationReport::new(RunContext::default());
+        report.add_table("sales", TableReport::from_differing_rows("orders", 0));
+
+        let delta = report.diff(&baseline);
+
+        assert_eq!(
+            delta
+                .tables
+                .iter()
+                .map(|table| (table.schema_name.as_str(), table.transition))
+                .collect::<Vec<_>>(),
+            vec![
+                ("sales", StatusTransition::Added),
+                ("public", StatusTransition::Removed)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_table_results_arrive_incrementally() {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let (ack_sender, mut ack_receiver) = mpsc::channel::<()>(1);
+        let table_result_sender = TableResultSender::new(sender);
+
+        // A mock pipeline that completes the next table only after the previous result is received
+        let pipeline = tokio::spawn(async move {
+            let mut report = ValidationReport::new(RunContext::default());
+            for (table_name, differing_rows) in [("orders", 0), ("users", 2)] {
+                let table_report = TableReport::from_differing_rows(table_name, differing_rows);
+                table_result_sender.send("public", &table_report).await;
+                report.add_table("public", table_report);
+                ack_receiver.recv().await.unwrap();
+            }
+            report
+        });
+
+        let first = receiver.recv().await.unwrap();
+        assert_eq!(first.schema_name, "public");
+        assert_eq!(first.table_report.table_name, "orders");
+        assert!(!pipeline.is_finished());
+        ack_sender.send(()).await.unwrap();
+
+        let second = receiver.recv().await.unwrap();
+        assert_eq!(second.table_report.differing_rows, Some(2));
+        ack_sender.send(()).await.unwrap();
+
+        let report = pipeline.await.unwrap();
+        assert_eq!(report.schemas["public"].tables.len(), 2);
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_table_results_without_channel() {
+        TableResultSender::default()
+            .send("public", &TableReport::from_differing_rows("orders", 0))
+            .await;
+    }
+}
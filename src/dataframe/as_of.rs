@@ -0,0 +1,163 @@
+use anyhow::{anyhow, Result};
+use aws_sdk_s3::primitives::DateTime;
+use chrono::NaiveDateTime;
+use polars::prelude::*;
+
+/// The formats of the commit timestamps written by DMS as text.
+const TIMESTAMP_FORMATS: [&str; 3] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.fZ",
+];
+
+/// Converts a point in time to microseconds since the epoch.
+fn to_micros(as_of: &DateTime) -> i64 {
+    as_of.secs() * 1_000_000 + i64::from(as_of.subsec_nanos() / 1_000)
+}
+
+/// Parses a commit timestamp written as text, in UTC.
+fn parse_micros(value: &str) -> Option<i64> {
+    TIMESTAMP_FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .map(|timestamp| timestamp.and_utc().timestamp_micros())
+    })
+}
+
+/// Keeps the rows of a DataFrame committed at or before a point in time, so that a file
+/// containing operations on both sides of the point is trimmed rather than skipped.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of a CDC file.
+/// * `column` - The commit timestamp column, either a datetime or text.
+/// * `as_of` - The point in time.
+///
+/// # Returns
+///
+/// The rows committed at or before the point in time. Rows without a commit timestamp are kept,
+/// while a commit timestamp that cannot be parsed is an error.
+pub fn filter_as_of(df: &DataFrame, column: &str, as_of: &DateTime) -> Result<DataFrame> {
+    let as_of_micros = to_micros(as_of);
+    let micros = commit_timestamp_micros(df, column)?;
+
+    let mask: BooleanChunked = micros
+        .into_iter()
+        .map(|value| value.is_none_or(|value| value <= as_of_micros))
+        .collect();
+
+    Ok(df.filter(&mask)?)
+}
+
+/// Reads the commit timestamps of the rows of a DataFrame, in microseconds since the epoch.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of a CDC file.
+/// * `column` - The commit timestamp column, either a datetime or text.
+///
+/// # Returns
+///
+/// The commit timestamp of each row, None for the rows without one, while a commit timestamp
+/// that cannot be parsed is an error.
+pub fn commit_timestamp_micros(df: &DataFrame, column: &str) -> Result<Vec<Option<i64>>> {
+    let timestamps = df.column(column)?;
+
+    let micros: Vec<Option<i64>> = match timestamps.dtype() {
+        DataType::Datetime(time_unit, _) => {
+            let to_micros = |value: i64| match time_unit {
+                TimeUnit::Nanoseconds => value / 1_000,
+                TimeUnit::Microseconds => value,
+                TimeUnit::Milliseconds => value * 1_000,
+            };
+            timestamps
+                .to_physical_repr()
+                .i64()?
+                .into_iter()
+                .map(|value| value.map(to_micros))
+                .collect()
+        }
+        DataType::String => timestamps
+            .str()?
+            .into_iter()
+            .map(|value| match value {
+                None | Some("") => Ok(None),
+                Some(value) => parse_micros(value).map(Some).ok_or_else(|| {
+                    anyhow!("Invalid commit timestamp {} in column {}", value, column)
+                }),
+            })
+            .collect::<Result<_>>()?,
+        data_type => {
+            return Err(anyhow!(
+                "Unsupported data type {} of commit timestamp column {}",
+                data_type,
+                column
+            ))
+        }
+    };
+
+    Ok(micros)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::primitives::DateTimeFormat;
+
+    fn as_of() -> DateTime {
+        DateTime::from_str("2024-01-01T12:00:00Z", DateTimeFormat::DateTimeWithOffset).unwrap()
+    }
+
+    #[test]
+    fn test_filter_as_of_trims_a_file_spanning_the_point() {
+        let df = df!(
+            "Op" => &["I", "U", "U", "D"],
+            "id" => &[1i64, 1, 1, 1],
+            "_dms_ingestion_timestamp" => &[
+                "2024-01-01 11:59:59.000000",
+                "2024-01-01 12:00:00.000000",
+                "2024-01-01 12:00:00.000001",
+                "2024-01-01 13:00:00.000000",
+            ]
+        )
+        .unwrap();
+
+        let filtered = filter_as_of(&df, "_dms_ingestion_timestamp", &as_of()).unwrap();
+
+        let ops = filtered.column("Op").unwrap().str().unwrap();
+        assert_eq!(ops.into_iter().collect::<Vec<_>>(), [Some("I"), Some("U")]);
+    }
+
+    #[test]
+    fn test_filter_as_of_on_datetime_column() {
+        let timestamps = Series::new(
+            "_dms_ingestion_timestamp",
+            &[1_704_110_399_000i64, 1_704_110_401_000],
+        )
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+        .unwrap();
+        let df = DataFrame::new(vec![Series::new("id", &[1i64, 2]), timestamps]).unwrap();
+
+        let filtered = filter_as_of(&df, "_dms_ingestion_timestamp", &as_of()).unwrap();
+
+        assert_eq!(filtered.height(), 1);
+        assert_eq!(
+            filtered.column("id").unwrap().i64().unwrap().get(0),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_filter_as_of_keeps_rows_without_timestamp_and_rejects_invalid() {
+        let df = df!(
+            "id" => &[1i64, 2],
+            "_dms_ingestion_timestamp" => &["", "2024-01-02 00:00:00"]
+        )
+        .unwrap();
+        let filtered = filter_as_of(&df, "_dms_ingestion_timestamp", &as_of()).unwrap();
+        assert_eq!(filtered.height(), 1);
+
+        let df = df!("id" => &[1i64], "_dms_ingestion_timestamp" => &["yesterday"]).unwrap();
+        assert!(filter_as_of(&df, "_dms_ingestion_timestamp", &as_of()).is_err());
+    }
+}
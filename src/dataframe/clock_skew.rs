@@ -0,0 +1,230 @@
+use anyhow::Result;
+use chrono::DateTime;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::as_of::commit_timestamp_micros;
+
+/// Represents the kind of a clock skew anomaly.
+///
+/// The kind can be one of the following:
+///
+/// * Future - The commit timestamp is later than the start of the run.
+/// * Backwards - The commit timestamp is earlier than the one of the preceding operation of the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockSkewKind {
+    Future,
+    Backwards,
+}
+
+/// Represents an operation with a commit timestamp that indicates a clock skew or a DMS bug.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSkewAnomaly {
+    pub kind: ClockSkewKind,
+    pub primary_key: String,
+    pub timestamp: String,
+    /// The commit timestamp of the preceding operation of the key, for a backwards step.
+    pub previous_timestamp: Option<String>,
+    pub file: String,
+}
+
+/// Detects the operations with a commit timestamp in the future, or earlier than the preceding
+/// operation of the same key.
+///
+/// The files are observed one by one, in the order they are applied, and only a 64-bit hash
+/// of each key is kept, along with its latest commit timestamp. The rows without a commit
+/// timestamp are skipped.
+#[derive(Debug)]
+pub struct ClockSkewDetector {
+    primary_key: Vec<String>,
+    timestamp_column: String,
+    now_micros: i64,
+    latest: HashMap<u64, i64>,
+    anomalies: Vec<ClockSkewAnomaly>,
+}
+
+/// Formats a commit timestamp in microseconds since the epoch.
+fn format_micros(micros: i64) -> String {
+    DateTime::from_timestamp_micros(micros)
+        .map(|timestamp| timestamp.naive_utc().to_string())
+        .unwrap_or_else(|| micros.to_string())
+}
+
+impl ClockSkewDetector {
+    /// Creates a new clock skew detector.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary_key` - The primary key columns of the table.
+    /// * `timestamp_column` - The commit timestamp column, either a datetime or text.
+    /// * `now_micros` - The current time in microseconds since the epoch, after which a commit
+    ///   timestamp is in the future.
+    ///
+    /// # Returns
+    ///
+    /// A new clock skew detector instance.
+    pub fn new(
+        primary_key: &[String],
+        timestamp_column: impl Into<String>,
+        now_micros: i64,
+    ) -> Self {
+        Self {
+            primary_key: primary_key.to_vec(),
+            timestamp_column: timestamp_column.into(),
+            now_micros,
+            latest: HashMap::new(),
+            anomalies: Vec::new(),
+        }
+    }
+
+    /// Observes the rows of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the file.
+    /// * `df` - The rows of the file, with the primary key and commit timestamp columns.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub fn observe(&mut self, file_name: &str, df: &DataFrame) -> Result<()> {
+        let key_columns = self
+            .primary_key
+            .iter()
+            .map(|key| df.column(key))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let timestamps = commit_timestamp_micros(df, &self.timestamp_column)?;
+
+        for (row, timestamp) in timestamps.into_iter().enumerate() {
+            let Some(timestamp) = timestamp else {
+                continue;
+            };
+
+            let mut hasher = DefaultHasher::new();
+            for column in &key_columns {
+                column.get(row)?.to_string().hash(&mut hasher);
+            }
+            let key_hash = hasher.finish();
+            let previous = self.latest.insert(key_hash, timestamp);
+
+            let (kind, previous_timestamp) = match previous {
+                Some(previous) if timestamp < previous => {
+                    // The latest commit timestamp of the key is kept
+                    self.latest.insert(key_hash, previous);
+                    (ClockSkewKind::Backwards, Some(format_micros(previous)))
+                }
+                _ if timestamp > self.now_micros => (ClockSkewKind::Future, None),
+                _ => continue,
+            };
+            let primary_key = key_columns
+                .iter()
+                .map(|column| column.get(row).map(|value| value.to_string()))
+                .collect::<PolarsResult<Vec<_>>>()?
+                .join(",");
+            self.anomalies.push(ClockSkewAnomaly {
+                kind,
+                primary_key,
+                timestamp: format_micros(timestamp),
+                previous_timestamp,
+                file: file_name.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the detector.
+    ///
+    /// # Returns
+    ///
+    /// The anomalies, in the order they were found.
+    pub fn finish(self) -> Vec<ClockSkewAnomaly> {
+        self.anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-02 00:00:00 in microseconds since the epoch.
+    const NOW_MICROS: i64 = 1_704_153_600_000_000;
+
+    #[test]
+    fn test_detect_future_timestamp_and_backwards_step() {
+        let mut detector =
+            ClockSkewDetector::new(&["id".to_string()], "_dms_ingestion_timestamp", NOW_MICROS);
+
+        detector
+            .observe(
+                "20240101-000000000.parquet",
+                &df!(
+                    "id" => &[1i64, 2],
+                    "_dms_ingestion_timestamp" => &["2024-01-01 00:00:02", "2024-01-01 00:00:01"]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        detector
+            .observe(
+                "20240101-000000001.parquet",
+                &df!(
+                    "id" => &[1i64, 2, 2, 3],
+                    "_dms_ingestion_timestamp" => &[
+                        Some("2024-01-01 00:00:01"),
+                        Some("2024-01-01 00:00:03"),
+                        None,
+                        Some("2024-01-03 00:00:00")
+                    ]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            detector.finish(),
+            vec![
+                ClockSkewAnomaly {
+                    kind: ClockSkewKind::Backwards,
+                    primary_key: "1".to_string(),
+                    timestamp: "2024-01-01 00:00:01".to_string(),
+                    previous_timestamp: Some("2024-01-01 00:00:02".to_string()),
+                    file: "20240101-000000001.parquet".to_string(),
+                },
+                ClockSkewAnomaly {
+                    kind: ClockSkewKind::Future,
+                    primary_key: "3".to_string(),
+                    timestamp: "2024-01-03 00:00:00".to_string(),
+                    previous_timestamp: None,
+                    file: "20240101-000000001.parquet".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_steps_of_different_keys_are_not_anomalies() {
+        let mut detector = ClockSkewDetector::new(
+            &["tenant_id".to_string(), "id".to_string()],
+            "_dms_ingestion_timestamp",
+            NOW_MICROS,
+        );
+
+        detector
+            .observe(
+                "file1",
+                &df!(
+                    "tenant_id" => &[1i64, 2],
+                    "id" => &[1i64, 1],
+                    "_dms_ingestion_timestamp" => &["2024-01-01 00:00:02", "2024-01-01 00:00:01"]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert!(detector.finish().is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use polars::prelude::*;
+
+/// The maximum number of columns of a Postgres table.
+pub const DEFAULT_MAX_COLUMNS: usize = 1600;
+
+/// Represents how a DataFrame with more columns than the limit is handled.
+///
+/// The severity can be one of the following:
+///
+/// * Warn - A warning is logged and the DataFrame is processed.
+/// * Error - The DataFrame is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnLimitSeverity {
+    Warn,
+    #[default]
+    Error,
+}
+
+/// Represents a guard against pathological Parquet files with so many columns that the
+/// generated statements would be enormous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnLimit {
+    pub max_columns: usize,
+    pub severity: ColumnLimitSeverity,
+}
+
+impl Default for ColumnLimit {
+    fn default() -> Self {
+        ColumnLimit {
+            max_columns: DEFAULT_MAX_COLUMNS,
+            severity: ColumnLimitSeverity::default(),
+        }
+    }
+}
+
+impl ColumnLimit {
+    /// Checks that a DataFrame does not exceed the column limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame of a Parquet file.
+    /// * `key` - The key of the Parquet file, for the error message.
+    ///
+    /// # Returns
+    ///
+    /// An error if the DataFrame exceeds the limit and the severity is `Error`.
+    pub fn check(&self, df: &DataFrame, key: &str) -> Result<()> {
+        if df.width() <= self.max_columns {
+            return Ok(());
+        }
+
+        match self.severity {
+            ColumnLimitSeverity::Warn => {
+                warn!(
+                    "File {} has {} columns, more than the limit of {}",
+                    key,
+                    df.width(),
+                    self.max_columns
+                );
+                Ok(())
+            }
+            ColumnLimitSeverity::Error => Err(anyhow!(
+                "File {} has {} columns, more than the limit of {}",
+                key,
+                df.width(),
+                self.max_columns
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide_dataframe(width: usize) -> DataFrame {
+        DataFrame::new(
+            (0..width)
+                .map(|index| Series::new(&format!("column_{index}"), &[1i64]))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dataframe_within_limit() {
+        let column_limit = ColumnLimit {
+            max_columns: 3,
+            severity: ColumnLimitSeverity::Error,
+        };
+
+        assert!(column_limit
+            .check(&wide_dataframe(3), "file.parquet")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_dataframe_exceeding_limit() {
+        let df = wide_dataframe(4);
+
+        let column_limit = ColumnLimit {
+            max_columns: 3,
+            severity: ColumnLimitSeverity::Warn,
+        };
+        assert!(column_limit.check(&df, "file.parquet").is_ok());
+
+        let column_limit = ColumnLimit {
+            max_columns: 3,
+            severity: ColumnLimitSeverity::Error,
+        };
+        assert_eq!(
+            column_limit
+                .check(&df, "file.parquet")
+                .unwrap_err()
+                .to_string(),
+            "File file.parquet has 4 columns, more than the limit of 3"
+        );
+    }
+
+    #[test]
+    fn test_default_column_limit() {
+        let column_limit = ColumnLimit::default();
+
+        assert_eq!(column_limit.max_columns, 1600);
+        assert_eq!(column_limit.severity, ColumnLimitSeverity::Error);
+    }
+}
@@ -0,0 +1,557 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+use polars_core::export::rayon::prelude::*;
+use polars_core::POOL;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::path::Path;
+
+use super::key_normalization::KeyNormalization;
+use super::value_comparator::ValueComparator;
+
+/// Represents the type of a difference between two DataFrames.
+///
+/// The type can be one of the following:
+///
+/// * MissingInTarget - The row exists only in the source.
+/// * MissingInSource - The row exists only in the target.
+/// * ValueMismatch - The row exists in both, but the value of a column differs.
+/// * DuplicateInSource - The primary key of the row is shared by an earlier row of the source,
+///   so the row is not compared.
+/// * DuplicateInTarget - The primary key of the row is shared by an earlier row of the target,
+///   so the row is not compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffType {
+    MissingInTarget,
+    MissingInSource,
+    ValueMismatch,
+    DuplicateInSource,
+    DuplicateInTarget,
+}
+
+impl Display for DiffType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffType::MissingInTarget => write!(f, "MissingInTarget"),
+            DiffType::MissingInSource => write!(f, "MissingInSource"),
+            DiffType::ValueMismatch => write!(f, "ValueMismatch"),
+            DiffType::DuplicateInSource => write!(f, "DuplicateInSource"),
+            DiffType::DuplicateInTarget => write!(f, "DuplicateInTarget"),
+        }
+    }
+}
+
+/// Represents a difference of a row between two DataFrames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowDiff {
+    pub primary_key: String,
+    pub column: Option<String>,
+    pub source_value: Option<String>,
+    pub target_value: Option<String>,
+    pub diff_type: DiffType,
+}
+
+impl RowDiff {
+    /// Creates a difference for a row that exists only in one of the DataFrames,
+    /// or that shares its primary key with an earlier row.
+    fn missing(primary_key: &str, diff_type: DiffType) -> Self {
+        Self {
+            primary_key: primary_key.to_string(),
+            column: None,
+            source_value: None,
+            target_value: None,
+            diff_type,
+        }
+    }
+}
+
+/// Represents the differences between two DataFrames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataFrameDiff {
+    pub rows: Vec<RowDiff>,
+}
+
+impl DataFrameDiff {
+    /// Checks if the two DataFrames are equal.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Gets the number of differences.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Converts the differences to a DataFrame with the columns
+    /// `primary_key`, `column`, `source_value`, `target_value` and `diff_type`.
+    pub fn to_dataframe(&self) -> Result<DataFrame> {
+        let primary_keys = self
+            .rows
+            .iter()
+            .map(|row| row.primary_key.as_str())
+            .collect::<Vec<_>>();
+        let columns = self
+            .rows
+            .iter()
+            .map(|row| row.column.as_deref())
+            .collect::<Vec<_>>();
+        let source_values = self
+            .rows
+            .iter()
+            .map(|row| row.source_value.as_deref())
+            .collect::<Vec<_>>();
+        let target_values = self
+            .rows
+            .iter()
+            .map(|row| row.target_value.as_deref())
+            .collect::<Vec<_>>();
+        let diff_types = self
+            .rows
+            .iter()
+            .map(|row| row.diff_type.to_string())
+            .collect::<Vec<_>>();
+
+        Ok(DataFrame::new(vec![
+            Series::new("primary_key", primary_keys),
+            Series::new("column", columns),
+            Series::new("source_value", source_values),
+            Series::new("target_value", target_values),
+            Series::new("diff_type", diff_types),
+        ])?)
+    }
+}
+
+/// Writes the differences between two DataFrames to a Parquet file,
+/// so that large diffs can be analyzed with other tools.
+///
+/// # Arguments
+///
+/// * `diff` - The differences to write.
+/// * `path` - The path of the Parquet file.
+///
+/// # Returns
+///
+/// A Result indicating success or failure.
+pub fn write_diff_to_parquet(diff: &DataFrameDiff, path: impl AsRef<Path>) -> Result<()> {
+    let mut df = diff.to_dataframe()?;
+    let file = File::create(path)?;
+    ParquetWriter::new(file).finish(&mut df)?;
+
+    Ok(())
+}
+
+/// Represents a differ that compares two DataFrames by primary key.
+#[derive(Debug, Clone, Default)]
+pub struct DataframeDiffer {
+    comparator: ValueComparator,
+    column_group_size: Option<usize>,
+    ignore_columns: Vec<String>,
+    key_normalization: KeyNormalization,
+}
+
+impl DataframeDiffer {
+    /// Creates a new DataFrame differ.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator of the column values.
+    /// * `column_group_size` - If set, the columns are split in groups of this size,
+    ///   which are compared in parallel. Useful for very wide tables.
+    ///
+    /// # Returns
+    ///
+    /// A new DataFrame differ instance.
+    pub fn new(comparator: ValueComparator, column_group_size: Option<usize>) -> Self {
+        Self {
+            comparator,
+            column_group_size,
+            ignore_columns: Vec::new(),
+            key_normalization: KeyNormalization::default(),
+        }
+    }
+
+    /// Sets the columns excluded from the comparison, e.g. sync or audit timestamps that differ
+    /// by design. The columns are still loaded, and the rows are still matched by primary key.
+    pub fn with_ignore_columns(mut self, ignore_columns: Vec<String>) -> Self {
+        self.ignore_columns = ignore_columns;
+        self
+    }
+
+    /// Sets the normalization of the primary key values of both DataFrames, so that rows whose
+    /// keys differ only by invisible characters or Unicode form are matched.
+    pub fn with_key_normalization(mut self, key_normalization: KeyNormalization) -> Self {
+        self.key_normalization = key_normalization;
+        self
+    }
+
+    /// Compares two DataFrames by primary key.
+    ///
+    /// The differences are ordered by the row order of the source, followed by the rows that
+    /// exist only in the target, and then by the column order of the source, regardless of
+    /// whether the columns are compared in parallel. The rows whose primary key is shared by an
+    /// earlier row of the same DataFrame are reported as duplicates, after the other differences,
+    /// and only the first row of each key is compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source DataFrame.
+    /// * `target` - The target DataFrame.
+    /// * `primary_key` - The primary key columns.
+    ///
+    /// # Returns
+    ///
+    /// The differences between the two DataFrames.
+    pub fn diff(
+        &self,
+        source: &DataFrame,
+        target: &DataFrame,
+        primary_key: &[String],
+    ) -> Result<DataFrameDiff> {
+        if primary_key.is_empty() {
+            return Err(anyhow!("A primary key is required to compare DataFrames"));
+        }
+
+        let (source_keys, source_duplicates) =
+            row_keys(source, primary_key, &self.key_normalization)?;
+        let (target_keys, target_duplicates) =
+            row_keys(target, primary_key, &self.key_normalization)?;
+
+        let matched_rows = source_keys
+            .iter()
+            .filter_map(|(key, source_row)| {
+                target_keys
+                    .get(key)
+                    .map(|target_row| (key.clone(), *source_row, *target_row))
+            })
+            .collect::<Vec<_>>();
+
+        let target_columns = target.get_column_names();
+        let columns = source
+            .get_column_names()
+            .into_iter()
+            .filter(|column| !primary_key.iter().any(|key| key == column))
+            .filter(|column| target_columns.contains(column))
+            .filter(|column| !self.ignore_columns.iter().any(|ignored| ignored == column))
+            .collect::<Vec<_>>();
+
+        let mut value_diffs = match self.column_group_size {
+            Some(column_group_size) if column_group_size > 0 => POOL
+                .install(|| {
+                    columns
+                        .par_chunks(column_group_size)
+                        .enumerate()
+                        .map(|(group_index, column_group)| {
+                            self.diff_columns(
+                                column_group,
+                                group_index * column_group_size,
+                                source,
+                                target,
+                                &matched_rows,
+                            )
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+            _ => self.diff_columns(&columns, 0, source, target, &matched_rows)?,
+        };
+
+        // Keep the same order as the sequential comparison
+        value_diffs.sort_by_key(|(row_index, column_index, _)| (*row_index, *column_index));
+        let mut value_diffs = value_diffs.into_iter().peekable();
+
+        let mut rows = Vec::new();
+        let mut row_index = 0;
+        for key in source_keys.keys() {
+            if target_keys.contains_key(key) {
+                while let Some((_, _, diff)) =
+                    value_diffs.next_if(|(diff_row_index, _, _)| *diff_row_index == row_index)
+                {
+                    rows.push(diff);
+                }
+                row_index += 1;
+            } else {
+                rows.push(RowDiff::missing(key, DiffType::MissingInTarget));
+            }
+        }
+
+        for key in target_keys.keys() {
+            if !source_keys.contains_key(key) {
+                rows.push(RowDiff::missing(key, DiffType::MissingInSource));
+            }
+        }
+
+        rows.extend(
+            source_duplicates
+                .iter()
+                .map(|key| RowDiff::missing(key, DiffType::DuplicateInSource)),
+        );
+        rows.extend(
+            target_duplicates
+                .iter()
+                .map(|key| RowDiff::missing(key, DiffType::DuplicateInTarget)),
+        );
+
+        Ok(DataFrameDiff { rows })
+    }
+
+    /// Compares a group of columns for the rows that exist in both DataFrames.
+    fn diff_columns(
+        &self,
+        columns: &[&str],
+        column_offset: usize,
+        source: &DataFrame,
+        target: &DataFrame,
+        matched_rows: &[(String, usize, usize)],
+    ) -> Result<Vec<(usize, usize, RowDiff)>> {
+        let mut diffs = Vec::new();
+
+        for (index, column) in columns.iter().enumerate() {
+            let source_column = source.column(column)?;
+            let target_column = target.column(column)?;
+
+            for (row_index, (key, source_row, target_row)) in matched_rows.iter().enumerate() {
+                let source_value = source_column.get(*source_row)?;
+                let target_value = target_column.get(*target_row)?;
+
+                if !self
+                    .comparator
+                    .values_equal(column, &source_value, &target_value)
+                {
+                    diffs.push((
+                        row_index,
+                        column_offset + index,
+                        RowDiff {
+                            primary_key: key.clone(),
+                            column: Some(column.to_string()),
+                            source_value: Some(source_value.to_string()),
+                            target_value: Some(target_value.to_string()),
+                            diff_type: DiffType::ValueMismatch,
+                        },
+                    ));
+                }
+            }
+        }
+
+        Ok(diffs)
+    }
+}
+
+/// Gets the primary key of each row of a DataFrame, along with the index of the first row
+/// of the key, and the keys of the later rows that share the key of an earlier row.
+fn row_keys(
+    df: &DataFrame,
+    primary_key: &[String],
+    key_normalization: &KeyNormalization,
+) -> Result<(IndexMap<String, usize>, Vec<String>)> {
+    let key_columns = primary_key
+        .iter()
+        .map(|key| df.column(key))
+        .collect::<PolarsResult<Vec<_>>>()?;
+
+    let mut keys = IndexMap::with_capacity(df.height());
+    let mut duplicates = Vec::new();
+    for row in 0..df.height() {
+        let key = key_columns
+            .iter()
+            .map(|column| {
+                column
+                    .get(row)
+                    .map(|value| key_normalization.normalize(&value.to_string()))
+            })
+            .collect::<PolarsResult<Vec<_>>>()?
+            .join(",");
+        if keys.contains_key(&key) {
+            duplicates.push(key);
+        } else {
+            keys.insert(key, row);
+        }
+    }
+
+    Ok((keys, duplicates))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide_dataframe(columns: usize, rows: i64, changed_row: Option<i64>) -> DataFrame {
+        let mut series = vec![Series::new("id", (0..rows).collect::<Vec<_>>())];
+        for column in 0..columns {
+            let values = (0..rows)
+                .map(|row| {
+                    if Some(row) == changed_row && column % 7 == 0 {
+                        -1
+                    } else {
+                        row * column as i64
+                    }
+                })
+                .collect::<Vec<_>>();
+            series.push(Series::new(&format!("column_{column}"), values));
+        }
+        DataFrame::new(series).unwrap()
+    }
+
+    #[test]
+    fn test_diff_finds_missing_and_mismatching_rows() {
+        let source = DataFrame::new(vec![
+            Series::new("id", &[1, 2, 3]),
+            Series::new("name", &["a", "b", "c"]),
+        ])
+        .unwrap();
+        let target = DataFrame::new(vec![
+            Series::new("id", &[1, 2, 4]),
+            Series::new("name", &["a", "x", "d"]),
+        ])
+        .unwrap();
+
+        let diff = DataframeDiffer::default()
+            .diff(&source, &target, &["id".to_string()])
+            .unwrap();
+
+        assert_eq!(diff.len(), 3);
+        assert_eq!(diff.rows[0].primary_key, "2");
+        assert_eq!(diff.rows[0].diff_type, DiffType::ValueMismatch);
+        assert_eq!(diff.rows[0].column.as_deref(), Some("name"));
+        assert_eq!(diff.rows[1].primary_key, "3");
+        assert_eq!(diff.rows[1].diff_type, DiffType::MissingInTarget);
+        assert_eq!(diff.rows[2].primary_key, "4");
+        assert_eq!(diff.rows[2].diff_type, DiffType::MissingInSource);
+    }
+
+    #[test]
+    fn test_diff_reports_duplicate_keys() {
+        let source = DataFrame::new(vec![
+            Series::new("id", &[1, 2, 2]),
+            Series::new("name", &["a", "b", "c"]),
+        ])
+        .unwrap();
+        let target = DataFrame::new(vec![
+            Series::new("id", &[1, 1, 2]),
+            Series::new("name", &["a", "x", "b"]),
+        ])
+        .unwrap();
+
+        let diff = DataframeDiffer::default()
+            .diff(&source, &target, &["id".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            diff.rows,
+            vec![
+                RowDiff::missing("2", DiffType::DuplicateInSource),
+                RowDiff::missing("1", DiffType::DuplicateInTarget),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_skips_ignored_columns() {
+        let source = DataFrame::new(vec![
+            Series::new("id", &[1, 2]),
+            Series::new("name", &["a", "b"]),
+            Series::new("last_synced_at", &["2024-01-01", "2024-01-01"]),
+        ])
+        .unwrap();
+        let target = DataFrame::new(vec![
+            Series::new("id", &[1, 2]),
+            Series::new("name", &["a", "x"]),
+            Series::new("last_synced_at", &["2024-01-02", "2024-01-02"]),
+        ])
+        .unwrap();
+        let primary_key = vec!["id".to_string()];
+
+        let diff = DataframeDiffer::default()
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+        assert_eq!(diff.len(), 3);
+
+        let diff = DataframeDiffer::default()
+            .with_ignore_columns(vec!["last_synced_at".to_string()])
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.rows[0].primary_key, "2");
+        assert_eq!(diff.rows[0].column.as_deref(), Some("name"));
+    }
+
+    #[test]
+    fn test_diff_matches_keys_differing_by_zero_width_space() {
+        let source = df!("id" => &["user\u{200B}-1"], "name" => &["a"]).unwrap();
+        let target = df!("id" => &["user-1"], "name" => &["a"]).unwrap();
+        let primary_key = vec!["id".to_string()];
+
+        let diff = DataframeDiffer::default()
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+        assert_eq!(diff.len(), 2);
+
+        let diff = DataframeDiffer::default()
+            .with_key_normalization(KeyNormalization {
+                strip_invisible_characters: true,
+                unicode_form: None,
+            })
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_write_diff_to_parquet_round_trips() {
+        let diff = DataFrameDiff {
+            rows: vec![
+                RowDiff {
+                    primary_key: "2".to_string(),
+                    column: Some("name".to_string()),
+                    source_value: Some("\"b\"".to_string()),
+                    target_value: Some("\"x\"".to_string()),
+                    diff_type: DiffType::ValueMismatch,
+                },
+                RowDiff::missing("3", DiffType::MissingInTarget),
+            ],
+        };
+        let path = std::env::temp_dir().join(format!("diff-{}.parquet", std::process::id()));
+
+        write_diff_to_parquet(&diff, &path).unwrap();
+        let df = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            df.get_column_names(),
+            vec![
+                "primary_key",
+                "column",
+                "source_value",
+                "target_value",
+                "diff_type"
+            ]
+        );
+        assert!(df.equals_missing(&diff.to_dataframe().unwrap()));
+        assert_eq!(
+            df.column("diff_type").unwrap().str().unwrap().get(1),
+            Some("MissingInTarget")
+        );
+        assert_eq!(df.column("column").unwrap().null_count(), 1);
+    }
+
+    #[test]
+    fn test_parallel_column_groups_match_sequential_diff() {
+        let source = wide_dataframe(200, 500, None);
+        let target = wide_dataframe(200, 500, Some(42));
+        let primary_key = vec!["id".to_string()];
+
+        let sequential = DataframeDiffer::default()
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+        let parallel = DataframeDiffer::new(ValueComparator::new(), Some(16))
+            .diff(&source, &target, &primary_key)
+            .unwrap();
+
+        assert_eq!(sequential.len(), 29);
+        assert_eq!(sequential, parallel);
+    }
+}
@@ -1,18 +1,130 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use aws_sdk_s3::types::RequestPayer;
 use aws_sdk_s3::Client as S3Client;
-use log::debug;
+use log::{debug, warn};
 use polars::prelude::*;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tracing::Instrument;
+
+use crate::dataframe::file_format::{read_avro_from_bytes, FileFormat};
+use crate::s3::server_side_encryption::{map_get_object_error, ServerSideEncryptionMode};
+use crate::telemetry::operation_span;
 
 #[cfg(test)]
 use mockall::automock;
 
+/// Represents how a mismatch between the row count declared in the Parquet footer
+/// and the number of rows actually read is handled.
+///
+/// The severity can be one of the following:
+///
+/// * Ignore - The row counts will not be compared.
+/// * Warn - The mismatch will be logged and the rows read will be used.
+/// * Error - The mismatch will fail the read of the Parquet file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RowCountMismatchSeverity {
+    Ignore,
+    #[default]
+    Warn,
+    Error,
+}
+
 pub struct CreateDataframePayload {
     pub bucket_name: String,
     pub key: String,
     pub database_name: String,
     pub schema_name: String,
     pub table_name: String,
+    pub row_count_mismatch_severity: RowCountMismatchSeverity,
+    pub streaming_threshold_bytes: Option<i64>,
+    pub n_rows: Option<usize>,
+    pub read_buffer_size: Option<usize>,
+    pub chunk_size: Option<usize>,
+    pub version_id: Option<String>,
+}
+
+/// Represents how a Parquet file is read from S3.
+///
+/// The strategy can be one of the following:
+///
+/// * Eager - The whole file is buffered in memory and decoded in parallel by row group.
+/// * Streaming - The file is streamed to a temporary file on disk and decoded in low memory mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStrategy {
+    Eager,
+    Streaming,
+}
+
+impl ReadStrategy {
+    /// Chooses the read strategy of a Parquet file based on its size.
+    ///
+    /// # Arguments
+    ///
+    /// * `content_length` - The size of the Parquet file in bytes.
+    /// * `streaming_threshold_bytes` - The size above which the file is streamed.
+    ///
+    /// # Returns
+    ///
+    /// The read strategy.
+    pub fn for_content_length(content_length: i64, streaming_threshold_bytes: i64) -> Self {
+        if content_length > streaming_threshold_bytes {
+            ReadStrategy::Streaming
+        } else {
+            ReadStrategy::Eager
+        }
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ParquetFileReader {
+    /// Gets the size of a Parquet file in S3.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload to create a DataFrame from a Parquet file.
+    ///
+    /// # Returns
+    ///
+    /// The size of the Parquet file in bytes.
+    async fn content_length(&self, payload: &CreateDataframePayload) -> Result<i64>;
+
+    /// Gets the row count declared in the footer of a Parquet file in S3,
+    /// without downloading its data pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload to create a DataFrame from a Parquet file.
+    ///
+    /// # Returns
+    ///
+    /// The row count declared in the Parquet footer.
+    async fn footer_row_count(&self, payload: &CreateDataframePayload) -> Result<usize>;
+
+    /// Reads a Parquet file from S3 by buffering it in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload to create a DataFrame from a Parquet file.
+    ///
+    /// # Returns
+    ///
+    /// A DataFrame.
+    async fn read_eagerly(&self, payload: &CreateDataframePayload) -> Result<DataFrame>;
+
+    /// Reads a Parquet file from S3 by streaming it to a temporary file.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload to create a DataFrame from a Parquet file.
+    ///
+    /// # Returns
+    ///
+    /// A DataFrame.
+    async fn read_streaming(&self, payload: &CreateDataframePayload) -> Result<DataFrame>;
 }
 
 #[cfg_attr(test, automock)]
@@ -35,11 +147,62 @@ pub trait DataframeOperator {
 
 pub struct DataframeOperatorImpl<'a> {
     s3_client: &'a S3Client,
+    request_payer: Option<RequestPayer>,
+    server_side_encryption: ServerSideEncryptionMode,
 }
 
 impl<'a> DataframeOperatorImpl<'a> {
     pub fn new(s3_client: &'a S3Client) -> Self {
-        Self { s3_client }
+        Self {
+            s3_client,
+            request_payer: None,
+            server_side_encryption: ServerSideEncryptionMode::default(),
+        }
+    }
+
+    /// Sets the server-side encryption expected for the Parquet files, so that a denied read
+    /// of a file encrypted with SSE-KMS is reported as such, instead of as a generic failure.
+    pub fn with_server_side_encryption(
+        mut self,
+        server_side_encryption: ServerSideEncryptionMode,
+    ) -> Self {
+        self.server_side_encryption = server_side_encryption;
+        self
+    }
+
+    /// Sets whether the requests are sent with the requester-pays header, which is required
+    /// to read from requester-pays buckets. The caller is charged for the requests and the data transfer.
+    pub fn with_requester_pays(mut self, requester_pays: bool) -> Self {
+        self.request_payer = requester_pays.then_some(RequestPayer::Requester);
+        self
+    }
+
+    /// Gets a byte range of a file in S3.
+    async fn get_object_range(
+        &self,
+        payload: &CreateDataframePayload,
+        range: &str,
+    ) -> Result<Vec<u8>> {
+        let object = self
+            .s3_client
+            .get_object()
+            .bucket(&payload.bucket_name)
+            .key(&payload.key)
+            .set_version_id(payload.version_id.clone())
+            .set_request_payer(self.request_payer.clone())
+            .range(range)
+            .send()
+            .await
+            .map_err(|e| {
+                map_get_object_error(
+                    e,
+                    &payload.bucket_name,
+                    &payload.key,
+                    self.server_side_encryption,
+                )
+            })?;
+
+        Ok(object.body.collect().await?.to_vec())
     }
 }
 
@@ -63,38 +226,427 @@ impl DataframeOperator for DataframeOperatorImpl<'_> {
         // debug!("{:?}", df.schema());
         // Ok(df)
 
+        let df = read_parquet_file(self, payload).await?;
+        debug!("First row: {:?}", df.get(0).unwrap());
+        debug!("{:?}", df.schema());
+
+        Ok(Some(df))
+    }
+}
+
+#[async_trait]
+impl ParquetFileReader for DataframeOperatorImpl<'_> {
+    async fn content_length(&self, payload: &CreateDataframePayload) -> Result<i64> {
+        let head_object = self
+            .s3_client
+            .head_object()
+            .bucket(&payload.bucket_name)
+            .key(&payload.key)
+            .set_version_id(payload.version_id.clone())
+            .set_request_payer(self.request_payer.clone())
+            .send()
+            .await
+            .map_err(aws_sdk_s3::Error::from)?;
+
+        Ok(head_object.content_length().unwrap_or_default())
+    }
+
+    async fn footer_row_count(&self, payload: &CreateDataframePayload) -> Result<usize> {
+        // The last 8 bytes of a Parquet file are the length of the footer and the magic number
+        let tail = self.get_object_range(payload, "bytes=-8").await?;
+        let footer_length = footer_length(&tail)?;
+
+        let footer = self
+            .get_object_range(payload, &format!("bytes=-{}", footer_length + 8))
+            .await?;
+
+        row_count_from_footer(&footer)
+    }
+
+    async fn read_eagerly(&self, payload: &CreateDataframePayload) -> Result<DataFrame> {
         let object = self
             .s3_client
             .get_object()
             .bucket(&payload.bucket_name)
             .key(&payload.key)
+            .set_version_id(payload.version_id.clone())
+            .set_request_payer(self.request_payer.clone())
             .send()
             .await
-            .unwrap();
+            .map_err(|e| {
+                map_get_object_error(
+                    e,
+                    &payload.bucket_name,
+                    &payload.key,
+                    self.server_side_encryption,
+                )
+            })?;
 
         let bytes = object.body.collect().await.unwrap().into_bytes();
-        let cursor = std::io::Cursor::new(bytes);
 
-        let reader = ParquetReader::new(cursor);
-        let df = reader
-            .read_parallel(ParallelStrategy::RowGroups)
-            .finish()
-            .unwrap();
-        debug!("First row: {:?}", df.get(0).unwrap());
-        debug!("{:?}", df.schema());
+        if FileFormat::from_key(&payload.key) == FileFormat::Avro {
+            return read_avro_from_bytes(bytes, payload.n_rows);
+        }
+        read_parquet_from_bytes(
+            bytes,
+            &payload.key,
+            payload.n_rows,
+            payload.row_count_mismatch_severity,
+        )
+    }
 
-        Ok(Some(df))
+    async fn read_streaming(&self, payload: &CreateDataframePayload) -> Result<DataFrame> {
+        let mut object = self
+            .s3_client
+            .get_object()
+            .bucket(&payload.bucket_name)
+            .key(&payload.key)
+            .set_version_id(payload.version_id.clone())
+            .set_request_payer(self.request_payer.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                map_get_object_error(
+                    e,
+                    &payload.bucket_name,
+                    &payload.key,
+                    self.server_side_encryption,
+                )
+            })?;
+
+        // The temporary file is removed when dropped, whether the read succeeds or fails
+        let temp_file = tempfile::Builder::new()
+            .prefix("dms-cdc-operator-")
+            .suffix(".parquet")
+            .tempfile()?;
+
+        let file = tokio::fs::File::from_std(temp_file.reopen()?);
+        let mut file = match payload.read_buffer_size {
+            Some(read_buffer_size) => BufWriter::with_capacity(read_buffer_size, file),
+            None => BufWriter::new(file),
+        };
+        while let Some(bytes) = object.body.try_next().await? {
+            file.write_all(&bytes).await?;
+        }
+        file.flush().await?;
+        drop(file);
+
+        read_parquet_from_file(
+            temp_file.path(),
+            &payload.key,
+            payload.n_rows,
+            payload.chunk_size,
+            payload.row_count_mismatch_severity,
+        )
+        .await
+    }
+}
+
+/// Reads a Parquet file from S3, choosing the read strategy based on its size.
+///
+/// If a streaming threshold is set in the payload, the size of the file is fetched with a HEAD
+/// request, and the files larger than the threshold are streamed to disk instead of being
+/// buffered in memory.
+///
+/// # Arguments
+///
+/// * `reader` - The reader of the Parquet files.
+/// * `payload` - The payload to create a DataFrame from a Parquet file.
+///
+/// # Returns
+///
+/// A DataFrame.
+pub async fn read_parquet_file(
+    reader: &(impl ParquetFileReader + Sync),
+    payload: &CreateDataframePayload,
+) -> Result<DataFrame> {
+    let span = operation_span!(
+        "read_parquet_file",
+        key = payload.key.as_str(),
+        table = payload.table_name.as_str(),
+        rows = tracing::field::Empty
+    );
+
+    let df = async {
+        let read_strategy = match payload.streaming_threshold_bytes {
+            // Only the Parquet files are streamed to disk
+            _ if FileFormat::from_key(&payload.key) == FileFormat::Avro => ReadStrategy::Eager,
+            Some(streaming_threshold_bytes) => {
+                let content_length = reader.content_length(payload).await?;
+                ReadStrategy::for_content_length(content_length, streaming_threshold_bytes)
+            }
+            None => ReadStrategy::Eager,
+        };
+        debug!("Reading {} with strategy: {:?}", payload.key, read_strategy);
+
+        match read_strategy {
+            ReadStrategy::Eager => reader.read_eagerly(payload).await,
+            ReadStrategy::Streaming => reader.read_streaming(payload).await,
+        }
+    }
+    .instrument(span.clone())
+    .await?;
+    span.record("rows", df.height());
+
+    Ok(df)
+}
+
+/// Reads a DataFrame from a local Parquet file in low memory mode.
+///
+/// The file is read with blocking I/O on a thread of the blocking pool, rather than on the
+/// threads of the runtime.
+///
+/// # Arguments
+///
+/// * `path` - The path of the Parquet file.
+/// * `key` - The S3 key of the Parquet file, used for logging.
+/// * `n_rows` - If set, only the first rows of the file up to this limit are read.
+/// * `chunk_size` - If set, the file is decoded in batches of this many rows, which are then
+///   concatenated. Otherwise, the file is decoded in one pass.
+/// * `severity` - How a row count mismatch is handled.
+///
+/// # Returns
+///
+/// A DataFrame.
+pub async fn read_parquet_from_file(
+    path: &Path,
+    key: &str,
+    n_rows: Option<usize>,
+    chunk_size: Option<usize>,
+    severity: RowCountMismatchSeverity,
+) -> Result<DataFrame> {
+    let path = path.to_path_buf();
+    let key = key.to_string();
+
+    tokio::task::spawn_blocking(move || {
+        read_parquet_from_file_blocking(&path, &key, n_rows, chunk_size, severity)
+    })
+    .await?
+}
+
+/// Reads a DataFrame from a local Parquet file in low memory mode, blocking the current thread.
+fn read_parquet_from_file_blocking(
+    path: &Path,
+    key: &str,
+    n_rows: Option<usize>,
+    chunk_size: Option<usize>,
+    severity: RowCountMismatchSeverity,
+) -> Result<DataFrame> {
+    let mut file = std::fs::File::open(path)?;
+    let mut tail = [0u8; 4];
+    if file.seek(SeekFrom::End(-4)).is_ok() {
+        file.read_exact(&mut tail)?;
+        check_not_encrypted(&tail, key)?;
+    }
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut reader = ParquetReader::new(file)
+        .set_low_memory(true)
+        .with_n_rows(n_rows);
+    let footer_row_count = reader.num_rows()?;
+
+    let df = match chunk_size {
+        Some(chunk_size) if expected_row_count(footer_row_count, n_rows) > 0 => {
+            let mut batched_reader = reader.batched(chunk_size)?;
+            let mut df: Option<DataFrame> = None;
+            let runtime = tokio::runtime::Handle::current();
+            while let Some(batches) = runtime.block_on(batched_reader.next_batches(1))? {
+                for batch in batches {
+                    match df.as_mut() {
+                        Some(df) => {
+                            df.vstack_mut(&batch)?;
+                        }
+                        None => df = Some(batch),
+                    }
+                }
+            }
+            df.ok_or_else(|| anyhow!("No rows were read from Parquet file {key}"))?
+        }
+        _ => reader.finish()?,
+    };
+
+    check_row_count(
+        key,
+        expected_row_count(footer_row_count, n_rows),
+        df.height(),
+        severity,
+    )?;
+
+    Ok(df)
+}
+
+/// Reads a DataFrame from the bytes of a Parquet file.
+///
+/// The row count declared in the Parquet footer is taken from the file metadata
+/// (`FileMetaData.num_rows`, i.e. the sum of the row counts of all row groups),
+/// which is parsed before any data page is decoded. It is then compared against
+/// the height of the decoded DataFrame, so that a silently truncated file is caught.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the Parquet file.
+/// * `key` - The S3 key of the Parquet file, used for logging.
+/// * `n_rows` - If set, only the first rows of the file up to this limit are read.
+/// * `severity` - How a row count mismatch is handled.
+///
+/// # Returns
+///
+/// A DataFrame.
+pub fn read_parquet_from_bytes<T>(
+    bytes: T,
+    key: &str,
+    n_rows: Option<usize>,
+    severity: RowCountMismatchSeverity,
+) -> Result<DataFrame>
+where
+    T: AsRef<[u8]> + Send + Sync,
+{
+    check_not_encrypted(bytes.as_ref(), key)?;
+    let cursor = std::io::Cursor::new(bytes);
+
+    let mut reader = ParquetReader::new(cursor).with_n_rows(n_rows);
+    let footer_row_count = reader.num_rows()?;
+    let df = reader.read_parallel(ParallelStrategy::RowGroups).finish()?;
+
+    check_row_count(
+        key,
+        expected_row_count(footer_row_count, n_rows),
+        df.height(),
+        severity,
+    )?;
+
+    Ok(df)
+}
+
+/// Checks that a Parquet file is not encrypted with Parquet Modular Encryption, which the
+/// Parquet reader does not support, so that such a file fails with a clear error instead of
+/// failing to decode its metadata or pages.
+///
+/// Only the files with an encrypted footer are detected, as they end with the `PARE` magic
+/// number instead of `PAR1`.
+///
+/// # Arguments
+///
+/// * `tail` - The last bytes of the Parquet file, or the whole file.
+/// * `key` - The S3 key of the Parquet file, used for logging.
+///
+/// # Returns
+///
+/// A Result indicating success, or an error if the file is encrypted.
+pub fn check_not_encrypted(tail: &[u8], key: &str) -> Result<()> {
+    if tail.ends_with(ENCRYPTED_FOOTER_MAGIC) {
+        return Err(anyhow!(
+            "Parquet file {key} is encrypted with Parquet Modular Encryption, which is not supported"
+        ));
+    }
+    Ok(())
+}
+
+/// The magic number ending the Parquet files with an encrypted footer.
+const ENCRYPTED_FOOTER_MAGIC: &[u8; 4] = b"PARE";
+
+/// Gets the length of the footer of a Parquet file from its last 8 bytes.
+fn footer_length(tail: &[u8]) -> Result<usize> {
+    match tail {
+        [a, b, c, d, b'P', b'A', b'R', b'1'] => Ok(u32::from_le_bytes([*a, *b, *c, *d]) as usize),
+        [_, _, _, _, b'P', b'A', b'R', b'E'] => Err(anyhow!(
+            "Parquet file is encrypted with Parquet Modular Encryption, which is not supported"
+        )),
+        _ => Err(anyhow!("Not a Parquet file: invalid footer")),
+    }
+}
+
+/// Gets the row count declared in the footer of a Parquet file.
+///
+/// The footer is prefixed with the magic number of the Parquet header, so that the Parquet reader
+/// can parse the file metadata, which is all it reads to get the row count.
+///
+/// # Arguments
+///
+/// * `footer` - The file metadata, followed by the footer length and the magic number.
+///
+/// # Returns
+///
+/// The row count declared in the Parquet footer.
+pub fn row_count_from_footer(footer: &[u8]) -> Result<usize> {
+    footer_length(&footer[footer.len().saturating_sub(8)..])?;
+
+    let mut bytes = b"PAR1".to_vec();
+    bytes.extend_from_slice(footer);
+
+    Ok(ParquetReader::new(std::io::Cursor::new(bytes)).num_rows()?)
+}
+
+/// Gets the number of rows expected to be read from a Parquet file, given an optional row limit.
+fn expected_row_count(footer_row_count: usize, n_rows: Option<usize>) -> usize {
+    match n_rows {
+        Some(n_rows) => footer_row_count.min(n_rows),
+        None => footer_row_count,
+    }
+}
+
+/// Compares the row count declared in the Parquet footer with the rows read.
+///
+/// # Arguments
+///
+/// * `key` - The S3 key of the Parquet file.
+/// * `footer_row_count` - The row count declared in the Parquet footer.
+/// * `rows_read` - The number of rows actually read.
+/// * `severity` - How a row count mismatch is handled.
+///
+/// # Returns
+///
+/// A Result indicating whether the row counts are accepted.
+pub fn check_row_count(
+    key: &str,
+    footer_row_count: usize,
+    rows_read: usize,
+    severity: RowCountMismatchSeverity,
+) -> Result<()> {
+    if footer_row_count == rows_read {
+        return Ok(());
+    }
+
+    match severity {
+        RowCountMismatchSeverity::Ignore => Ok(()),
+        RowCountMismatchSeverity::Warn => {
+            warn!(
+                "Parquet file {key} declares {footer_row_count} rows in its footer, but {rows_read} rows were read"
+            );
+            Ok(())
+        }
+        RowCountMismatchSeverity::Error => Err(anyhow!(
+            "Parquet file {key} declares {footer_row_count} rows in its footer, but {rows_read} rows were read"
+        )),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use polars::prelude::DataFrame;
+    use polars::prelude::*;
 
     use crate::dataframe::dataframe_ops::{
-        CreateDataframePayload, DataframeOperator, MockDataframeOperator,
+        check_row_count, read_parquet_file, read_parquet_from_bytes, read_parquet_from_file,
+        row_count_from_footer, CreateDataframePayload, DataframeOperator, MockDataframeOperator,
+        MockParquetFileReader, ReadStrategy, RowCountMismatchSeverity,
     };
 
+    fn create_dataframe_payload(streaming_threshold_bytes: Option<i64>) -> CreateDataframePayload {
+        CreateDataframePayload {
+            bucket_name: "bucket_name".to_string(),
+            key: "key".to_string(),
+            database_name: "database_name".to_string(),
+            schema_name: "schema_name".to_string(),
+            table_name: "table_name".to_string(),
+            row_count_mismatch_severity: RowCountMismatchSeverity::Warn,
+            streaming_threshold_bytes,
+            n_rows: None,
+            read_buffer_size: None,
+            chunk_size: None,
+            version_id: None,
+        }
+    }
+
     #[tokio::test]
     async fn test_create_dataframe_from_parquet_file() {
         let mut dataframe_operator = MockDataframeOperator::new();
@@ -103,13 +655,7 @@ mod tests {
             .expect_create_dataframe_from_parquet_file()
             .returning(|_| Ok(Some(DataFrame::empty())));
 
-        let create_dataframe_payload = CreateDataframePayload {
-            bucket_name: "bucket_name".to_string(),
-            key: "key".to_string(),
-            database_name: "database_name".to_string(),
-            schema_name: "schema_name".to_string(),
-            table_name: "table_name".to_string(),
-        };
+        let create_dataframe_payload = create_dataframe_payload(None);
 
         let df = dataframe_operator
             .create_dataframe_from_parquet_file(&create_dataframe_payload)
@@ -118,4 +664,246 @@ mod tests {
 
         assert_eq!(df.unwrap().height(), 0);
     }
+
+    #[test]
+    fn test_read_parquet_from_bytes_matches_footer_row_count() {
+        let mut df = DataFrame::new(vec![
+            Series::new("id", &[1, 2, 3]),
+            Series::new("name", &["a", "b", "c"]),
+        ])
+        .unwrap();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        ParquetWriter::new(&mut bytes).finish(&mut df).unwrap();
+
+        let read_df =
+            read_parquet_from_bytes(bytes, "key", None, RowCountMismatchSeverity::Error).unwrap();
+
+        assert_eq!(read_df.height(), 3);
+        assert!(read_df.equals(&df));
+    }
+
+    #[test]
+    fn test_read_parquet_from_bytes_with_row_limit() {
+        let mut df =
+            DataFrame::new(vec![Series::new("id", (0..10).collect::<Vec<i64>>())]).unwrap();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        ParquetWriter::new(&mut bytes).finish(&mut df).unwrap();
+
+        let sampled_df =
+            read_parquet_from_bytes(&bytes, "key", Some(3), RowCountMismatchSeverity::Error)
+                .unwrap();
+        assert_eq!(sampled_df.height(), 3);
+
+        let full_df =
+            read_parquet_from_bytes(&bytes, "key", Some(100), RowCountMismatchSeverity::Error)
+                .unwrap();
+        assert_eq!(full_df.height(), 10);
+    }
+
+    #[test]
+    fn test_row_count_from_footer() {
+        let mut df =
+            DataFrame::new(vec![Series::new("id", (0..42).collect::<Vec<i64>>())]).unwrap();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        ParquetWriter::new(&mut bytes).finish(&mut df).unwrap();
+
+        let tail = &bytes[bytes.len() - 8..];
+        let footer_length = u32::from_le_bytes(tail[..4].try_into().unwrap()) as usize;
+        let footer = &bytes[bytes.len() - footer_length - 8..];
+
+        assert_eq!(row_count_from_footer(footer).unwrap(), 42);
+        assert!(row_count_from_footer(b"not a parquet file").is_err());
+    }
+
+    #[test]
+    fn test_check_row_count() {
+        assert!(check_row_count("key", 3, 3, RowCountMismatchSeverity::Error).is_ok());
+        assert!(check_row_count("key", 3, 2, RowCountMismatchSeverity::Ignore).is_ok());
+        assert!(check_row_count("key", 3, 2, RowCountMismatchSeverity::Warn).is_ok());
+        assert!(check_row_count("key", 3, 2, RowCountMismatchSeverity::Error).is_err());
+    }
+
+    #[test]
+    fn test_read_strategy_for_content_length() {
+        assert_eq!(
+            ReadStrategy::for_content_length(2048, 1024),
+            ReadStrategy::Streaming
+        );
+        assert_eq!(
+            ReadStrategy::for_content_length(1024, 1024),
+            ReadStrategy::Eager
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_file_above_threshold_is_streamed() {
+        let mut reader = MockParquetFileReader::new();
+        reader.expect_content_length().returning(|_| Ok(2048));
+        reader.expect_read_eagerly().times(0);
+        reader
+            .expect_read_streaming()
+            .times(1)
+            .returning(|_| Ok(DataFrame::empty()));
+
+        read_parquet_file(&reader, &create_dataframe_payload(Some(1024)))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_file_below_threshold_is_read_eagerly() {
+        let mut reader = MockParquetFileReader::new();
+        reader.expect_content_length().returning(|_| Ok(512));
+        reader
+            .expect_read_eagerly()
+            .times(1)
+            .returning(|_| Ok(DataFrame::empty()));
+        reader.expect_read_streaming().times(0);
+
+        read_parquet_file(&reader, &create_dataframe_payload(Some(1024)))
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_read_parquet_file_emits_span() {
+        use crate::telemetry::span_recorder::SpanRecorder;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = SpanRecorder::default();
+        let _default =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let mut reader = MockParquetFileReader::new();
+        reader
+            .expect_read_eagerly()
+            .returning(|_| Ok(df!("id" => &[1i64, 2, 3]).unwrap()));
+
+        read_parquet_file(&reader, &create_dataframe_payload(None))
+            .await
+            .unwrap();
+
+        let spans = recorder.spans();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "read_parquet_file");
+        assert_eq!(spans[0].fields["key"], "key");
+        assert_eq!(spans[0].fields["table"], "table_name");
+        assert_eq!(spans[0].fields["rows"], "3");
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_file_without_threshold_skips_head_request() {
+        let mut reader = MockParquetFileReader::new();
+        reader.expect_content_length().times(0);
+        reader
+            .expect_read_eagerly()
+            .times(1)
+            .returning(|_| Ok(DataFrame::empty()));
+
+        read_parquet_file(&reader, &create_dataframe_payload(None))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_avro_file_is_read_eagerly() {
+        let mut reader = MockParquetFileReader::new();
+        reader.expect_content_length().times(0);
+        reader
+            .expect_read_eagerly()
+            .times(1)
+            .returning(|_| Ok(DataFrame::empty()));
+        reader.expect_read_streaming().times(0);
+
+        let payload = CreateDataframePayload {
+            key: "users/20240101-000000000.avro".to_string(),
+            ..create_dataframe_payload(Some(1))
+        };
+        read_parquet_file(&reader, &payload).await.unwrap();
+    }
+
+    /// Writes a Parquet file with the given number of rows to a temporary path.
+    fn write_parquet_file(name: &str, rows: i64) -> std::path::PathBuf {
+        let mut df = df!(
+            "id" => (0..rows).collect::<Vec<_>>(),
+            "name" => (0..rows).map(|i| format!("name {i}")).collect::<Vec<_>>()
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("{name}-{}.parquet", std::process::id()));
+        ParquetWriter::new(std::fs::File::create(&path).unwrap())
+            .with_row_group_size(Some(1000))
+            .finish(&mut df)
+            .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_read_parquet_from_file_in_chunks() {
+        let path = write_parquet_file("chunks", 2500);
+
+        let whole =
+            read_parquet_from_file(&path, "key", None, None, RowCountMismatchSeverity::Error)
+                .await
+                .unwrap();
+        for chunk_size in [1, 100, 5000] {
+            let chunked = read_parquet_from_file(
+                &path,
+                "key",
+                None,
+                Some(chunk_size),
+                RowCountMismatchSeverity::Error,
+            )
+            .await
+            .unwrap();
+            assert!(chunked.equals(&whole));
+        }
+        let limited = read_parquet_from_file(
+            &path,
+            "key",
+            Some(10),
+            Some(100),
+            RowCountMismatchSeverity::Error,
+        )
+        .await
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(whole.height(), 2500);
+        assert_eq!(limited.height(), 10);
+    }
+
+    /// Builds a fixture of a Parquet file with an encrypted footer, i.e. with the `PARE` magic
+    /// number instead of `PAR1`.
+    fn encrypted_parquet_bytes() -> Vec<u8> {
+        let mut df = df!("id" => &[1i64, 2, 3]).unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        ParquetWriter::new(&mut bytes).finish(&mut df).unwrap();
+
+        let length = bytes.len();
+        bytes[..4].copy_from_slice(b"PARE");
+        bytes[length - 4..].copy_from_slice(b"PARE");
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_read_encrypted_parquet_fails_gracefully() {
+        let bytes = encrypted_parquet_bytes();
+        let error =
+            "Parquet file key is encrypted with Parquet Modular Encryption, which is not supported";
+
+        let result = read_parquet_from_bytes(&bytes, "key", None, RowCountMismatchSeverity::Error);
+        assert_eq!(result.unwrap_err().to_string(), error);
+
+        let path = std::env::temp_dir().join(format!("encrypted-{}.parquet", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let result =
+            read_parquet_from_file(&path, "key", None, None, RowCountMismatchSeverity::Error).await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap_err().to_string(), error);
+
+        assert!(row_count_from_footer(&bytes[4..]).is_err());
+    }
 }
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use polars::prelude::*;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use crate::postgres::data_type_mapping::postgres_data_type;
+
+/// Represents a user-provided hook that transforms each DataFrame after it is read from S3
+/// and before it is applied in the target database.
+///
+/// The hook may add or remove columns, e.g. to derive a column or to redact PII,
+/// and the target table is created with the transformed schema.
+#[derive(Clone)]
+pub struct DataframeTransform(Arc<dyn Fn(DataFrame) -> Result<DataFrame> + Send + Sync>);
+
+impl DataframeTransform {
+    /// Creates a new DataFrame transform.
+    ///
+    /// # Arguments
+    ///
+    /// * `transform` - The function to apply on each DataFrame.
+    ///
+    /// # Returns
+    ///
+    /// A new DataFrame transform instance.
+    pub fn new(transform: impl Fn(DataFrame) -> Result<DataFrame> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(transform))
+    }
+
+    /// Applies the transform on a DataFrame.
+    pub fn apply(&self, df: DataFrame) -> Result<DataFrame> {
+        (self.0)(df)
+    }
+}
+
+impl Debug for DataframeTransform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "DataframeTransform")
+    }
+}
+
+/// Gets the columns of the target table for a transformed DataFrame.
+///
+/// The columns that exist in the source table keep their data type, the columns added by the
+/// transform get the data type mapped from the DataFrame and the columns removed by the transform
+/// are left out. The columns added by DMS are ignored.
+///
+/// # Arguments
+///
+/// * `source_table_columns` - The columns of the source table.
+/// * `df` - The transformed DataFrame.
+///
+/// # Returns
+///
+/// A IndexMap containing the column names and their data types.
+pub fn transformed_table_columns(
+    source_table_columns: &IndexMap<String, String>,
+    df: &DataFrame,
+) -> IndexMap<String, String> {
+    df.get_columns()
+        .iter()
+        .filter(|column| column.name() != "Op" && column.name() != "_dms_ingestion_timestamp")
+        .map(|column| {
+            let data_type = source_table_columns
+                .get(column.name())
+                .cloned()
+                .unwrap_or_else(|| postgres_data_type(column.dtype()));
+            (column.name().to_string(), data_type)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_adds_column() {
+        let transform = DataframeTransform::new(|mut df: DataFrame| {
+            let total = df.column("price")?.i64()? * df.column("quantity")?.i64()?;
+            df.with_column(total.into_series().with_name("total"))?;
+            _ = df.drop_in_place("secret")?;
+            Ok(df)
+        });
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I", "I"]),
+            Series::new("id", &[1i32, 2]),
+            Series::new("price", &[10i64, 20]),
+            Series::new("quantity", &[2i64, 3]),
+            Series::new("secret", &["a", "b"]),
+        ])
+        .unwrap();
+
+        let df = transform.apply(df).unwrap();
+        assert_eq!(
+            df.column("total")
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![20, 60]
+        );
+
+        let mut source_table_columns = IndexMap::new();
+        source_table_columns.insert("id".to_string(), "integer".to_string());
+        source_table_columns.insert("price".to_string(), "numeric".to_string());
+        source_table_columns.insert("quantity".to_string(), "integer".to_string());
+        source_table_columns.insert("secret".to_string(), "text".to_string());
+
+        let table_columns = transformed_table_columns(&source_table_columns, &df);
+        assert_eq!(
+            table_columns.keys().collect::<Vec<_>>(),
+            vec!["id", "price", "quantity", "total"]
+        );
+        assert_eq!(table_columns.get("price").unwrap(), "numeric");
+        assert_eq!(table_columns.get("total").unwrap(), "bigint");
+    }
+}
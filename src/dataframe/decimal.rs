@@ -0,0 +1,80 @@
+/// The environment variable that makes Polars read Parquet decimals as `Decimal` instead of
+/// converting them to `Float64`.
+const ACTIVATE_DECIMAL_VARIABLE: &str = "POLARS_ACTIVATE_DECIMAL";
+
+/// Makes the Parquet decimal columns read from now on keep their exact values as `Decimal`,
+/// instead of being converted to `Float64`, which silently loses precision, e.g. of amounts.
+///
+/// The setting is global to the process, as it is read by Polars when reading a file.
+pub fn activate_decimals() {
+    std::env::set_var(ACTIVATE_DECIMAL_VARIABLE, "1");
+}
+
+/// Formats the physical value of a decimal exactly, e.g. `12345` with a scale of 2 as `123.45`,
+/// for any precision supported by Polars, unlike a conversion to a float or to a 96-bit decimal.
+///
+/// # Arguments
+///
+/// * `integer` - The unscaled value of the decimal.
+/// * `scale` - The number of digits after the decimal point.
+///
+/// # Returns
+///
+/// The value of the decimal as text.
+pub fn format_decimal(integer: i128, scale: usize) -> String {
+    let sign = if integer < 0 { "-" } else { "" };
+    let digits = integer.unsigned_abs().to_string();
+    if scale == 0 {
+        return format!("{sign}{digits}");
+    }
+
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (integer_part, fractional_part) = digits.split_at(digits.len() - scale);
+
+    format!("{sign}{integer_part}.{fractional_part}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use std::io::Cursor;
+
+    const HIGH_PRECISION_VALUE: &str = "12345678901234567890.123456789";
+
+    #[test]
+    fn test_format_decimal() {
+        assert_eq!(format_decimal(12345, 2), "123.45");
+        assert_eq!(format_decimal(-5, 2), "-0.05");
+        assert_eq!(format_decimal(42, 0), "42");
+        assert_eq!(format_decimal(0, 3), "0.000");
+        assert_eq!(
+            format_decimal(12345678901234567890123456789, 9),
+            HIGH_PRECISION_VALUE
+        );
+    }
+
+    #[test]
+    fn test_high_precision_decimal_round_trips_without_loss() {
+        let mut df = DataFrame::new(vec![Series::new("amount", &[HIGH_PRECISION_VALUE])
+            .cast(&DataType::Decimal(Some(38), Some(9)))
+            .unwrap()])
+        .unwrap();
+        let mut buffer = Vec::new();
+        ParquetWriter::new(&mut buffer).finish(&mut df).unwrap();
+
+        activate_decimals();
+        let df = ParquetReader::new(Cursor::new(buffer)).finish().unwrap();
+
+        let amount = df.column("amount").unwrap();
+        assert_eq!(amount.dtype(), &DataType::Decimal(Some(38), Some(9)));
+        let AnyValue::Decimal(integer, scale) = amount.get(0).unwrap() else {
+            panic!("Expected a decimal value");
+        };
+        assert_eq!(format_decimal(integer, scale), HIGH_PRECISION_VALUE);
+
+        // The float path loses the last digits
+        let float_value = HIGH_PRECISION_VALUE.parse::<f64>().unwrap();
+        assert_ne!(float_value.to_string(), HIGH_PRECISION_VALUE);
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+
+/// Counts the distinct primary keys of a DataFrame.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame.
+/// * `key_columns` - The primary key columns.
+///
+/// # Returns
+///
+/// The number of distinct primary keys of the DataFrame.
+pub fn count_distinct_keys(df: &DataFrame, key_columns: &[String]) -> Result<usize> {
+    Ok(df
+        .select(key_columns)?
+        .unique(None, UniqueKeepStrategy::Any, None)?
+        .height())
+}
+
+/// Compares the number of distinct primary keys of the source with the one of the target,
+/// as a check cheaper than comparing the rows, which catches missing rows or keys collapsed
+/// into one.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of the source.
+/// * `key_columns` - The primary key columns.
+/// * `target_key_count` - The number of distinct primary keys of the target.
+///
+/// # Returns
+///
+/// A Result indicating whether the numbers of distinct primary keys match.
+pub fn check_distinct_key_count(
+    df: &DataFrame,
+    key_columns: &[String],
+    target_key_count: i64,
+) -> Result<()> {
+    let source_key_count = count_distinct_keys(df, key_columns)?;
+    if source_key_count as i64 != target_key_count {
+        return Err(anyhow!(
+            "Source has {} distinct keys ({}) but target has {}",
+            source_key_count,
+            key_columns.join(","),
+            target_key_count
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_columns() -> Vec<String> {
+        vec!["tenant_id".to_string(), "id".to_string()]
+    }
+
+    fn source() -> DataFrame {
+        df!(
+            "tenant_id" => &[1i64, 1, 2, 2],
+            "id" => &[1i64, 2, 1, 1],
+            "name" => &["a", "b", "c", "d"]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_count_distinct_composite_keys() {
+        assert_eq!(count_distinct_keys(&source(), &key_columns()).unwrap(), 3);
+        assert_eq!(
+            count_distinct_keys(&source(), &["tenant_id".to_string()]).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_distinct_key_count_mismatch() {
+        assert!(check_distinct_key_count(&source(), &key_columns(), 3).is_ok());
+
+        let error = check_distinct_key_count(&source(), &key_columns(), 2).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Source has 3 distinct keys (tenant_id,id) but target has 2"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+use std::collections::BTreeSet;
+
+/// The default maximum number of distinct values of a column to compare, since the comparison
+/// is only meaningful for low-cardinality columns, e.g. a status or a type.
+pub const DEFAULT_MAX_DISTINCT_VALUES: usize = 100;
+
+/// Represents a divergence of the distinct values of a column between the source and the target.
+///
+/// The divergence can be one of the following:
+///
+/// * UnexpectedValues - The values found in the target but not in the source.
+/// * MissingValues - The values found in the source but not in the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DistinctValuesDivergence {
+    UnexpectedValues { column: String, values: Vec<String> },
+    MissingValues { column: String, values: Vec<String> },
+}
+
+/// Gets the distinct non-null values of a DataFrame column, as text.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame.
+/// * `column` - The name of the column.
+/// * `max_distinct_values` - The maximum number of distinct values, above which the column
+///   is considered of high cardinality.
+///
+/// # Returns
+///
+/// The distinct values of the column, or an error if there are more than `max_distinct_values`.
+pub fn distinct_values_from_dataframe(
+    df: &DataFrame,
+    column: &str,
+    max_distinct_values: usize,
+) -> Result<BTreeSet<String>> {
+    let values = df.column(column)?.drop_nulls().unique()?;
+    if values.len() > max_distinct_values {
+        return Err(anyhow!(
+            "Column {} has more than {} distinct values",
+            column,
+            max_distinct_values
+        ));
+    }
+
+    // Strings are displayed in quotes by polars, unlike the rest of the values
+    let as_text = |value: AnyValue| match value {
+        AnyValue::String(value) => value.to_string(),
+        AnyValue::StringOwned(value) => value.to_string(),
+        value => value.to_string(),
+    };
+
+    (0..values.len())
+        .map(|index| Ok(as_text(values.get(index)?)))
+        .collect()
+}
+
+/// Compares the distinct values of a column between the source and the target.
+///
+/// # Arguments
+///
+/// * `column` - The name of the column.
+/// * `source` - The distinct values of the source.
+/// * `target` - The distinct values of the target.
+///
+/// # Returns
+///
+/// The divergences, if any, with the values in ascending order.
+pub fn compare_distinct_values(
+    column: &str,
+    source: &BTreeSet<String>,
+    target: &BTreeSet<String>,
+) -> Vec<DistinctValuesDivergence> {
+    let mut divergences = Vec::new();
+
+    let unexpected_values = target.difference(source).cloned().collect::<Vec<_>>();
+    if !unexpected_values.is_empty() {
+        divergences.push(DistinctValuesDivergence::UnexpectedValues {
+            column: column.to_string(),
+            values: unexpected_values,
+        });
+    }
+
+    let missing_values = source.difference(target).cloned().collect::<Vec<_>>();
+    if !missing_values.is_empty() {
+        divergences.push(DistinctValuesDivergence::MissingValues {
+            column: column.to_string(),
+            values: missing_values,
+        });
+    }
+
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_distinct_values_from_dataframe() {
+        let df = df!("status" => &[Some("active"), None, Some("deleted"), Some("active")]).unwrap();
+
+        let distinct_values =
+            distinct_values_from_dataframe(&df, "status", DEFAULT_MAX_DISTINCT_VALUES).unwrap();
+
+        assert_eq!(distinct_values, values(&["active", "deleted"]));
+    }
+
+    #[test]
+    fn test_distinct_values_of_high_cardinality_column() {
+        let df = df!("id" => &[1i64, 2, 3]).unwrap();
+
+        let error = distinct_values_from_dataframe(&df, "id", 2).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Column id has more than 2 distinct values"
+        );
+    }
+
+    #[test]
+    fn test_compare_distinct_values() {
+        let source = values(&["active", "deleted", "pending"]);
+        let target = values(&["active", "archived", "deleted"]);
+
+        assert!(compare_distinct_values("status", &source, &source).is_empty());
+        assert_eq!(
+            compare_distinct_values("status", &source, &target),
+            vec![
+                DistinctValuesDivergence::UnexpectedValues {
+                    column: "status".to_string(),
+                    values: vec!["archived".to_string()],
+                },
+                DistinctValuesDivergence::MissingValues {
+                    column: "status".to_string(),
+                    values: vec!["pending".to_string()],
+                },
+            ]
+        );
+    }
+}
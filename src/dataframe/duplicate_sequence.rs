@@ -0,0 +1,200 @@
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Represents a (key, sequence) pair claimed by rows of two different files,
+/// which indicates that DMS wrote the same change twice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateSequence {
+    pub primary_key: String,
+    pub sequence: String,
+    pub first_file: String,
+    pub duplicate_file: String,
+}
+
+/// Detects the (key, sequence) pairs claimed by more than one file of a table.
+///
+/// The files are observed one by one, in the order they are applied. Each (key, sequence) pair
+/// is kept as a single encoded string, along with the index of the first file that claimed it,
+/// so that a duplicate is only reported when the values themselves are equal, and not when
+/// their hashes collide. The rows with a null sequence are skipped.
+#[derive(Debug)]
+pub struct DuplicateSequenceDetector {
+    primary_key: Vec<String>,
+    sequence_column: String,
+    files: Vec<String>,
+    seen: HashMap<Box<str>, u32>,
+    duplicates: Vec<DuplicateSequence>,
+}
+
+/// Encodes a (key, sequence) pair, prefixing each value with its length so that
+/// different pairs never share an encoding.
+fn encode_pair(key_values: &[String], sequence: &str) -> Box<str> {
+    let mut encoded = String::new();
+    for value in key_values.iter().map(String::as_str).chain([sequence]) {
+        encoded.push_str(&value.len().to_string());
+        encoded.push(':');
+        encoded.push_str(value);
+    }
+
+    encoded.into_boxed_str()
+}
+
+impl DuplicateSequenceDetector {
+    /// Creates a new duplicate sequence detector.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary_key` - The primary key columns of the table.
+    /// * `sequence_column` - The column with the sequence of the changes.
+    ///
+    /// # Returns
+    ///
+    /// A new duplicate sequence detector instance.
+    pub fn new(primary_key: &[String], sequence_column: impl Into<String>) -> Self {
+        Self {
+            primary_key: primary_key.to_vec(),
+            sequence_column: sequence_column.into(),
+            files: Vec::new(),
+            seen: HashMap::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    /// Observes the rows of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the file.
+    /// * `df` - The rows of the file, with the primary key and sequence columns.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub fn observe(&mut self, file_name: &str, df: &DataFrame) -> Result<()> {
+        let file_index = self.files.len() as u32;
+        self.files.push(file_name.to_string());
+
+        let key_columns = self
+            .primary_key
+            .iter()
+            .map(|key| df.column(key))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let sequence_column = df.column(&self.sequence_column)?;
+
+        for row in 0..df.height() {
+            let sequence = sequence_column.get(row)?;
+            if sequence.is_null() {
+                continue;
+            }
+
+            let key_values = key_columns
+                .iter()
+                .map(|column| column.get(row).map(|value| value.to_string()))
+                .collect::<PolarsResult<Vec<_>>>()?;
+            let sequence = sequence.to_string();
+
+            let first_file_index = *self
+                .seen
+                .entry(encode_pair(&key_values, &sequence))
+                .or_insert(file_index);
+            if first_file_index != file_index {
+                self.duplicates.push(DuplicateSequence {
+                    primary_key: key_values.join(","),
+                    sequence,
+                    first_file: self.files[first_file_index as usize].clone(),
+                    duplicate_file: file_name.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Consumes the detector.
+    ///
+    /// # Returns
+    ///
+    /// The duplicate (key, sequence) pairs, in the order they were found.
+    pub fn finish(self) -> Vec<DuplicateSequence> {
+        self.duplicates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_duplicated_key_and_sequence() {
+        let mut detector =
+            DuplicateSequenceDetector::new(&["id".to_string()], "_dms_ingestion_timestamp");
+
+        detector
+            .observe(
+                "20240101-000000000.parquet",
+                &df!(
+                    "id" => &[1i64, 2],
+                    "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01", "2024-01-01 00:00:02"]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        detector
+            .observe(
+                "20240101-000000001.parquet",
+                &df!(
+                    "id" => &[1i64, 2, 3],
+                    "_dms_ingestion_timestamp" => &[
+                        Some("2024-01-01 00:00:03"),
+                        Some("2024-01-01 00:00:02"),
+                        None
+                    ]
+                )
+                .unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            detector.finish(),
+            vec![DuplicateSequence {
+                primary_key: "2".to_string(),
+                sequence: r#""2024-01-01 00:00:02""#.to_string(),
+                first_file: "20240101-000000000.parquet".to_string(),
+                duplicate_file: "20240101-000000001.parquet".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_same_sequence_of_different_keys_is_not_duplicate() {
+        let mut detector = DuplicateSequenceDetector::new(
+            &["tenant_id".to_string(), "id".to_string()],
+            "sequence",
+        );
+
+        detector
+            .observe(
+                "file1",
+                &df!("tenant_id" => &[1i64], "id" => &[1i64], "sequence" => &[10i64]).unwrap(),
+            )
+            .unwrap();
+        detector
+            .observe(
+                "file2",
+                &df!("tenant_id" => &[2i64], "id" => &[1i64], "sequence" => &[10i64]).unwrap(),
+            )
+            .unwrap();
+
+        assert!(detector.finish().is_empty());
+    }
+
+    #[test]
+    fn test_encode_pair_is_unambiguous() {
+        assert_ne!(
+            encode_pair(&["1".to_string(), "2:3".to_string()], "4"),
+            encode_pair(&["1".to_string(), "2".to_string()], "3:4")
+        );
+    }
+}
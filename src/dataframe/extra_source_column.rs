@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::warn;
+use polars::prelude::*;
+
+use crate::postgres::data_type_mapping::postgres_data_type;
+
+/// The columns added by DMS, which are not columns of the table.
+const DMS_COLUMNS: [&str; 2] = ["Op", "_dms_ingestion_timestamp"];
+
+/// Represents how the columns of a Parquet file that the target table lacks are handled.
+///
+/// The policy can be one of the following:
+///
+/// * Error - The file fails to apply.
+/// * Skip - The columns are dropped from the file, with a warning.
+/// * AddColumn - The columns are added to the table, with the data types inferred from the DataFrame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtraSourceColumn {
+    #[default]
+    Error,
+    Skip,
+    AddColumn,
+}
+
+impl ExtraSourceColumn {
+    /// Applies the policy on the columns of a DataFrame that the target table lacks.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame.
+    /// * `table_columns` - The column names of the table and their data types.
+    ///
+    /// # Returns
+    ///
+    /// The DataFrame after applying the policy, along with the columns to add to the table
+    /// and their Postgres data types.
+    pub fn apply(
+        &self,
+        mut df: DataFrame,
+        table_columns: &IndexMap<String, String>,
+    ) -> Result<(DataFrame, Vec<(String, String)>)> {
+        let extra_columns = df
+            .get_columns()
+            .iter()
+            .filter(|column| !DMS_COLUMNS.contains(&column.name()))
+            .filter(|column| !table_columns.contains_key(column.name()))
+            .map(|column| (column.name().to_string(), column.dtype().clone()))
+            .collect::<Vec<_>>();
+
+        if extra_columns.is_empty() {
+            return Ok((df, vec![]));
+        }
+
+        match self {
+            ExtraSourceColumn::Error => Err(anyhow!(
+                "Columns missing in the target table: {:?}",
+                extra_columns
+                    .iter()
+                    .map(|(column, _)| column)
+                    .collect::<Vec<_>>()
+            )),
+            ExtraSourceColumn::Skip => {
+                for (column, _) in &extra_columns {
+                    warn!("Skipping column {column} missing in the target table");
+                    _ = df.drop_in_place(column)?;
+                }
+                Ok((df, vec![]))
+            }
+            ExtraSourceColumn::AddColumn => {
+                let added_columns = extra_columns
+                    .iter()
+                    .map(|(column, data_type)| (column.clone(), postgres_data_type(data_type)))
+                    .collect();
+                Ok((df, added_columns))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn parquet_file_with_extra_column() -> DataFrame {
+        let mut df = df!(
+            "Op" => &["I"],
+            "id" => &[1i64],
+            "name" => &["a"],
+            "score" => &[1.5f64]
+        )
+        .unwrap();
+        let mut buffer = Vec::new();
+        ParquetWriter::new(&mut buffer).finish(&mut df).unwrap();
+
+        ParquetReader::new(Cursor::new(buffer)).finish().unwrap()
+    }
+
+    fn table_columns() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("id".to_string(), "bigint".to_string()),
+            ("name".to_string(), "text".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_extra_column_is_rejected() {
+        let error = ExtraSourceColumn::Error
+            .apply(parquet_file_with_extra_column(), &table_columns())
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            r#"Columns missing in the target table: ["score"]"#
+        );
+    }
+
+    #[test]
+    fn test_extra_column_is_skipped() {
+        let (df, added_columns) = ExtraSourceColumn::Skip
+            .apply(parquet_file_with_extra_column(), &table_columns())
+            .unwrap();
+
+        assert_eq!(df.get_column_names(), vec!["Op", "id", "name"]);
+        assert!(added_columns.is_empty());
+    }
+
+    #[test]
+    fn test_extra_column_is_added() {
+        let (df, added_columns) = ExtraSourceColumn::AddColumn
+            .apply(parquet_file_with_extra_column(), &table_columns())
+            .unwrap();
+
+        assert_eq!(df.get_column_names(), vec!["Op", "id", "name", "score"]);
+        assert_eq!(
+            added_columns,
+            vec![("score".to_string(), "double precision".to_string())]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use polars::io::avro::AvroReader;
+use polars::prelude::*;
+
+/// Represents the format of the files written by DMS, detected from their key.
+///
+/// The format can be one of the following:
+///
+/// * Parquet - The files are Parquet files, as written by default.
+/// * Avro - The files are Avro object container files, with a `.avro` extension.
+///
+/// The `Op` column and the LOAD files are the same in both formats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileFormat {
+    #[default]
+    Parquet,
+    Avro,
+}
+
+impl FileFormat {
+    /// Detects the format of a file from its key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The S3 key of the file.
+    ///
+    /// # Returns
+    ///
+    /// The format of the file, Parquet unless the key has an `.avro` extension.
+    pub fn from_key(key: &str) -> Self {
+        if key.to_lowercase().ends_with(".avro") {
+            FileFormat::Avro
+        } else {
+            FileFormat::Parquet
+        }
+    }
+}
+
+/// Reads a DataFrame from the bytes of an Avro file.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the Avro file.
+/// * `n_rows` - If set, only the first rows of the file up to this limit are read.
+///
+/// # Returns
+///
+/// A DataFrame.
+pub fn read_avro_from_bytes<T>(bytes: T, n_rows: Option<usize>) -> Result<DataFrame>
+where
+    T: AsRef<[u8]>,
+{
+    let cursor = std::io::Cursor::new(bytes);
+
+    Ok(AvroReader::new(cursor).with_n_rows(n_rows).finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::dataframe_ops::{read_parquet_from_bytes, RowCountMismatchSeverity};
+
+    /// An Avro file with the rows of a CDC file, with a `Op`, `_dms_ingestion_timestamp`,
+    /// `id` and nullable `name` column.
+    const CDC_AVRO: &[u8] = include_bytes!("fixtures/cdc.avro");
+
+    #[test]
+    fn test_file_format_from_key() {
+        assert_eq!(
+            FileFormat::from_key("users/20240101-000000000.parquet"),
+            FileFormat::Parquet
+        );
+        assert_eq!(
+            FileFormat::from_key("users/20240101-000000000.avro"),
+            FileFormat::Avro
+        );
+        assert_eq!(
+            FileFormat::from_key("users/LOAD00000001.AVRO"),
+            FileFormat::Avro
+        );
+    }
+
+    #[test]
+    fn test_avro_file_matches_equivalent_parquet_file() {
+        let mut expected = df!(
+            "Op" => &["I", "U", "D"],
+            "_dms_ingestion_timestamp" => &[
+                "2024-01-01 00:00:01",
+                "2024-01-01 00:00:02",
+                "2024-01-01 00:00:03"
+            ],
+            "id" => &[1i64, 1, 2],
+            "name" => &[Some("a"), None, Some("b")]
+        )
+        .unwrap();
+        let mut bytes: Vec<u8> = Vec::new();
+        ParquetWriter::new(&mut bytes)
+            .finish(&mut expected)
+            .unwrap();
+        let parquet_df =
+            read_parquet_from_bytes(&bytes, "key", None, RowCountMismatchSeverity::Error).unwrap();
+
+        let avro_df = read_avro_from_bytes(CDC_AVRO, None).unwrap();
+
+        assert_eq!(avro_df.schema(), parquet_df.schema());
+        assert!(avro_df.equals_missing(&parquet_df));
+        assert_eq!(read_avro_from_bytes(CDC_AVRO, Some(2)).unwrap().height(), 2);
+    }
+}
@@ -0,0 +1,333 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+
+/// Represents the expression of a `GENERATED ALWAYS AS (expr) STORED` column, recomputed with Polars.
+///
+/// Only simple arithmetic expressions are supported, i.e. columns, numeric literals, `+`, `-`,
+/// `*`, `/`, parentheses and casts, as rendered by Postgres in `generation_expression`,
+/// e.g. `(price * (quantity)::numeric)`. The casts are ignored and the expression is evaluated
+/// with floats. Since an integer division is truncated by Postgres but not by floats, the
+/// expressions with a division are only supported if they cast to a numeric or float type.
+#[derive(Debug, Clone)]
+pub struct GeneratedColumnExpression {
+    pub expr: Expr,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Column(String),
+    Number(f64),
+    Operator(char),
+    OpenParenthesis,
+    CloseParenthesis,
+    Cast,
+}
+
+impl GeneratedColumnExpression {
+    /// Parses the generation expression of a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - The generation expression, as found in `information_schema.columns`.
+    ///
+    /// # Returns
+    ///
+    /// The expression, or an error if the expression is not supported.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            columns: Vec::new(),
+        };
+
+        let expr = parser.expression()?;
+        if parser.position != tokens.len() {
+            return Err(anyhow!("Unsupported generation expression: {expression}"));
+        }
+        if tokens.contains(&Token::Operator('/')) && !has_float_cast(&tokens) {
+            return Err(anyhow!(
+                "Unsupported generation expression, possibly an integer division: {expression}"
+            ));
+        }
+
+        Ok(Self {
+            expr,
+            columns: parser.columns,
+        })
+    }
+
+    /// Counts the rows whose generated column differs from the recomputation of its expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The rows, with the generated column and the columns of its expression.
+    /// * `column` - The name of the generated column.
+    ///
+    /// # Returns
+    ///
+    /// The number of mismatching rows.
+    pub fn count_mismatches(&self, df: &DataFrame, column: &str) -> Result<usize> {
+        let df = df
+            .clone()
+            .lazy()
+            .select([
+                self.expr.clone().alias("expected"),
+                col(column).cast(DataType::Float64).alias("actual"),
+            ])
+            .collect()?;
+
+        let expected = df.column("expected")?.f64()?;
+        let actual = df.column("actual")?.f64()?;
+
+        let mismatches = expected
+            .into_iter()
+            .zip(actual)
+            .filter(|(expected, actual)| match (expected, actual) {
+                (Some(expected), Some(actual)) => {
+                    (expected - actual).abs() > 1e-9 * expected.abs().max(1.0)
+                }
+                (None, None) => false,
+                _ => true,
+            })
+            .count();
+
+        Ok(mismatches)
+    }
+}
+
+/// Splits a generation expression into tokens.
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::OpenParenthesis);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::CloseParenthesis);
+                chars.next();
+            }
+            ':' => {
+                chars.next();
+                if chars.next() != Some(':') {
+                    return Err(anyhow!("Unsupported generation expression: {expression}"));
+                }
+                tokens.push(Token::Cast);
+            }
+            '"' => {
+                chars.next();
+                let mut column = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            column.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => column.push(c),
+                        None => {
+                            return Err(anyhow!("Unterminated identifier in: {expression}"));
+                        }
+                    }
+                }
+                tokens.push(Token::Column(column));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                    number.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse()?));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut column = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    column.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Column(column));
+            }
+            _ => return Err(anyhow!("Unsupported generation expression: {expression}")),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Checks if the tokens of a generation expression cast to a numeric or float type.
+fn has_float_cast(tokens: &[Token]) -> bool {
+    tokens.windows(2).any(|window| match window {
+        [Token::Cast, Token::Column(data_type)] => matches!(
+            data_type.to_lowercase().as_str(),
+            "numeric" | "decimal" | "real" | "double" | "float4" | "float8"
+        ),
+        _ => false,
+    })
+}
+
+/// Parses the tokens of a generation expression by recursive descent.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    columns: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) if operators.contains(operator) => {
+                self.position += 1;
+                Some(*operator)
+            }
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            let term = self.term()?;
+            expr = match operator {
+                '+' => expr + term,
+                _ => expr - term,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut expr = self.factor()?;
+        while let Some(operator) = self.next_operator(&['*', '/']) {
+            let factor = self.factor()?;
+            expr = match operator {
+                '*' => expr * factor,
+                _ => expr / factor,
+            };
+        }
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr> {
+        if self.next_operator(&['-']).is_some() {
+            return Ok(lit(0.0) - self.factor()?);
+        }
+
+        let expr = match self.tokens.get(self.position) {
+            Some(Token::Number(number)) => {
+                self.position += 1;
+                lit(*number)
+            }
+            Some(Token::Column(column)) => {
+                self.position += 1;
+                if !self.columns.contains(column) {
+                    self.columns.push(column.clone());
+                }
+                col(column).cast(DataType::Float64)
+            }
+            Some(Token::OpenParenthesis) => {
+                self.position += 1;
+                let expr = self.expression()?;
+                if self.tokens.get(self.position) != Some(&Token::CloseParenthesis) {
+                    return Err(anyhow!("Unbalanced parentheses in generation expression"));
+                }
+                self.position += 1;
+                expr
+            }
+            token => {
+                return Err(anyhow!(
+                    "Unexpected token in generation expression: {token:?}"
+                ))
+            }
+        };
+
+        // The casts are ignored, since the expression is evaluated with floats
+        while self.tokens.get(self.position) == Some(&Token::Cast) {
+            match self.tokens.get(self.position + 1) {
+                Some(Token::Column(data_type)) => {
+                    self.position += 2;
+                    // The type of the cast may span two words, i.e. `double precision`
+                    if data_type == "double"
+                        && self.tokens.get(self.position)
+                            == Some(&Token::Column("precision".to_string()))
+                    {
+                        self.position += 1;
+                    }
+                }
+                _ => return Err(anyhow!("Missing type of cast in generation expression")),
+            }
+        }
+
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns_of_expression() {
+        let expression =
+            GeneratedColumnExpression::parse(r#"((price * (quantity)::numeric) - "Discount")"#)
+                .unwrap();
+
+        assert_eq!(expression.columns, vec!["price", "quantity", "Discount"]);
+    }
+
+    #[test]
+    fn test_count_mismatches_of_arithmetic_expression() {
+        let df = df!(
+            "price" => &[2.5, 10.0, 4.0],
+            "quantity" => &[2i64, 3, 5],
+            "total" => &[6.0, 31.0, 19.0]
+        )
+        .unwrap();
+
+        let expression =
+            GeneratedColumnExpression::parse("((price * (quantity)::numeric) + 1)").unwrap();
+
+        // Only the last row differs, since 4 * 5 + 1 = 21
+        assert_eq!(expression.count_mismatches(&df, "total").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_count_mismatches_with_nulls() {
+        let df = df!(
+            "a" => &[Some(1i64), None],
+            "b" => &[Some(-2i64), Some(1)],
+            "c" => &[Some(-1i64), None]
+        )
+        .unwrap();
+
+        let expression = GeneratedColumnExpression::parse("(a + b)").unwrap();
+
+        assert_eq!(expression.count_mismatches(&df, "c").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_division_requires_float_cast() {
+        assert!(GeneratedColumnExpression::parse("(a / b)").is_err());
+        assert!(GeneratedColumnExpression::parse("((a)::numeric / (b)::numeric)").is_ok());
+        assert!(GeneratedColumnExpression::parse("((a)::double precision / 2)").is_ok());
+    }
+
+    #[test]
+    fn test_parse_unsupported_expression() {
+        assert!(GeneratedColumnExpression::parse("(first_name || ' ' || last_name)").is_err());
+        assert!(GeneratedColumnExpression::parse("upper(name)").is_err());
+        assert!(GeneratedColumnExpression::parse("((a + b)").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+
+/// Represents a bucket of the histogram of a numeric column whose count differs between the
+/// source and the target by more than the tolerance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketDivergence {
+    pub bucket: usize,
+    pub source_count: i64,
+    pub target_count: i64,
+}
+
+/// Gets the bucket of a value in a histogram of equal-width buckets between the minimum and
+/// the maximum value, in the same way as `width_bucket` in Postgres, except that the maximum
+/// value is counted in the last bucket.
+///
+/// # Arguments
+///
+/// * `value` - The value.
+/// * `min_value` - The minimum value of the column.
+/// * `max_value` - The maximum value of the column.
+/// * `buckets` - The number of buckets.
+///
+/// # Returns
+///
+/// The bucket of the value, starting from 1.
+fn bucket_of(value: f64, min_value: f64, max_value: f64, buckets: usize) -> usize {
+    if max_value <= min_value {
+        return 1;
+    }
+    let bucket = ((value - min_value) / (max_value - min_value) * buckets as f64).floor() as usize;
+
+    (bucket + 1).min(buckets)
+}
+
+/// Gets the histogram of a numeric DataFrame column, with equal-width buckets between the
+/// minimum and the maximum value of the column.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame.
+/// * `column` - The name of the column.
+/// * `buckets` - The number of buckets.
+///
+/// # Returns
+///
+/// The number of non-null values in each bucket, in the order of the buckets.
+pub fn histogram_from_dataframe(df: &DataFrame, column: &str, buckets: usize) -> Result<Vec<i64>> {
+    if buckets == 0 {
+        return Err(anyhow!("A histogram requires at least one bucket"));
+    }
+
+    let values = df.column(column)?.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+    let mut bucket_counts = vec![0; buckets];
+
+    if let (Some(min_value), Some(max_value)) = (values.min(), values.max()) {
+        for value in values.into_iter().flatten() {
+            bucket_counts[bucket_of(value, min_value, max_value, buckets) - 1] += 1;
+        }
+    }
+
+    Ok(bucket_counts)
+}
+
+/// Compares the histograms of a numeric column between the source and the target, to catch
+/// a skew of the distribution that aggregate checks miss.
+///
+/// # Arguments
+///
+/// * `source` - The bucket counts of the source.
+/// * `target` - The bucket counts of the target.
+/// * `tolerance` - The accepted difference of a bucket count, as a fraction of the source count,
+///   e.g. 0.05 for 5%.
+///
+/// # Returns
+///
+/// The buckets whose counts differ by more than the tolerance. A missing bucket counts as empty.
+pub fn compare_histograms(source: &[i64], target: &[i64], tolerance: f64) -> Vec<BucketDivergence> {
+    (0..source.len().max(target.len()))
+        .filter_map(|index| {
+            let source_count = source.get(index).copied().unwrap_or_default();
+            let target_count = target.get(index).copied().unwrap_or_default();
+            let difference = (source_count - target_count).abs() as f64;
+
+            (difference > tolerance * source_count as f64).then_some(BucketDivergence {
+                bucket: index + 1,
+                source_count,
+                target_count,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_from_dataframe() {
+        let df = df!(
+            "amount" => &[Some(0i64), Some(1), Some(4), Some(5), Some(9), Some(10), None]
+        )
+        .unwrap();
+
+        assert_eq!(
+            histogram_from_dataframe(&df, "amount", 2).unwrap(),
+            vec![3, 3]
+        );
+        assert_eq!(
+            histogram_from_dataframe(&df, "amount", 5).unwrap(),
+            vec![2, 0, 2, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_histogram_of_constant_column() {
+        let df = df!("amount" => &[7.5f64, 7.5, 7.5]).unwrap();
+
+        assert_eq!(
+            histogram_from_dataframe(&df, "amount", 4).unwrap(),
+            vec![3, 0, 0, 0]
+        );
+        assert!(histogram_from_dataframe(&df, "amount", 0).is_err());
+    }
+
+    #[test]
+    fn test_compare_histograms_flags_skewed_bucket() {
+        let source = vec![100, 100, 100, 0];
+        let target = vec![102, 100, 60, 1];
+
+        assert_eq!(
+            compare_histograms(&source, &target, 0.05),
+            vec![
+                BucketDivergence {
+                    bucket: 3,
+                    source_count: 100,
+                    target_count: 60,
+                },
+                BucketDivergence {
+                    bucket: 4,
+                    source_count: 0,
+                    target_count: 1,
+                },
+            ]
+        );
+        assert!(compare_histograms(&source, &source, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::warn;
+use polars::prelude::*;
+
+/// Represents how the values of the binary columns bound as text that are not valid UTF-8
+/// are handled, since binding them as text would fail or corrupt them.
+///
+/// The policy can be one of the following:
+///
+/// * Error - The file is rejected, listing the offending rows by primary key.
+/// * Replace - The offending values are replaced by the sentinel, with a warning.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InvalidUtf8Policy {
+    #[default]
+    Error,
+    Replace(String),
+}
+
+/// Represents a value that is not valid UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidUtf8Value {
+    pub column: String,
+    pub primary_key_value: String,
+}
+
+impl InvalidUtf8Policy {
+    /// Applies the policy on the binary columns of a DataFrame that are bound as text, i.e.
+    /// whose column in the table is not `bytea`. The checked columns are converted to text.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame of a Parquet file.
+    /// * `primary_key` - The primary key columns, to report the offending rows.
+    /// * `table_columns` - The column names of the table and their data types.
+    ///
+    /// # Returns
+    ///
+    /// The DataFrame after applying the policy, or an error if a value is not valid UTF-8
+    /// and the policy is `Error`.
+    pub fn apply(
+        &self,
+        mut df: DataFrame,
+        primary_key: &[String],
+        table_columns: &IndexMap<String, String>,
+    ) -> Result<DataFrame> {
+        let text_columns = df
+            .get_columns()
+            .iter()
+            .filter(|column| column.dtype() == &DataType::Binary)
+            .filter(|column| {
+                table_columns
+                    .get(column.name())
+                    .is_some_and(|data_type| data_type != "bytea")
+            })
+            .map(|column| column.name().to_string())
+            .collect::<Vec<_>>();
+
+        let invalid_values = find_invalid_utf8(&df, &text_columns, primary_key)?;
+        if *self == InvalidUtf8Policy::Error && !invalid_values.is_empty() {
+            return Err(anyhow!(
+                "Values that are not valid UTF-8: {}",
+                invalid_values
+                    .iter()
+                    .map(|value| format!("{} of row ({})", value.column, value.primary_key_value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        let sentinel = match self {
+            InvalidUtf8Policy::Error => "",
+            InvalidUtf8Policy::Replace(sentinel) => sentinel.as_str(),
+        };
+        for value in &invalid_values {
+            warn!(
+                "Replacing value of column {} of row ({}) that is not valid UTF-8 with {:?}",
+                value.column, value.primary_key_value, sentinel
+            );
+        }
+
+        for column in &text_columns {
+            let text_column = Series::new(
+                column,
+                df.column(column)?
+                    .binary()?
+                    .into_iter()
+                    .map(|bytes| bytes.map(|bytes| std::str::from_utf8(bytes).unwrap_or(sentinel)))
+                    .collect::<Vec<_>>(),
+            );
+            df.with_column(text_column)?;
+        }
+
+        Ok(df)
+    }
+}
+
+/// Finds the values of binary columns that are not valid UTF-8.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of a Parquet file.
+/// * `columns` - The binary columns to check.
+/// * `primary_key` - The primary key columns, to report the offending rows.
+///
+/// # Returns
+///
+/// The offending values, by column and row.
+pub fn find_invalid_utf8(
+    df: &DataFrame,
+    columns: &[String],
+    primary_key: &[String],
+) -> Result<Vec<InvalidUtf8Value>> {
+    let mut invalid_values = Vec::new();
+
+    for column in columns {
+        for (row, bytes) in df.column(column)?.binary()?.into_iter().enumerate() {
+            if bytes.is_none_or(|bytes| std::str::from_utf8(bytes).is_ok()) {
+                continue;
+            }
+
+            let primary_key_value = primary_key
+                .iter()
+                .map(|key| Ok(format!("{}={}", key, df.column(key)?.get(row)?)))
+                .collect::<Result<Vec<_>>>()?
+                .join(", ");
+            invalid_values.push(InvalidUtf8Value {
+                column: column.clone(),
+                primary_key_value,
+            });
+        }
+    }
+
+    Ok(invalid_values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataframe_with_invalid_utf8() -> DataFrame {
+        DataFrame::new(vec![
+            Series::new("id", &[1i64, 2, 3]),
+            Series::new(
+                "name",
+                &[
+                    Some(b"caf\xc3\xa9".as_slice()),
+                    Some(b"caf\xe9".as_slice()),
+                    None,
+                ],
+            ),
+            Series::new("payload", &[b"\xff".as_slice(), b"\xfe", b"\xfd"]),
+        ])
+        .unwrap()
+    }
+
+    fn table_columns() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("id".to_string(), "bigint".to_string()),
+            ("name".to_string(), "text".to_string()),
+            ("payload".to_string(), "bytea".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_find_invalid_utf8() {
+        let invalid_values = find_invalid_utf8(
+            &dataframe_with_invalid_utf8(),
+            &["name".to_string()],
+            &["id".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            invalid_values,
+            vec![InvalidUtf8Value {
+                column: "name".to_string(),
+                primary_key_value: "id=2".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_rejected() {
+        let result = InvalidUtf8Policy::Error.apply(
+            dataframe_with_invalid_utf8(),
+            &["id".to_string()],
+            &table_columns(),
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Values that are not valid UTF-8: name of row (id=2)"
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_replaced() {
+        let df = InvalidUtf8Policy::Replace("?".to_string())
+            .apply(
+                dataframe_with_invalid_utf8(),
+                &["id".to_string()],
+                &table_columns(),
+            )
+            .unwrap();
+
+        let names = df.column("name").unwrap().str().unwrap();
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![Some("café"), Some("?"), None]
+        );
+        // The bytea columns are not checked
+        assert_eq!(df.column("payload").unwrap().dtype(), &DataType::Binary);
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use polars::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+
+/// Represents the Unicode normalization form of the keys.
+///
+/// The form can be one of the following:
+///
+/// * Nfc - Canonical composition, e.g. `e` followed by a combining acute accent becomes `é`.
+/// * Nfkc - Compatibility composition, which also folds compatibility characters, e.g. `ﬁ` becomes `fi`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnicodeForm {
+    Nfc,
+    Nfkc,
+}
+
+/// Represents a normalization of the text primary key values, to prevent phantom mismatches
+/// between the source and the target caused by invisible characters, e.g. a stray control
+/// character or zero-width space emitted by a source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyNormalization {
+    pub strip_invisible_characters: bool,
+    pub unicode_form: Option<UnicodeForm>,
+}
+
+/// Checks if a character is invisible, i.e. a control character or a zero-width character.
+fn is_invisible(character: char) -> bool {
+    character.is_control()
+        || matches!(
+            character,
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+        )
+}
+
+impl KeyNormalization {
+    /// Normalizes a key value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The key value.
+    ///
+    /// # Returns
+    ///
+    /// The normalized key value.
+    pub fn normalize(&self, value: &str) -> String {
+        let value = if self.strip_invisible_characters {
+            value
+                .chars()
+                .filter(|character| !is_invisible(*character))
+                .collect()
+        } else {
+            value.to_string()
+        };
+
+        match self.unicode_form {
+            Some(UnicodeForm::Nfc) => value.nfc().collect(),
+            Some(UnicodeForm::Nfkc) => value.nfkc().collect(),
+            None => value,
+        }
+    }
+
+    /// Normalizes the text primary key columns of a DataFrame.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame.
+    /// * `primary_key` - The primary key columns. The columns missing from the DataFrame or
+    ///   not of a text type are left as is.
+    ///
+    /// # Returns
+    ///
+    /// The DataFrame with normalized primary key values.
+    pub fn apply(&self, mut df: DataFrame, primary_key: &[String]) -> Result<DataFrame> {
+        for column in primary_key {
+            let Ok(values) = df.column(column).and_then(|series| series.str()) else {
+                continue;
+            };
+
+            let normalized = Series::new(
+                column,
+                values
+                    .into_iter()
+                    .map(|value| value.map(|value| self.normalize(value)))
+                    .collect::<Vec<_>>(),
+            );
+            df.with_column(normalized)?;
+        }
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRIP_INVISIBLE: KeyNormalization = KeyNormalization {
+        strip_invisible_characters: true,
+        unicode_form: Some(UnicodeForm::Nfc),
+    };
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(STRIP_INVISIBLE.normalize("user\u{200B}-1"), "user-1");
+        assert_eq!(STRIP_INVISIBLE.normalize("\u{FEFF}user-1\r\n"), "user-1");
+        assert_eq!(STRIP_INVISIBLE.normalize("cafe\u{0301}"), "café");
+        assert_eq!(
+            KeyNormalization::default().normalize("user\u{200B}-1"),
+            "user\u{200B}-1"
+        );
+        assert_eq!(
+            KeyNormalization {
+                strip_invisible_characters: false,
+                unicode_form: Some(UnicodeForm::Nfkc),
+            }
+            .normalize("\u{FB01}le"),
+            "file"
+        );
+    }
+
+    #[test]
+    fn test_keys_differing_by_zero_width_space_match() {
+        let incoming = df!("id" => &["user\u{200B}-1", "user-2"], "name" => &["a", "b"]).unwrap();
+        let target = df!("id" => &["user-1", "user-2"], "name" => &["a", "b"]).unwrap();
+
+        let incoming = STRIP_INVISIBLE
+            .apply(incoming, &["id".to_string()])
+            .unwrap();
+
+        assert!(incoming.equals(&target));
+        assert!(!KeyNormalization::default()
+            .apply(
+                df!("id" => &["user\u{200B}-1", "user-2"], "name" => &["a", "b"]).unwrap(),
+                &["id".to_string()]
+            )
+            .unwrap()
+            .equals(&target));
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use polars::prelude::*;
+use std::fmt::{self, Display, Formatter};
+
+/// Represents a bound of a key range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyValue {
+    Int(i64),
+    Text(String),
+}
+
+impl Display for KeyValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyValue::Int(value) => write!(f, "{}", value),
+            KeyValue::Text(value) => write!(f, "'{}'", value.replace('\'', "''")),
+        }
+    }
+}
+
+/// Represents a range of a key column, so that a huge table can be validated by several workers,
+/// each one handling a range.
+///
+/// The range includes the start and excludes the end, so that adjacent ranges do not overlap,
+/// and a missing bound leaves the range unbounded on that side. Text keys are compared byte-wise,
+/// as in the "C" collation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRange {
+    pub column: String,
+    pub start: Option<KeyValue>,
+    pub end: Option<KeyValue>,
+}
+
+impl KeyRange {
+    /// Creates a new key range.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The key column.
+    /// * `start` - The inclusive start of the range, if any.
+    /// * `end` - The exclusive end of the range, if any.
+    ///
+    /// # Returns
+    ///
+    /// A new key range instance.
+    pub fn new(column: impl Into<String>, start: Option<KeyValue>, end: Option<KeyValue>) -> Self {
+        Self {
+            column: column.into(),
+            start,
+            end,
+        }
+    }
+
+    /// Gets the SQL predicate of the range, e.g. `"id" >= 0 AND "id" < 1000000`.
+    pub fn to_sql_predicate(&self) -> String {
+        let bound = |value: &KeyValue, operator: &str| match value {
+            KeyValue::Int(_) => format!(r#""{}" {} {}"#, self.column, operator, value),
+            KeyValue::Text(_) => {
+                format!(r#""{}" COLLATE "C" {} {}"#, self.column, operator, value)
+            }
+        };
+
+        let predicates = [
+            self.start.as_ref().map(|start| bound(start, ">=")),
+            self.end.as_ref().map(|end| bound(end, "<")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if predicates.is_empty() {
+            "TRUE".to_string()
+        } else {
+            predicates.join(" AND ")
+        }
+    }
+
+    /// Keeps the rows of a DataFrame within the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame to filter.
+    ///
+    /// # Returns
+    ///
+    /// The rows of the DataFrame within the range. Rows with a null key are dropped.
+    pub fn filter(&self, df: &DataFrame) -> Result<DataFrame> {
+        let column = df.column(&self.column)?;
+        let mut mask = column.is_not_null();
+
+        for (value, is_start) in [(&self.start, true), (&self.end, false)] {
+            let bound_mask = match value {
+                None => continue,
+                Some(KeyValue::Int(value)) => {
+                    let column = column.cast(&DataType::Int64)?;
+                    if is_start {
+                        column.gt_eq(*value)?
+                    } else {
+                        column.lt(*value)?
+                    }
+                }
+                Some(KeyValue::Text(value)) => {
+                    let column = column.str()?;
+                    if is_start {
+                        column.gt_eq(value.as_str())
+                    } else {
+                        column.lt(value.as_str())
+                    }
+                }
+            };
+            mask = &mask & &bound_mask;
+        }
+
+        Ok(df.filter(&mask)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_predicate() {
+        let key_range = KeyRange::new("id", Some(KeyValue::Int(0)), Some(KeyValue::Int(1_000_000)));
+        assert_eq!(
+            key_range.to_sql_predicate(),
+            r#""id" >= 0 AND "id" < 1000000"#
+        );
+
+        let key_range = KeyRange::new("code", None, Some(KeyValue::Text("o'k".to_string())));
+        assert_eq!(
+            key_range.to_sql_predicate(),
+            r#""code" COLLATE "C" < 'o''k'"#
+        );
+
+        assert_eq!(KeyRange::new("id", None, None).to_sql_predicate(), "TRUE");
+    }
+
+    #[test]
+    fn test_disjoint_integer_ranges_cover_all_rows() {
+        let df = df!("id" => (0i32..100).collect::<Vec<_>>()).unwrap();
+        let ranges = [
+            KeyRange::new("id", None, Some(KeyValue::Int(30))),
+            KeyRange::new("id", Some(KeyValue::Int(30)), Some(KeyValue::Int(60))),
+            KeyRange::new("id", Some(KeyValue::Int(60)), None),
+        ];
+
+        let mut ids = ranges
+            .iter()
+            .flat_map(|range| {
+                let filtered = range.filter(&df).unwrap();
+                filtered
+                    .column("id")
+                    .unwrap()
+                    .i32()
+                    .unwrap()
+                    .into_no_null_iter()
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(ids, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_disjoint_text_ranges_cover_all_rows() {
+        let df =
+            df!("code" => &[Some("a"), Some("M"), Some("m"), Some("mz"), Some("z"), None]).unwrap();
+        let ranges = [
+            KeyRange::new("code", None, Some(KeyValue::Text("m".to_string()))),
+            KeyRange::new("code", Some(KeyValue::Text("m".to_string())), None),
+        ];
+
+        let heights = ranges
+            .iter()
+            .map(|range| range.filter(&df).unwrap().height())
+            .collect::<Vec<_>>();
+
+        // "M" sorts before "a" byte-wise, and the null key is in no range
+        assert_eq!(heights, vec![2, 3]);
+        assert_eq!(
+            ranges[1]
+                .filter(&df)
+                .unwrap()
+                .column("code")
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec!["m", "mz", "z"]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+
+/// Represents the minimum and maximum value of a column, as text.
+///
+/// Both values are None if the column has only nulls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MinMax {
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+/// Represents a column whose minimum or maximum value differs between the source and the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinMaxDivergence {
+    pub column: String,
+    pub source: MinMax,
+    pub target: MinMax,
+}
+
+impl MinMax {
+    /// Gets the minimum and maximum value of a date column of a DataFrame.
+    ///
+    /// Only date columns are supported, since their `YYYY-MM-DD` format is the same as their
+    /// Postgres text representation, so that they can be compared with the values of
+    /// [`TableQuery::MinMax`]. The text of the other types, e.g. timestamps with a time zone,
+    /// fractional seconds or numerics, differs between Polars and Postgres.
+    ///
+    /// [`TableQuery::MinMax`]: crate::postgres::table_query::TableQuery::MinMax
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame.
+    /// * `column` - The name of the date column.
+    ///
+    /// # Returns
+    ///
+    /// The minimum and maximum value of the column, or an error if it is not a date column.
+    pub fn from_dataframe(df: &DataFrame, column: &str) -> Result<Self> {
+        let values = df.column(column)?;
+        if values.dtype() != &DataType::Date {
+            return Err(anyhow!(
+                "Column {} is {}, but only date columns are supported",
+                column,
+                values.dtype()
+            ));
+        }
+
+        let values = values.drop_nulls();
+        if values.is_empty() {
+            return Ok(MinMax::default());
+        }
+
+        let sorted = values.sort(SortOptions::default())?;
+
+        Ok(MinMax {
+            min: Some(sorted.get(0)?.to_string()),
+            max: Some(sorted.get(sorted.len() - 1)?.to_string()),
+        })
+    }
+
+    /// Compares the minimum and maximum value of a column between the source and the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the column.
+    /// * `source` - The minimum and maximum value of the source.
+    /// * `target` - The minimum and maximum value of the target.
+    ///
+    /// # Returns
+    ///
+    /// The divergence, if any.
+    pub fn compare(column: &str, source: MinMax, target: MinMax) -> Option<MinMaxDivergence> {
+        if source == target {
+            return None;
+        }
+
+        Some(MinMaxDivergence {
+            column: column.to_string(),
+            source,
+            target,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::PostgresOperator;
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::test_support::test_pool;
+    use chrono::NaiveDate;
+    use deadpool_postgres::GenericClient;
+
+    /// Creates a DataFrame with a date column, from dates in the `YYYY-MM-DD` format.
+    fn dates(values: &[Option<&str>]) -> DataFrame {
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+        let days = values
+            .iter()
+            .map(|value| {
+                value.map(|value| {
+                    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+                    (date - epoch).num_days() as i32
+                })
+            })
+            .collect::<Vec<_>>();
+        let dates = Series::new("created_at", days)
+            .cast(&DataType::Date)
+            .unwrap();
+        DataFrame::new(vec![dates]).unwrap()
+    }
+
+    #[test]
+    fn test_min_max_of_date_column() {
+        let df = dates(&[
+            Some("2024-02-14"),
+            None,
+            Some("2024-01-01"),
+            Some("2024-03-31"),
+        ]);
+
+        let min_max = MinMax::from_dataframe(&df, "created_at").unwrap();
+
+        assert_eq!(min_max.min.as_deref(), Some("2024-01-01"));
+        assert_eq!(min_max.max.as_deref(), Some("2024-03-31"));
+    }
+
+    #[test]
+    fn test_min_max_of_non_date_column_is_rejected() {
+        let df = df!("amount" => &[1.5f64, 2.0]).unwrap();
+
+        assert!(MinMax::from_dataframe(&df, "amount").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_min_max_matches_postgres_text() {
+        let pool = test_pool("min_max").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE min_max.orders (created_at date);
+                INSERT INTO min_max.orders VALUES ('2024-02-14'), (NULL), ('2024-01-01'), ('2024-03-31');",
+            )
+            .await
+            .unwrap();
+        drop(client);
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let target = postgres_operator
+            .get_min_max("min_max", "orders", "created_at")
+            .await
+            .unwrap();
+        let source = MinMax::from_dataframe(
+            &dates(&[
+                Some("2024-02-14"),
+                None,
+                Some("2024-01-01"),
+                Some("2024-03-31"),
+            ]),
+            "created_at",
+        )
+        .unwrap();
+
+        assert_eq!(source, target);
+    }
+
+    #[test]
+    fn test_min_max_of_null_column() {
+        let df = dates(&[None, None]);
+
+        let min_max = MinMax::from_dataframe(&df, "created_at").unwrap();
+
+        assert_eq!(min_max, MinMax::default());
+        assert!(MinMax::compare("created_at", min_max, MinMax::default()).is_none());
+    }
+
+    #[test]
+    fn test_compare_flags_truncated_date_range() {
+        let source = MinMax {
+            min: Some("2024-01-01".to_string()),
+            max: Some("2024-03-31".to_string()),
+        };
+        let target = MinMax {
+            min: Some("2024-01-01".to_string()),
+            max: Some("2024-02-14".to_string()),
+        };
+
+        assert!(MinMax::compare("created_at", source.clone(), source.clone()).is_none());
+
+        let divergence = MinMax::compare("created_at", source.clone(), target.clone()).unwrap();
+        assert_eq!(divergence.column, "created_at");
+        assert_eq!(divergence.source, source);
+        assert_eq!(divergence.target, target);
+
+        let divergence = MinMax::compare("created_at", source, MinMax::default());
+        assert!(divergence.is_some());
+    }
+}
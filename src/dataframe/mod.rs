@@ -1 +1,26 @@
+pub mod as_of;
+pub mod clock_skew;
+pub mod column_limit;
+pub mod dataframe_diff;
 pub mod dataframe_ops;
+pub mod dataframe_transform;
+pub mod decimal;
+pub mod distinct_keys;
+pub mod distinct_values;
+pub mod duplicate_sequence;
+pub mod extra_source_column;
+pub mod file_format;
+pub mod generated_column;
+pub mod histogram;
+pub mod invalid_utf8;
+pub mod key_normalization;
+pub mod key_range;
+pub mod min_max;
+pub mod required_columns;
+pub mod rollup_hash;
+pub mod sampling;
+pub mod schema_alignment;
+pub mod table_identifier;
+pub mod unsupported_column;
+pub mod uuid_coercion;
+pub mod value_comparator;
@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+
+/// Asserts that a DataFrame contains the expected business columns, besides the columns added by DMS,
+/// since a file missing a column signals an upstream problem.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame of a Parquet file.
+/// * `required` - The names of the required columns.
+///
+/// # Returns
+///
+/// An error naming the missing columns, if any.
+pub fn assert_required_columns(df: &DataFrame, required: &[String]) -> Result<()> {
+    let missing_columns = required
+        .iter()
+        .filter(|column| df.column(column).is_err())
+        .map(|column| column.as_str())
+        .collect::<Vec<_>>();
+
+    if missing_columns.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Missing required columns: {}",
+            missing_columns.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_missing_one_required_column() {
+        let df = df!(
+            "Op" => &["I"],
+            "_dms_ingestion_timestamp" => &["2024-02-14 10:00:00"],
+            "id" => &[1i64],
+            "name" => &["a"]
+        )
+        .unwrap();
+        let required = vec!["id".to_string(), "name".to_string(), "email".to_string()];
+
+        let result = assert_required_columns(&df, &required);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing required columns: email"
+        );
+    }
+
+    #[test]
+    fn test_frame_with_all_required_columns() {
+        let df = df!("id" => &[1i64], "name" => &["a"]).unwrap();
+
+        assert!(assert_required_columns(&df, &["id".to_string()]).is_ok());
+    }
+}
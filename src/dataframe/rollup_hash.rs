@@ -0,0 +1,171 @@
+use anyhow::Result;
+use polars::prelude::*;
+use polars_core::export::rayon::prelude::*;
+use polars_core::export::rayon::ThreadPoolBuilder;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+/// The number of rows hashed by a task of the parallel rollup hash.
+const ROWS_PER_CHUNK: usize = 16_384;
+
+/// Represents an order-independent hash of all the rows of a table, along with the number of rows.
+///
+/// The hash is the wrapping sum of the hashes of the rows, so equal rollups imply, with high
+/// probability, equal tables, regardless of the order of their rows. A sum is used instead of
+/// a XOR, so that duplicate rows do not cancel each other out.
+///
+/// The rows are hashed with `DefaultHasher` over the string form of their values, so the rollup
+/// can only be compared with another rollup computed by this module, e.g. between two S3 prefixes.
+/// It cannot be compared with the md5 rollup of `TableQuery::RollupHash` computed by Postgres.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RollupHash {
+    pub row_count: usize,
+    pub hash: u64,
+}
+
+/// Computes the rollup hash of the rows of a DataFrame.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame, with its columns in the order to hash them.
+///
+/// # Returns
+///
+/// The rollup hash of the DataFrame.
+pub fn rollup_hash(df: &DataFrame) -> Result<RollupHash> {
+    Ok(RollupHash {
+        row_count: df.height(),
+        hash: rows_hash(df, 0..df.height())?,
+    })
+}
+
+/// Computes the rollup hash of the rows of a DataFrame in parallel over chunks of rows.
+///
+/// Since the rollup is a sum, the hash is identical to the one of `rollup_hash`, regardless
+/// of the chunking and the number of threads.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame, with its columns in the order to hash them.
+/// * `threads` - The number of threads to hash the rows with.
+///
+/// # Returns
+///
+/// The rollup hash of the DataFrame.
+pub fn parallel_rollup_hash(df: &DataFrame, threads: usize) -> Result<RollupHash> {
+    let chunks = (0..df.height())
+        .step_by(ROWS_PER_CHUNK)
+        .map(|start| start..(start + ROWS_PER_CHUNK).min(df.height()))
+        .collect::<Vec<_>>();
+
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+    let hash = pool.install(|| {
+        chunks
+            .into_par_iter()
+            .map(|rows| rows_hash(df, rows))
+            .try_reduce(|| 0, |left, right| Ok(left.wrapping_add(right)))
+    })?;
+
+    Ok(RollupHash {
+        row_count: df.height(),
+        hash,
+    })
+}
+
+/// Computes the wrapping sum of the hashes of a range of rows of a DataFrame.
+fn rows_hash(df: &DataFrame, rows: Range<usize>) -> Result<u64> {
+    let columns = df.get_columns();
+
+    let mut hash = 0u64;
+    for row in rows {
+        let mut hasher = DefaultHasher::new();
+        for column in columns {
+            column.get(row)?.to_string().hash(&mut hasher);
+        }
+        hash = hash.wrapping_add(hasher.finish());
+    }
+
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataframe() -> DataFrame {
+        df!(
+            "id" => &[1i64, 2, 3],
+            "name" => &["a", "b", "c"]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rollup_hash_ignores_row_order() {
+        let reversed = dataframe().reverse();
+
+        assert_eq!(
+            rollup_hash(&dataframe()).unwrap(),
+            rollup_hash(&reversed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rollup_hash_changes_with_added_row() {
+        let mut added = dataframe();
+        added
+            .vstack_mut(&df!("id" => &[4i64], "name" => &["d"]).unwrap())
+            .unwrap();
+
+        let rollup = rollup_hash(&dataframe()).unwrap();
+        let added_rollup = rollup_hash(&added).unwrap();
+
+        assert_eq!(added_rollup.row_count, 4);
+        assert_ne!(rollup.hash, added_rollup.hash);
+    }
+
+    #[test]
+    fn test_rollup_hash_changes_with_changed_row() {
+        let changed = df!(
+            "id" => &[1i64, 2, 3],
+            "name" => &["a", "x", "c"]
+        )
+        .unwrap();
+
+        let rollup = rollup_hash(&dataframe()).unwrap();
+        let changed_rollup = rollup_hash(&changed).unwrap();
+
+        assert_eq!(rollup.row_count, changed_rollup.row_count);
+        assert_ne!(rollup, changed_rollup);
+    }
+
+    #[test]
+    fn test_parallel_rollup_hash_matches_sequential() {
+        let df = df!(
+            "id" => (0..50_000i64).collect::<Vec<_>>(),
+            "name" => (0..50_000).map(|id| format!("name_{id}")).collect::<Vec<_>>()
+        )
+        .unwrap();
+
+        let rollup = rollup_hash(&df).unwrap();
+        for threads in [1, 2, 4] {
+            assert_eq!(parallel_rollup_hash(&df, threads).unwrap(), rollup);
+        }
+        assert_eq!(
+            parallel_rollup_hash(&dataframe(), 2).unwrap(),
+            rollup_hash(&dataframe()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rollup_hash_counts_duplicate_rows() {
+        let duplicated = df!(
+            "id" => &[1i64, 1],
+            "name" => &["a", "a"]
+        )
+        .unwrap();
+
+        assert_ne!(rollup_hash(&duplicated).unwrap().hash, 0);
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use polars::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Represents a deterministic sample of the keys of the tables, to validate a fraction of the
+/// rows of very large tables quickly.
+///
+/// A row is kept when the seeded hash of its primary key falls within the ratio, so all the
+/// operations of a sampled key are kept across the files, and the same seed always samples
+/// the same keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sampling {
+    pub ratio: f64,
+    pub seed: u64,
+}
+
+impl Sampling {
+    /// Creates a new sampling.
+    ///
+    /// # Arguments
+    ///
+    /// * `ratio` - The fraction of the keys to keep, in (0, 1].
+    /// * `seed` - The seed of the hash of the keys.
+    ///
+    /// # Returns
+    ///
+    /// A new sampling instance, or an error if the ratio is out of range.
+    pub fn new(ratio: f64, seed: u64) -> Result<Self> {
+        if !(ratio > 0.0 && ratio <= 1.0) {
+            return Err(anyhow!("Sample ratio {} is not in (0, 1]", ratio));
+        }
+
+        Ok(Self { ratio, seed })
+    }
+
+    /// Keeps the rows of a DataFrame whose primary key is sampled.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame.
+    /// * `primary_key` - The primary key columns of the table.
+    ///
+    /// # Returns
+    ///
+    /// The rows of the sampled keys.
+    pub fn sample(&self, df: DataFrame, primary_key: &[String]) -> Result<DataFrame> {
+        if self.ratio >= 1.0 {
+            return Ok(df);
+        }
+
+        let key_columns = primary_key
+            .iter()
+            .map(|key| df.column(key))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let mask = (0..df.height())
+            .map(|row| {
+                let mut hasher = DefaultHasher::new();
+                self.seed.hash(&mut hasher);
+                for column in &key_columns {
+                    column.get(row)?.to_string().hash(&mut hasher);
+                }
+                Ok((hasher.finish() as f64 / u64::MAX as f64) < self.ratio)
+            })
+            .collect::<PolarsResult<BooleanChunked>>()?;
+
+        Ok(df.filter(&mask)?)
+    }
+
+    /// Scales a count of the sampled rows to an estimate for the whole table.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The count of the sampled rows.
+    ///
+    /// # Returns
+    ///
+    /// The estimated count.
+    pub fn scale(&self, count: i64) -> i64 {
+        (count as f64 / self.ratio).round() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(count: i64) -> DataFrame {
+        df!("id" => (0..count).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn sampled_ids(df: &DataFrame) -> Vec<Option<i64>> {
+        df.column("id")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_samples_same_rows() {
+        let primary_key = ["id".to_string()];
+        let sampling = Sampling::new(0.1, 42).unwrap();
+
+        let first = sampling.sample(keys(1000), &primary_key).unwrap();
+        let second = sampling.sample(keys(1000), &primary_key).unwrap();
+        let other_seed = Sampling::new(0.1, 7)
+            .unwrap()
+            .sample(keys(1000), &primary_key)
+            .unwrap();
+
+        assert_eq!(sampled_ids(&first), sampled_ids(&second));
+        assert_ne!(sampled_ids(&first), sampled_ids(&other_seed));
+    }
+
+    #[test]
+    fn test_sample_ratio_is_approximately_honored() {
+        let sampling = Sampling::new(0.1, 42).unwrap();
+
+        let sample = sampling.sample(keys(10000), &["id".to_string()]).unwrap();
+
+        assert!((800..1200).contains(&sample.height()));
+        assert_eq!(sampling.scale(25), 250);
+    }
+
+    #[test]
+    fn test_invalid_ratio_is_rejected() {
+        assert!(Sampling::new(0.0, 42).is_err());
+        assert!(Sampling::new(1.5, 42).is_err());
+    }
+}
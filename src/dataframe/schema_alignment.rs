@@ -0,0 +1,114 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+/// The columns added by DMS, which are kept first and in this order.
+const DMS_COLUMNS: [&str; 2] = ["Op", "_dms_ingestion_timestamp"];
+
+/// Aligns the DataFrames of the Parquet files of a table to the union of their schemas,
+/// since DMS schema evolution can add columns to the CDC files relative to the LOAD file.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaAligner {
+    columns: IndexMap<String, DataType>,
+}
+
+impl SchemaAligner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the union of the schemas of the DataFrames aligned so far, without the columns added by DMS.
+    pub fn columns(&self) -> &IndexMap<String, DataType> {
+        &self.columns
+    }
+
+    /// Aligns a DataFrame to the union of the schemas seen so far, including its own.
+    ///
+    /// The columns of the union that are missing in the DataFrame are filled with nulls,
+    /// and the columns are ordered as in the union, after the columns added by DMS.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame to align.
+    ///
+    /// # Returns
+    ///
+    /// The aligned DataFrame, along with the columns seen for the first time and their data types.
+    pub fn align(&mut self, mut df: DataFrame) -> Result<(DataFrame, Vec<(String, DataType)>)> {
+        let new_columns = df
+            .get_columns()
+            .iter()
+            .filter(|column| !DMS_COLUMNS.contains(&column.name()))
+            .filter(|column| !self.columns.contains_key(column.name()))
+            .map(|column| (column.name().to_string(), column.dtype().clone()))
+            .collect::<Vec<_>>();
+        self.columns.extend(new_columns.iter().cloned());
+
+        let height = df.height();
+        for (column, data_type) in &self.columns {
+            if df.column(column).is_err() {
+                df.with_column(Series::full_null(column, height, data_type))?;
+            }
+        }
+
+        let ordered_columns = DMS_COLUMNS
+            .iter()
+            .filter(|column| df.column(column).is_ok())
+            .map(|column| column.to_string())
+            .chain(self.columns.keys().cloned())
+            .collect::<Vec<_>>();
+
+        Ok((df.select(ordered_columns)?, new_columns))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cdc_file_with_extra_column() {
+        let load_df = df!(
+            "Op" => &["I"],
+            "_dms_ingestion_timestamp" => &["2024-02-14 10:00:00"],
+            "id" => &[1i64],
+            "name" => &["a"]
+        )
+        .unwrap();
+        let cdc_df = df!(
+            "Op" => &["U"],
+            "_dms_ingestion_timestamp" => &["2024-02-14 11:00:00"],
+            "id" => &[1i64],
+            "email" => &["a@example.com"],
+            "name" => &["b"]
+        )
+        .unwrap();
+        let later_cdc_df = df!(
+            "Op" => &["U"],
+            "_dms_ingestion_timestamp" => &["2024-02-14 12:00:00"],
+            "id" => &[1i64],
+            "name" => &["c"]
+        )
+        .unwrap();
+
+        let mut aligner = SchemaAligner::new();
+        let (_, new_columns) = aligner.align(load_df).unwrap();
+        assert_eq!(new_columns.len(), 2);
+
+        let (cdc_df, new_columns) = aligner.align(cdc_df).unwrap();
+        assert_eq!(new_columns, vec![("email".to_string(), DataType::String)]);
+        assert_eq!(
+            cdc_df.get_column_names(),
+            vec!["Op", "_dms_ingestion_timestamp", "id", "name", "email"]
+        );
+
+        let (later_cdc_df, new_columns) = aligner.align(later_cdc_df).unwrap();
+        assert!(new_columns.is_empty());
+        assert_eq!(later_cdc_df.width(), 5);
+        assert_eq!(later_cdc_df.column("email").unwrap().null_count(), 1);
+        assert_eq!(
+            later_cdc_df.column("email").unwrap().dtype(),
+            &DataType::String
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use polars::prelude::*;
+
+/// Represents what happens to the rows of a file that belong to another table.
+///
+/// The policy can be one of the following:
+///
+/// * Skip - The foreign rows are skipped and reported, while the rest of the file is applied.
+/// * Error - The file is rejected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForeignRowPolicy {
+    #[default]
+    Skip,
+    Error,
+}
+
+/// Represents a column of the Parquet files that identifies the table of each row, e.g. added
+/// by a DMS transformation rule, to catch a misrouted file with rows of a different table.
+///
+/// The files without the column are not checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableIdentifier {
+    pub column: String,
+    pub on_foreign_row: ForeignRowPolicy,
+}
+
+impl TableIdentifier {
+    /// Creates a new table identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The column with the name of the table of each row.
+    /// * `on_foreign_row` - What happens to the rows of another table.
+    ///
+    /// # Returns
+    ///
+    /// A new table identifier instance.
+    pub fn new(column: impl Into<String>, on_foreign_row: ForeignRowPolicy) -> Self {
+        Self {
+            column: column.into(),
+            on_foreign_row,
+        }
+    }
+
+    /// Checks that the rows of a file belong to the expected table.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The rows of the file.
+    /// * `table_name` - The expected table.
+    /// * `file_name` - The name of the file.
+    ///
+    /// # Returns
+    ///
+    /// The rows of the expected table, and the number of foreign rows, including the ones
+    /// without a table, which were skipped.
+    pub fn check(
+        &self,
+        df: DataFrame,
+        table_name: &str,
+        file_name: &str,
+    ) -> Result<(DataFrame, usize)> {
+        let Ok(identifiers) = df.column(&self.column) else {
+            return Ok((df, 0));
+        };
+
+        let identifiers = identifiers.cast(&DataType::String)?;
+        let is_expected_table: BooleanChunked = identifiers
+            .str()?
+            .into_iter()
+            .map(|identifier| identifier == Some(table_name))
+            .collect();
+        let foreign_rows = is_expected_table
+            .into_iter()
+            .filter(|is_expected_table| *is_expected_table == Some(false))
+            .count();
+        if foreign_rows == 0 {
+            return Ok((df, 0));
+        }
+
+        match self.on_foreign_row {
+            ForeignRowPolicy::Error => Err(anyhow!(
+                "File {} has {} rows of a table other than {} in column {}",
+                file_name,
+                foreign_rows,
+                table_name,
+                self.column
+            )),
+            ForeignRowPolicy::Skip => {
+                warn!(
+                    "Skipping {} rows of a table other than {} in file {}",
+                    foreign_rows, table_name, file_name
+                );
+                Ok((df.filter(&is_expected_table)?, foreign_rows))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn misrouted_file() -> DataFrame {
+        df!(
+            "Op" => &["I", "I", "U"],
+            "table_name" => &[Some("users"), Some("orders"), None],
+            "id" => &[1i64, 2, 1]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mismatched_row_is_skipped() {
+        let (df, foreign_rows) = TableIdentifier::new("table_name", ForeignRowPolicy::Skip)
+            .check(misrouted_file(), "users", "20240101-000000000.parquet")
+            .unwrap();
+
+        assert_eq!(foreign_rows, 2);
+        let ids = df.column("id").unwrap().i64().unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), vec![Some(1)]);
+    }
+
+    #[test]
+    fn test_mismatched_row_is_rejected() {
+        let error = TableIdentifier::new("table_name", ForeignRowPolicy::Error)
+            .check(misrouted_file(), "users", "20240101-000000000.parquet")
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "File 20240101-000000000.parquet has 2 rows of a table other than users in column table_name"
+        );
+    }
+
+    #[test]
+    fn test_file_without_identifier_column_is_not_checked() {
+        let df = df!("Op" => &["I"], "id" => &[1i64]).unwrap();
+
+        let (df, foreign_rows) = TableIdentifier::new("table_name", ForeignRowPolicy::Error)
+            .check(df, "users", "20240101-000000000.parquet")
+            .unwrap();
+
+        assert_eq!((df.height(), foreign_rows), (1, 0));
+    }
+}
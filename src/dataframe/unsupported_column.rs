@@ -0,0 +1,165 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::warn;
+use polars::prelude::*;
+
+use crate::postgres::data_type_mapping::is_supported_data_type;
+
+/// Represents how the DataFrame columns whose data type cannot be mapped to Postgres are handled.
+///
+/// The policy can be one of the following:
+///
+/// * Error - The table fails to load.
+/// * Skip - The columns are dropped from both the table definition and the inserts, with a warning.
+/// * AsText - The columns are cast to text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsupportedColumn {
+    #[default]
+    Error,
+    Skip,
+    AsText,
+}
+
+impl UnsupportedColumn {
+    /// Applies the policy on the unsupported columns of a DataFrame.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame.
+    ///
+    /// # Returns
+    ///
+    /// The DataFrame after applying the policy, along with the names of the unsupported columns.
+    pub fn apply(&self, mut df: DataFrame) -> Result<(DataFrame, Vec<String>)> {
+        let unsupported_columns = df
+            .get_columns()
+            .iter()
+            .filter(|column| !is_supported_data_type(column.dtype()))
+            .map(|column| (column.name().to_string(), column.dtype().clone()))
+            .collect::<Vec<_>>();
+
+        if unsupported_columns.is_empty() {
+            return Ok((df, vec![]));
+        }
+
+        match self {
+            UnsupportedColumn::Error => {
+                return Err(anyhow!(
+                    "Columns with unsupported data types: {:?}",
+                    unsupported_columns
+                ));
+            }
+            UnsupportedColumn::Skip => {
+                for (column, data_type) in &unsupported_columns {
+                    warn!("Skipping column {column} with unsupported data type {data_type}");
+                    _ = df.drop_in_place(column)?;
+                }
+            }
+            UnsupportedColumn::AsText => {
+                for (column, data_type) in &unsupported_columns {
+                    warn!("Casting column {column} with unsupported data type {data_type} to text");
+                    let text_column = df.column(column)?.cast(&DataType::String)?;
+                    df.with_column(text_column)?;
+                }
+            }
+        }
+
+        let unsupported_columns = unsupported_columns
+            .into_iter()
+            .map(|(column, _)| column)
+            .collect();
+
+        Ok((df, unsupported_columns))
+    }
+
+    /// Applies the policy on the unsupported columns of a table definition.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_columns` - The column names of the table and their data types.
+    /// * `unsupported_columns` - The names of the unsupported columns.
+    pub fn apply_to_table_columns(
+        &self,
+        table_columns: &mut IndexMap<String, String>,
+        unsupported_columns: &[String],
+    ) {
+        for column in unsupported_columns {
+            match self {
+                UnsupportedColumn::Error => (),
+                UnsupportedColumn::Skip => {
+                    table_columns.shift_remove(column);
+                }
+                UnsupportedColumn::AsText => {
+                    table_columns.insert(column.clone(), "text".to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataframe_with_unsupported_column() -> DataFrame {
+        DataFrame::new(vec![
+            Series::new("id", &[1i64, 2]),
+            Int128Chunked::from_vec("amount", vec![1, 2])
+                .into_decimal_unchecked(Some(2), 10)
+                .into_series(),
+        ])
+        .unwrap()
+    }
+
+    fn table_columns() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("id".to_string(), "bigint".to_string()),
+            ("amount".to_string(), "numeric".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let result = UnsupportedColumn::Error.apply(dataframe_with_unsupported_column());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_skip_policy() {
+        let (df, unsupported_columns) = UnsupportedColumn::Skip
+            .apply(dataframe_with_unsupported_column())
+            .unwrap();
+        let mut table_columns = table_columns();
+        UnsupportedColumn::Skip.apply_to_table_columns(&mut table_columns, &unsupported_columns);
+
+        assert_eq!(df.get_column_names(), vec!["id"]);
+        assert_eq!(unsupported_columns, vec!["amount"]);
+        assert_eq!(
+            table_columns,
+            IndexMap::from([("id".to_string(), "bigint".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_as_text_policy() {
+        let (df, unsupported_columns) = UnsupportedColumn::AsText
+            .apply(dataframe_with_unsupported_column())
+            .unwrap();
+        let mut table_columns = table_columns();
+        UnsupportedColumn::AsText.apply_to_table_columns(&mut table_columns, &unsupported_columns);
+
+        assert_eq!(df.column("amount").unwrap().dtype(), &DataType::String);
+        assert_eq!(table_columns.get("amount").unwrap(), "text");
+    }
+
+    #[test]
+    fn test_supported_columns_are_untouched() {
+        let df = DataFrame::new(vec![Series::new("id", &[1i64, 2])]).unwrap();
+
+        let (df, unsupported_columns) = UnsupportedColumn::Error.apply(df).unwrap();
+
+        assert_eq!(df.width(), 1);
+        assert!(unsupported_columns.is_empty());
+    }
+}
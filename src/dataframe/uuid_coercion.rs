@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+/// Parses a UUID stored as a string and returns it in its canonical form.
+///
+/// Both the hyphenated (`a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11`) and the simple
+/// (`a0eebc999c0b4ef8bb6d6bb9bd380a11`) forms are accepted, optionally wrapped in braces.
+///
+/// # Arguments
+///
+/// * `value` - The string value of the UUID.
+///
+/// # Returns
+///
+/// The lowercase hyphenated UUID.
+pub fn parse_uuid(value: &str) -> Result<String> {
+    let trimmed = value
+        .trim()
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(value.trim());
+
+    let is_hyphenated = trimmed.len() == 36
+        && trimmed
+            .char_indices()
+            .all(|(index, c)| matches!(index, 8 | 13 | 18 | 23) == (c == '-'));
+    let hex_digits = trimmed.replace('-', "");
+
+    if (!is_hyphenated && trimmed.len() != 32)
+        || hex_digits.len() != 32
+        || !hex_digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(anyhow!("Malformed UUID: {value}"));
+    }
+
+    let hex_digits = hex_digits.to_ascii_lowercase();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex_digits[0..8],
+        &hex_digits[8..12],
+        &hex_digits[12..16],
+        &hex_digits[16..20],
+        &hex_digits[20..32]
+    ))
+}
+
+/// Coerces the string columns of a DataFrame that target `uuid` columns into canonical UUIDs.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame read from S3.
+/// * `column_data_types` - The columns of the target table and their data types.
+///
+/// # Returns
+///
+/// The DataFrame with the UUID columns in canonical form, or an error naming the malformed value.
+pub fn coerce_uuid_columns(
+    mut df: DataFrame,
+    column_data_types: &IndexMap<String, String>,
+) -> Result<DataFrame> {
+    let uuid_columns = column_data_types
+        .iter()
+        .filter(|(_, data_type)| data_type.eq_ignore_ascii_case("uuid"))
+        .map(|(column, _)| column.as_str())
+        .filter(|column| {
+            df.column(column)
+                .map(|series| series.dtype() == &DataType::String)
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+
+    for column in uuid_columns {
+        let values = df
+            .column(column)?
+            .str()?
+            .into_iter()
+            .map(|value| value.map(parse_uuid).transpose())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| anyhow!("Failed to coerce column {column} to uuid: {e}"))?;
+
+        df.with_column(Series::new(column, values))?;
+    }
+
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uuid() {
+        assert_eq!(
+            parse_uuid("A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11").unwrap(),
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"
+        );
+        assert_eq!(
+            parse_uuid("{a0eebc999c0b4ef8bb6d6bb9bd380a11}").unwrap(),
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"
+        );
+        assert!(parse_uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1").is_err());
+        assert!(parse_uuid("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1z").is_err());
+        assert!(parse_uuid("a0eebc999-c0b-4ef8-bb6d-6bb9bd380a11").is_err());
+    }
+
+    #[test]
+    fn test_coerce_uuid_columns_round_trip() {
+        let df = DataFrame::new(vec![
+            Series::new("id", &["A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11"]),
+            Series::new("name", &["A0EEBC99"]),
+        ])
+        .unwrap();
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "uuid".to_string());
+        column_data_types.insert("name".to_string(), "text".to_string());
+
+        let df = coerce_uuid_columns(df, &column_data_types).unwrap();
+
+        assert_eq!(
+            df.column("id").unwrap().str().unwrap().get(0),
+            Some("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")
+        );
+        assert_eq!(
+            df.column("name").unwrap().str().unwrap().get(0),
+            Some("A0EEBC99")
+        );
+    }
+
+    #[test]
+    fn test_coerce_uuid_columns_with_malformed_uuid() {
+        let df = DataFrame::new(vec![Series::new("id", &["not-a-uuid"])]).unwrap();
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "uuid".to_string());
+
+        let error = coerce_uuid_columns(df, &column_data_types).unwrap_err();
+
+        assert!(error.to_string().contains("Malformed UUID: not-a-uuid"));
+    }
+}
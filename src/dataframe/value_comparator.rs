@@ -0,0 +1,365 @@
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io::Read;
+use std::sync::Arc;
+
+/// Represents how the values of an array column are compared.
+///
+/// The comparison can be one of the following:
+///
+/// * Ordered - The arrays are compared as lists, so the order of the elements matters.
+/// * Unordered - The arrays are compared as sets, so the elements are sorted before comparing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayComparison {
+    #[default]
+    Ordered,
+    Unordered,
+}
+
+/// Represents how the values of a JSON column are compared.
+///
+/// The comparison can be one of the following:
+///
+/// * Raw - The values are compared as strings, so key ordering and whitespace matter.
+/// * Semantic - The values are parsed and compared structurally, so key ordering and whitespace are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonComparison {
+    #[default]
+    Raw,
+    Semantic,
+}
+
+/// Represents a user-provided function that decodes the values of a binary column before comparing,
+/// e.g. when the same logical value is stored gzipped in one system and raw in the other.
+///
+/// A value that fails to decode is compared as is.
+#[derive(Clone)]
+pub struct BinaryDecoder(Arc<DecodeFn>);
+
+/// The function of a binary decoder.
+type DecodeFn = dyn Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync;
+
+impl BinaryDecoder {
+    /// Creates a new binary decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `decode` - The function to apply on each binary value.
+    ///
+    /// # Returns
+    ///
+    /// A new binary decoder instance.
+    pub fn new(decode: impl Fn(&[u8]) -> Result<Vec<u8>> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(decode))
+    }
+
+    /// Creates a binary decoder that gunzips the values.
+    pub fn gunzip() -> Self {
+        Self::new(|bytes| {
+            let mut decoded = Vec::new();
+            GzDecoder::new(bytes).read_to_end(&mut decoded)?;
+            Ok(decoded)
+        })
+    }
+
+    /// Decodes a binary value, falling back to the value itself if it fails to decode.
+    pub fn decode(&self, bytes: &[u8]) -> Vec<u8> {
+        (self.0)(bytes).unwrap_or_else(|_| bytes.to_vec())
+    }
+}
+
+impl Debug for BinaryDecoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "BinaryDecoder")
+    }
+}
+
+/// Represents the comparison of the values of two DataFrames, column by column.
+#[derive(Debug, Clone, Default)]
+pub struct ValueComparator {
+    array_comparisons: HashMap<String, ArrayComparison>,
+    json_comparisons: HashMap<String, JsonComparison>,
+    binary_decoders: HashMap<String, BinaryDecoder>,
+}
+
+impl ValueComparator {
+    /// Creates a new value comparator.
+    ///
+    /// # Returns
+    ///
+    /// A new value comparator instance, which compares all arrays as ordered lists.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the arrays of a column are compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the array column.
+    /// * `array_comparison` - How the arrays of the column are compared.
+    pub fn set_array_comparison(
+        &mut self,
+        column: impl Into<String>,
+        array_comparison: ArrayComparison,
+    ) {
+        self.array_comparisons
+            .insert(column.into(), array_comparison);
+    }
+
+    /// Gets how the arrays of a column are compared.
+    pub fn array_comparison(&self, column: &str) -> ArrayComparison {
+        self.array_comparisons
+            .get(column)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets how the values of a JSON column are compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the JSON column.
+    /// * `json_comparison` - How the values of the column are compared.
+    pub fn set_json_comparison(
+        &mut self,
+        column: impl Into<String>,
+        json_comparison: JsonComparison,
+    ) {
+        self.json_comparisons.insert(column.into(), json_comparison);
+    }
+
+    /// Gets how the values of a JSON column are compared.
+    pub fn json_comparison(&self, column: &str) -> JsonComparison {
+        self.json_comparisons
+            .get(column)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Sets the decoder of the values of a binary column.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the binary column.
+    /// * `binary_decoder` - The decoder applied on both values before comparing.
+    pub fn set_binary_decoder(&mut self, column: impl Into<String>, binary_decoder: BinaryDecoder) {
+        self.binary_decoders.insert(column.into(), binary_decoder);
+    }
+
+    /// Compares two values of a column.
+    ///
+    /// Binary values of the columns with a decoder are decoded before comparing.
+    ///
+    /// String values of the columns with a semantic JSON comparison are parsed and compared
+    /// structurally, falling back to a string comparison if either value is not valid JSON.
+    ///
+    /// List values are compared element by element. String values are parsed as Postgres
+    /// array literals, e.g. `{1,2,3}`, only for the columns with a configured array comparison.
+    ///
+    /// # Arguments
+    ///
+    /// * `column` - The name of the column.
+    /// * `source` - The value of the source.
+    /// * `target` - The value of the target.
+    ///
+    /// # Returns
+    ///
+    /// Whether the values are considered equal.
+    pub fn values_equal(&self, column: &str, source: &AnyValue, target: &AnyValue) -> bool {
+        if let Some(binary_decoder) = self.binary_decoders.get(column) {
+            if let (Some(source_bytes), Some(target_bytes)) =
+                (binary_value(source), binary_value(target))
+            {
+                return binary_decoder.decode(source_bytes) == binary_decoder.decode(target_bytes);
+            }
+        }
+
+        if self.json_comparison(column) == JsonComparison::Semantic {
+            if let (Some(source_json), Some(target_json)) = (json_value(source), json_value(target))
+            {
+                return source_json == target_json;
+            }
+        }
+
+        let parse_literals = self.array_comparisons.contains_key(column);
+
+        match (
+            array_elements(source, parse_literals),
+            array_elements(target, parse_literals),
+        ) {
+            (Some(mut source_elements), Some(mut target_elements)) => {
+                if self.array_comparison(column) == ArrayComparison::Unordered {
+                    source_elements.sort();
+                    target_elements.sort();
+                }
+                source_elements == target_elements
+            }
+            _ => source == target,
+        }
+    }
+}
+
+/// Gets the elements of an array value, either a List value or a Postgres array literal.
+fn array_elements(value: &AnyValue, parse_literals: bool) -> Option<Vec<String>> {
+    match value {
+        AnyValue::List(series) => Some(
+            (0..series.len())
+                .map(|index| {
+                    series
+                        .get(index)
+                        .map(|element| element.to_string())
+                        .unwrap_or_default()
+                })
+                .collect(),
+        ),
+        AnyValue::String(literal) if parse_literals => parse_postgres_array(literal),
+        AnyValue::StringOwned(literal) if parse_literals => parse_postgres_array(literal.as_str()),
+        _ => None,
+    }
+}
+
+/// Gets the bytes of a binary value.
+fn binary_value<'a>(value: &'a AnyValue) -> Option<&'a [u8]> {
+    match value {
+        AnyValue::Binary(bytes) => Some(bytes),
+        AnyValue::BinaryOwned(bytes) => Some(bytes.as_slice()),
+        _ => None,
+    }
+}
+
+/// Parses a string value as JSON.
+fn json_value(value: &AnyValue) -> Option<serde_json::Value> {
+    match value {
+        AnyValue::String(json) => serde_json::from_str(json).ok(),
+        AnyValue::StringOwned(json) => serde_json::from_str(json.as_str()).ok(),
+        _ => None,
+    }
+}
+
+/// Parses the elements of a one-dimensional Postgres array literal, e.g. `{1,2,3}`.
+fn parse_postgres_array(literal: &str) -> Option<Vec<String>> {
+    let inner = literal.strip_prefix('{')?.strip_suffix('}')?;
+    if inner.is_empty() {
+        return Some(vec![]);
+    }
+
+    let mut elements = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => current.extend(chars.next()),
+            ',' if !in_quotes => elements.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    elements.push(current);
+
+    Some(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_literals_under_unordered_comparison() {
+        let mut comparator = ValueComparator::new();
+        comparator.set_array_comparison("tags", ArrayComparison::Unordered);
+
+        assert!(comparator.values_equal(
+            "tags",
+            &AnyValue::String("{1,2,3}"),
+            &AnyValue::String("{3,2,1}")
+        ));
+    }
+
+    #[test]
+    fn test_array_literals_under_ordered_comparison() {
+        let mut comparator = ValueComparator::new();
+        comparator.set_array_comparison("tags", ArrayComparison::Ordered);
+
+        assert!(!comparator.values_equal(
+            "tags",
+            &AnyValue::String("{1,2,3}"),
+            &AnyValue::String("{3,2,1}")
+        ));
+        assert!(comparator.values_equal(
+            "tags",
+            &AnyValue::String("{1,2,3}"),
+            &AnyValue::String("{1,2,3}")
+        ));
+    }
+
+    #[test]
+    fn test_list_values_under_both_comparisons() {
+        let source = AnyValue::List(Series::new("", &[1i32, 2, 3]));
+        let target = AnyValue::List(Series::new("", &[3i32, 2, 1]));
+
+        let mut comparator = ValueComparator::new();
+        assert!(!comparator.values_equal("tags", &source, &target));
+
+        comparator.set_array_comparison("tags", ArrayComparison::Unordered);
+        assert!(comparator.values_equal("tags", &source, &target));
+    }
+
+    #[test]
+    fn test_json_values_under_both_comparisons() {
+        let source = AnyValue::String(r#"{"a":1,"b":2}"#);
+        let target = AnyValue::String(r#"{"b": 2, "a": 1}"#);
+
+        let mut comparator = ValueComparator::new();
+        assert!(!comparator.values_equal("payload", &source, &target));
+
+        comparator.set_json_comparison("payload", JsonComparison::Semantic);
+        assert!(comparator.values_equal("payload", &source, &target));
+        assert!(!comparator.values_equal(
+            "payload",
+            &source,
+            &AnyValue::String(r#"{"a":1,"b":3}"#)
+        ));
+    }
+
+    #[test]
+    fn test_gzipped_and_raw_binary_values_under_gunzip_decoder() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let raw = b"the same logical value".to_vec();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let source = AnyValue::BinaryOwned(gzipped);
+        let target = AnyValue::Binary(&raw);
+
+        let mut comparator = ValueComparator::new();
+        assert!(!comparator.values_equal("payload", &source, &target));
+
+        comparator.set_binary_decoder("payload", BinaryDecoder::gunzip());
+        assert!(comparator.values_equal("payload", &source, &target));
+        assert!(!comparator.values_equal(
+            "payload",
+            &source,
+            &AnyValue::Binary(b"another logical value")
+        ));
+    }
+
+    #[test]
+    fn test_parse_postgres_array_with_quoted_elements() {
+        assert_eq!(
+            parse_postgres_array(r#"{"a,b",c}"#).unwrap(),
+            vec!["a,b".to_string(), "c".to_string()]
+        );
+        assert_eq!(parse_postgres_array("{}").unwrap(), Vec::<String>::new());
+        assert!(parse_postgres_array("not an array").is_none());
+    }
+}
@@ -2,3 +2,4 @@ pub mod cdc;
 pub mod dataframe;
 pub mod postgres;
 pub mod s3;
+mod telemetry;
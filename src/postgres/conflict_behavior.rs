@@ -0,0 +1,126 @@
+/// Represents how an upserted row that conflicts with an existing row on the primary key is handled.
+///
+/// The behavior can be one of the following:
+///
+/// * DoUpdate - The existing row is updated, so the last row wins.
+/// * DoNothing - The existing row is left unchanged, so the first row wins. Useful for idempotent re-loads.
+///
+/// The behavior applies to the inserted rows, while the updated rows always update the existing row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictBehavior {
+    #[default]
+    DoUpdate,
+    DoNothing,
+}
+
+impl ConflictBehavior {
+    /// Generates the ON CONFLICT clause of an upsert.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary_key` - The comma separated primary key columns.
+    /// * `assignments` - The assignments of the upserted values, e.g. `name = 'a'`.
+    ///
+    /// # Returns
+    ///
+    /// The ON CONFLICT clause.
+    pub fn on_conflict_clause(&self, primary_key: &str, assignments: &[String]) -> String {
+        match self {
+            ConflictBehavior::DoUpdate => format!(
+                " ON CONFLICT ({}) DO UPDATE SET {}",
+                primary_key,
+                assignments.join(", ")
+            ),
+            ConflictBehavior::DoNothing => format!(" ON CONFLICT ({}) DO NOTHING", primary_key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deadpool_postgres::GenericClient;
+    use polars::prelude::*;
+
+    use super::ConflictBehavior;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::postgres_operator::UpsertDataframePayload;
+    use crate::postgres::postgres_operator_impl::upsert_dataframe_with_client;
+    use crate::postgres::test_support::test_pool;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+
+    #[test]
+    fn test_on_conflict_clause() {
+        let assignments = vec!["id = 1".to_string(), "name = 'a'".to_string()];
+
+        assert_eq!(
+            ConflictBehavior::DoUpdate.on_conflict_clause("id", &assignments),
+            " ON CONFLICT (id) DO UPDATE SET id = 1, name = 'a'"
+        );
+        assert_eq!(
+            ConflictBehavior::DoNothing.on_conflict_clause("id", &assignments),
+            " ON CONFLICT (id) DO NOTHING"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_do_nothing_leaves_existing_row_unchanged() {
+        let pool = test_pool("conflict_behavior").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE conflict_behavior.users (id int PRIMARY KEY, name text);
+                INSERT INTO conflict_behavior.users VALUES (1, 'first');",
+            )
+            .await
+            .unwrap();
+
+        let name = |op: &'static str, upserted_name: &'static str, conflict_behavior| {
+            let df = DataFrame::new(vec![
+                Series::new("Op", &[op]),
+                Series::new("_dms_ingestion_timestamp", &[""]),
+                Series::new("id", &[1]),
+                Series::new("name", &[upserted_name]),
+            ])
+            .unwrap();
+            let payload = UpsertDataframePayload {
+                database_name: "mydb".to_string(),
+                schema_name: "conflict_behavior".to_string(),
+                table_name: "users".to_string(),
+                primary_key: "id".to_string(),
+                conflict_behavior,
+                unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+                incomplete_delete_policy: IncompleteDeletePolicy::default(),
+                delete_batch_size: None,
+                deferred_constraints: false,
+            };
+            let client = &client;
+            async move {
+                upsert_dataframe_with_client(client, &df, &payload)
+                    .await
+                    .unwrap();
+                let name: String = client
+                    .query_one("SELECT name FROM conflict_behavior.users WHERE id = 1", &[])
+                    .await
+                    .unwrap()
+                    .get("name");
+                name
+            }
+        };
+
+        // A re-inserted row is ignored or wins, based on the conflict behavior
+        assert_eq!(
+            name("I", "second", ConflictBehavior::DoNothing).await,
+            "first"
+        );
+        assert_eq!(
+            name("I", "second", ConflictBehavior::DoUpdate).await,
+            "second"
+        );
+        // An updated row always wins
+        assert_eq!(
+            name("U", "third", ConflictBehavior::DoNothing).await,
+            "third"
+        );
+    }
+}
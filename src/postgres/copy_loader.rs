@@ -0,0 +1,455 @@
+use anyhow::{anyhow, Result};
+use chrono::DateTime;
+use deadpool_postgres::Client;
+use futures::SinkExt;
+use log::{debug, info};
+use polars::prelude::*;
+use polars_core::export::num::ToPrimitive;
+use std::time::Instant;
+
+use super::table_query::TableQuery;
+use crate::dataframe::decimal::format_decimal;
+
+/// Represents how the rows of the LOAD files are inserted in a table.
+///
+/// The method can be one of the following:
+///
+/// * Insert - The rows are inserted with multi-row INSERT statements.
+/// * Copy - The rows are serialized to CSV and streamed with COPY, which is much faster for large files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InsertMethod {
+    #[default]
+    Insert,
+    Copy,
+}
+
+/// Copies a DataFrame, without the columns added by DMS, in a table using the given client.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the copy.
+/// * `df` - The DataFrame to copy.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+///
+/// # Returns
+///
+/// The number of rows copied.
+pub(crate) async fn copy_dataframe_with_client(
+    client: &Client,
+    df: &DataFrame,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<u64> {
+    let query = TableQuery::CopyFromStdin(
+        schema_name.to_string(),
+        table_name.to_string(),
+        df.get_column_names().join(", "),
+    );
+    debug!("Query: {}", query);
+
+    let copy_start = Instant::now();
+    let sink = client.copy_in(&query.to_string()).await?;
+    futures::pin_mut!(sink);
+
+    let rows_per_chunk = 10_000;
+    let mut offset = 0;
+    while offset < df.height() {
+        let df_chunk = df.slice(offset.to_i64().unwrap(), rows_per_chunk);
+        sink.send(bytes::Bytes::from(dataframe_to_csv(&df_chunk)?))
+            .await?;
+        offset += rows_per_chunk;
+    }
+    let rows_copied = sink.as_mut().finish().await?;
+
+    let copy_duration = copy_start.elapsed().as_millis();
+    info!("Copying {rows_copied} rows took: {copy_duration}ms");
+
+    Ok(rows_copied)
+}
+
+/// Represents the progress of a copy committed in chunks, reported after each chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyProgress {
+    pub chunk: usize,
+    pub chunk_count: usize,
+    pub rows_committed: usize,
+    pub total_rows: usize,
+}
+
+/// Splits the rows of a copy in the chunks committed separately.
+///
+/// # Arguments
+///
+/// * `total_rows` - The number of rows to copy.
+/// * `rows_per_commit` - The maximum number of rows of a chunk.
+///
+/// # Returns
+///
+/// The offset and the length of each chunk.
+pub fn commit_chunks(total_rows: usize, rows_per_commit: usize) -> Vec<(usize, usize)> {
+    let rows_per_commit = rows_per_commit.max(1);
+    (0..total_rows)
+        .step_by(rows_per_commit)
+        .map(|offset| (offset, rows_per_commit.min(total_rows - offset)))
+        .collect()
+}
+
+/// Copies a DataFrame in a table with a separate COPY statement per chunk of rows, each committed
+/// on its own, e.g. to checkpoint the load of a very large LOAD file and bound the WAL growth.
+///
+/// The copy is not atomic: if a chunk fails, the chunks before it stay committed.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the copy, outside of a transaction.
+/// * `df` - The DataFrame to copy.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+/// * `rows_per_commit` - The maximum number of rows of a chunk.
+/// * `on_progress` - Called after each chunk is committed.
+///
+/// # Returns
+///
+/// The number of rows copied.
+pub(crate) async fn copy_dataframe_in_commits(
+    client: &Client,
+    df: &DataFrame,
+    schema_name: &str,
+    table_name: &str,
+    rows_per_commit: usize,
+    mut on_progress: impl FnMut(CopyProgress) + Send,
+) -> Result<u64> {
+    let chunks = commit_chunks(df.height(), rows_per_commit);
+    let mut rows_copied = 0;
+    for (index, (offset, length)) in chunks.iter().enumerate() {
+        let df_chunk = df.slice(offset.to_i64().unwrap(), *length);
+        rows_copied +=
+            copy_dataframe_with_client(client, &df_chunk, schema_name, table_name).await?;
+        on_progress(CopyProgress {
+            chunk: index + 1,
+            chunk_count: chunks.len(),
+            rows_committed: offset + length,
+            total_rows: df.height(),
+        });
+    }
+
+    Ok(rows_copied)
+}
+
+/// Serializes a DataFrame to CSV, in the format expected by `COPY ... WITH (FORMAT csv)`.
+///
+/// Strings are always quoted, so that an empty string is distinguished from a NULL,
+/// which is written as an unquoted empty value. The other values are written in the input
+/// format of their Postgres data type.
+///
+/// # Arguments
+///
+/// * `df` - The DataFrame to serialize.
+///
+/// # Returns
+///
+/// The CSV rows, without a header, or an error if a column has a data type that COPY does not support.
+pub fn dataframe_to_csv(df: &DataFrame) -> Result<String> {
+    let columns = df.get_columns();
+    let mut csv = String::new();
+
+    for row in 0..df.height() {
+        let values = columns
+            .iter()
+            .map(|column| csv_value(&column.get(row)?))
+            .collect::<Result<Vec<_>>>()?;
+        csv.push_str(&values.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+/// Formats a value as a CSV field for COPY, in the input format of its Postgres data type.
+fn csv_value(value: &AnyValue) -> Result<String> {
+    let field = match value {
+        AnyValue::Null => String::new(),
+        AnyValue::Boolean(v) => v.to_string(),
+        AnyValue::String(v) => quote(v),
+        AnyValue::StringOwned(v) => quote(v.as_str()),
+        AnyValue::Float32(v) => float_value(f64::from(*v), v.to_string()),
+        AnyValue::Float64(v) => float_value(*v, v.to_string()),
+        v if v.dtype().is_integer() => v.to_string(),
+        AnyValue::Decimal(integer, scale) => format_decimal(*integer, *scale),
+        AnyValue::Binary(v) => bytea_value(v),
+        AnyValue::BinaryOwned(v) => bytea_value(v),
+        AnyValue::Date(days) => DateTime::from_timestamp(i64::from(*days) * 86_400, 0)
+            .ok_or_else(|| anyhow!("Date out of range: {days}"))?
+            .format("%Y-%m-%d")
+            .to_string(),
+        AnyValue::Datetime(v, time_unit, time_zone) => {
+            let timestamp = DateTime::from_timestamp_micros(to_micros(*v, *time_unit))
+                .ok_or_else(|| anyhow!("Datetime out of range: {v}"))?;
+            // The values of a datetime with a time zone are UTC instants
+            match time_zone {
+                Some(_) => timestamp.format("%Y-%m-%dT%H:%M:%S%.6f+00:00").to_string(),
+                None => timestamp.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+            }
+        }
+        AnyValue::Duration(v, time_unit) => {
+            format!("{} microseconds", to_micros(*v, *time_unit))
+        }
+        AnyValue::List(series) => {
+            let elements = (0..series.len())
+                .map(|index| match series.get(index).unwrap() {
+                    AnyValue::Null => "NULL".to_string(),
+                    element => {
+                        let element = match element {
+                            AnyValue::String(v) => v.to_string(),
+                            element => element.to_string(),
+                        };
+                        format!("\"{}\"", element.replace('\\', "\\\\").replace('"', "\\\""))
+                    }
+                })
+                .collect::<Vec<_>>();
+            quote(&format!("{{{}}}", elements.join(",")))
+        }
+        v => return Err(anyhow!("Data type {} is not supported by COPY", v.dtype())),
+    };
+
+    Ok(field)
+}
+
+/// Formats a float, with the infinities spelled the way Postgres reads them.
+fn float_value(value: f64, displayed: String) -> String {
+    if value.is_infinite() {
+        let sign = if value.is_sign_negative() { "-" } else { "" };
+        format!("{sign}Infinity")
+    } else {
+        displayed
+    }
+}
+
+/// Formats a binary value in the hex format of bytea, e.g. `\xdeadbeef`.
+fn bytea_value(value: &[u8]) -> String {
+    let hex = value
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("\\x{hex}")
+}
+
+/// Converts a value of a datetime or duration to microseconds, the precision of Postgres.
+fn to_micros(value: i64, time_unit: TimeUnit) -> i64 {
+    match time_unit {
+        TimeUnit::Nanoseconds => value.div_euclid(1_000),
+        TimeUnit::Microseconds => value,
+        TimeUnit::Milliseconds => value * 1_000,
+    }
+}
+
+/// Quotes a CSV field, escaping its quotes.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use deadpool_postgres::GenericClient;
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::postgres::test_support::test_pool;
+
+    #[test]
+    fn test_dataframe_to_csv_with_nulls_and_quotes() {
+        let df = DataFrame::new(vec![
+            Series::new("id", &[Some(1), None, Some(3)]),
+            Series::new("name", &[Some(r#"say "hi", then leave"#), Some(""), None]),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            dataframe_to_csv(&df).unwrap(),
+            "1,\"say \"\"hi\"\", then leave\"\n,\"\"\n3,\n"
+        );
+    }
+
+    #[test]
+    fn test_commit_chunks_with_remainder() {
+        assert_eq!(commit_chunks(25, 10), vec![(0, 10), (10, 10), (20, 5)]);
+        assert_eq!(commit_chunks(20, 10), vec![(0, 10), (10, 10)]);
+        assert!(commit_chunks(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_csv_value_of_decimal_and_list() {
+        assert_eq!(csv_value(&AnyValue::Decimal(12345, 2)).unwrap(), "123.45");
+        assert_eq!(
+            csv_value(&AnyValue::List(Series::new("", &["a", "b\"c"]))).unwrap(),
+            r#""{""a"",""b\""c""}""#
+        );
+    }
+
+    #[test]
+    fn test_csv_value_of_binary_and_datetimes() {
+        assert_eq!(
+            csv_value(&AnyValue::Binary(&[0xde, 0xad, 0x00, 0x0f])).unwrap(),
+            r"\xdead000f"
+        );
+        assert_eq!(csv_value(&AnyValue::Boolean(true)).unwrap(), "true");
+        assert_eq!(
+            csv_value(&AnyValue::Float64(f64::NEG_INFINITY)).unwrap(),
+            "-Infinity"
+        );
+        assert_eq!(csv_value(&AnyValue::Date(19723)).unwrap(), "2024-01-01");
+
+        // 2024-01-01 12:30:00.5 UTC
+        let micros = 1_704_112_200_500_000;
+        let utc = Some("UTC".to_string());
+        assert_eq!(
+            csv_value(&AnyValue::Datetime(
+                micros * 1_000,
+                TimeUnit::Nanoseconds,
+                &utc
+            ))
+            .unwrap(),
+            "2024-01-01T12:30:00.500000+00:00"
+        );
+        assert_eq!(
+            csv_value(&AnyValue::Datetime(micros, TimeUnit::Microseconds, &None)).unwrap(),
+            "2024-01-01T12:30:00.500000"
+        );
+        assert_eq!(
+            csv_value(&AnyValue::Duration(1_500, TimeUnit::Milliseconds)).unwrap(),
+            "1500000 microseconds"
+        );
+    }
+
+    async fn client(schema: &str) -> Client {
+        test_pool(schema).await.get().await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_copy_round_trips_nulls_and_quotes() {
+        let client = client("copy_loader").await;
+        client
+            .batch_execute("CREATE TABLE copy_loader.users (id int PRIMARY KEY, name text);")
+            .await
+            .unwrap();
+
+        let df = DataFrame::new(vec![
+            Series::new("id", &[1, 2, 3]),
+            Series::new("name", &[Some(r#"a "quoted", name"#), Some(""), None]),
+        ])
+        .unwrap();
+        let rows_copied = copy_dataframe_with_client(&client, &df, "copy_loader", "users")
+            .await
+            .unwrap();
+        assert_eq!(rows_copied, 3);
+
+        let names = client
+            .query("SELECT name FROM copy_loader.users ORDER BY id", &[])
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<_, Option<String>>("name"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                Some(r#"a "quoted", name"#.to_string()),
+                Some("".to_string()),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_copy_in_commits_reports_progress_per_chunk() {
+        let client = client("copy_loader_chunks").await;
+        client
+            .batch_execute(
+                "CREATE TABLE copy_loader_chunks.chunked (id int PRIMARY KEY, name text);",
+            )
+            .await
+            .unwrap();
+
+        let df = DataFrame::new(vec![
+            Series::new("id", (0..25).collect::<Vec<i32>>()),
+            Series::new(
+                "name",
+                (0..25).map(|i| format!("name {i}")).collect::<Vec<_>>(),
+            ),
+        ])
+        .unwrap();
+        let mut progress = Vec::new();
+        let rows_copied =
+            copy_dataframe_in_commits(&client, &df, "copy_loader_chunks", "chunked", 10, |event| {
+                progress.push(event)
+            })
+            .await
+            .unwrap();
+        assert_eq!(rows_copied, 25);
+
+        assert_eq!(
+            progress
+                .iter()
+                .map(|event| (event.chunk, event.chunk_count, event.rows_committed))
+                .collect::<Vec<_>>(),
+            vec![(1, 3, 10), (2, 3, 20), (3, 3, 25)]
+        );
+        let row_count: i64 = client
+            .query_one("SELECT COUNT(*) FROM copy_loader_chunks.chunked", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(row_count, 25);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_copy_round_trips_bytea_and_timestamptz() {
+        let client = client("copy_loader_events").await;
+        client
+            .batch_execute(
+                "CREATE TABLE copy_loader_events.events (id int PRIMARY KEY, payload bytea, created_at timestamptz);",
+            )
+            .await
+            .unwrap();
+
+        // 2024-01-01 12:30:00.5 UTC
+        let micros = 1_704_112_200_500_000i64;
+        let df = DataFrame::new(vec![
+            Series::new("id", &[1, 2]),
+            Series::new("payload", &[Some(&[0xde_u8, 0xad, 0x00][..]), None]),
+            Series::new("created_at", &[micros, micros])
+                .cast(&DataType::Datetime(
+                    TimeUnit::Microseconds,
+                    Some("UTC".to_string()),
+                ))
+                .unwrap(),
+        ])
+        .unwrap();
+        let rows_copied = copy_dataframe_with_client(&client, &df, "copy_loader_events", "events")
+            .await
+            .unwrap();
+        assert_eq!(rows_copied, 2);
+
+        let rows = client
+            .query(
+                "SELECT payload, (EXTRACT(EPOCH FROM created_at) * 1000000)::bigint AS created_at
+                FROM copy_loader_events.events ORDER BY id",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            rows.iter()
+                .map(|row| row.get::<_, Option<Vec<u8>>>("payload"))
+                .collect::<Vec<_>>(),
+            vec![Some(vec![0xde, 0xad, 0x00]), None]
+        );
+        assert!(rows
+            .iter()
+            .all(|row| row.get::<_, i64>("created_at") == micros));
+    }
+}
@@ -0,0 +1,93 @@
+use polars::prelude::DataType;
+
+/// Maps the data type of a DataFrame column to a Postgres data type.
+///
+/// # Arguments
+///
+/// * `data_type` - The data type of the DataFrame column.
+///
+/// # Returns
+///
+/// The Postgres data type.
+pub fn postgres_data_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Boolean => "boolean".to_string(),
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => "smallint".to_string(),
+        DataType::Int32 | DataType::UInt16 => "integer".to_string(),
+        DataType::Int64 | DataType::UInt32 => "bigint".to_string(),
+        DataType::UInt64 => "numeric".to_string(),
+        DataType::Float32 => "real".to_string(),
+        DataType::Float64 => "double precision".to_string(),
+        DataType::Decimal(Some(precision), Some(scale)) => {
+            format!("numeric({precision},{scale})")
+        }
+        DataType::Decimal(_, _) => "numeric".to_string(),
+        DataType::Date => "date".to_string(),
+        DataType::Datetime(_, Some(_)) => "timestamp with time zone".to_string(),
+        DataType::Datetime(_, None) => "timestamp without time zone".to_string(),
+        DataType::Binary => "bytea".to_string(),
+        DataType::List(_) => "text[]".to_string(),
+        _ => "text".to_string(),
+    }
+}
+
+/// Checks if the data type of a DataFrame column can be mapped to a Postgres data type.
+///
+/// Decimals must fit the precision and scale limits of the Postgres numeric type.
+///
+/// # Arguments
+///
+/// * `data_type` - The data type of the DataFrame column.
+///
+/// # Returns
+///
+/// Whether the data type is supported.
+pub fn is_supported_data_type(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Decimal(Some(precision), Some(scale)) => {
+            (1..=1000).contains(precision) && scale <= precision
+        }
+        DataType::List(inner) => is_supported_data_type(inner),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::TimeUnit;
+
+    #[test]
+    fn test_postgres_data_type() {
+        assert_eq!(postgres_data_type(&DataType::Int64), "bigint");
+        assert_eq!(postgres_data_type(&DataType::String), "text");
+        assert_eq!(
+            postgres_data_type(&DataType::Decimal(Some(10), Some(2))),
+            "numeric(10,2)"
+        );
+        assert_eq!(
+            postgres_data_type(&DataType::Datetime(TimeUnit::Microseconds, None)),
+            "timestamp without time zone"
+        );
+        assert_eq!(
+            postgres_data_type(&DataType::List(Box::new(DataType::String))),
+            "text[]"
+        );
+    }
+
+    #[test]
+    fn test_is_supported_data_type() {
+        assert!(is_supported_data_type(&DataType::Int64));
+        assert!(is_supported_data_type(&DataType::Decimal(
+            Some(10),
+            Some(2)
+        )));
+        assert!(!is_supported_data_type(&DataType::Decimal(
+            Some(2),
+            Some(10)
+        )));
+        assert!(!is_supported_data_type(&DataType::List(Box::new(
+            DataType::Decimal(Some(2), Some(10))
+        ))));
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use deadpool_postgres::tokio_postgres::error::SqlState;
+use deadpool_postgres::tokio_postgres::Error as PostgresError;
+use log::warn;
+use std::future::Future;
+use std::time::Duration;
+
+/// Represents the retries of the apply of a file that fails with a deadlock (`40P01`) or a
+/// serialization failure (`40001`), e.g. under a concurrent apply of the tables, as these
+/// errors are safe to retry. The other errors are not retried.
+///
+/// The delay before each retry doubles from `base_delay`, with a random jitter of up to
+/// `base_delay` so that the conflicting applies do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlockRetry {
+    pub max_retries: usize,
+    pub base_delay: Duration,
+}
+
+impl Default for DeadlockRetry {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Checks if an error is a deadlock or a serialization failure of Postgres.
+///
+/// # Arguments
+///
+/// * `error` - The error.
+///
+/// # Returns
+///
+/// Whether the error can be retried.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<PostgresError>())
+        .filter_map(PostgresError::code)
+        .any(|code| {
+            code == &SqlState::T_R_DEADLOCK_DETECTED || code == &SqlState::T_R_SERIALIZATION_FAILURE
+        })
+}
+
+impl DeadlockRetry {
+    /// Gets the delay before a retry.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, starting from 0.
+    ///
+    /// # Returns
+    ///
+    /// The delay, with a random jitter.
+    pub fn delay(&self, retry: usize) -> Duration {
+        let backoff = self.base_delay * 2u32.saturating_pow(retry as u32);
+        let jitter_millis = (self.base_delay.as_millis() as u64).max(1);
+        let jitter = fastrand::u64(..jitter_millis);
+
+        backoff + Duration::from_millis(jitter)
+    }
+
+    /// Runs an operation, retrying it while it fails with a deadlock or a serialization failure,
+    /// up to `max_retries` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation, e.g. the transaction that applies a file.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last attempt.
+    pub async fn run<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.run_with(is_retryable, operation).await
+    }
+
+    /// Runs an operation, retrying it while it fails with an error that the given check
+    /// accepts, up to `max_retries` times.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_retryable` - The check of whether an error can be retried.
+    /// * `operation` - The operation, e.g. the transaction that applies a file.
+    ///
+    /// # Returns
+    ///
+    /// The result of the last attempt.
+    pub async fn run_with<T, R, F, Fut>(&self, is_retryable: R, mut operation: F) -> Result<T>
+    where
+        R: Fn(&anyhow::Error) -> bool,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match operation().await {
+                Err(error) if retry < self.max_retries && is_retryable(&error) => {
+                    let delay = self.delay(retry);
+                    warn!(
+                        "Retrying after {}ms ({}/{}): {:?}",
+                        delay.as_millis(),
+                        retry + 1,
+                        self.max_retries,
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn retry() -> DeadlockRetry {
+        DeadlockRetry {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_with_jitter() {
+        let retry = DeadlockRetry {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+
+        for (attempt, backoff) in [(0, 100), (1, 200), (2, 400)] {
+            let delay = retry.delay(attempt).as_millis();
+            assert!((backoff..backoff + 100).contains(&delay));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_error_is_not_retried() {
+        let attempts = &AtomicUsize::new(0);
+
+        let result: Result<()> = retry()
+            .run(move || async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow!("duplicate key value violates unique constraint"))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retryable_error_is_retried_up_to_max_retries() {
+        let attempts = &AtomicUsize::new(0);
+
+        let result: Result<()> = retry()
+            .run_with(
+                |_| true,
+                move || async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(anyhow!("deadlock detected"))
+                },
+            )
+            .await;
+
+        assert_eq!(result.unwrap_err().to_string(), "deadlock detected");
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_retryable_error_is_retried_until_success() {
+        let attempts = &AtomicUsize::new(0);
+
+        let result = retry()
+            .run_with(
+                |error| error.to_string() == "deadlock detected",
+                move || async move {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => Err(anyhow!("deadlock detected")),
+                        attempt => Ok(attempt),
+                    }
+                },
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::postgres_operator::PostgresOperator;
+
+/// Represents a column expected to be filled by its default, which holds NULL values in the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingDefault {
+    pub column: String,
+    pub null_rows: i64,
+}
+
+/// Verifies that the columns expected to be filled by their default, as they are omitted by the
+/// CDC inserts, hold no NULL values in the target, e.g. to catch a misconfigured default.
+///
+/// # Arguments
+///
+/// * `postgres_operator` - The operator of the target database.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+/// * `columns` - The columns expected to be filled by their default.
+///
+/// # Returns
+///
+/// The columns that hold NULL values, with the number of such rows.
+pub async fn verify_defaults(
+    postgres_operator: &(impl PostgresOperator + Sync),
+    schema_name: &str,
+    table_name: &str,
+    columns: &[String],
+) -> Result<Vec<MissingDefault>> {
+    let mut missing_defaults = Vec::new();
+    for column in columns {
+        let null_rows = postgres_operator
+            .count_null_values(schema_name, table_name, column)
+            .await?;
+        if null_rows > 0 {
+            warn!(
+                "Column {} of table {}.{} is NULL in {} rows instead of its default",
+                column, schema_name, table_name, null_rows
+            );
+            missing_defaults.push(MissingDefault {
+                column: column.clone(),
+                null_rows,
+            });
+        }
+    }
+
+    Ok(missing_defaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::copy_loader::InsertMethod;
+    use crate::postgres::postgres_operator::{InsertDataframePayload, MockPostgresOperator};
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::staging_load::LoadMode;
+    use crate::postgres::test_support::test_pool;
+    use deadpool_postgres::GenericClient;
+    use polars::prelude::*;
+
+    #[tokio::test]
+    async fn test_null_column_fails_verification() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_count_null_values()
+            .returning(|_, _, column_name| Ok(if column_name == "status" { 0 } else { 2 }));
+
+        let missing_defaults = verify_defaults(
+            &postgres_operator,
+            "public",
+            "users",
+            &["status".to_string(), "created_at".to_string()],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            missing_defaults,
+            vec![MissingDefault {
+                column: "created_at".to_string(),
+                null_rows: 2,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_partial_insert_is_filled_by_default() {
+        let pool = test_pool("defaults").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE defaults.users (
+                    id int PRIMARY KEY,
+                    name text,
+                    status text DEFAULT 'active',
+                    nickname text
+                );",
+            )
+            .await
+            .unwrap();
+
+        let df = df!(
+            "Op" => &["I"],
+            "_dms_ingestion_timestamp" => &["2024-01-01 00:00:01"],
+            "id" => &[1i32],
+            "name" => &["a"]
+        )
+        .unwrap();
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+        postgres_operator
+            .insert_dataframe_in_target_db(
+                &df,
+                &InsertDataframePayload {
+                    database_name: "mydb".to_string(),
+                    schema_name: "defaults".to_string(),
+                    table_name: "users".to_string(),
+                    insert_method: InsertMethod::default(),
+                    load_mode: LoadMode::default(),
+                    copy_commit_rows: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        let status: String = client
+            .query_one("SELECT status FROM defaults.users WHERE id = 1", &[])
+            .await
+            .unwrap()
+            .get("status");
+        assert_eq!(status, "active");
+
+        let missing_defaults = verify_defaults(
+            &postgres_operator,
+            "defaults",
+            "users",
+            &["status".to_string(), "nickname".to_string()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            missing_defaults,
+            vec![MissingDefault {
+                column: "nickname".to_string(),
+                null_rows: 1,
+            }]
+        );
+    }
+}
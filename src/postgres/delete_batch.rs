@@ -0,0 +1,180 @@
+use anyhow::Result;
+use deadpool_postgres::tokio_postgres::types::ToSql;
+use deadpool_postgres::GenericClient;
+use log::debug;
+use polars::prelude::*;
+
+use super::postgres_operator::UpsertDataframePayload;
+use super::query_dataframe::QueryParam;
+use super::table_query::TableQuery;
+
+/// Represents the deletes of a DataFrame collected to be applied with a single statement per
+/// batch, instead of one statement per key.
+///
+/// The keys are bound as parameters, so the primary key columns must be of boolean, integer,
+/// float or text types. Since a batch reports only the total number of deleted rows, the
+/// unmatched delete policy applies to a batch that matched no row at all.
+pub(crate) struct DeleteBatch<'a> {
+    payload: &'a UpsertDataframePayload,
+    primary_key: &'a [String],
+    batch_size: usize,
+    keys: Vec<QueryParam>,
+    primary_key_values: Vec<String>,
+}
+
+impl<'a> DeleteBatch<'a> {
+    pub fn new(
+        payload: &'a UpsertDataframePayload,
+        primary_key: &'a [String],
+        batch_size: usize,
+    ) -> Self {
+        DeleteBatch {
+            payload,
+            primary_key,
+            batch_size: batch_size.max(1),
+            keys: Vec::new(),
+            primary_key_values: Vec::new(),
+        }
+    }
+
+    /// Adds the key of a delete row to the batch, and applies the batch once it is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to use for the delete.
+    /// * `df` - The DataFrame of the CDC file.
+    /// * `row` - The index of the delete row.
+    /// * `primary_key_value` - The values of the primary key columns of the row, as text.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn push(
+        &mut self,
+        client: &impl GenericClient,
+        df: &DataFrame,
+        row: usize,
+        primary_key_value: Vec<String>,
+    ) -> Result<()> {
+        for column in self.primary_key {
+            self.keys
+                .push(QueryParam::from(&df.column(column)?.get(row)?));
+        }
+        self.primary_key_values.push(primary_key_value.join(","));
+
+        if self.primary_key_values.len() >= self.batch_size {
+            self.flush(client).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the collected deletes, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to use for the delete.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn flush(&mut self, client: &impl GenericClient) -> Result<()> {
+        if self.primary_key_values.is_empty() {
+            return Ok(());
+        }
+
+        let query = TableQuery::DeleteRowsBatch(
+            self.payload.schema_name.clone(),
+            self.payload.table_name.clone(),
+            self.primary_key.to_vec(),
+            self.primary_key_values.len(),
+        );
+        debug!("Query: {}", query);
+
+        let params = self
+            .keys
+            .iter()
+            .map(|param| param as &(dyn ToSql + Sync))
+            .collect::<Vec<_>>();
+        let rows_deleted = client.execute(&query.to_string(), &params).await?;
+        debug!(
+            "Deleted {} rows for {} keys",
+            rows_deleted,
+            self.primary_key_values.len()
+        );
+
+        self.payload.unmatched_delete_policy.on_delete(
+            &self.payload.schema_name,
+            &self.payload.table_name,
+            &self.primary_key_values.join(";"),
+            rows_deleted,
+        );
+
+        self.keys.clear();
+        self.primary_key_values.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::conflict_behavior::ConflictBehavior;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::postgres_operator_impl::upsert_dataframe_with_client;
+    use crate::postgres::test_support::test_pool;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_batched_delete_of_multiple_keys() {
+        let pool = test_pool("delete_batch").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE delete_batch.users (
+                    tenant_id int, id bigint, name text, PRIMARY KEY (tenant_id, id)
+                );
+                INSERT INTO delete_batch.users VALUES
+                    (1, 1, 'a'), (1, 2, 'b'), (2, 1, 'c'), (2, 2, 'd'), (2, 3, 'e');",
+            )
+            .await
+            .unwrap();
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["D", "D", "D", "I", "D"]),
+            Series::new("_dms_ingestion_timestamp", &["", "", "", "", ""]),
+            Series::new("tenant_id", &[1, 1, 2, 3, 2]),
+            Series::new("id", &[1i64, 2, 1, 1, 3]),
+            Series::new("name", &["a", "b", "c", "f", "e"]),
+        ])
+        .unwrap();
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "delete_batch".to_string(),
+            table_name: "users".to_string(),
+            primary_key: "tenant_id,id".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: Some(2),
+            deferred_constraints: false,
+        };
+        upsert_dataframe_with_client(&client, &df, &payload)
+            .await
+            .unwrap();
+
+        let rows = client
+            .query(
+                "SELECT tenant_id, id FROM delete_batch.users ORDER BY tenant_id, id",
+                &[],
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| (row.get::<_, i32>(0), row.get::<_, i64>(1)))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![(2, 2), (3, 1)]);
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use polars::prelude::*;
+
+/// Represents how a delete row missing a primary key column, or with a null one, is handled,
+/// since it cannot be applied without deleting the wrong rows.
+///
+/// The policy can be one of the following:
+///
+/// * Skip - A warning is logged and the delete is skipped.
+/// * Error - The apply fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IncompleteDeletePolicy {
+    #[default]
+    Skip,
+    Error,
+}
+
+impl IncompleteDeletePolicy {
+    /// Gets the primary key value of a delete row, checking that all the primary key columns
+    /// are present and non-null.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame of the CDC file.
+    /// * `row` - The index of the delete row.
+    /// * `primary_key` - The primary key columns.
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// The values of the primary key columns, in the same order, `None` if the delete is
+    /// skipped, or an error if the delete is incomplete and the policy is `Error`.
+    pub fn primary_key_value(
+        &self,
+        df: &DataFrame,
+        row: usize,
+        primary_key: &[String],
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Option<Vec<String>>> {
+        let values = primary_key
+            .iter()
+            .map(|column| {
+                df.column(column)
+                    .ok()
+                    .and_then(|series| series.get(row).ok())
+                    .filter(|value| !matches!(value, AnyValue::Null))
+                    .map(|value| value.to_string())
+            })
+            .collect::<Vec<_>>();
+
+        if values.iter().all(Option::is_some) {
+            return Ok(Some(values.into_iter().flatten().collect()));
+        }
+
+        let key = primary_key
+            .iter()
+            .zip(values.iter())
+            .map(|(column, value)| format!("{}={}", column, value.as_deref().unwrap_or("null")))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self {
+            IncompleteDeletePolicy::Skip => {
+                warn!("Skipping delete of ({key}) in table {schema_name}.{table_name}, as its primary key is incomplete");
+                Ok(None)
+            }
+            IncompleteDeletePolicy::Error => Err(anyhow!(
+                "Delete of ({}) in table {}.{} has an incomplete primary key",
+                key,
+                schema_name,
+                table_name
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn primary_key() -> Vec<String> {
+        vec!["tenant_id".to_string(), "id".to_string()]
+    }
+
+    fn deletes() -> DataFrame {
+        DataFrame::new(vec![
+            Series::new("Op", &["D", "D"]),
+            Series::new("tenant_id", &[Some(7), None]),
+            Series::new("id", &[1, 2]),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_complete_delete() {
+        for policy in [IncompleteDeletePolicy::Skip, IncompleteDeletePolicy::Error] {
+            let value = policy
+                .primary_key_value(&deletes(), 0, &primary_key(), "public", "users")
+                .unwrap();
+
+            assert_eq!(value, Some(vec!["7".to_string(), "1".to_string()]));
+        }
+    }
+
+    #[test]
+    fn test_skip_incomplete_delete() {
+        let policy = IncompleteDeletePolicy::Skip;
+
+        let null_key = policy
+            .primary_key_value(&deletes(), 1, &primary_key(), "public", "users")
+            .unwrap();
+        let missing_column = policy
+            .primary_key_value(
+                &deletes().drop("id").unwrap(),
+                0,
+                &primary_key(),
+                "public",
+                "users",
+            )
+            .unwrap();
+
+        assert_eq!(null_key, None);
+        assert_eq!(missing_column, None);
+    }
+
+    #[test]
+    fn test_error_on_incomplete_delete() {
+        let policy = IncompleteDeletePolicy::Error;
+
+        let error = policy
+            .primary_key_value(&deletes(), 1, &primary_key(), "public", "users")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Delete of (tenant_id=null, id=2) in table public.users has an incomplete primary key"
+        );
+
+        let error = policy
+            .primary_key_value(
+                &deletes().drop("id").unwrap(),
+                0,
+                &primary_key(),
+                "public",
+                "users",
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Delete of (tenant_id=7, id=null) in table public.users has an incomplete primary key"
+        );
+    }
+}
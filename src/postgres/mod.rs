@@ -1,9 +1,28 @@
+pub mod conflict_behavior;
+pub mod copy_loader;
+pub mod data_type_mapping;
+pub mod deadlock_retry;
+pub mod default_verification;
+pub mod delete_batch;
+pub mod incomplete_delete;
+pub mod password_provider;
 pub mod postgres_config;
 pub mod postgres_operator;
 pub mod postgres_operator_impl;
 pub mod postgres_row_struct;
+pub mod provisioning;
+pub mod query_dataframe;
+pub mod referential_integrity;
+pub mod schema_diff;
+pub mod staging_load;
 pub mod table_mode;
 pub mod table_query;
+pub mod temp_table_session;
+pub mod unique_key_fallback;
+pub mod unmatched_delete;
+pub mod upsert_verification;
 
 #[cfg(test)]
 mod postgres_operator_tests;
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,41 @@
+use deadpool_postgres::tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use deadpool_postgres::tokio_postgres::{Client, Config as PgConfig, Error, Socket};
+use deadpool_postgres::{ConfigConnectImpl, Connect};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Represents a provider of the current password of the database, e.g. fetched from a
+/// secrets manager, for passwords that rotate during a long-running validation.
+pub type PasswordProvider = Arc<dyn Fn() -> BoxFuture<'static, String> + Send + Sync>;
+
+/// Represents a connector that fetches a fresh password from the provider for every new
+/// connection of the pool, while the connections already open are not affected.
+pub(crate) struct PasswordProviderConnect<T> {
+    pub password_provider: PasswordProvider,
+    pub tls: T,
+}
+
+impl<T> Connect for PasswordProviderConnect<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + Sync + Send + 'static,
+    T::Stream: Sync + Send,
+    T::TlsConnect: Sync + Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    fn connect(
+        &self,
+        pg_config: &PgConfig,
+    ) -> BoxFuture<'_, Result<(Client, JoinHandle<()>), Error>> {
+        let mut pg_config = pg_config.clone();
+        let connect = ConfigConnectImpl {
+            tls: self.tls.clone(),
+        };
+
+        Box::pin(async move {
+            let password = (self.password_provider)().await;
+            pg_config.password(password);
+            connect.connect(&pg_config).await
+        })
+    }
+}
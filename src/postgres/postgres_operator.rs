@@ -1,4 +1,12 @@
+use crate::dataframe::min_max::MinMax;
+use crate::postgres::conflict_behavior::ConflictBehavior;
+use crate::postgres::copy_loader::InsertMethod;
+use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+use crate::postgres::provisioning::ForeignKey;
+use crate::postgres::query_dataframe::QueryParam;
+use crate::postgres::staging_load::LoadMode;
 use crate::postgres::table_mode::TableMode;
+use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -10,6 +18,9 @@ pub struct InsertDataframePayload {
     pub database_name: String,
     pub schema_name: String,
     pub table_name: String,
+    pub insert_method: InsertMethod,
+    pub load_mode: LoadMode,
+    pub copy_commit_rows: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -18,6 +29,11 @@ pub struct UpsertDataframePayload {
     pub schema_name: String,
     pub table_name: String,
     pub primary_key: String,
+    pub conflict_behavior: ConflictBehavior,
+    pub unmatched_delete_policy: UnmatchedDeletePolicy,
+    pub incomplete_delete_policy: IncompleteDeletePolicy,
+    pub delete_batch_size: Option<usize>,
+    pub deferred_constraints: bool,
 }
 
 #[cfg_attr(test, automock)]
@@ -32,7 +48,9 @@ pub trait PostgresOperator {
     ///
     /// # Returns
     ///
-    /// A IndexMap containing the column names and their data types.
+    /// A IndexMap containing the column names and their data types. Numeric columns with a
+    /// precision and scale are reported as e.g. `numeric(10,2)`. Columns of a domain type are
+    /// reported with the base type of the domain, so that the table can be created without it.
     async fn get_table_columns(
         &self,
         schema_name: &str,
@@ -48,9 +66,36 @@ pub trait PostgresOperator {
     ///
     /// # Returns
     ///
-    /// The primary key of the table.
+    /// The primary key columns of the table, in the order of the constraint. If the table has no
+    /// primary key, the columns of a unique key may be returned instead, based on the configured
+    /// fallback.
     async fn get_primary_key(&self, table_name: &str, schema_name: &str) -> Result<Vec<String>>;
 
+    /// Get the foreign keys of the tables in a schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    ///
+    /// # Returns
+    ///
+    /// The foreign keys of the tables in the schema, one per constraint.
+    async fn get_foreign_keys(&self, schema_name: &str) -> Result<Vec<ForeignKey>>;
+
+    /// Count the rows of a table that reference a missing row through a foreign key,
+    /// whether the foreign key is enforced or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of both the table and the referenced table.
+    /// * `foreign_key` - The foreign key, with the names of the tables and of their columns.
+    ///
+    /// # Returns
+    ///
+    /// The number of orphaned rows.
+    async fn count_orphaned_rows(&self, schema_name: &str, foreign_key: &ForeignKey)
+        -> Result<i64>;
+
     /// Create a schema in the target database.
     ///
     /// # Arguments
@@ -62,14 +107,91 @@ pub trait PostgresOperator {
     /// A Result indicating success or failure.
     async fn create_schema(&self, schema_name: &str) -> Result<()>;
 
+    /// Check if a schema exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    ///
+    /// # Returns
+    ///
+    /// Whether the schema exists.
+    async fn schema_exists(&self, schema_name: &str) -> Result<bool>;
+
+    /// Advance the sequence owned by a column, e.g. a serial or identity column, past the
+    /// maximum value of the column, so that later inserts do not collide with loaded rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    ///
+    /// # Returns
+    ///
+    /// The current value of the sequence, or None if the column owns no sequence.
+    async fn advance_sequence(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<Option<i64>>;
+
+    /// Check if a table exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// Whether the table exists.
+    async fn table_exists(&self, schema_name: &str, table_name: &str) -> Result<bool>;
+
+    /// Count the rows of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows of the table.
+    async fn count_rows(&self, schema_name: &str, table_name: &str) -> Result<i64>;
+
+    /// Find the columns of a row of a table that do not hold the expected values.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `values` - The columns and their expected values, as SQL literals.
+    /// * `primary_key` - The primary key columns and the values of the row, as SQL literals.
+    ///
+    /// # Returns
+    ///
+    /// The columns that do not hold their expected value, or None if the row does not exist.
+    async fn find_unapplied_columns(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        values: &[(String, String)],
+        primary_key: &[(String, String)],
+    ) -> Result<Option<Vec<String>>>;
+
     /// Create a table in the target database.
     ///
     /// # Arguments
     ///
     /// * `column_data_types` - The data types of the columns in the table.
-    /// * `primary_key` - The primary key of the table.
+    /// * `primary_key` - The primary key columns of the table, rendered in this exact order
+    ///   regardless of the order of the columns.
     /// * `schema_name` - The name of the schema.
     /// * `table_name` - The name of the table.
+    /// * `storage_parameters` - The storage parameters of the table, e.g. `fillfactor`,
+    ///   rendered in a WITH clause if any.
     ///
     /// # Returns
     ///
@@ -80,6 +202,27 @@ pub trait PostgresOperator {
         primary_key: &[String],
         schema_name: &str,
         table_name: &str,
+        storage_parameters: &indexmap::IndexMap<String, String>,
+    ) -> Result<()>;
+
+    /// Add a column to a table, if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    /// * `data_type` - The data type of the column.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    async fn add_column(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        data_type: &str,
     ) -> Result<()>;
 
     /// Get the tables in a schema.
@@ -139,6 +282,138 @@ pub trait PostgresOperator {
         payload: &UpsertDataframePayload,
     ) -> Result<()>;
 
+    /// Rename columns of a table, e.g. to keep the columns added by DMS under cleaner names.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `renames` - The current column names and their new names.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure. Fails without renaming any column
+    /// if a column does not exist or a new name is already taken.
+    async fn rename_columns(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        renames: &indexmap::IndexMap<String, String>,
+    ) -> Result<()>;
+
+    /// Get the minimum and maximum value of a column, e.g. to check the date range of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    ///
+    /// # Returns
+    ///
+    /// The minimum and maximum value of the column, as text, or None if the column has only nulls.
+    async fn get_min_max(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<MinMax>;
+
+    /// Count the distinct primary keys of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `key_columns` - The primary key columns.
+    ///
+    /// # Returns
+    ///
+    /// The number of distinct primary keys of the table.
+    async fn count_distinct_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<i64>;
+
+    /// Count the rows of a table where a column is NULL.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows where the column is NULL.
+    async fn count_null_values(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<i64>;
+
+    /// Get the distinct values of a low-cardinality column, e.g. a status or a type.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    /// * `max_distinct_values` - The maximum number of distinct values, above which the column
+    ///   is considered of high cardinality.
+    ///
+    /// # Returns
+    ///
+    /// The distinct non-null values of the column, as text, or an error if there are more than
+    /// `max_distinct_values`.
+    async fn get_distinct_values(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        max_distinct_values: usize,
+    ) -> Result<std::collections::BTreeSet<String>>;
+
+    /// Get the histogram of a numeric column, with equal-width buckets between the minimum and
+    /// the maximum value of the column.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column.
+    /// * `buckets` - The number of buckets.
+    ///
+    /// # Returns
+    ///
+    /// The number of non-null values in each bucket, in the order of the buckets.
+    async fn get_histogram(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        buckets: usize,
+    ) -> Result<Vec<i64>>;
+
+    /// Run a read-only validation query and materialize its result set into a DataFrame.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - The query, which must be a single SELECT statement.
+    /// * `params` - The parameters of the query, bound to `$1`, `$2` etc.
+    ///
+    /// # Returns
+    ///
+    /// A DataFrame with the result set of the query.
+    async fn query_to_dataframe(
+        &self,
+        sql: &str,
+        params: &[QueryParam],
+    ) -> Result<polars::frame::DataFrame>;
+
     /// Drop schema in the target database.
     ///
     /// # Arguments
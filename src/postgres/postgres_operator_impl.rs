@@ -1,15 +1,17 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use deadpool_postgres::{GenericClient, Pool};
+use deadpool_postgres::tokio_postgres::types::ToSql;
+use deadpool_postgres::{Client, GenericClient, Pool};
 use indexmap::IndexMap;
-use log::{debug, error};
+use log::{debug, error, warn};
 use polars::prelude::*;
 
 use polars_core::export::num::ToPrimitive;
 
+use std::collections::BTreeSet;
 use std::{fmt::Display, time::Instant};
 
-use tracing::info;
+use tracing::{info, Instrument};
 use TableQuery::*;
 
 pub(crate) use super::postgres_operator::PostgresOperator;
@@ -18,8 +20,22 @@ use super::{
     table_query::TableQuery,
 };
 
+use crate::dataframe::min_max::MinMax;
+use crate::postgres::conflict_behavior::ConflictBehavior;
+use crate::postgres::copy_loader::{
+    copy_dataframe_in_commits, copy_dataframe_with_client, InsertMethod,
+};
+use crate::postgres::deadlock_retry::DeadlockRetry;
+use crate::postgres::delete_batch::DeleteBatch;
 use crate::postgres::postgres_row_struct::RowStruct;
+use crate::postgres::provisioning::ForeignKey;
+use crate::postgres::query_dataframe::{check_read_only_query, rows_to_dataframe, QueryParam};
+use crate::postgres::schema_diff::check_column_renames;
+use crate::postgres::staging_load::{insert_ignoring_duplicates_with_client, LoadMode};
 use crate::postgres::table_mode::TableMode;
+use crate::postgres::temp_table_session::TempTableSession;
+use crate::postgres::unique_key_fallback::{UniqueKey, UniqueKeyFallback};
+use crate::telemetry::operation_span;
 
 /// Represents the data type of a column in a table.
 enum ColumnDataType {
@@ -27,6 +43,35 @@ enum ColumnDataType {
     Rest(String),
 }
 
+impl ColumnDataType {
+    /// Maps a column as reported by information_schema.columns to its data type.
+    /// The columns of a domain are reported with the base type of the domain.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_type` - The data_type of the column.
+    /// * `numeric_precision` - The numeric_precision of the column.
+    /// * `numeric_scale` - The numeric_scale of the column.
+    ///
+    /// # Returns
+    ///
+    /// The data type of the column.
+    fn from_information_schema(
+        data_type: String,
+        numeric_precision: Option<i32>,
+        numeric_scale: Option<i32>,
+    ) -> Self {
+        match (data_type.as_str(), numeric_precision, numeric_scale) {
+            ("ARRAY", _, _) => ColumnDataType::Array,
+            // Keep the precision and scale, since values beyond the scale are rounded
+            ("numeric", Some(precision), Some(scale)) => {
+                ColumnDataType::Rest(format!("numeric({precision},{scale})"))
+            }
+            _ => ColumnDataType::Rest(data_type),
+        }
+    }
+}
+
 impl Display for ColumnDataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -38,11 +83,48 @@ impl Display for ColumnDataType {
 
 pub struct PostgresOperatorImpl {
     db_client: Pool,
+    catalog: Option<String>,
+    unique_key_fallback: UniqueKeyFallback,
+    deadlock_retry: DeadlockRetry,
 }
 
 impl PostgresOperatorImpl {
     pub fn new(db_client: Pool) -> Self {
-        Self { db_client }
+        Self {
+            db_client,
+            catalog: None,
+            unique_key_fallback: UniqueKeyFallback::default(),
+            deadlock_retry: DeadlockRetry::default(),
+        }
+    }
+
+    /// Sets the unique key used as the primary key of the tables without a primary key.
+    pub fn with_unique_key_fallback(mut self, unique_key_fallback: UniqueKeyFallback) -> Self {
+        self.unique_key_fallback = unique_key_fallback;
+        self
+    }
+
+    /// Sets the retries of the upsert of a file that fails with a deadlock or a serialization failure.
+    pub fn with_deadlock_retry(mut self, deadlock_retry: DeadlockRetry) -> Self {
+        self.deadlock_retry = deadlock_retry;
+        self
+    }
+
+    /// Sets the catalog to scope the information_schema queries with, or the current database if None.
+    pub fn with_catalog(mut self, catalog: impl Into<Option<String>>) -> Self {
+        self.catalog = catalog.into();
+        self
+    }
+
+    /// Pins a client of the pool for a session with temporary tables.
+    ///
+    /// # Returns
+    ///
+    /// A session pinned to a single client.
+    pub async fn temp_table_session(&self) -> Result<TempTableSession> {
+        let client = self.db_client.get().await?;
+
+        Ok(TempTableSession::new(client))
     }
 }
 
@@ -54,7 +136,11 @@ impl PostgresOperator for PostgresOperatorImpl {
         table_name: &str,
     ) -> Result<IndexMap<String, String>> {
         // Prepare the query to get all columns for a table
-        let query = FindAllColumns(schema_name.to_string(), table_name.to_string());
+        let query = FindAllColumns(
+            schema_name.to_string(),
+            table_name.to_string(),
+            self.catalog.clone(),
+        );
 
         // Fetch columns for the table
         let client = self.db_client.get().await?;
@@ -64,11 +150,17 @@ impl PostgresOperator for PostgresOperatorImpl {
         for row in rows {
             let column_name: String = row.get("column_name");
             let data_type: String = row.get("data_type");
-            if data_type.eq("ARRAY") {
-                res.insert(column_name, ColumnDataType::Array.to_string());
-            } else {
-                res.insert(column_name, ColumnDataType::Rest(data_type).to_string());
-            }
+            let numeric_precision: Option<i32> = row.get("numeric_precision");
+            let numeric_scale: Option<i32> = row.get("numeric_scale");
+            res.insert(
+                column_name,
+                ColumnDataType::from_information_schema(
+                    data_type,
+                    numeric_precision,
+                    numeric_scale,
+                )
+                .to_string(),
+            );
         }
 
         Ok(res)
@@ -90,8 +182,88 @@ impl PostgresOperator for PostgresOperatorImpl {
             .iter()
             .map(|row| row.get("attname"))
             .collect::<Vec<String>>();
+        if !primary_key_list.is_empty() || self.unique_key_fallback == UniqueKeyFallback::Disabled {
+            return Ok(primary_key_list);
+        }
+
+        // Fall back to a unique key of the table
+        let query = FindUniqueKeys(table_name.to_string(), schema_name.to_string());
+        let unique_keys = client
+            .query(&query.to_string(), &[])
+            .await?
+            .iter()
+            .map(|row| UniqueKey {
+                name: row.get("key_name"),
+                columns: row.get("columns"),
+            })
+            .collect::<Vec<_>>();
 
-        Ok(primary_key_list)
+        match self.unique_key_fallback.select(unique_keys) {
+            Some(unique_key) => {
+                warn!(
+                    "Table {}.{} has no primary key, using unique key {} ({})",
+                    schema_name,
+                    table_name,
+                    unique_key.name,
+                    unique_key.columns.join(",")
+                );
+                Ok(unique_key.columns)
+            }
+            None => {
+                warn!(
+                    "Table {}.{} has no primary key nor a matching unique key",
+                    schema_name, table_name
+                );
+                Ok(primary_key_list)
+            }
+        }
+    }
+
+    async fn get_foreign_keys(&self, schema_name: &str) -> Result<Vec<ForeignKey>> {
+        // Prepare the query to get the foreign keys of the tables in a schema
+        let query = FindForeignKeys(schema_name.to_string());
+        debug!("Query: {}", query);
+
+        let client = self.db_client.get().await?;
+        let rows = client.query(&query.to_string(), &[]).await?;
+
+        let foreign_keys = rows
+            .iter()
+            .map(|row| ForeignKey {
+                schema_name: row.get("schema_name"),
+                table_name: row.get("table_name"),
+                referenced_schema_name: row.get("referenced_schema_name"),
+                referenced_table_name: row.get("referenced_table_name"),
+                columns: row.get("columns"),
+                referenced_columns: row.get("referenced_columns"),
+            })
+            .collect();
+
+        Ok(foreign_keys)
+    }
+
+    async fn count_orphaned_rows(
+        &self,
+        schema_name: &str,
+        foreign_key: &ForeignKey,
+    ) -> Result<i64> {
+        let query = CountOrphanedRows(
+            schema_name.to_string(),
+            foreign_key.table_name.clone(),
+            foreign_key.referenced_table_name.clone(),
+            foreign_key
+                .columns
+                .iter()
+                .cloned()
+                .zip(foreign_key.referenced_columns.iter().cloned())
+                .collect(),
+        );
+        debug!("Query: {}", query);
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("orphaned_rows"))
     }
 
     async fn create_schema(&self, schema_name: &str) -> Result<()> {
@@ -107,6 +279,96 @@ impl PostgresOperator for PostgresOperatorImpl {
         Ok(())
     }
 
+    async fn schema_exists(&self, schema_name: &str) -> Result<bool> {
+        // Prepare the query to check if a schema exists
+        let query = SchemaExists(schema_name.to_string());
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("schema_exists"))
+    }
+
+    async fn advance_sequence(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<Option<i64>> {
+        // Prepare the query to find the sequence owned by the column
+        let query = FindSerialSequence(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+        );
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+        let Some(sequence_name) = row.get::<_, Option<String>>("sequence_name") else {
+            return Ok(None);
+        };
+
+        let query = SetSequence(
+            sequence_name,
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+        );
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(Some(row.get("sequence_value")))
+    }
+
+    async fn table_exists(&self, schema_name: &str, table_name: &str) -> Result<bool> {
+        // Prepare the query to check if a table exists
+        let query = TableExists(schema_name.to_string(), table_name.to_string());
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("table_exists"))
+    }
+
+    async fn count_rows(&self, schema_name: &str, table_name: &str) -> Result<i64> {
+        // Prepare the query to count the rows of a table
+        let query = CountRows(schema_name.to_string(), table_name.to_string());
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("row_count"))
+    }
+
+    async fn find_unapplied_columns(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        values: &[(String, String)],
+        primary_key: &[(String, String)],
+    ) -> Result<Option<Vec<String>>> {
+        // Prepare the query to compare each column of the row with its expected value
+        let query = VerifyRow(
+            schema_name.to_string(),
+            table_name.to_string(),
+            values.to_vec(),
+            primary_key.to_vec(),
+        );
+
+        let client = self.db_client.get().await?;
+        let Some(row) = client.query_opt(&query.to_string(), &[]).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            row.columns()
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !row.get::<_, bool>(*index))
+                .map(|(_, column)| column.name().to_string())
+                .collect(),
+        ))
+    }
+
     async fn get_tables_in_schema(
         &self,
         schema_name: &str,
@@ -138,7 +400,7 @@ impl PostgresOperator for PostgresOperatorImpl {
             TableMode::AllTables => "".to_string(),
         };
 
-        let query = FindTablesForSchema(schema_name.to_string(), subquery);
+        let query = FindTablesForSchema(schema_name.to_string(), subquery, self.catalog.clone());
 
         let client = self.db_client.get().await?;
         let rows = client
@@ -159,13 +421,15 @@ impl PostgresOperator for PostgresOperatorImpl {
         primary_keys: &[String],
         schema_name: &str,
         table_name: &str,
+        storage_parameters: &IndexMap<String, String>,
     ) -> Result<()> {
         // Prepare the query to create a table
         let query = CreateTable(
             schema_name.to_string(),
             table_name.to_string(),
             column_data_types.clone(),
-            primary_keys.join(","),
+            primary_keys.to_vec(),
+            storage_parameters.clone(),
         );
 
         let client = self.db_client.get().await?;
@@ -177,6 +441,194 @@ impl PostgresOperator for PostgresOperatorImpl {
         Ok(())
     }
 
+    async fn add_column(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        data_type: &str,
+    ) -> Result<()> {
+        // Prepare the query to add a column
+        let query = AddColumn(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+            data_type.to_string(),
+        );
+        debug!("Query: {}", query);
+
+        let client = self.db_client.get().await?;
+        client.execute(&query.to_string(), &[]).await?;
+
+        Ok(())
+    }
+
+    async fn rename_columns(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        renames: &IndexMap<String, String>,
+    ) -> Result<()> {
+        let table_columns = self.get_table_columns(schema_name, table_name).await?;
+        check_column_renames(&table_columns, renames)?;
+
+        let mut client = self.db_client.get().await?;
+        let transaction = client.transaction().await?;
+        for (from, to) in renames {
+            // Prepare the query to rename a column
+            let query = RenameColumn(
+                schema_name.to_string(),
+                table_name.to_string(),
+                from.clone(),
+                to.clone(),
+            );
+            debug!("Query: {}", query);
+
+            transaction.execute(&query.to_string(), &[]).await?;
+        }
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_min_max(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<MinMax> {
+        // Prepare the query to get the minimum and maximum value of a column
+        let query = TableQuery::MinMax(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+        );
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(MinMax {
+            min: row.get("min_value"),
+            max: row.get("max_value"),
+        })
+    }
+
+    async fn count_distinct_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        key_columns: &[String],
+    ) -> Result<i64> {
+        // Prepare the query to count the distinct primary keys of a table
+        let query = CountDistinctKeys(
+            schema_name.to_string(),
+            table_name.to_string(),
+            key_columns.to_vec(),
+        );
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("key_count"))
+    }
+
+    async fn count_null_values(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<i64> {
+        // Prepare the query to count the rows where a column is NULL
+        let query = CountNullValues(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+        );
+
+        let client = self.db_client.get().await?;
+        let row = client.query_one(&query.to_string(), &[]).await?;
+
+        Ok(row.get("null_count"))
+    }
+
+    async fn get_distinct_values(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        max_distinct_values: usize,
+    ) -> Result<BTreeSet<String>> {
+        // Prepare the query to get the distinct values of a column, fetching one more
+        // than the maximum to detect a high-cardinality column without reading all of them
+        let query = DistinctValues(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+            max_distinct_values + 1,
+        );
+
+        let client = self.db_client.get().await?;
+        let rows = client.query(&query.to_string(), &[]).await?;
+        if rows.len() > max_distinct_values {
+            return Err(anyhow!(
+                "Column {} has more than {} distinct values",
+                column_name,
+                max_distinct_values
+            ));
+        }
+
+        Ok(rows.iter().map(|row| row.get("value")).collect())
+    }
+
+    async fn get_histogram(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        column_name: &str,
+        buckets: usize,
+    ) -> Result<Vec<i64>> {
+        if buckets == 0 {
+            return Err(anyhow!("A histogram requires at least one bucket"));
+        }
+
+        // Prepare the query to count the values of a column per bucket
+        let query = Histogram(
+            schema_name.to_string(),
+            table_name.to_string(),
+            column_name.to_string(),
+            buckets,
+        );
+
+        let client = self.db_client.get().await?;
+        let rows = client.query(&query.to_string(), &[]).await?;
+
+        // Empty buckets are not returned by the query
+        let mut bucket_counts = vec![0; buckets];
+        for row in rows {
+            let bucket: i32 = row.get("bucket");
+            bucket_counts[bucket as usize - 1] = row.get("bucket_count");
+        }
+
+        Ok(bucket_counts)
+    }
+
+    async fn query_to_dataframe(&self, sql: &str, params: &[QueryParam]) -> Result<DataFrame> {
+        check_read_only_query(sql)?;
+
+        let params = params
+            .iter()
+            .map(|param| param as &(dyn ToSql + Sync))
+            .collect::<Vec<_>>();
+
+        // Run the query in a read-only transaction, so that it cannot modify the database
+        let mut client = self.db_client.get().await?;
+        let transaction = client.build_transaction().read_only(true).start().await?;
+        let rows = transaction.query(sql, &params).await?;
+        transaction.rollback().await?;
+
+        rows_to_dataframe(&rows)
+    }
+
     async fn drop_schema(&self, schema_name: &str) -> Result<()> {
         // Prepare the query to drop a schema
         let query = DropSchema(schema_name.to_string());
@@ -203,70 +655,53 @@ impl PostgresOperator for PostgresOperatorImpl {
             .drop_in_place("_dms_ingestion_timestamp")
             .expect("Failed to drop '_dms_ingestion_timestamp' column");
 
-        let column_names = df.get_column_names();
-        let fields = column_names.join(", ");
-
-        let df_height = df.height().to_i64().unwrap();
-
-        info!("Total DF height: {df_height}");
-
-        let insert_by_chunk_start = Instant::now();
         let client = self.db_client.get().await?;
-        let rows_per_df = 10_000;
-        let mut offset = 0i64;
-
-        while offset < df_height {
-            debug!("Inserting rows at offset: {offset}");
-            let df_chunk = df.slice(offset, rows_per_df);
-            let df_chunk_height = df_chunk.height();
-            let df_columns = df_chunk.get_columns();
-
-            let values = (0..df_chunk_height)
-                .map(|row_idx| {
-                    let values = df_columns
-                        .iter()
-                        .map(|column| {
-                            let v = column.get(row_idx).unwrap();
-                            RowStruct::new(&v).displayed()
-                        })
-                        .collect::<Vec<_>>()
-                        .join(", ");
-
-                    format!("({})", values)
-                })
-                .collect::<Vec<String>>()
-                .join(", ");
-
-            let query = format!(
-                "INSERT INTO {schema_name}.{table_name} ({fields}) VALUES {values}",
-                schema_name = payload.schema_name,
-                table_name = payload.table_name,
-            );
-
-            let insert_result = client.execute(query.as_str(), &[]).await;
-
-            match insert_result {
-                Ok(_) => (),
-                Err(e) => {
-                    error!("DF height at point: {df_height}");
-                    error!("DF chunk height at point: {df_chunk_height}");
-                    error!("Offset at point: {offset}");
-                    error!("Failed to insert: {e}");
-                    debug!("Query: {}", query);
-                    error!(
-                        "Failed to insert data into table -> {}: {e}",
-                        payload.table_name
-                    );
+        match payload.load_mode {
+            LoadMode::Direct => match (payload.insert_method, payload.copy_commit_rows) {
+                (InsertMethod::Copy, Some(rows_per_commit)) => {
+                    copy_dataframe_in_commits(
+                        &client,
+                        &df,
+                        &payload.schema_name,
+                        &payload.table_name,
+                        rows_per_commit,
+                        |progress| {
+                            info!(
+                                "Committed chunk {}/{} of table {}, {}/{} rows",
+                                progress.chunk,
+                                progress.chunk_count,
+                                payload.table_name,
+                                progress.rows_committed,
+                                progress.total_rows
+                            )
+                        },
+                    )
+                    .await?;
+                    Ok(())
+                }
+                _ => {
+                    insert_dataframe_with_method(
+                        &client,
+                        &df,
+                        &payload.schema_name,
+                        &payload.table_name,
+                        payload.insert_method,
+                    )
+                    .await
                 }
+            },
+            LoadMode::IgnoreDuplicates => {
+                insert_ignoring_duplicates_with_client(
+                    &client,
+                    &df,
+                    &payload.schema_name,
+                    &payload.table_name,
+                    payload.insert_method,
+                )
+                .await?;
+                Ok(())
             }
-
-            offset += rows_per_df.to_i64().unwrap();
         }
-
-        let insert_by_chunk_duration = insert_by_chunk_start.elapsed().as_millis();
-        info!("Inserting DF by chunk took: {insert_by_chunk_duration}ms");
-
-        Ok(())
     }
 
     async fn upsert_dataframe_in_target_db(
@@ -274,136 +709,559 @@ impl PostgresOperator for PostgresOperatorImpl {
         df: &DataFrame,
         payload: &UpsertDataframePayload,
     ) -> Result<()> {
-        let mut row_values = Vec::new();
-        let mut deleted_row: bool;
-
-        let column_names = df
-            .get_column_names()
-            .into_iter()
-            .filter(|column| {
-                let is_not_op = *column != "Op";
-                let is_not_dms_ingestion_timestamp = *column != "_dms_ingestion_timestamp";
-                is_not_op && is_not_dms_ingestion_timestamp
-            })
-            .collect::<Vec<_>>();
-        let fields = column_names.join(", ");
-        let client = self.db_client.get().await?;
+        let span = operation_span!(
+            "upsert_dataframe",
+            schema = payload.schema_name.as_str(),
+            table = payload.table_name.as_str(),
+            rows = df.height()
+        );
 
-        for row in 0..df.height() {
-            row_values.clear();
-            deleted_row = false;
-
-            let pk_vector = payload
-                .primary_key
-                .split(',')
-                .map(|key| df.column(key).unwrap().get(row).unwrap().to_string())
-                .collect::<Vec<String>>();
-
-            for column in df.get_columns() {
-                // Operation: Delete
-                // Delete the rows where Op="D"
-                let column_name = column.name();
-                let is_op = column_name == "Op";
-
-                let value = column.get(row).unwrap();
-                let is_delete = value.to_string().contains('D');
-                let is_op_and_delete = is_op && is_delete;
-
-                if !is_op_and_delete {
-                    row_values.push(value);
-                    continue;
+        // The whole file is applied in a single transaction, which is applied again on a deadlock
+        // or a serialization failure
+        self.deadlock_retry
+            .run(move || async move {
+                // The unmatched deletes of an attempt are only recorded once it is committed
+                let attempt_payload = UpsertDataframePayload {
+                    database_name: payload.database_name.clone(),
+                    schema_name: payload.schema_name.clone(),
+                    table_name: payload.table_name.clone(),
+                    primary_key: payload.primary_key.clone(),
+                    conflict_behavior: payload.conflict_behavior,
+                    unmatched_delete_policy: payload.unmatched_delete_policy.for_attempt(),
+                    incomplete_delete_policy: payload.incomplete_delete_policy,
+                    delete_batch_size: payload.delete_batch_size,
+                    deferred_constraints: payload.deferred_constraints,
+                };
+
+                let mut client = self.db_client.get().await?;
+                let transaction = client.transaction().await?;
+                // The DEFERRABLE constraints are checked at commit rather than after each statement
+                if payload.deferred_constraints {
+                    transaction
+                        .batch_execute(&DeferConstraints.to_string())
+                        .await?;
                 }
+                upsert_dataframe_with_client(&transaction, df, &attempt_payload).await?;
+                transaction.commit().await?;
+                payload
+                    .unmatched_delete_policy
+                    .commit(&attempt_payload.unmatched_delete_policy);
 
-                let query = DeleteRows(
-                    payload.schema_name.clone(),
-                    payload.table_name.clone(),
-                    payload.primary_key.clone(),
-                    pk_vector.as_slice().join(","),
-                );
-                debug!("Query: {}", query);
+                Ok(())
+            })
+            .instrument(span)
+            .await
+    }
 
-                let query = query.to_string().replace('"', "'");
+    async fn close_connection_pool(&self) {
+        self.db_client.close();
+    }
+}
 
-                client.query(&query, &[]).await.unwrap_or_else(|_| {
-                    panic!(
-                        "Failed to delete rows from table: {schema_name}.{table_name}",
-                        schema_name = payload.schema_name.clone(),
-                        table_name = payload.table_name.clone()
-                    )
-                });
+/// Inserts a DataFrame, without the columns added by DMS, in a table using the given client.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the insert.
+/// * `df` - The DataFrame to insert.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+///
+/// # Returns
+///
+/// A Result indicating success or failure.
+pub(crate) async fn insert_dataframe_with_client(
+    client: &Client,
+    df: &DataFrame,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<()> {
+    let column_names = df.get_column_names();
+    let fields = column_names.join(", ");
+
+    let df_height = df.height().to_i64().unwrap();
+
+    info!("Total DF height: {df_height}");
+
+    let insert_by_chunk_start = Instant::now();
+    let rows_per_df = 10_000;
+    let mut offset = 0i64;
+
+    while offset < df_height {
+        debug!("Inserting rows at offset: {offset}");
+        let df_chunk = df.slice(offset, rows_per_df);
+        let df_chunk_height = df_chunk.height();
+        let df_columns = df_chunk.get_columns();
+
+        let values = (0..df_chunk_height)
+            .map(|row_idx| {
+                let values = df_columns
+                    .iter()
+                    .map(|column| {
+                        let v = column.get(row_idx).unwrap();
+                        RowStruct::new(&v).displayed()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
 
-                deleted_row = true;
-                break;
-            }
+                format!("({})", values)
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
 
-            if deleted_row {
-                debug!("Deleted row");
-                continue;
+        let query = format!("INSERT INTO {schema_name}.{table_name} ({fields}) VALUES {values}");
+
+        let insert_result = client.execute(query.as_str(), &[]).await;
+
+        match insert_result {
+            Ok(_) => (),
+            Err(e) => {
+                error!("DF height at point: {df_height}");
+                error!("DF chunk height at point: {df_chunk_height}");
+                error!("Offset at point: {offset}");
+                error!("Failed to insert: {e}");
+                debug!("Query: {}", query);
+                error!("Failed to insert data into table -> {table_name}: {e}");
             }
+        }
 
-            // Operation: Update
-            let is_update_op = row_values.first().unwrap().to_string().contains('U');
+        offset += rows_per_df.to_i64().unwrap();
+    }
 
-            debug!("Row values: {:?}", row_values);
+    let insert_by_chunk_duration = insert_by_chunk_start.elapsed().as_millis();
+    info!("Inserting DF by chunk took: {insert_by_chunk_duration}ms");
 
-            // Remove the Op and _dms_ingestion_timestamp column from the row values
-            let row_values = row_values.iter().skip(2).collect::<Vec<_>>();
-            let values_of_row = row_values
-                .iter()
-                .map(|v| RowStruct::new(v).displayed())
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            let on_conflict_strategy = if !is_update_op {
-                String::from("")
-            } else {
-                let column_names = column_names
-                    .clone()
-                    .into_iter()
-                    .enumerate()
-                    .map(|(index, column)| {
-                        format!(
-                            "{} = {}",
-                            column,
-                            RowStruct::new(row_values.get(index).unwrap()).displayed()
-                        )
-                    })
-                    .collect::<Vec<_>>();
+    Ok(())
+}
+
+/// Inserts a DataFrame, without the columns added by DMS, in a table using the given client
+/// and insert method.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the insert.
+/// * `df` - The DataFrame to insert.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+/// * `insert_method` - Whether the rows are inserted with INSERT or COPY statements.
+///
+/// # Returns
+///
+/// A Result indicating success or failure.
+pub(crate) async fn insert_dataframe_with_method(
+    client: &Client,
+    df: &DataFrame,
+    schema_name: &str,
+    table_name: &str,
+    insert_method: InsertMethod,
+) -> Result<()> {
+    match insert_method {
+        InsertMethod::Insert => {
+            insert_dataframe_with_client(client, df, schema_name, table_name).await
+        }
+        InsertMethod::Copy => {
+            copy_dataframe_with_client(client, df, schema_name, table_name).await?;
+            Ok(())
+        }
+    }
+}
 
-                // Construct the query, on Conflict, update the row
-                let strategy = format!(" ON CONFLICT ({}) DO UPDATE SET ", payload.primary_key);
-                let concatenated_values = column_names.join(", ");
+/// Upserts a DataFrame in a table using the given client.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the upsert.
+/// * `df` - The DataFrame to upsert.
+/// * `payload` - The payload describing the target table.
+///
+/// # Returns
+///
+/// A Result indicating success or failure.
+pub(crate) async fn upsert_dataframe_with_client(
+    client: &impl GenericClient,
+    df: &DataFrame,
+    payload: &UpsertDataframePayload,
+) -> Result<()> {
+    let mut row_values = Vec::new();
+    let mut deleted_row: bool;
+
+    let column_names = df
+        .get_column_names()
+        .into_iter()
+        .filter(|column| {
+            let is_not_op = *column != "Op";
+            let is_not_dms_ingestion_timestamp = *column != "_dms_ingestion_timestamp";
+            is_not_op && is_not_dms_ingestion_timestamp
+        })
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    let primary_key = payload
+        .primary_key
+        .split(',')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let mut delete_batch = payload
+        .delete_batch_size
+        .map(|batch_size| DeleteBatch::new(payload, &primary_key, batch_size));
+
+    for row in 0..df.height() {
+        row_values.clear();
+        deleted_row = false;
+
+        for column in df.get_columns() {
+            // Operation: Delete
+            // Delete the rows where Op="D"
+            let column_name = column.name();
+            let is_op = column_name == "Op";
+
+            let value = column.get(row).unwrap();
+            let is_delete = value.to_string().contains('D');
+            let is_op_and_delete = is_op && is_delete;
+
+            if !is_op_and_delete {
+                row_values.push(value);
+                continue;
+            }
 
-                format!("{strategy} {concatenated_values}")
+            deleted_row = true;
+            let Some(pk_vector) = payload.incomplete_delete_policy.primary_key_value(
+                df,
+                row,
+                &primary_key,
+                &payload.schema_name,
+                &payload.table_name,
+            )?
+            else {
+                break;
             };
 
-            let query = format!(
-                "INSERT INTO {schema_name}.{table_name} ({fields}) VALUES ({values_of_row});",
-                schema_name = payload.schema_name,
-                table_name = payload.table_name,
-            );
-            let query = format!("{query}{on_conflict_strategy}");
+            if let Some(delete_batch) = delete_batch.as_mut() {
+                delete_batch.push(client, df, row, pk_vector).await?;
+                break;
+            }
 
+            let query = TableQuery::delete_rows(
+                payload.schema_name.as_str(),
+                payload.table_name.as_str(),
+                &primary_key,
+                &pk_vector,
+            )?;
             debug!("Query: {}", query);
 
-            let client = self.db_client.get().await?;
+            let query = query.to_string().replace('"', "'");
 
-            client
-                .execute(query.as_str(), &[])
-                .await
-                .unwrap_or_else(|_| {
-                    panic!(
-                        "Failed to upsert data in table: {schema_name}.{table_name}",
-                        schema_name = payload.schema_name.clone(),
-                        table_name = payload.table_name.clone()
-                    )
-                });
+            let rows_deleted = client.execute(&query, &[]).await.with_context(|| {
+                format!(
+                    "Failed to delete rows from table: {}.{}",
+                    payload.schema_name, payload.table_name
+                )
+            })?;
+            payload.unmatched_delete_policy.on_delete(
+                &payload.schema_name,
+                &payload.table_name,
+                &pk_vector.join(","),
+                rows_deleted,
+            );
+
+            break;
         }
 
-        Ok(())
+        if deleted_row {
+            debug!("Deleted row");
+            continue;
+        }
+
+        // Apply the pending deletes first, so that the operations are applied in order
+        if let Some(delete_batch) = delete_batch.as_mut() {
+            delete_batch.flush(client).await?;
+        }
+
+        // Operation: Update
+        // The updates always update the existing row, and the conflict behavior applies to the inserts
+        let is_update_op = row_values.first().unwrap().to_string().contains('U');
+        let conflict_behavior = if is_update_op {
+            ConflictBehavior::DoUpdate
+        } else {
+            payload.conflict_behavior
+        };
+
+        debug!("Row values: {:?}", row_values);
+
+        // Remove the Op and _dms_ingestion_timestamp column from the row values
+        let row_values = row_values.iter().skip(2).collect::<Vec<_>>();
+        let values_of_row = row_values
+            .iter()
+            .map(|v| RowStruct::new(v).displayed())
+            .collect::<Vec<_>>();
+
+        let query = TableQuery::upsert_row(
+            payload.schema_name.as_str(),
+            payload.table_name.as_str(),
+            &column_names,
+            &values_of_row,
+            &primary_key,
+            conflict_behavior,
+        )?
+        .to_string();
+
+        debug!("Query: {}", query);
+
+        client.execute(query.as_str(), &[]).await.with_context(|| {
+            format!(
+                "Failed to upsert data in table: {}.{}",
+                payload.schema_name, payload.table_name
+            )
+        })?;
     }
 
-    async fn close_connection_pool(&self) {
-        self.db_client.close();
+    if let Some(delete_batch) = delete_batch.as_mut() {
+        delete_batch.flush(client).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::test_support::test_pool;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_upsert_with_deferred_constraints() {
+        let pool = test_pool("deferred").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE deferred.employees (
+                    id int PRIMARY KEY,
+                    manager_id int REFERENCES deferred.employees (id) DEFERRABLE
+                );",
+            )
+            .await
+            .unwrap();
+        drop(client);
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        // The first insert references a manager that is only inserted by the second one
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I", "I"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[2, 1]),
+            Series::new("manager_id", &[Some(1), None]),
+        ])
+        .unwrap();
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "deferred".to_string(),
+            table_name: "employees".to_string(),
+            primary_key: "id".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: true,
+        };
+        postgres_operator
+            .upsert_dataframe_in_target_db(&df, &payload)
+            .await
+            .unwrap();
+
+        let df = postgres_operator
+            .query_to_dataframe("SELECT id FROM deferred.employees ORDER BY id", &[])
+            .await
+            .unwrap();
+        assert_eq!(df.height(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_upsert_into_keyless_table() {
+        let pool = test_pool("keyless").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute("CREATE TABLE keyless.events (id int, name text);")
+            .await
+            .unwrap();
+        drop(client);
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I", "I"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[1, 1]),
+            Series::new("name", &["a", "a"]),
+        ])
+        .unwrap();
+        // A table without a primary key or unique key has an empty primary key
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "keyless".to_string(),
+            table_name: "events".to_string(),
+            primary_key: "".to_string(),
+            conflict_behavior: ConflictBehavior::DoNothing,
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
+        };
+        postgres_operator
+            .upsert_dataframe_in_target_db(&df, &payload)
+            .await
+            .unwrap();
+
+        let df = postgres_operator
+            .query_to_dataframe("SELECT id FROM keyless.events", &[])
+            .await
+            .unwrap();
+        assert_eq!(df.height(), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_upsert_is_retried_on_deadlock() {
+        let pool = test_pool("deadlock").await;
+        let client = pool.get().await.unwrap();
+        // The insert of the second row of the first attempt fails with a simulated deadlock,
+        // counted by a sequence since sequences are not rolled back
+        client
+            .batch_execute(
+                "CREATE TABLE deadlock.users (id int PRIMARY KEY, name text);
+                CREATE SEQUENCE deadlock.inserts;
+                CREATE FUNCTION deadlock.fail_once() RETURNS trigger AS $$
+                BEGIN
+                    IF nextval('deadlock.inserts') = 2 THEN
+                        RAISE EXCEPTION 'deadlock detected' USING ERRCODE = '40P01';
+                    END IF;
+                    RETURN NEW;
+                END $$ LANGUAGE plpgsql;
+                CREATE TRIGGER fail_once BEFORE INSERT ON deadlock.users
+                    FOR EACH ROW EXECUTE FUNCTION deadlock.fail_once();",
+            )
+            .await
+            .unwrap();
+        drop(client);
+        let postgres_operator =
+            PostgresOperatorImpl::new(pool).with_deadlock_retry(DeadlockRetry {
+                max_retries: 3,
+                base_delay: std::time::Duration::from_millis(1),
+            });
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I", "I"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[1, 2]),
+            Series::new("name", &["a", "b"]),
+        ])
+        .unwrap();
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "deadlock".to_string(),
+            table_name: "users".to_string(),
+            primary_key: "id".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
+        };
+        postgres_operator
+            .upsert_dataframe_in_target_db(&df, &payload)
+            .await
+            .unwrap();
+
+        // The first attempt was rolled back, and the whole file was applied by the second one
+        let df = postgres_operator
+            .query_to_dataframe(
+                "SELECT (SELECT COUNT(*) FROM deadlock.users) AS row_count,
+                    (SELECT last_value FROM deadlock.inserts) AS inserts",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            df.column("row_count").unwrap().get(0).unwrap(),
+            AnyValue::Int64(2)
+        );
+        assert_eq!(
+            df.column("inserts").unwrap().get(0).unwrap(),
+            AnyValue::Int64(4)
+        );
+    }
+
+    #[test]
+    fn test_create_table_of_domain_columns() {
+        // The columns of a domain as reported by information_schema.columns,
+        // e.g. for CREATE DOMAIN email AS text and CREATE DOMAIN price AS numeric(10,2)
+        let columns = [
+            ("id", "integer", Some(32), Some(0)),
+            ("email", "text", None, None),
+            ("price", "numeric", Some(10), Some(2)),
+            ("tags", "ARRAY", None, None),
+        ]
+        .into_iter()
+        .map(
+            |(column_name, data_type, numeric_precision, numeric_scale)| {
+                (
+                    column_name.to_string(),
+                    ColumnDataType::from_information_schema(
+                        data_type.to_string(),
+                        numeric_precision,
+                        numeric_scale,
+                    )
+                    .to_string(),
+                )
+            },
+        )
+        .collect::<IndexMap<_, _>>();
+        let query = CreateTable(
+            "domains".to_string(),
+            "users_copy".to_string(),
+            columns,
+            vec!["id".to_string()],
+            IndexMap::new(),
+        );
+
+        assert_eq!(
+            query.to_string(),
+            "CREATE TABLE IF NOT EXISTS domains.users_copy (id integer,email text,price numeric(10,2),tags text[],PRIMARY KEY (id))"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_domain_column_is_created_with_base_type() {
+        let pool = test_pool("domains").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE DOMAIN domains.email AS text CHECK (VALUE LIKE '%@%');
+                CREATE DOMAIN domains.price AS numeric(10,2);
+                CREATE TABLE domains.users (id int PRIMARY KEY, email domains.email, price domains.price);",
+            )
+            .await
+            .unwrap();
+        drop(client);
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let columns = postgres_operator
+            .get_table_columns("domains", "users")
+            .await
+            .unwrap();
+        let query = CreateTable(
+            "domains".to_string(),
+            "users_copy".to_string(),
+            columns,
+            vec!["id".to_string()],
+            IndexMap::new(),
+        );
+
+        assert_eq!(
+            query.to_string(),
+            "CREATE TABLE IF NOT EXISTS domains.users_copy (id integer,email text,price numeric(10,2),PRIMARY KEY (id))"
+        );
     }
 }
@@ -4,9 +4,14 @@ mod tests {
     use mockall::predicate::*;
     use polars::prelude::*;
 
+    use crate::postgres::conflict_behavior::ConflictBehavior;
+    use crate::postgres::copy_loader::InsertMethod;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
     use crate::postgres::postgres_operator::{
         InsertDataframePayload, MockPostgresOperator, PostgresOperator, UpsertDataframePayload,
     };
+    use crate::postgres::staging_load::LoadMode;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
 
     #[tokio::test]
     async fn test_get_table_columns() {
@@ -53,7 +58,7 @@ mod tests {
         postgres_operator
             .expect_create_table()
             .times(1)
-            .returning(|_, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _| Ok(()));
 
         let mut column_data_types = IndexMap::new();
         column_data_types.insert("column1".to_string(), "text".to_string());
@@ -65,11 +70,27 @@ mod tests {
                 vec!["primary_key".to_string()].as_slice(),
                 "schema",
                 "table",
+                &IndexMap::new(),
             )
             .await
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_add_column() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_add_column()
+            .times(1)
+            .with(eq("schema"), eq("table"), eq("email"), eq("text"))
+            .returning(|_, _, _, _| Ok(()));
+
+        postgres_operator
+            .add_column("schema", "table", "email", "text")
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_insert_dataframe_in_target_db() {
         let mut postgres_operator = MockPostgresOperator::new();
@@ -83,6 +104,9 @@ mod tests {
             database_name: "database".to_string(),
             schema_name: "schema".to_string(),
             table_name: "table".to_string(),
+            insert_method: InsertMethod::default(),
+            load_mode: LoadMode::default(),
+            copy_commit_rows: None,
         };
 
         postgres_operator
@@ -105,6 +129,11 @@ mod tests {
             schema_name: "schema".to_string(),
             table_name: "table".to_string(),
             primary_key: "primary_key".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
         };
         postgres_operator
             .upsert_dataframe_in_target_db(&df, &payload)
@@ -1,6 +1,6 @@
 use polars::datatypes::AnyValue;
-use polars_core::export::num::ToPrimitive;
-use rust_decimal::Decimal;
+
+use crate::dataframe::decimal::format_decimal;
 
 #[allow(clippy::enum_variant_names)]
 pub enum RowStruct<'a> {
@@ -39,10 +39,6 @@ impl<'a> RowStruct<'a> {
     }
 
     fn process_decimal_value(integer: i128, precision: usize) -> String {
-        let decimal = Decimal::new(
-            integer.to_i64().expect("Could not convert value to i64"),
-            precision.to_u32().expect("Could not convert value to u32"),
-        );
-        format!("'{:}'", decimal)
+        format!("'{}'", format_decimal(integer, precision))
     }
 }
@@ -0,0 +1,240 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use indexmap::{IndexMap, IndexSet};
+use log::info;
+
+use super::postgres_operator::PostgresOperator;
+
+/// Represents a foreign key of a table, referencing another table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    pub schema_name: String,
+    pub table_name: String,
+    pub referenced_schema_name: String,
+    pub referenced_table_name: String,
+    pub columns: Vec<String>,
+    pub referenced_columns: Vec<String>,
+}
+
+/// Sorts tables so that each table comes after the tables it references through its foreign keys.
+///
+/// Self-references and references to tables that are not in the list are ignored, and the tables
+/// without a dependency between them keep their order.
+///
+/// # Arguments
+///
+/// * `tables` - The tables to sort, as (schema, table) pairs.
+/// * `foreign_keys` - The foreign keys of the tables.
+///
+/// # Returns
+///
+/// The sorted tables, or an error naming the tables of a cyclic dependency.
+pub fn sort_by_dependencies(
+    tables: &[(String, String)],
+    foreign_keys: &[ForeignKey],
+) -> Result<Vec<(String, String)>> {
+    let mut dependencies: IndexMap<(String, String), IndexSet<(String, String)>> = tables
+        .iter()
+        .map(|table| (table.clone(), IndexSet::new()))
+        .collect();
+
+    for foreign_key in foreign_keys {
+        let table = (
+            foreign_key.schema_name.clone(),
+            foreign_key.table_name.clone(),
+        );
+        let referenced_table = (
+            foreign_key.referenced_schema_name.clone(),
+            foreign_key.referenced_table_name.clone(),
+        );
+        if table == referenced_table || !dependencies.contains_key(&referenced_table) {
+            continue;
+        }
+        if let Some(table_dependencies) = dependencies.get_mut(&table) {
+            table_dependencies.insert(referenced_table);
+        }
+    }
+
+    let mut sorted_tables = Vec::with_capacity(dependencies.len());
+    while !dependencies.is_empty() {
+        let Some(table) = dependencies
+            .iter()
+            .find(|(_, table_dependencies)| table_dependencies.is_empty())
+            .map(|(table, _)| table.clone())
+        else {
+            let cycle = dependencies
+                .keys()
+                .map(|(schema_name, table_name)| format!("{schema_name}.{table_name}"))
+                .collect::<Vec<_>>();
+            return Err(anyhow!(
+                "Cyclic foreign key dependency between tables: {}",
+                cycle.join(", ")
+            ));
+        };
+
+        dependencies.shift_remove(&table);
+        for table_dependencies in dependencies.values_mut() {
+            table_dependencies.shift_remove(&table);
+        }
+        sorted_tables.push(table);
+    }
+
+    Ok(sorted_tables)
+}
+
+/// Creates the schemas and tables of a target database, in the order of their foreign key dependencies.
+///
+/// The columns, primary keys and foreign keys of the tables are read from the source database.
+///
+/// # Arguments
+///
+/// * `source_postgres_operator` - The operator of the source database.
+/// * `target_postgres_operator` - The operator of the target database.
+/// * `tables` - The tables to create, as (schema, table) pairs.
+///
+/// # Returns
+///
+/// A Result indicating success or failure. Fails without creating anything on a cyclic dependency.
+pub async fn provision(
+    source_postgres_operator: &(impl PostgresOperator + Sync),
+    target_postgres_operator: &(impl PostgresOperator + Sync),
+    tables: &[(String, String)],
+) -> Result<()> {
+    info!("{}", "Provisioning the target DB...".bold().blue());
+
+    let schema_names = tables
+        .iter()
+        .map(|(schema_name, _)| schema_name.clone())
+        .collect::<IndexSet<_>>();
+
+    let mut foreign_keys = Vec::new();
+    for schema_name in &schema_names {
+        foreign_keys.extend(
+            source_postgres_operator
+                .get_foreign_keys(schema_name)
+                .await?,
+        );
+    }
+    let sorted_tables = sort_by_dependencies(tables, &foreign_keys)?;
+
+    for schema_name in &schema_names {
+        target_postgres_operator.create_schema(schema_name).await?;
+    }
+
+    for (schema_name, table_name) in &sorted_tables {
+        info!("Creating table {schema_name}.{table_name}");
+        let table_columns = source_postgres_operator
+            .get_table_columns(schema_name, table_name)
+            .await?;
+        let primary_key = source_postgres_operator
+            .get_primary_key(table_name, schema_name)
+            .await?;
+        target_postgres_operator
+            .create_table(
+                &table_columns,
+                &primary_key,
+                schema_name,
+                table_name,
+                &IndexMap::new(),
+            )
+            .await?;
+    }
+
+    info!("Provisioned {} tables", sorted_tables.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use mockall::predicate::*;
+    use mockall::Sequence;
+
+    fn table(table_name: &str) -> (String, String) {
+        ("public".to_string(), table_name.to_string())
+    }
+
+    fn foreign_key(table_name: &str, referenced_table_name: &str) -> ForeignKey {
+        ForeignKey {
+            schema_name: "public".to_string(),
+            table_name: table_name.to_string(),
+            referenced_schema_name: "public".to_string(),
+            referenced_table_name: referenced_table_name.to_string(),
+            columns: vec![format!("{referenced_table_name}_id")],
+            referenced_columns: vec!["id".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_sort_child_after_parent() {
+        let tables = vec![table("order_items"), table("orders"), table("users")];
+        let foreign_keys = vec![
+            foreign_key("order_items", "orders"),
+            foreign_key("orders", "users"),
+            foreign_key("users", "users"),
+            foreign_key("orders", "not_provisioned"),
+        ];
+
+        let sorted_tables = sort_by_dependencies(&tables, &foreign_keys).unwrap();
+
+        assert_eq!(
+            sorted_tables,
+            vec![table("users"), table("orders"), table("order_items")]
+        );
+    }
+
+    #[test]
+    fn test_sort_with_cycle() {
+        let tables = vec![table("users"), table("a"), table("b")];
+        let foreign_keys = vec![foreign_key("a", "b"), foreign_key("b", "a")];
+
+        let error = sort_by_dependencies(&tables, &foreign_keys).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Cyclic foreign key dependency between tables: public.a, public.b"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provision_creates_parent_before_child() {
+        let mut source_postgres_operator = MockPostgresOperator::new();
+        source_postgres_operator
+            .expect_get_foreign_keys()
+            .with(eq("public"))
+            .returning(|_| Ok(vec![foreign_key("orders", "users")]));
+        source_postgres_operator
+            .expect_get_table_columns()
+            .returning(|_, _| Ok(IndexMap::from([("id".to_string(), "bigint".to_string())])));
+        source_postgres_operator
+            .expect_get_primary_key()
+            .returning(|_, _| Ok(vec!["id".to_string()]));
+
+        let mut target_postgres_operator = MockPostgresOperator::new();
+        let mut sequence = Sequence::new();
+        target_postgres_operator
+            .expect_create_schema()
+            .with(eq("public"))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(()));
+        for table_name in ["users", "orders"] {
+            target_postgres_operator
+                .expect_create_table()
+                .withf(move |_, _, _, table, _| table == table_name)
+                .times(1)
+                .in_sequence(&mut sequence)
+                .returning(|_, _, _, _, _| Ok(()));
+        }
+
+        provision(
+            &source_postgres_operator,
+            &target_postgres_operator,
+            &[table("orders"), table("users")],
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Result};
+use bytes::BytesMut;
+use deadpool_postgres::tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+use deadpool_postgres::tokio_postgres::Row;
+use polars::prelude::*;
+use polars_core::export::num::ToPrimitive;
+use rust_decimal::Decimal;
+use std::error::Error;
+
+/// Represents a parameter of a validation query.
+///
+/// Integers and floats are bound with the width of the parameter type inferred by Postgres,
+/// e.g. an integer compared with an `int4` column is bound as an `i32`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParam {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl ToSql for QueryParam {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match (self, ty) {
+            (QueryParam::Null, _) => Ok(IsNull::Yes),
+            (QueryParam::Bool(v), &Type::BOOL) => v.to_sql(ty, out),
+            (QueryParam::Int(v), &Type::INT2) => i16::try_from(*v)?.to_sql(ty, out),
+            (QueryParam::Int(v), &Type::INT4) => i32::try_from(*v)?.to_sql(ty, out),
+            (QueryParam::Int(v), &Type::INT8) => v.to_sql(ty, out),
+            (QueryParam::Float(v), &Type::FLOAT4) => (*v as f32).to_sql(ty, out),
+            (QueryParam::Float(v), &Type::FLOAT8) => v.to_sql(ty, out),
+            (QueryParam::Text(v), _) if <String as ToSql>::accepts(ty) => v.to_sql(ty, out),
+            (param, ty) => Err(format!("Cannot bind {param:?} to a parameter of type {ty}").into()),
+        }
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+impl From<&AnyValue<'_>> for QueryParam {
+    /// Converts a DataFrame value to a parameter. Values of other types than booleans, integers,
+    /// floats and strings are bound as text.
+    fn from(value: &AnyValue<'_>) -> Self {
+        match value {
+            AnyValue::Null => QueryParam::Null,
+            AnyValue::Boolean(v) => QueryParam::Bool(*v),
+            AnyValue::Int8(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::Int16(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::Int32(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::Int64(v) => QueryParam::Int(*v),
+            AnyValue::UInt8(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::UInt16(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::UInt32(v) => QueryParam::Int(i64::from(*v)),
+            AnyValue::Float32(v) => QueryParam::Float(f64::from(*v)),
+            AnyValue::Float64(v) => QueryParam::Float(*v),
+            AnyValue::String(v) => QueryParam::Text(v.to_string()),
+            AnyValue::StringOwned(v) => QueryParam::Text(v.to_string()),
+            value => QueryParam::Text(value.to_string()),
+        }
+    }
+}
+
+/// Checks that a validation query is read-only, i.e. that it is a single SELECT statement.
+///
+/// # Arguments
+///
+/// * `sql` - The query.
+///
+/// # Returns
+///
+/// A Result indicating whether the query is accepted.
+pub fn check_read_only_query(sql: &str) -> Result<()> {
+    let statement = sql.trim().trim_end_matches(';').trim_start_matches('(');
+    let first_keyword = statement
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+
+    if statement.contains(';') {
+        return Err(anyhow!("Only a single statement is allowed: {}", sql));
+    }
+
+    match first_keyword.as_str() {
+        "SELECT" | "WITH" | "VALUES" | "TABLE" => Ok(()),
+        _ => Err(anyhow!("Only SELECT statements are allowed: {}", sql)),
+    }
+}
+
+/// Materializes the rows of a query into a DataFrame.
+///
+/// The Postgres types are mapped to the following data types:
+///
+/// * `bool` - Boolean
+/// * `smallint`, `integer` - Int32
+/// * `bigint` - Int64
+/// * `real` - Float32
+/// * `double precision`, `numeric` - Float64
+/// * `text`, `varchar`, `char`, `name` - String
+///
+/// Other types must be cast to text in the query.
+///
+/// # Arguments
+///
+/// * `rows` - The rows of the query.
+///
+/// # Returns
+///
+/// A DataFrame with a column per column of the query.
+pub fn rows_to_dataframe(rows: &[Row]) -> Result<DataFrame> {
+    let Some(first_row) = rows.first() else {
+        return Ok(DataFrame::empty());
+    };
+
+    let columns = first_row
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let name = column.name();
+            let series = match *column.type_() {
+                Type::BOOL => Series::new(name, column_values::<bool>(rows, index)?),
+                Type::INT2 => Series::new(
+                    name,
+                    column_values::<i16>(rows, index)?
+                        .into_iter()
+                        .map(|v| v.map(i32::from))
+                        .collect::<Vec<_>>(),
+                ),
+                Type::INT4 => Series::new(name, column_values::<i32>(rows, index)?),
+                Type::INT8 => Series::new(name, column_values::<i64>(rows, index)?),
+                Type::FLOAT4 => Series::new(name, column_values::<f32>(rows, index)?),
+                Type::FLOAT8 => Series::new(name, column_values::<f64>(rows, index)?),
+                Type::NUMERIC => Series::new(
+                    name,
+                    column_values::<Decimal>(rows, index)?
+                        .into_iter()
+                        .map(|v| v.and_then(|v| v.to_f64()))
+                        .collect::<Vec<_>>(),
+                ),
+                Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME => {
+                    Series::new(name, column_values::<String>(rows, index)?)
+                }
+                ref ty => {
+                    return Err(anyhow!(
+                        "Unsupported type {ty} of column {name}, cast it to text in the query"
+                    ))
+                }
+            };
+            Ok(series)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Gets the values of a column of the rows.
+fn column_values<'a, T>(rows: &'a [Row], index: usize) -> Result<Vec<Option<T>>>
+where
+    T: deadpool_postgres::tokio_postgres::types::FromSql<'a>,
+{
+    Ok(rows
+        .iter()
+        .map(|row| row.try_get::<_, Option<T>>(index))
+        .collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::PostgresOperator;
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::test_support::connect_to_test_db;
+
+    async fn postgres_operator() -> PostgresOperatorImpl {
+        PostgresOperatorImpl::new(connect_to_test_db().await)
+    }
+
+    #[test]
+    fn test_check_read_only_query() {
+        assert!(check_read_only_query("SELECT COUNT(*) FROM orders").is_ok());
+        assert!(check_read_only_query("  with totals AS (SELECT 1) SELECT * FROM totals;").is_ok());
+        assert!(check_read_only_query("DELETE FROM orders").is_err());
+        assert!(check_read_only_query("SELECT 1; DROP TABLE orders").is_err());
+    }
+
+    #[test]
+    fn test_query_param_from_any_value() {
+        assert_eq!(QueryParam::from(&AnyValue::Int32(7)), QueryParam::Int(7));
+        assert_eq!(
+            QueryParam::from(&AnyValue::String("a")),
+            QueryParam::Text("a".to_string())
+        );
+        assert_eq!(
+            QueryParam::from(&AnyValue::Boolean(true)),
+            QueryParam::Bool(true)
+        );
+        assert_eq!(QueryParam::from(&AnyValue::Null), QueryParam::Null);
+    }
+
+    #[tokio::test]
+    async fn test_query_to_dataframe_rejects_delete() {
+        // The query is rejected before a connection is acquired from the pool
+        let result = postgres_operator()
+            .await
+            .query_to_dataframe("DELETE FROM orders WHERE id = $1", &[QueryParam::Int(1)])
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_query_to_dataframe_with_aggregate() {
+        let df = postgres_operator()
+            .await
+            .query_to_dataframe(
+                "SELECT category, COUNT(*) AS orders, SUM(amount) AS total
+                FROM (VALUES ('a', 1.5), ('a', 2.5), ('b', 4.0)) AS orders(category, amount)
+                WHERE amount > $1::float8
+                GROUP BY category
+                ORDER BY category",
+                &[QueryParam::Float(1.0)],
+            )
+            .await
+            .unwrap();
+
+        let expected = df!(
+            "category" => &["a", "b"],
+            "orders" => &[2i64, 1],
+            "total" => &[4.0f64, 4.0]
+        )
+        .unwrap();
+        assert!(df.equals(&expected));
+    }
+}
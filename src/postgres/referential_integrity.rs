@@ -0,0 +1,166 @@
+use anyhow::Result;
+use colored::Colorize;
+use log::{info, warn};
+
+use super::postgres_operator::PostgresOperator;
+use super::provisioning::ForeignKey;
+
+/// Represents the rows of a table that reference a missing row through a foreign key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedRows {
+    pub foreign_key: ForeignKey,
+    pub orphaned_rows: i64,
+}
+
+/// Checks the referential integrity of the tables loaded in a target schema, based on the
+/// foreign keys of the source schema, since the foreign keys are not created in the target.
+///
+/// The foreign keys that reference a table of another schema are not checked.
+///
+/// # Arguments
+///
+/// * `source_postgres_operator` - The operator of the source database, to discover the foreign keys.
+/// * `target_postgres_operator` - The operator of the target database.
+/// * `source_schema_name` - The name of the source schema.
+/// * `target_schema_name` - The name of the target schema, with tables of the same names.
+///
+/// # Returns
+///
+/// The foreign keys with orphaned rows in the target schema, along with their number.
+pub async fn find_orphaned_rows(
+    source_postgres_operator: &(impl PostgresOperator + Sync),
+    target_postgres_operator: &(impl PostgresOperator + Sync),
+    source_schema_name: &str,
+    target_schema_name: &str,
+) -> Result<Vec<OrphanedRows>> {
+    info!("{}", "Checking referential integrity...".bold().blue());
+
+    let foreign_keys = source_postgres_operator
+        .get_foreign_keys(source_schema_name)
+        .await?;
+
+    let mut orphaned_rows = Vec::new();
+    for foreign_key in foreign_keys
+        .into_iter()
+        .filter(|foreign_key| foreign_key.referenced_schema_name == source_schema_name)
+    {
+        let count = target_postgres_operator
+            .count_orphaned_rows(target_schema_name, &foreign_key)
+            .await?;
+        if count > 0 {
+            warn!(
+                "Table {}.{} has {} rows referencing missing rows of table {}.{} through columns ({})",
+                target_schema_name,
+                foreign_key.table_name,
+                count,
+                target_schema_name,
+                foreign_key.referenced_table_name,
+                foreign_key.columns.join(", ")
+            );
+            orphaned_rows.push(OrphanedRows {
+                foreign_key,
+                orphaned_rows: count,
+            });
+        }
+    }
+
+    Ok(orphaned_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::test_support::test_pool;
+    use deadpool_postgres::GenericClient;
+    use mockall::predicate::*;
+
+    fn foreign_key(table_name: &str, referenced_table_name: &str) -> ForeignKey {
+        ForeignKey {
+            schema_name: "public".to_string(),
+            table_name: table_name.to_string(),
+            referenced_schema_name: "public".to_string(),
+            referenced_table_name: referenced_table_name.to_string(),
+            columns: vec![format!("{referenced_table_name}_id")],
+            referenced_columns: vec!["id".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_orphaned_rows() {
+        let mut source_postgres_operator = MockPostgresOperator::new();
+        source_postgres_operator
+            .expect_get_foreign_keys()
+            .with(eq("public"))
+            .returning(|_| {
+                Ok(vec![
+                    foreign_key("orders", "users"),
+                    foreign_key("order_items", "orders"),
+                ])
+            });
+
+        let mut target_postgres_operator = MockPostgresOperator::new();
+        target_postgres_operator
+            .expect_count_orphaned_rows()
+            .withf(|schema_name, _| schema_name == "scratch")
+            .returning(|_, foreign_key| match foreign_key.table_name.as_str() {
+                "order_items" => Ok(1),
+                _ => Ok(0),
+            });
+
+        let orphaned_rows = find_orphaned_rows(
+            &source_postgres_operator,
+            &target_postgres_operator,
+            "public",
+            "scratch",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            orphaned_rows,
+            vec![OrphanedRows {
+                foreign_key: foreign_key("order_items", "orders"),
+                orphaned_rows: 1,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_count_orphaned_rows() {
+        let pool = test_pool("referential_integrity").await;
+        pool.get()
+            .await
+            .unwrap()
+            .batch_execute(
+                "CREATE TABLE referential_integrity.orders (id int PRIMARY KEY);
+                CREATE TABLE referential_integrity.order_items (
+                    id int PRIMARY KEY,
+                    order_id int REFERENCES referential_integrity.orders (id)
+                );
+                INSERT INTO referential_integrity.orders VALUES (1);
+                INSERT INTO referential_integrity.order_items VALUES (1, 1), (2, NULL);
+                ALTER TABLE referential_integrity.order_items DISABLE TRIGGER ALL;
+                INSERT INTO referential_integrity.order_items VALUES (3, 2);",
+            )
+            .await
+            .unwrap();
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let foreign_keys = postgres_operator
+            .get_foreign_keys("referential_integrity")
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].columns, vec!["order_id"]);
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["id"]);
+
+        let orphaned_rows = postgres_operator
+            .count_orphaned_rows("referential_integrity", &foreign_keys[0])
+            .await
+            .unwrap();
+        assert_eq!(orphaned_rows, 1);
+    }
+}
@@ -0,0 +1,443 @@
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::warn;
+
+/// Represents a column whose data type differs between two schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnTypeMismatch {
+    pub column: String,
+    pub expected_data_type: String,
+    pub actual_data_type: String,
+}
+
+/// Represents a column whose position differs between two schemas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnOrderMismatch {
+    pub column: String,
+    pub expected_position: usize,
+    pub actual_position: usize,
+}
+
+/// Represents a decimal column whose scale in the Parquet files exceeds the scale of the
+/// target column, so that its values are rounded when loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalScaleMismatch {
+    pub column: String,
+    pub parquet_data_type: String,
+    pub target_data_type: String,
+}
+
+/// Represents the differences between an expected and an actual table schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub missing_columns: Vec<String>,
+    pub extra_columns: Vec<String>,
+    pub type_mismatches: Vec<ColumnTypeMismatch>,
+    pub order_mismatches: Vec<ColumnOrderMismatch>,
+}
+
+impl SchemaDiff {
+    /// Compares an expected with an actual table schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The expected column names and their data types.
+    /// * `actual` - The actual column names and their data types.
+    ///
+    /// # Returns
+    ///
+    /// The differences between the two schemas.
+    pub fn new(expected: &IndexMap<String, String>, actual: &IndexMap<String, String>) -> Self {
+        let mut schema_diff = SchemaDiff::default();
+
+        for (column, expected_data_type) in expected {
+            match actual.get(column) {
+                Some(actual_data_type)
+                    if !actual_data_type.eq_ignore_ascii_case(expected_data_type) =>
+                {
+                    schema_diff.type_mismatches.push(ColumnTypeMismatch {
+                        column: column.clone(),
+                        expected_data_type: expected_data_type.clone(),
+                        actual_data_type: actual_data_type.clone(),
+                    })
+                }
+                Some(_) => (),
+                None => schema_diff.missing_columns.push(column.clone()),
+            }
+        }
+
+        schema_diff.extra_columns = actual
+            .keys()
+            .filter(|column| !expected.contains_key(*column))
+            .cloned()
+            .collect();
+
+        schema_diff
+    }
+
+    /// Compares an expected with an actual table schema, including the order of the columns,
+    /// for downstream tools that rely on the physical column order.
+    ///
+    /// The columns that exist in both schemas are compared by their relative order, so a missing
+    /// or extra column does not shift the rest. The positions reported are those in each schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The expected column names and their data types, in order.
+    /// * `actual` - The actual column names and their data types, in order.
+    ///
+    /// # Returns
+    ///
+    /// The differences between the two schemas.
+    pub fn with_column_order(
+        expected: &IndexMap<String, String>,
+        actual: &IndexMap<String, String>,
+    ) -> Self {
+        let mut schema_diff = SchemaDiff::new(expected, actual);
+
+        let expected_common_columns = expected
+            .keys()
+            .filter(|column| actual.contains_key(*column));
+        let actual_common_columns = actual
+            .keys()
+            .filter(|column| expected.contains_key(*column));
+
+        schema_diff.order_mismatches = expected_common_columns
+            .zip(actual_common_columns)
+            .filter(|(expected_column, actual_column)| expected_column != actual_column)
+            .map(|(expected_column, _)| ColumnOrderMismatch {
+                column: expected_column.clone(),
+                expected_position: expected.get_index_of(expected_column).unwrap_or_default(),
+                actual_position: actual.get_index_of(expected_column).unwrap_or_default(),
+            })
+            .collect();
+
+        schema_diff
+    }
+
+    /// Checks if the two schemas match.
+    pub fn is_empty(&self) -> bool {
+        self.missing_columns.is_empty()
+            && self.extra_columns.is_empty()
+            && self.type_mismatches.is_empty()
+            && self.order_mismatches.is_empty()
+    }
+
+    /// Describes each difference between the two schemas.
+    pub fn findings(&self) -> Vec<String> {
+        let missing_columns = self
+            .missing_columns
+            .iter()
+            .map(|column| format!("column {column} is missing in the target"));
+        let extra_columns = self
+            .extra_columns
+            .iter()
+            .map(|column| format!("column {column} is not expected in the target"));
+        let type_mismatches = self.type_mismatches.iter().map(|mismatch| {
+            format!(
+                "column {} is {} but {} in the target",
+                mismatch.column, mismatch.expected_data_type, mismatch.actual_data_type
+            )
+        });
+        let order_mismatches = self.order_mismatches.iter().map(|mismatch| {
+            format!(
+                "column {} is at position {} but {} in the target",
+                mismatch.column, mismatch.expected_position, mismatch.actual_position
+            )
+        });
+
+        missing_columns
+            .chain(extra_columns)
+            .chain(type_mismatches)
+            .chain(order_mismatches)
+            .collect()
+    }
+}
+
+/// Represents how the differences between the expected schema of a table and its schema in
+/// the target are handled.
+///
+/// The strictness can be one of the following:
+///
+/// * Lenient - A warning is logged and the data are loaded.
+/// * Strict - The run fails before any data is loaded, e.g. for production gating.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaStrictness {
+    #[default]
+    Lenient,
+    Strict,
+}
+
+impl SchemaStrictness {
+    /// Checks the differences between the expected schema of a table and its schema in the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_diff` - The differences between the two schemas.
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// An error if the schemas differ and the strictness is `Strict`.
+    pub fn check(
+        &self,
+        schema_diff: &SchemaDiff,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<()> {
+        if schema_diff.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            SchemaStrictness::Lenient => {
+                warn!("Table {schema_name}.{table_name} exists with a different schema: {schema_diff:?}");
+                Ok(())
+            }
+            SchemaStrictness::Strict => Err(anyhow!(
+                "Table {}.{} exists with a different schema: {}",
+                schema_name,
+                table_name,
+                schema_diff.findings().join(", ")
+            )),
+        }
+    }
+}
+
+/// Parses the precision and scale of a Postgres numeric data type, e.g. `numeric(10,2)`.
+///
+/// # Arguments
+///
+/// * `data_type` - The Postgres data type.
+///
+/// # Returns
+///
+/// The precision and scale, or None if the data type is not a numeric with a precision and scale.
+pub fn numeric_precision_scale(data_type: &str) -> Option<(u32, u32)> {
+    let arguments = data_type
+        .trim()
+        .to_lowercase()
+        .strip_prefix("numeric(")?
+        .strip_suffix(')')?
+        .to_string();
+    let (precision, scale) = arguments.split_once(',')?;
+
+    Some((precision.trim().parse().ok()?, scale.trim().parse().ok()?))
+}
+
+/// Finds the decimal columns of the Parquet files whose scale exceeds the scale of the target
+/// column, since their values are silently rounded when loaded.
+///
+/// # Arguments
+///
+/// * `parquet_columns` - The column names of the Parquet files and their Postgres data types.
+/// * `target_columns` - The column names of the target table and their data types.
+///
+/// # Returns
+///
+/// The decimal columns at risk of rounding. Columns without a precision and scale on either side
+/// are not compared.
+pub fn decimal_scale_mismatches(
+    parquet_columns: &IndexMap<String, String>,
+    target_columns: &IndexMap<String, String>,
+) -> Vec<DecimalScaleMismatch> {
+    parquet_columns
+        .iter()
+        .filter_map(|(column, parquet_data_type)| {
+            let target_data_type = target_columns.get(column)?;
+            let (_, parquet_scale) = numeric_precision_scale(parquet_data_type)?;
+            let (_, target_scale) = numeric_precision_scale(target_data_type)?;
+
+            (parquet_scale > target_scale).then(|| DecimalScaleMismatch {
+                column: column.clone(),
+                parquet_data_type: parquet_data_type.clone(),
+                target_data_type: target_data_type.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Checks that a list of column renames can be applied, in order, to the columns of a table.
+///
+/// # Arguments
+///
+/// * `columns` - The column names of the table and their data types.
+/// * `renames` - The current column names and their new names.
+///
+/// # Returns
+///
+/// A Result indicating whether the renames can be applied.
+pub fn check_column_renames(
+    columns: &IndexMap<String, String>,
+    renames: &IndexMap<String, String>,
+) -> Result<()> {
+    let mut column_names = columns.keys().cloned().collect::<Vec<_>>();
+
+    for (from, to) in renames {
+        if column_names.contains(to) {
+            return Err(anyhow!(
+                "Cannot rename column {from} to {to}, since a column named {to} already exists"
+            ));
+        }
+        match column_names.iter_mut().find(|column| *column == from) {
+            Some(column) => *column = to.clone(),
+            None => {
+                return Err(anyhow!(
+                    "Cannot rename column {from}, since it does not exist"
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(columns: &[(&str, &str)]) -> IndexMap<String, String> {
+        columns
+            .iter()
+            .map(|(column, data_type)| (column.to_string(), data_type.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_diff_of_matching_schemas() {
+        let expected = columns(&[("id", "integer"), ("name", "text")]);
+        let actual = columns(&[("id", "INTEGER"), ("name", "text")]);
+
+        assert!(SchemaDiff::new(&expected, &actual).is_empty());
+    }
+
+    #[test]
+    fn test_schema_diff_of_differing_schemas() {
+        let expected = columns(&[("id", "integer"), ("name", "text"), ("age", "integer")]);
+        let actual = columns(&[("id", "bigint"), ("name", "text"), ("email", "text")]);
+
+        let schema_diff = SchemaDiff::new(&expected, &actual);
+
+        assert_eq!(schema_diff.missing_columns, vec!["age"]);
+        assert_eq!(schema_diff.extra_columns, vec!["email"]);
+        assert_eq!(
+            schema_diff.type_mismatches,
+            vec![ColumnTypeMismatch {
+                column: "id".to_string(),
+                expected_data_type: "integer".to_string(),
+                actual_data_type: "bigint".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_schema_diff_of_columns_in_different_order() {
+        let expected = columns(&[("id", "integer"), ("name", "text"), ("age", "integer")]);
+        let actual = columns(&[("id", "integer"), ("age", "integer"), ("name", "text")]);
+
+        assert!(SchemaDiff::new(&expected, &actual).is_empty());
+
+        let schema_diff = SchemaDiff::with_column_order(&expected, &actual);
+        assert!(schema_diff.missing_columns.is_empty());
+        assert!(schema_diff.extra_columns.is_empty());
+        assert_eq!(
+            schema_diff.order_mismatches,
+            vec![
+                ColumnOrderMismatch {
+                    column: "name".to_string(),
+                    expected_position: 1,
+                    actual_position: 2,
+                },
+                ColumnOrderMismatch {
+                    column: "age".to_string(),
+                    expected_position: 2,
+                    actual_position: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_diff_of_extra_column_keeps_order() {
+        let expected = columns(&[("id", "integer"), ("name", "text")]);
+        let actual = columns(&[("id", "integer"), ("email", "text"), ("name", "text")]);
+
+        let schema_diff = SchemaDiff::with_column_order(&expected, &actual);
+
+        assert_eq!(schema_diff.extra_columns, vec!["email"]);
+        assert!(schema_diff.order_mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_schema_strictness_with_type_mismatch() {
+        let expected = columns(&[("id", "integer"), ("name", "text")]);
+        let actual = columns(&[("id", "bigint"), ("name", "text")]);
+        let schema_diff = SchemaDiff::new(&expected, &actual);
+
+        assert!(SchemaStrictness::Lenient
+            .check(&schema_diff, "public", "users")
+            .is_ok());
+        assert_eq!(
+            SchemaStrictness::Strict
+                .check(&schema_diff, "public", "users")
+                .unwrap_err()
+                .to_string(),
+            "Table public.users exists with a different schema: column id is integer but bigint in the target"
+        );
+        assert!(SchemaStrictness::Strict
+            .check(&SchemaDiff::new(&expected, &expected), "public", "users")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_numeric_precision_scale() {
+        assert_eq!(numeric_precision_scale("numeric(10,2)"), Some((10, 2)));
+        assert_eq!(numeric_precision_scale("NUMERIC(38, 6)"), Some((38, 6)));
+        assert_eq!(numeric_precision_scale("numeric"), None);
+        assert_eq!(numeric_precision_scale("integer"), None);
+    }
+
+    #[test]
+    fn test_decimal_scale_exceeding_target_scale() {
+        let parquet_columns = columns(&[
+            ("id", "bigint"),
+            ("price", "numeric(38,6)"),
+            ("tax", "numeric(10,2)"),
+            ("total", "numeric(38,6)"),
+        ]);
+        let target_columns = columns(&[
+            ("id", "bigint"),
+            ("price", "numeric(10,2)"),
+            ("tax", "numeric(12,4)"),
+            ("total", "numeric"),
+        ]);
+
+        assert_eq!(
+            decimal_scale_mismatches(&parquet_columns, &target_columns),
+            vec![DecimalScaleMismatch {
+                column: "price".to_string(),
+                parquet_data_type: "numeric(38,6)".to_string(),
+                target_data_type: "numeric(10,2)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_column_renames() {
+        let table_columns = columns(&[("Op", "text"), ("id", "integer")]);
+
+        let renames = IndexMap::from([("Op".to_string(), "dms_op".to_string())]);
+        assert!(check_column_renames(&table_columns, &renames).is_ok());
+
+        let renames = IndexMap::from([("Op".to_string(), "id".to_string())]);
+        let error = check_column_renames(&table_columns, &renames).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot rename column Op to id, since a column named id already exists"
+        );
+
+        let renames = IndexMap::from([("missing".to_string(), "other".to_string())]);
+        assert!(check_column_renames(&table_columns, &renames).is_err());
+    }
+}
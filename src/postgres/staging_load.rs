@@ -0,0 +1,217 @@
+use anyhow::Result;
+use deadpool_postgres::{Client, GenericClient};
+use log::info;
+use polars::prelude::*;
+use uuid::Uuid;
+
+use super::copy_loader::InsertMethod;
+use super::postgres_operator_impl::insert_dataframe_with_method;
+use super::table_query::TableQuery;
+
+/// Represents how the rows of the LOAD files are written in a table.
+///
+/// The mode can be one of the following:
+///
+/// * Direct - The rows are inserted directly in the table, so a re-run fails or duplicates rows.
+/// * IgnoreDuplicates - The rows are inserted in a staging table first, and then moved to the table
+///   ignoring the rows that already exist, so a re-run is idempotent without the cost of an upsert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadMode {
+    #[default]
+    Direct,
+    IgnoreDuplicates,
+}
+
+/// Gets a name for a temporary table of a table that is unique within the session, so that
+/// concurrent stagings of tables in one connection do not collide.
+///
+/// The random suffix comes before the table name, since Postgres truncates identifiers longer
+/// than 63 characters.
+///
+/// # Arguments
+///
+/// * `prefix` - The prefix of the name, e.g. `staging`.
+/// * `table_name` - The name of the table.
+///
+/// # Returns
+///
+/// The name of the temporary table, e.g. `staging_1a2b3c4d_users`.
+pub fn unique_table_name(prefix: &str, table_name: &str) -> String {
+    let suffix = Uuid::new_v4().simple().to_string();
+    format!("{}_{}_{}", prefix, &suffix[..8], table_name)
+}
+
+/// Gets a unique name of a staging table for a table.
+pub fn staging_table_name(table_name: &str) -> String {
+    unique_table_name("staging", table_name)
+}
+
+/// Inserts a DataFrame, without the columns added by DMS, in a table through a staging table,
+/// ignoring the rows that conflict with existing rows.
+///
+/// The staging table is temporary, so it is only visible to the given client, and it is
+/// dropped afterwards, even if the insert fails, so nothing is left behind when the client
+/// returns to the pool.
+///
+/// # Arguments
+///
+/// * `client` - The client to use for the insert.
+/// * `df` - The DataFrame to insert.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+/// * `insert_method` - Whether the rows are inserted in the staging table with INSERT or COPY statements.
+///
+/// # Returns
+///
+/// The number of rows inserted in the table.
+pub(crate) async fn insert_ignoring_duplicates_with_client(
+    client: &Client,
+    df: &DataFrame,
+    schema_name: &str,
+    table_name: &str,
+    insert_method: InsertMethod,
+) -> Result<u64> {
+    let staging_table_name = staging_table_name(table_name);
+
+    let query = TableQuery::CreateStagingTable(
+        schema_name.to_string(),
+        table_name.to_string(),
+        staging_table_name.clone(),
+    );
+    client.execute(&query.to_string(), &[]).await?;
+
+    let result: Result<u64> = async {
+        insert_dataframe_with_method(client, df, "pg_temp", &staging_table_name, insert_method)
+            .await?;
+
+        let query = TableQuery::InsertFromStaging(
+            schema_name.to_string(),
+            table_name.to_string(),
+            staging_table_name.clone(),
+            df.get_column_names().join(", "),
+        );
+        Ok(client.execute(&query.to_string(), &[]).await?)
+    }
+    .await;
+
+    let query = TableQuery::DropStagingTable(staging_table_name);
+    client.execute(&query.to_string(), &[]).await?;
+
+    let rows_inserted = result?;
+    info!(
+        "Inserted {} of {} rows in table {}.{}, ignoring duplicates",
+        rows_inserted,
+        df.height(),
+        schema_name,
+        table_name
+    );
+
+    Ok(rows_inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::test_support::test_pool;
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_rerun_of_load_does_not_duplicate_rows() {
+        let pool = test_pool("staging_load").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute("CREATE TABLE staging_load.users (id int PRIMARY KEY, name text);")
+            .await
+            .unwrap();
+
+        let df = df!("id" => &[1, 2], "name" => &["a", "b"]).unwrap();
+        for (insert_method, expected_rows_inserted) in [
+            (InsertMethod::Copy, 2),
+            (InsertMethod::Copy, 0),
+            (InsertMethod::Insert, 0),
+        ] {
+            let rows_inserted = insert_ignoring_duplicates_with_client(
+                &client,
+                &df,
+                "staging_load",
+                "users",
+                insert_method,
+            )
+            .await
+            .unwrap();
+            assert_eq!(rows_inserted, expected_rows_inserted);
+        }
+
+        let count: i64 = client
+            .query_one("SELECT COUNT(*) FROM staging_load.users", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(count, 2);
+
+        assert_eq!(count_staging_tables(&client).await, 0);
+    }
+
+    async fn count_staging_tables(client: &Client) -> i64 {
+        client
+            .query_one(
+                "SELECT COUNT(*) FROM pg_class
+                WHERE relnamespace = pg_my_temp_schema() AND relname LIKE 'staging\\_%'",
+                &[],
+            )
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    #[test]
+    fn test_staging_table_names_are_unique() {
+        let first = staging_table_name("users");
+        let second = staging_table_name("users");
+
+        assert_ne!(first, second);
+        assert!(first.starts_with("staging_") && first.ends_with("_users"));
+        assert_eq!(first.len(), "staging_12345678_users".len());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_concurrent_stagings_in_one_connection() {
+        let pool = test_pool("staging_load_concurrent").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE staging_load_concurrent.orders (id int PRIMARY KEY, amount int);",
+            )
+            .await
+            .unwrap();
+
+        let first = df!("id" => &[1, 2], "amount" => &[10, 20]).unwrap();
+        let second = df!("id" => &[2, 3], "amount" => &[20, 30]).unwrap();
+        let (first_result, second_result) = futures::join!(
+            insert_ignoring_duplicates_with_client(
+                &client,
+                &first,
+                "staging_load_concurrent",
+                "orders",
+                InsertMethod::Insert,
+            ),
+            insert_ignoring_duplicates_with_client(
+                &client,
+                &second,
+                "staging_load_concurrent",
+                "orders",
+                InsertMethod::Insert,
+            )
+        );
+        assert_eq!(first_result.unwrap() + second_result.unwrap(), 3);
+
+        let count: i64 = client
+            .query_one("SELECT COUNT(*) FROM staging_load_concurrent.orders", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(count, 3);
+        assert_eq!(count_staging_tables(&client).await, 0);
+    }
+}
@@ -1,40 +1,104 @@
+use anyhow::{anyhow, Result};
 use indexmap::IndexMap;
 use std::fmt::Display;
 
+use super::conflict_behavior::ConflictBehavior;
+
 pub enum TableQuery {
-    FindAllColumns(String, String),
-    FindTablesForSchema(String, String),
-    DeleteRows(String, String, String, String),
+    FindAllColumns(String, String, Option<String>),
+    FindTablesForSchema(String, String, Option<String>),
+    DeleteRows(String, String, Vec<(String, String)>),
+    UpsertRow(
+        String,
+        String,
+        Vec<(String, String)>,
+        String,
+        ConflictBehavior,
+    ),
+    DeleteRowsBatch(String, String, Vec<String>, usize),
     FindPrimaryKey(String, String),
+    FindUniqueKeys(String, String),
+    FindForeignKeys(String),
     CreateSchema(String),
-    CreateTable(String, String, IndexMap<String, String>, String),
+    SchemaExists(String),
+    FindSerialSequence(String, String, String),
+    SetSequence(String, String, String, String),
+    TableExists(String, String),
+    CountRows(String, String),
+    VerifyRow(String, String, Vec<(String, String)>, Vec<(String, String)>),
+    CreateTable(
+        String,
+        String,
+        IndexMap<String, String>,
+        Vec<String>,
+        IndexMap<String, String>,
+    ),
+    CreateTempTable(String, IndexMap<String, String>, Vec<String>),
+    CountTempTableDifferences(String, String, String, String, Option<String>),
+    RollupHash(String, String, String, Option<String>),
+    DiscardTemp,
+    DeferConstraints,
+    FindCheckConstraints(String, String),
+    FindGeneratedColumns(String, String),
+    CountCheckConstraintViolations(String, String),
+    RenameColumn(String, String, String, String),
+    AddColumn(String, String, String, String),
+    MinMax(String, String, String),
+    DistinctValues(String, String, String, usize),
+    CountDistinctKeys(String, String, Vec<String>),
+    CountNullValues(String, String, String),
+    Histogram(String, String, String, usize),
+    CountOrphanedRows(String, String, String, Vec<(String, String)>),
+    CreateKeyTable(String, String, String, Vec<String>),
+    FindMissingKeys(String, String, String, Vec<String>),
+    CopyFromStdin(String, String, String),
+    CreateStagingTable(String, String, String),
+    InsertFromStaging(String, String, String, String),
+    DropStagingTable(String),
     DropSchema(String),
 }
 
 impl Display for TableQuery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            TableQuery::FindAllColumns(schema, table) => {
+            TableQuery::FindAllColumns(schema, table, catalog) => {
                 write!(
                     f,
-                    "SELECT column_name , data_type
+                    "SELECT column_name , data_type , numeric_precision::integer AS numeric_precision , numeric_scale::integer AS numeric_scale
                     FROM information_schema.columns 
                     WHERE table_schema = '{}' 
-                    AND table_name = '{}'",
-                    schema, table
+                    AND table_name = '{}'
+                    AND table_catalog = {}",
+                    schema,
+                    table,
+                    Self::catalog_predicate(catalog)
                 )
             }
-            TableQuery::FindTablesForSchema(schema, subquery) => {
+            TableQuery::FindTablesForSchema(schema, subquery, catalog) => {
                 write!(
                     f,
                     r#"SELECT table_name
                     FROM information_schema.tables
                     WHERE table_schema = '{}' {}
+                    AND table_catalog = {}
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_catalog.pg_class c
+                        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                        WHERE n.nspname = table_schema::name
+                        AND c.relname = table_name::name
+                        AND c.relispartition
+                    )
                     "#,
-                    schema, subquery
+                    schema,
+                    subquery,
+                    Self::catalog_predicate(catalog)
                 )
             }
-            TableQuery::DeleteRows(schema, table, primary_key, primary_key_value) => {
+            TableQuery::DeleteRows(schema, table, primary_key) => {
+                let (columns, values): (Vec<_>, Vec<_>) = primary_key
+                    .iter()
+                    .map(|(column, value)| (column.as_str(), value.as_str()))
+                    .unzip();
                 write!(
                     f,
                     // language=postgresql
@@ -42,7 +106,59 @@ impl Display for TableQuery {
                     DELETE FROM {}.{}
                     WHERE ({})=({})
                     "#,
-                    schema, table, primary_key, primary_key_value
+                    schema,
+                    table,
+                    columns.join(","),
+                    values.join(",")
+                )
+            }
+            TableQuery::UpsertRow(schema, table, values, primary_key, conflict_behavior) => {
+                let (columns, values): (Vec<_>, Vec<_>) = values
+                    .iter()
+                    .map(|(column, value)| (column.as_str(), value.as_str()))
+                    .unzip();
+                // On conflict, update the row or leave it unchanged, based on the conflict behavior.
+                // The rows of a table without a key are only inserted
+                let on_conflict = if primary_key.is_empty() {
+                    String::new()
+                } else {
+                    let assignments = columns
+                        .iter()
+                        .zip(values.iter())
+                        .map(|(column, value)| format!("{} = {}", column, value))
+                        .collect::<Vec<_>>();
+                    conflict_behavior.on_conflict_clause(primary_key, &assignments)
+                };
+                write!(
+                    f,
+                    "INSERT INTO {}.{} ({}) VALUES ({}){};",
+                    schema,
+                    table,
+                    columns.join(", "),
+                    values.join(", "),
+                    on_conflict
+                )
+            }
+            TableQuery::DeleteRowsBatch(schema, table, primary_key, rows) => {
+                let keys = (0..*rows)
+                    .map(|row| {
+                        let parameters = (1..=primary_key.len())
+                            .map(|column| format!("${}", row * primary_key.len() + column))
+                            .collect::<Vec<_>>();
+                        format!("({})", parameters.join(","))
+                    })
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    DELETE FROM {}.{}
+                    WHERE ({}) IN ({})
+                    "#,
+                    schema,
+                    table,
+                    primary_key.join(","),
+                    keys.join(",")
                 )
             }
             TableQuery::FindPrimaryKey(table, schema) => {
@@ -55,10 +171,148 @@ impl Display for TableQuery {
                     JOIN   pg_attribute a ON a.attrelid = i.indrelid
                     AND a.attnum = ANY(i.indkey)
                     WHERE  i.indrelid = '{}.{}'::regclass
-                    AND    i.indisprimary"#,
+                    AND    i.indisprimary
+                    ORDER  BY array_position(i.indkey::int2[], a.attnum)"#,
+                    schema, table,
+                )
+            }
+            TableQuery::FindUniqueKeys(table, schema) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT c.relname AS key_name,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(i.indkey::int2[]) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS columns
+                    FROM   pg_index i
+                    JOIN   pg_class c ON c.oid = i.indexrelid
+                    WHERE  i.indrelid = '{}.{}'::regclass
+                    AND    i.indisunique
+                    AND    NOT i.indisprimary
+                    AND    i.indpred IS NULL
+                    AND    i.indexprs IS NULL
+                    ORDER  BY c.relname"#,
                     schema, table,
                 )
             }
+            TableQuery::FindForeignKeys(schema) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT tn.nspname AS schema_name, t.relname AS table_name,
+                        rn.nspname AS referenced_schema_name, r.relname AS referenced_table_name,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(c.conkey) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS columns,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(c.confkey) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = c.confrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS referenced_columns
+                    FROM   pg_constraint c
+                    JOIN   pg_class t ON t.oid = c.conrelid
+                    JOIN   pg_namespace tn ON tn.oid = t.relnamespace
+                    JOIN   pg_class r ON r.oid = c.confrelid
+                    JOIN   pg_namespace rn ON rn.oid = r.relnamespace
+                    WHERE  c.contype = 'f'
+                    AND    tn.nspname = '{}'
+                    "#,
+                    schema
+                )
+            }
+            TableQuery::FindSerialSequence(schema, table, column) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT pg_get_serial_sequence('{}.{}', '{}') AS sequence_name
+                    "#,
+                    schema, table, column
+                )
+            }
+            TableQuery::SetSequence(sequence, schema, table, column) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT setval('{}', COALESCE(MAX({}), 1), MAX({}) IS NOT NULL) AS sequence_value
+                    FROM {}.{}
+                    "#,
+                    sequence, column, column, schema, table
+                )
+            }
+            TableQuery::SchemaExists(schema) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM information_schema.schemata
+                        WHERE schema_name = '{}'
+                    ) AS schema_exists
+                    "#,
+                    schema
+                )
+            }
+            TableQuery::TableExists(schema, table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM information_schema.tables
+                        WHERE table_schema = '{}'
+                        AND table_name = '{}'
+                    ) AS table_exists
+                    "#,
+                    schema, table
+                )
+            }
+            TableQuery::CountRows(schema, table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS row_count FROM {}.{}
+                    "#,
+                    schema, table
+                )
+            }
+            TableQuery::VerifyRow(schema, table, values, primary_key) => {
+                let checks = values
+                    .iter()
+                    .map(|(column, value)| {
+                        format!("{column} IS NOT DISTINCT FROM {value} AS {column}")
+                    })
+                    .collect::<Vec<_>>();
+                let (columns, values): (Vec<_>, Vec<_>) = primary_key
+                    .iter()
+                    .map(|(column, value)| (column.as_str(), value.as_str()))
+                    .unzip();
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT {}
+                    FROM {}.{}
+                    WHERE ({})=({})
+                    "#,
+                    checks.join(", "),
+                    schema,
+                    table,
+                    columns.join(","),
+                    values.join(",")
+                )
+            }
             TableQuery::CreateSchema(schema) => {
                 write!(
                     f,
@@ -70,22 +324,326 @@ impl Display for TableQuery {
                 )
             }
 
-            TableQuery::CreateTable(schema, table, column_data_types, primary_key) => {
-                let mut query = format!("CREATE TABLE IF NOT EXISTS {}.{} (", schema, table);
-
-                for (column, data_type) in column_data_types {
-                    query.push_str(&format!("{} {},", column, data_type));
-                }
-                if !primary_key.is_empty() {
-                    query.push_str(&format!("PRIMARY KEY ({})", primary_key));
-                } else {
-                    query.pop();
+            TableQuery::CreateTable(
+                schema,
+                table,
+                column_data_types,
+                primary_key,
+                storage_parameters,
+            ) => {
+                let query = format!("CREATE TABLE IF NOT EXISTS {}.{} (", schema, table);
+                let mut query = Self::with_table_definition(query, column_data_types, primary_key);
+                if !storage_parameters.is_empty() {
+                    let storage_parameters = storage_parameters
+                        .iter()
+                        .map(|(parameter, value)| format!("{} = {}", parameter, value))
+                        .collect::<Vec<_>>();
+                    query.push_str(&format!(" WITH ({})", storage_parameters.join(", ")));
                 }
-                query.push(')');
 
                 write!(f, "{}", query)
             }
 
+            TableQuery::CreateTempTable(table, column_data_types, primary_key) => {
+                let query = format!("CREATE TEMP TABLE IF NOT EXISTS {} (", table);
+
+                write!(
+                    f,
+                    "{}",
+                    Self::with_table_definition(query, column_data_types, primary_key)
+                )
+            }
+
+            TableQuery::CountTempTableDifferences(
+                schema,
+                table,
+                temp_table,
+                columns,
+                predicate,
+            ) => {
+                let table = match predicate {
+                    Some(predicate) => format!("{schema}.{table} WHERE {predicate}"),
+                    None => format!("{schema}.{table}"),
+                };
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS difference_count FROM (
+                        (SELECT {columns} FROM pg_temp.{temp_table} EXCEPT ALL SELECT {columns} FROM {table})
+                        UNION ALL
+                        (SELECT {columns} FROM {table} EXCEPT ALL SELECT {columns} FROM pg_temp.{temp_table})
+                    ) AS differences
+                    "#
+                )
+            }
+
+            TableQuery::RollupHash(schema, table, columns, predicate) => {
+                let table = match predicate {
+                    Some(predicate) => format!("{schema}.{table} WHERE {predicate}"),
+                    None => format!("{schema}.{table}"),
+                };
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS row_count,
+                        COALESCE(SUM(('x' || substr(md5(ROW({columns})::text), 1, 16))::bit(64)::bigint::numeric), 0)::text AS rollup_hash
+                    FROM {table}
+                    "#
+                )
+            }
+
+            TableQuery::DiscardTemp => {
+                write!(f, "DISCARD TEMP")
+            }
+            TableQuery::DeferConstraints => {
+                write!(f, "SET CONSTRAINTS ALL DEFERRED")
+            }
+
+            TableQuery::FindCheckConstraints(schema, table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT conname AS constraint_name, pg_get_constraintdef(oid) AS definition
+                    FROM pg_constraint
+                    WHERE conrelid = '{}.{}'::regclass
+                    AND contype = 'c'
+                    "#,
+                    schema, table
+                )
+            }
+
+            TableQuery::FindGeneratedColumns(schema, table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT column_name::text AS column_name, generation_expression::text AS generation_expression
+                    FROM information_schema.columns
+                    WHERE table_schema = '{}'
+                    AND table_name = '{}'
+                    AND is_generated = 'ALWAYS'
+                    ORDER BY ordinal_position
+                    "#,
+                    schema, table
+                )
+            }
+
+            TableQuery::CountCheckConstraintViolations(temp_table, check_expression) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS violating_rows
+                    FROM pg_temp.{}
+                    WHERE NOT {}
+                    "#,
+                    temp_table, check_expression
+                )
+            }
+
+            TableQuery::RenameColumn(schema, table, from, to) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    ALTER TABLE {}.{} RENAME COLUMN "{}" TO "{}"
+                    "#,
+                    schema, table, from, to
+                )
+            }
+            TableQuery::AddColumn(schema, table, column, data_type) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    ALTER TABLE {}.{} ADD COLUMN IF NOT EXISTS {} {}
+                    "#,
+                    schema, table, column, data_type
+                )
+            }
+            TableQuery::MinMax(schema, table, column) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT MIN({})::text AS min_value, MAX({})::text AS max_value
+                    FROM {}.{}
+                    "#,
+                    column, column, schema, table
+                )
+            }
+            TableQuery::DistinctValues(schema, table, column, limit) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT DISTINCT {}::text AS value
+                    FROM {}.{}
+                    WHERE {} IS NOT NULL
+                    LIMIT {}
+                    "#,
+                    column, schema, table, column, limit
+                )
+            }
+            TableQuery::CountNullValues(schema, table, column) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS null_count FROM {}.{} WHERE {} IS NULL
+                    "#,
+                    schema, table, column
+                )
+            }
+            TableQuery::CountDistinctKeys(schema, table, key_columns) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS key_count
+                    FROM (SELECT DISTINCT {} FROM {}.{}) AS distinct_keys
+                    "#,
+                    key_columns.join(", "),
+                    schema,
+                    table
+                )
+            }
+            TableQuery::Histogram(schema, table, column, buckets) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    WITH bounds AS (
+                        SELECT MIN({}::float8) AS min_value, MAX({}::float8) AS max_value
+                        FROM {}.{}
+                    )
+                    SELECT CASE
+                            WHEN bounds.max_value = bounds.min_value THEN 1
+                            ELSE LEAST(width_bucket({}::float8, bounds.min_value, bounds.max_value, {}), {})
+                        END AS bucket,
+                        COUNT(*) AS bucket_count
+                    FROM {}.{}, bounds
+                    WHERE {} IS NOT NULL
+                    GROUP BY bucket
+                    ORDER BY bucket
+                    "#,
+                    column, column, schema, table, column, buckets, buckets, schema, table, column
+                )
+            }
+            TableQuery::CountOrphanedRows(schema, table, referenced_table, columns) => {
+                let join_condition = columns
+                    .iter()
+                    .map(|(column, referenced_column)| {
+                        format!("c.{column} = p.{referenced_column}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" AND ");
+                // Rows with a null in the foreign key columns do not reference any row
+                let not_null = columns
+                    .iter()
+                    .map(|(column, _)| format!("c.{column} IS NOT NULL"))
+                    .collect::<Vec<_>>()
+                    .join(" AND ");
+                let (_, first_referenced_column) = columns.first().cloned().unwrap_or_default();
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT COUNT(*) AS orphaned_rows
+                    FROM {}.{} c
+                    LEFT JOIN {}.{} p ON {}
+                    WHERE p.{} IS NULL AND {}
+                    "#,
+                    schema,
+                    table,
+                    schema,
+                    referenced_table,
+                    join_condition,
+                    first_referenced_column,
+                    not_null
+                )
+            }
+            TableQuery::CreateKeyTable(schema, table, key_table, columns) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    CREATE TEMP TABLE IF NOT EXISTS {} AS
+                    SELECT {} FROM {}.{} WITH NO DATA
+                    "#,
+                    key_table,
+                    columns.join(", "),
+                    schema,
+                    table
+                )
+            }
+            TableQuery::FindMissingKeys(schema, table, key_table, columns) => {
+                let key = columns
+                    .iter()
+                    .map(|column| format!("k.{column}::text"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let join_condition = columns
+                    .iter()
+                    .map(|column| format!("k.{column} = t.{column}"))
+                    .collect::<Vec<_>>()
+                    .join(" AND ");
+                let first_column = columns.first().cloned().unwrap_or_default();
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    SELECT DISTINCT concat_ws(',', {}) AS primary_key
+                    FROM pg_temp.{} k
+                    LEFT JOIN {}.{} t ON {}
+                    WHERE t.{} IS NULL
+                    ORDER BY primary_key
+                    "#,
+                    key, key_table, schema, table, join_condition, first_column
+                )
+            }
+            TableQuery::CopyFromStdin(schema, table, columns) => {
+                write!(
+                    f,
+                    "COPY {}.{} ({}) FROM STDIN WITH (FORMAT csv)",
+                    schema, table, columns
+                )
+            }
+            TableQuery::CreateStagingTable(schema, table, staging_table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    CREATE TEMP TABLE IF NOT EXISTS {} (LIKE {}.{})
+                    "#,
+                    staging_table, schema, table
+                )
+            }
+            TableQuery::InsertFromStaging(schema, table, staging_table, columns) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    INSERT INTO {}.{} ({})
+                    SELECT {} FROM pg_temp.{}
+                    ON CONFLICT DO NOTHING
+                    "#,
+                    schema, table, columns, columns, staging_table
+                )
+            }
+            TableQuery::DropStagingTable(staging_table) => {
+                write!(
+                    f,
+                    // language=postgresql
+                    r#"
+                    DROP TABLE IF EXISTS pg_temp.{}
+                    "#,
+                    staging_table
+                )
+            }
             TableQuery::DropSchema(schema) => {
                 write!(
                     f,
@@ -100,39 +658,364 @@ impl Display for TableQuery {
     }
 }
 
+impl TableQuery {
+    /// Creates the query to delete the row of a primary key, binding each value
+    /// to the primary key column at the same position.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The name of the schema.
+    /// * `table` - The name of the table.
+    /// * `primary_key` - The primary key columns.
+    /// * `primary_key_value` - The values of the primary key columns, in the same order.
+    ///
+    /// # Returns
+    ///
+    /// The query, or an error if the number of columns and values differ.
+    pub fn delete_rows(
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        primary_key: &[String],
+        primary_key_value: &[String],
+    ) -> Result<Self> {
+        if primary_key.len() != primary_key_value.len() {
+            return Err(anyhow!(
+                "Primary key has {} columns ({}) but {} values ({})",
+                primary_key.len(),
+                primary_key.join(","),
+                primary_key_value.len(),
+                primary_key_value.join(",")
+            ));
+        }
+
+        let primary_key = primary_key
+            .iter()
+            .cloned()
+            .zip(primary_key_value.iter().cloned())
+            .collect();
+
+        Ok(TableQuery::DeleteRows(
+            schema.into(),
+            table.into(),
+            primary_key,
+        ))
+    }
+
+    /// Creates the query to upsert a row, i.e. to insert it or update the existing row
+    /// with the same primary key.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The name of the schema.
+    /// * `table` - The name of the table.
+    /// * `columns` - The columns of the row.
+    /// * `values` - The values of the columns, as SQL literals or parameters, in the same order.
+    /// * `primary_key` - The primary key columns. Without them, the row is only inserted.
+    /// * `conflict_behavior` - How a conflict with an existing row is handled.
+    ///
+    /// # Returns
+    ///
+    /// The query, or an error if the number of columns and values differ.
+    pub fn upsert_row(
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        columns: &[String],
+        values: &[String],
+        primary_key: &[String],
+        conflict_behavior: ConflictBehavior,
+    ) -> Result<Self> {
+        if columns.len() != values.len() {
+            return Err(anyhow!(
+                "Row has {} columns ({}) but {} values",
+                columns.len(),
+                columns.join(","),
+                values.len()
+            ));
+        }
+
+        Ok(TableQuery::UpsertRow(
+            schema.into(),
+            table.into(),
+            columns
+                .iter()
+                .cloned()
+                .zip(values.iter().cloned())
+                .collect(),
+            primary_key
+                .iter()
+                .filter(|column| !column.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(","),
+            conflict_behavior,
+        ))
+    }
+
+    /// Gets the catalog to scope the information_schema queries with.
+    ///
+    /// If no catalog is configured, the current database is used.
+    fn catalog_predicate(catalog: &Option<String>) -> String {
+        match catalog {
+            Some(catalog) => format!("'{}'", catalog),
+            None => "current_database()".to_string(),
+        }
+    }
+
+    /// Appends the column definitions and the primary key to a CREATE TABLE query.
+    fn with_table_definition(
+        mut query: String,
+        column_data_types: &IndexMap<String, String>,
+        primary_key: &[String],
+    ) -> String {
+        for (column, data_type) in column_data_types {
+            query.push_str(&format!("{} {},", column, data_type));
+        }
+        if !primary_key.is_empty() {
+            query.push_str(&format!("PRIMARY KEY ({})", primary_key.join(",")));
+        } else {
+            query.pop();
+        }
+        query.push(')');
+
+        query
+    }
+}
+
+/// Generates the upsert statement of a row, with a `$n` parameter for each column, e.g. to
+/// inspect or log it before a run. The upserts of the CDC files are built the same way,
+/// with the values of each row.
+///
+/// # Arguments
+///
+/// * `columns` - The columns of the table.
+/// * `primary_key` - The primary key columns.
+/// * `schema_name` - The name of the schema.
+/// * `table_name` - The name of the table.
+///
+/// # Returns
+///
+/// The parameterized upsert statement.
+pub fn generate_upsert_sql(
+    columns: &[String],
+    primary_key: &[String],
+    schema_name: &str,
+    table_name: &str,
+) -> String {
+    let parameters = (1..=columns.len())
+        .map(|parameter| format!("${}", parameter))
+        .collect::<Vec<_>>();
+
+    TableQuery::UpsertRow(
+        schema_name.to_string(),
+        table_name.to_string(),
+        columns.iter().cloned().zip(parameters).collect(),
+        primary_key.join(","),
+        ConflictBehavior::default(),
+    )
+    .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_display_find_all_columns() {
-        let query = TableQuery::FindAllColumns("schema".to_string(), "table".to_string());
+        let query = TableQuery::FindAllColumns("schema".to_string(), "table".to_string(), None);
         assert_eq!(
             query.to_string(),
-            "SELECT column_name , data_type
+            "SELECT column_name , data_type , numeric_precision::integer AS numeric_precision , numeric_scale::integer AS numeric_scale
                     FROM information_schema.columns 
                     WHERE table_schema = 'schema' 
-                    AND table_name = 'table'"
+                    AND table_name = 'table'
+                    AND table_catalog = current_database()"
         );
     }
 
     #[test]
-    fn test_display_delete_rows() {
-        let query = TableQuery::DeleteRows(
+    fn test_display_find_all_columns_with_catalog() {
+        let query = TableQuery::FindAllColumns(
             "schema".to_string(),
             "table".to_string(),
-            vec!["primary_key".to_string(), "primary_key2".to_string()]
-                .as_slice()
-                .join(","),
-            vec!["1".to_string(), "2".to_string()].as_slice().join(","),
+            Some("mydb".to_string()),
         );
         assert_eq!(
             query.to_string(),
-            r#"
-                    DELETE FROM schema.table
-                    WHERE (primary_key,primary_key2)=(1,2)
-                    "#
-        );
+            "SELECT column_name , data_type , numeric_precision::integer AS numeric_precision , numeric_scale::integer AS numeric_scale
+                    FROM information_schema.columns 
+                    WHERE table_schema = 'schema' 
+                    AND table_name = 'table'
+                    AND table_catalog = 'mydb'"
+        );
+    }
+
+    #[test]
+    fn test_display_find_tables_for_schema() {
+        let query = TableQuery::FindTablesForSchema(
+            "schema".to_string(),
+            "AND table_name IN ('table')".to_string(),
+            None,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"SELECT table_name
+                    FROM information_schema.tables
+                    WHERE table_schema = 'schema' AND table_name IN ('table')
+                    AND table_catalog = current_database()
+                    AND NOT EXISTS (
+                        SELECT 1 FROM pg_catalog.pg_class c
+                        JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+                        WHERE n.nspname = table_schema::name
+                        AND c.relname = table_name::name
+                        AND c.relispartition
+                    )
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_delete_rows() {
+        let query = TableQuery::delete_rows(
+            "schema",
+            "table",
+            &["primary_key".to_string(), "primary_key2".to_string()],
+            &["1".to_string(), "2".to_string()],
+        )
+        .unwrap();
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    DELETE FROM schema.table
+                    WHERE (primary_key,primary_key2)=(1,2)
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_generate_upsert_sql_with_composite_key() {
+        let columns = ["tenant_id", "id", "name", "email"].map(str::to_string);
+        let primary_key = ["tenant_id", "id"].map(str::to_string);
+
+        assert_eq!(
+            generate_upsert_sql(&columns, &primary_key, "schema", "table"),
+            "INSERT INTO schema.table (tenant_id, id, name, email) VALUES ($1, $2, $3, $4) \
+            ON CONFLICT (tenant_id,id) DO UPDATE SET tenant_id = $1, id = $2, name = $3, email = $4;"
+        );
+    }
+
+    #[test]
+    fn test_upsert_row_with_literals() {
+        let query = TableQuery::upsert_row(
+            "schema",
+            "table",
+            &["id".to_string(), "name".to_string()],
+            &["1".to_string(), "'a'".to_string()],
+            &["id".to_string()],
+            ConflictBehavior::DoNothing,
+        )
+        .unwrap();
+        assert_eq!(
+            query.to_string(),
+            "INSERT INTO schema.table (id, name) VALUES (1, 'a') ON CONFLICT (id) DO NOTHING;"
+        );
+
+        assert!(TableQuery::upsert_row(
+            "schema",
+            "table",
+            &["id".to_string()],
+            &[],
+            &["id".to_string()],
+            ConflictBehavior::DoUpdate,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_upsert_row_of_keyless_table() {
+        // A table without a primary key has an empty key, e.g. split from an empty string
+        let query = TableQuery::upsert_row(
+            "schema",
+            "table",
+            &["id".to_string(), "name".to_string()],
+            &["1".to_string(), "'a'".to_string()],
+            &["".to_string()],
+            ConflictBehavior::DoUpdate,
+        )
+        .unwrap();
+        assert_eq!(
+            query.to_string(),
+            "INSERT INTO schema.table (id, name) VALUES (1, 'a');"
+        );
+    }
+
+    #[test]
+    fn test_display_delete_rows_batch() {
+        let query = TableQuery::DeleteRowsBatch(
+            "schema".to_string(),
+            "table".to_string(),
+            vec!["tenant_id".to_string(), "id".to_string()],
+            3,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    DELETE FROM schema.table
+                    WHERE (tenant_id,id) IN (($1,$2),($3,$4),($5,$6))
+                    "#
+        );
+
+        let query = TableQuery::DeleteRowsBatch(
+            "schema".to_string(),
+            "table".to_string(),
+            vec!["id".to_string()],
+            2,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    DELETE FROM schema.table
+                    WHERE (id) IN (($1),($2))
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_delete_rows_pairs_columns_with_values() {
+        let query = TableQuery::delete_rows(
+            "schema",
+            "table",
+            &["tenant_id".to_string(), "id".to_string()],
+            &["7".to_string(), "42".to_string()],
+        )
+        .unwrap();
+
+        let TableQuery::DeleteRows(_, _, primary_key) = query else {
+            unreachable!();
+        };
+        assert_eq!(
+            primary_key,
+            vec![
+                ("tenant_id".to_string(), "7".to_string()),
+                ("id".to_string(), "42".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_delete_rows_with_mismatched_values() {
+        let error = TableQuery::delete_rows(
+            "schema",
+            "table",
+            &["tenant_id".to_string(), "id".to_string()],
+            &["42".to_string()],
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Primary key has 2 columns (tenant_id,id) but 1 values (42)"
+        );
     }
 
     #[test]
@@ -146,7 +1029,214 @@ mod tests {
                     JOIN   pg_attribute a ON a.attrelid = i.indrelid
                     AND a.attnum = ANY(i.indkey)
                     WHERE  i.indrelid = 'schema.table'::regclass
-                    AND    i.indisprimary"#
+                    AND    i.indisprimary
+                    ORDER  BY array_position(i.indkey::int2[], a.attnum)"#
+        );
+    }
+
+    #[test]
+    fn test_display_find_unique_keys() {
+        let query = TableQuery::FindUniqueKeys("table".to_string(), "schema".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT c.relname AS key_name,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(i.indkey::int2[]) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS columns
+                    FROM   pg_index i
+                    JOIN   pg_class c ON c.oid = i.indexrelid
+                    WHERE  i.indrelid = 'schema.table'::regclass
+                    AND    i.indisunique
+                    AND    NOT i.indisprimary
+                    AND    i.indpred IS NULL
+                    AND    i.indexprs IS NULL
+                    ORDER  BY c.relname"#
+        );
+    }
+
+    #[test]
+    fn test_display_find_foreign_keys() {
+        let query = TableQuery::FindForeignKeys("schema".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT tn.nspname AS schema_name, t.relname AS table_name,
+                        rn.nspname AS referenced_schema_name, r.relname AS referenced_table_name,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(c.conkey) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS columns,
+                        ARRAY(
+                            SELECT a.attname::text
+                            FROM   unnest(c.confkey) WITH ORDINALITY k(attnum, position)
+                            JOIN   pg_attribute a ON a.attrelid = c.confrelid AND a.attnum = k.attnum
+                            ORDER  BY k.position
+                        ) AS referenced_columns
+                    FROM   pg_constraint c
+                    JOIN   pg_class t ON t.oid = c.conrelid
+                    JOIN   pg_namespace tn ON tn.oid = t.relnamespace
+                    JOIN   pg_class r ON r.oid = c.confrelid
+                    JOIN   pg_namespace rn ON rn.oid = r.relnamespace
+                    WHERE  c.contype = 'f'
+                    AND    tn.nspname = 'schema'
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_orphaned_rows() {
+        let query = TableQuery::CountOrphanedRows(
+            "schema".to_string(),
+            "order_items".to_string(),
+            "orders".to_string(),
+            vec![
+                ("order_id".to_string(), "id".to_string()),
+                ("tenant_id".to_string(), "tenant_id".to_string()),
+            ],
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS orphaned_rows
+                    FROM schema.order_items c
+                    LEFT JOIN schema.orders p ON c.order_id = p.id AND c.tenant_id = p.tenant_id
+                    WHERE p.id IS NULL AND c.order_id IS NOT NULL AND c.tenant_id IS NOT NULL
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_key_table_queries() {
+        let columns = vec!["tenant_id".to_string(), "id".to_string()];
+
+        let query = TableQuery::CreateKeyTable(
+            "schema".to_string(),
+            "table".to_string(),
+            "keys_table".to_string(),
+            columns.clone(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    CREATE TEMP TABLE IF NOT EXISTS keys_table AS
+                    SELECT tenant_id, id FROM schema.table WITH NO DATA
+                    "#
+        );
+
+        let query = TableQuery::FindMissingKeys(
+            "schema".to_string(),
+            "table".to_string(),
+            "keys_table".to_string(),
+            columns,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT DISTINCT concat_ws(',', k.tenant_id::text, k.id::text) AS primary_key
+                    FROM pg_temp.keys_table k
+                    LEFT JOIN schema.table t ON k.tenant_id = t.tenant_id AND k.id = t.id
+                    WHERE t.tenant_id IS NULL
+                    ORDER BY primary_key
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_find_serial_sequence() {
+        let query = TableQuery::FindSerialSequence(
+            "schema".to_string(),
+            "table".to_string(),
+            "id".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT pg_get_serial_sequence('schema.table', 'id') AS sequence_name
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_set_sequence() {
+        let query = TableQuery::SetSequence(
+            "schema.table_id_seq".to_string(),
+            "schema".to_string(),
+            "table".to_string(),
+            "id".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT setval('schema.table_id_seq', COALESCE(MAX(id), 1), MAX(id) IS NOT NULL) AS sequence_value
+                    FROM schema.table
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_schema_exists() {
+        let query = TableQuery::SchemaExists("schema".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM information_schema.schemata
+                        WHERE schema_name = 'schema'
+                    ) AS schema_exists
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_table_exists() {
+        let query = TableQuery::TableExists("schema".to_string(), "table".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM information_schema.tables
+                        WHERE table_schema = 'schema'
+                        AND table_name = 'table'
+                    ) AS table_exists
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_rows() {
+        let query = TableQuery::CountRows("schema".to_string(), "table".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS row_count FROM schema.table
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_verify_row() {
+        let query = TableQuery::VerifyRow(
+            "schema".to_string(),
+            "table".to_string(),
+            vec![
+                ("id".to_string(), "1".to_string()),
+                ("name".to_string(), "'a'".to_string()),
+            ],
+            vec![("id".to_string(), "1".to_string())],
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT id IS NOT DISTINCT FROM 1 AS id, name IS NOT DISTINCT FROM 'a' AS name
+                    FROM schema.table
+                    WHERE (id)=(1)
+                    "#
         );
     }
 
@@ -161,6 +1251,183 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_display_rename_column() {
+        let query = TableQuery::RenameColumn(
+            "schema".to_string(),
+            "table".to_string(),
+            "Op".to_string(),
+            "dms_op".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    ALTER TABLE schema.table RENAME COLUMN "Op" TO "dms_op"
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_add_column() {
+        let query = TableQuery::AddColumn(
+            "schema".to_string(),
+            "table".to_string(),
+            "email".to_string(),
+            "text".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    ALTER TABLE schema.table ADD COLUMN IF NOT EXISTS email text
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_min_max() {
+        let query = TableQuery::MinMax(
+            "schema".to_string(),
+            "table".to_string(),
+            "created_at".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT MIN(created_at)::text AS min_value, MAX(created_at)::text AS max_value
+                    FROM schema.table
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_distinct_values() {
+        let query = TableQuery::DistinctValues(
+            "schema".to_string(),
+            "table".to_string(),
+            "status".to_string(),
+            101,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT DISTINCT status::text AS value
+                    FROM schema.table
+                    WHERE status IS NOT NULL
+                    LIMIT 101
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_distinct_keys() {
+        let query = TableQuery::CountDistinctKeys(
+            "schema".to_string(),
+            "table".to_string(),
+            vec!["tenant_id".to_string(), "id".to_string()],
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS key_count
+                    FROM (SELECT DISTINCT tenant_id, id FROM schema.table) AS distinct_keys
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_null_values() {
+        let query = TableQuery::CountNullValues(
+            "schema".to_string(),
+            "table".to_string(),
+            "created_at".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS null_count FROM schema.table WHERE created_at IS NULL
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_histogram() {
+        let query = TableQuery::Histogram(
+            "schema".to_string(),
+            "table".to_string(),
+            "amount".to_string(),
+            10,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    WITH bounds AS (
+                        SELECT MIN(amount::float8) AS min_value, MAX(amount::float8) AS max_value
+                        FROM schema.table
+                    )
+                    SELECT CASE
+                            WHEN bounds.max_value = bounds.min_value THEN 1
+                            ELSE LEAST(width_bucket(amount::float8, bounds.min_value, bounds.max_value, 10), 10)
+                        END AS bucket,
+                        COUNT(*) AS bucket_count
+                    FROM schema.table, bounds
+                    WHERE amount IS NOT NULL
+                    GROUP BY bucket
+                    ORDER BY bucket
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_copy_from_stdin() {
+        let query = TableQuery::CopyFromStdin(
+            "schema".to_string(),
+            "table".to_string(),
+            "id, name".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            "COPY schema.table (id, name) FROM STDIN WITH (FORMAT csv)"
+        );
+    }
+
+    #[test]
+    fn test_display_staging_table_queries() {
+        let query = TableQuery::CreateStagingTable(
+            "schema".to_string(),
+            "table".to_string(),
+            "staging_table".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    CREATE TEMP TABLE IF NOT EXISTS staging_table (LIKE schema.table)
+                    "#
+        );
+
+        let query = TableQuery::InsertFromStaging(
+            "schema".to_string(),
+            "table".to_string(),
+            "staging_table".to_string(),
+            "id, name".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    INSERT INTO schema.table (id, name)
+                    SELECT id, name FROM pg_temp.staging_table
+                    ON CONFLICT DO NOTHING
+                    "#
+        );
+
+        let query = TableQuery::DropStagingTable("staging_table".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    DROP TABLE IF EXISTS pg_temp.staging_table
+                    "#
+        );
+    }
+
     #[test]
     fn test_display_drop_schema() {
         let query = TableQuery::DropSchema("schema".to_string());
@@ -177,19 +1444,189 @@ mod tests {
         let mut column_data_types = IndexMap::new();
         column_data_types.insert("column1".to_string(), "varchar".to_string());
         column_data_types.insert("column2".to_string(), "int".to_string());
-        let primary_keys = vec!["primary_key".to_string(), "primary_key2".to_string()]
-            .as_slice()
-            .join(",");
+        let primary_keys = vec!["primary_key".to_string(), "primary_key2".to_string()];
+
+        let query = TableQuery::CreateTable(
+            "schema".to_string(),
+            "table".to_string(),
+            column_data_types.clone(),
+            primary_keys.clone(),
+            IndexMap::new(),
+        );
+        assert_eq!(
+            query.to_string(),
+            "CREATE TABLE IF NOT EXISTS schema.table (column1 varchar,column2 int,PRIMARY KEY (primary_key,primary_key2))"
+        );
 
         let query = TableQuery::CreateTable(
             "schema".to_string(),
             "table".to_string(),
             column_data_types,
             primary_keys,
+            IndexMap::from([
+                ("fillfactor".to_string(), "70".to_string()),
+                ("autovacuum_enabled".to_string(), "false".to_string()),
+            ]),
         );
         assert_eq!(
             query.to_string(),
-            "CREATE TABLE IF NOT EXISTS schema.table (column1 varchar,column2 int,PRIMARY KEY (primary_key,primary_key2))"
+            "CREATE TABLE IF NOT EXISTS schema.table (column1 varchar,column2 int,PRIMARY KEY (primary_key,primary_key2)) \
+            WITH (fillfactor = 70, autovacuum_enabled = false)"
         );
     }
+
+    #[test]
+    fn test_display_create_table_keeps_primary_key_order() {
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("tenant_id".to_string(), "int".to_string());
+        column_data_types.insert("created_at".to_string(), "timestamp".to_string());
+        column_data_types.insert("id".to_string(), "bigint".to_string());
+
+        let query = TableQuery::CreateTable(
+            "schema".to_string(),
+            "table".to_string(),
+            column_data_types,
+            vec!["id".to_string(), "tenant_id".to_string()],
+            IndexMap::new(),
+        );
+        assert_eq!(
+            query.to_string(),
+            "CREATE TABLE IF NOT EXISTS schema.table (tenant_id int,created_at timestamp,id bigint,PRIMARY KEY (id,tenant_id))"
+        );
+    }
+
+    #[test]
+    fn test_display_create_temp_table() {
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("column1".to_string(), "varchar".to_string());
+        column_data_types.insert("column2".to_string(), "int".to_string());
+
+        let query = TableQuery::CreateTempTable(
+            "tmp_table".to_string(),
+            column_data_types,
+            vec!["column1".to_string()],
+        );
+        assert_eq!(
+            query.to_string(),
+            "CREATE TEMP TABLE IF NOT EXISTS tmp_table (column1 varchar,column2 int,PRIMARY KEY (column1))"
+        );
+    }
+
+    #[test]
+    fn test_display_count_temp_table_differences() {
+        let query = TableQuery::CountTempTableDifferences(
+            "schema".to_string(),
+            "table".to_string(),
+            "tmp_table".to_string(),
+            "column1, column2".to_string(),
+            None,
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS difference_count FROM (
+                        (SELECT column1, column2 FROM pg_temp.tmp_table EXCEPT ALL SELECT column1, column2 FROM schema.table)
+                        UNION ALL
+                        (SELECT column1, column2 FROM schema.table EXCEPT ALL SELECT column1, column2 FROM pg_temp.tmp_table)
+                    ) AS differences
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_rollup_hash() {
+        let query = TableQuery::RollupHash(
+            "pg_temp".to_string(),
+            "tmp_table".to_string(),
+            "column1, column2".to_string(),
+            Some("column1 >= 1".to_string()),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS row_count,
+                        COALESCE(SUM(('x' || substr(md5(ROW(column1, column2)::text), 1, 16))::bit(64)::bigint::numeric), 0)::text AS rollup_hash
+                    FROM pg_temp.tmp_table WHERE column1 >= 1
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_temp_table_differences_with_predicate() {
+        let query = TableQuery::CountTempTableDifferences(
+            "schema".to_string(),
+            "table".to_string(),
+            "tmp_table".to_string(),
+            "id".to_string(),
+            Some(r#""id" >= 0 AND "id" < 10"#.to_string()),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS difference_count FROM (
+                        (SELECT id FROM pg_temp.tmp_table EXCEPT ALL SELECT id FROM schema.table WHERE "id" >= 0 AND "id" < 10)
+                        UNION ALL
+                        (SELECT id FROM schema.table WHERE "id" >= 0 AND "id" < 10 EXCEPT ALL SELECT id FROM pg_temp.tmp_table)
+                    ) AS differences
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_find_check_constraints() {
+        let query = TableQuery::FindCheckConstraints("schema".to_string(), "table".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT conname AS constraint_name, pg_get_constraintdef(oid) AS definition
+                    FROM pg_constraint
+                    WHERE conrelid = 'schema.table'::regclass
+                    AND contype = 'c'
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_find_generated_columns() {
+        let query = TableQuery::FindGeneratedColumns("schema".to_string(), "table".to_string());
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT column_name::text AS column_name, generation_expression::text AS generation_expression
+                    FROM information_schema.columns
+                    WHERE table_schema = 'schema'
+                    AND table_name = 'table'
+                    AND is_generated = 'ALWAYS'
+                    ORDER BY ordinal_position
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_count_check_constraint_violations() {
+        let query = TableQuery::CountCheckConstraintViolations(
+            "tmp_table".to_string(),
+            "(price > 0)".to_string(),
+        );
+        assert_eq!(
+            query.to_string(),
+            r#"
+                    SELECT COUNT(*) AS violating_rows
+                    FROM pg_temp.tmp_table
+                    WHERE NOT (price > 0)
+                    "#
+        );
+    }
+
+    #[test]
+    fn test_display_discard_temp() {
+        let query = TableQuery::DiscardTemp;
+        assert_eq!(query.to_string(), "DISCARD TEMP");
+    }
+
+    #[test]
+    fn test_display_defer_constraints() {
+        let query = TableQuery::DeferConstraints;
+        assert_eq!(query.to_string(), "SET CONSTRAINTS ALL DEFERRED");
+    }
 }
@@ -0,0 +1,994 @@
+use anyhow::{anyhow, Result};
+use deadpool_postgres::{Client, GenericClient};
+use indexmap::IndexMap;
+use log::info;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::copy_loader::copy_dataframe_with_client;
+use super::postgres_operator::{InsertDataframePayload, UpsertDataframePayload};
+use super::postgres_operator_impl::{insert_dataframe_with_method, upsert_dataframe_with_client};
+use super::staging_load::unique_table_name;
+use super::table_query::TableQuery::*;
+use crate::dataframe::key_range::KeyRange;
+
+/// Represents a check constraint of a target table that rows loaded in a temporary table violate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckConstraintViolation {
+    pub constraint_name: String,
+    pub definition: String,
+    pub violating_rows: i64,
+}
+
+/// Represents the primary keys of the Parquet files of a table that are missing in the target table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingKeys {
+    pub table_name: String,
+    pub primary_keys: Vec<String>,
+}
+
+/// Represents a session that is pinned to a single client of the connection pool.
+///
+/// Temporary tables are only visible to the connection that created them, so the
+/// loading of the Parquet files and the comparison against the target tables
+/// must go through the same client. The temporary tables are discarded when the
+/// session is closed, so nothing is left behind when the client returns to the pool.
+pub struct TempTableSession {
+    client: Client,
+    temp_table_names: Mutex<HashMap<String, String>>,
+    key_table_names: Mutex<HashMap<String, String>>,
+}
+
+impl TempTableSession {
+    /// Creates a new temp table session.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to pin for the whole session.
+    ///
+    /// # Returns
+    ///
+    /// A new temp table session instance.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            temp_table_names: Mutex::new(HashMap::new()),
+            key_table_names: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the name of the temporary table created for a table in the session.
+    pub fn temp_table_name(&self, table_name: &str) -> Result<String> {
+        self.temp_table_names
+            .lock()
+            .unwrap()
+            .get(table_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("No temporary table was created for table {}", table_name))
+    }
+
+    /// Gets a unique name of the temporary table of the primary keys of a table.
+    pub fn key_table_name(table_name: &str) -> String {
+        unique_table_name("keys", table_name)
+    }
+
+    /// Creates a temporary table with the same columns as the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `column_data_types` - The data types of the columns in the table.
+    /// * `primary_key` - The primary key of the table.
+    /// * `table_name` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// The name of the temporary table, unique within the session, which is tracked until
+    /// the temporary tables are discarded when the session is closed.
+    pub async fn create_temp_table(
+        &self,
+        column_data_types: &IndexMap<String, String>,
+        primary_key: &[String],
+        table_name: &str,
+    ) -> Result<String> {
+        let temp_table_name = unique_table_name("tmp", table_name);
+        let query = CreateTempTable(
+            temp_table_name.clone(),
+            column_data_types.clone(),
+            primary_key.to_vec(),
+        );
+
+        self.client.execute(&query.to_string(), &[]).await?;
+        self.temp_table_names
+            .lock()
+            .unwrap()
+            .insert(table_name.to_string(), temp_table_name.clone());
+
+        Ok(temp_table_name)
+    }
+
+    /// Adds a column to the temporary table of a table, for the columns added by later files.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_name` - The name of the table.
+    /// * `column_name` - The name of the column to add.
+    /// * `data_type` - The data type of the column to add.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn add_column_to_temp_table(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        data_type: &str,
+    ) -> Result<()> {
+        let query = AddColumn(
+            "pg_temp".to_string(),
+            self.temp_table_name(table_name)?,
+            column_name.to_string(),
+            data_type.to_string(),
+        );
+        self.client.execute(&query.to_string(), &[]).await?;
+
+        Ok(())
+    }
+
+    /// Inserts a DataFrame in the temporary table of the table in the payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame to insert.
+    /// * `payload` - The payload describing the table.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn insert_dataframe_in_temp_table(
+        &self,
+        df: &DataFrame,
+        payload: &InsertDataframePayload,
+    ) -> Result<()> {
+        let mut df = df.clone();
+
+        // Drop the columns added by DMS
+        _ = df.drop_in_place("Op")?;
+        _ = df.drop_in_place("_dms_ingestion_timestamp")?;
+
+        insert_dataframe_with_method(
+            &self.client,
+            &df,
+            "pg_temp",
+            &self.temp_table_name(&payload.table_name)?,
+            payload.insert_method,
+        )
+        .await
+    }
+
+    /// Upserts a DataFrame in the temporary table of the table in the payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The DataFrame to upsert.
+    /// * `payload` - The payload describing the table.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn upsert_dataframe_in_temp_table(
+        &self,
+        df: &DataFrame,
+        payload: &UpsertDataframePayload,
+    ) -> Result<()> {
+        let temp_table_payload = UpsertDataframePayload {
+            database_name: payload.database_name.clone(),
+            schema_name: "pg_temp".to_string(),
+            table_name: self.temp_table_name(&payload.table_name)?,
+            primary_key: payload.primary_key.clone(),
+            conflict_behavior: payload.conflict_behavior,
+            unmatched_delete_policy: payload.unmatched_delete_policy.clone(),
+            incomplete_delete_policy: payload.incomplete_delete_policy,
+            delete_batch_size: payload.delete_batch_size,
+            deferred_constraints: false,
+        };
+
+        upsert_dataframe_with_client(&self.client, df, &temp_table_payload).await
+    }
+
+    /// Gets the predicate that restricts the rows of a target table to the ones compared with its
+    /// temporary table, i.e. the rows within the key range and, when the files were sampled, the
+    /// rows with a key loaded in the temporary table.
+    fn target_predicate(
+        &self,
+        table_name: &str,
+        key_range: Option<&KeyRange>,
+        sampled_key: Option<&[String]>,
+    ) -> Result<Option<String>> {
+        let sampled_key_predicate = match sampled_key {
+            Some(sampled_key) => {
+                let key = sampled_key
+                    .iter()
+                    .map(|column| format!(r#""{column}""#))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some(format!(
+                    "({key}) IN (SELECT {key} FROM pg_temp.{})",
+                    self.temp_table_name(table_name)?
+                ))
+            }
+            None => None,
+        };
+        let predicates = [
+            key_range.map(KeyRange::to_sql_predicate),
+            sampled_key_predicate,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        Ok((!predicates.is_empty()).then(|| predicates.join(" AND ")))
+    }
+
+    /// Counts the rows that differ between the temporary table and the target table.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the target table.
+    /// * `table_name` - The name of the target table.
+    /// * `columns` - The columns to compare.
+    /// * `key_range` - If set, only the rows of the table within the key range are compared.
+    /// * `sampled_key` - If set, the primary key of the sampled rows loaded in the temporary
+    ///   table, and only the rows of the table with these keys are compared.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows found only in one of the two tables.
+    pub async fn count_differences(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        columns: &[String],
+        key_range: Option<&KeyRange>,
+        sampled_key: Option<&[String]>,
+    ) -> Result<i64> {
+        let query = CountTempTableDifferences(
+            schema_name.to_string(),
+            table_name.to_string(),
+            self.temp_table_name(table_name)?,
+            columns.join(", "),
+            self.target_predicate(table_name, key_range, sampled_key)?,
+        );
+
+        let row = self.client.query_one(&query.to_string(), &[]).await?;
+        let difference_count: i64 = row.get("difference_count");
+        info!("Rows differing for table {schema_name}.{table_name}: {difference_count}");
+
+        Ok(difference_count)
+    }
+
+    /// Checks whether the temporary table and the target table have the same rollup hash,
+    /// i.e. the same number of rows and the same order-independent sum of the hashes of their rows.
+    ///
+    /// Equal rollups imply, with high probability, equal tables, so the detailed comparison can
+    /// be skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the target table.
+    /// * `table_name` - The name of the target table.
+    /// * `columns` - The columns to hash.
+    /// * `key_range` - If set, only the rows of the table within the key range are hashed.
+    /// * `sampled_key` - If set, the primary key of the sampled rows loaded in the temporary
+    ///   table, and only the rows of the table with these keys are hashed.
+    ///
+    /// # Returns
+    ///
+    /// Whether the rollup hashes of the two tables are equal.
+    pub async fn rollup_hashes_match(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        columns: &[String],
+        key_range: Option<&KeyRange>,
+        sampled_key: Option<&[String]>,
+    ) -> Result<bool> {
+        let temp_table_query = RollupHash(
+            "pg_temp".to_string(),
+            self.temp_table_name(table_name)?,
+            columns.join(", "),
+            None,
+        );
+        let table_query = RollupHash(
+            schema_name.to_string(),
+            table_name.to_string(),
+            columns.join(", "),
+            self.target_predicate(table_name, key_range, sampled_key)?,
+        );
+
+        let mut rollups = Vec::with_capacity(2);
+        for query in [temp_table_query, table_query] {
+            let row = self.client.query_one(&query.to_string(), &[]).await?;
+            let row_count: i64 = row.get("row_count");
+            let rollup_hash: String = row.get("rollup_hash");
+            rollups.push((row_count, rollup_hash));
+        }
+        let rollup_hashes_match = rollups[0] == rollups[1];
+        info!("Rollup hashes match for table {schema_name}.{table_name}: {rollup_hashes_match}");
+
+        Ok(rollup_hashes_match)
+    }
+
+    /// Stages the primary keys of a chunk of the rows of the Parquet files of a table, e.g. the rows
+    /// of a single file, so that the keys of a large table are never held in memory at once.
+    ///
+    /// The keys are streamed with COPY into a temporary table with the key columns of the target
+    /// table, created with the first chunk. If the copy fails, the staged keys are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the target table.
+    /// * `table_name` - The name of the target table.
+    /// * `primary_key` - The primary key columns of the table.
+    /// * `df` - The rows of the chunk, including at least the primary key columns.
+    ///
+    /// # Returns
+    ///
+    /// The number of keys staged.
+    pub async fn stage_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        primary_key: &[String],
+        df: &DataFrame,
+    ) -> Result<u64> {
+        let key_table_name = self
+            .key_table_names
+            .lock()
+            .unwrap()
+            .entry(table_name.to_string())
+            .or_insert_with(|| Self::key_table_name(table_name))
+            .clone();
+
+        let result = async {
+            let query = CreateKeyTable(
+                schema_name.to_string(),
+                table_name.to_string(),
+                key_table_name.clone(),
+                primary_key.to_vec(),
+            );
+            self.client.execute(&query.to_string(), &[]).await?;
+
+            copy_dataframe_with_client(
+                &self.client,
+                &df.select(primary_key)?,
+                "pg_temp",
+                &key_table_name,
+            )
+            .await
+        }
+        .await;
+        if result.is_err() {
+            self.drop_key_table(table_name).await?;
+        }
+
+        result
+    }
+
+    /// Finds the staged primary keys of a table that are missing in the target table.
+    ///
+    /// The temporary table of the staged keys is anti-joined with the target table, and dropped
+    /// afterwards, whether the query succeeded or failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the target table.
+    /// * `table_name` - The name of the target table.
+    /// * `primary_key` - The primary key columns of the table.
+    ///
+    /// # Returns
+    ///
+    /// The primary keys missing in the target table, with the values of composite keys comma separated.
+    pub async fn find_missing_keys(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        primary_key: &[String],
+    ) -> Result<MissingKeys> {
+        let key_table_name = self
+            .key_table_names
+            .lock()
+            .unwrap()
+            .get(table_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("No primary keys were staged for table {}", table_name))?;
+
+        let query = FindMissingKeys(
+            schema_name.to_string(),
+            table_name.to_string(),
+            key_table_name,
+            primary_key.to_vec(),
+        );
+        let rows = self.client.query(&query.to_string(), &[]).await;
+        let dropped = self.drop_key_table(table_name).await;
+        let primary_keys = rows?
+            .iter()
+            .map(|row| row.get("primary_key"))
+            .collect::<Vec<String>>();
+        dropped?;
+        info!(
+            "Primary keys missing in table {schema_name}.{table_name}: {}",
+            primary_keys.len()
+        );
+
+        Ok(MissingKeys {
+            table_name: table_name.to_string(),
+            primary_keys,
+        })
+    }
+
+    /// Drops the temporary table of the staged primary keys of a table, if any.
+    async fn drop_key_table(&self, table_name: &str) -> Result<()> {
+        let key_table_name = self.key_table_names.lock().unwrap().remove(table_name);
+        if let Some(key_table_name) = key_table_name {
+            self.client
+                .execute(&DropStagingTable(key_table_name).to_string(), &[])
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Finds the rows of the temporary table that violate the check constraints of the target table.
+    ///
+    /// The check constraints are discovered from the target table, and each one is evaluated
+    /// against the rows of the temporary table, without inserting anything in the target table.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema of the target table.
+    /// * `table_name` - The name of the target table.
+    ///
+    /// # Returns
+    ///
+    /// The violated check constraints, along with the number of violating rows.
+    pub async fn find_check_constraint_violations(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+    ) -> Result<Vec<CheckConstraintViolation>> {
+        let query = FindCheckConstraints(schema_name.to_string(), table_name.to_string());
+        let constraints = self.client.query(&query.to_string(), &[]).await?;
+
+        let mut violations = Vec::new();
+        for constraint in constraints {
+            let constraint_name: String = constraint.get("constraint_name");
+            let definition: String = constraint.get("definition");
+
+            let query = CountCheckConstraintViolations(
+                self.temp_table_name(table_name)?,
+                check_expression(&definition).to_string(),
+            );
+            let row = self.client.query_one(&query.to_string(), &[]).await?;
+            let violating_rows: i64 = row.get("violating_rows");
+
+            if violating_rows > 0 {
+                violations.push(CheckConstraintViolation {
+                    constraint_name,
+                    definition,
+                    violating_rows,
+                });
+            }
+        }
+
+        Ok(violations)
+    }
+
+    /// Discards the temporary tables of the session and returns the client to the pool.
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    pub async fn close(self) -> Result<()> {
+        self.client.execute(&DiscardTemp.to_string(), &[]).await?;
+
+        Ok(())
+    }
+
+    /// Closes the session after the work done in it, whether it succeeded or failed, so that
+    /// a failed table does not return the client to the pool with its temporary tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the work done in the session.
+    ///
+    /// # Returns
+    ///
+    /// The result of the work, or the error of the close if the work succeeded.
+    pub async fn close_with<T>(self, result: Result<T>) -> Result<T> {
+        let closed = self.close().await;
+        let value = result?;
+        closed?;
+
+        Ok(value)
+    }
+}
+
+/// Gets the expression of a check constraint definition, e.g. `(price > 0)` of `CHECK ((price > 0))`.
+fn check_expression(definition: &str) -> &str {
+    let definition = definition.trim();
+    let definition = definition
+        .strip_suffix("NOT VALID")
+        .unwrap_or(definition)
+        .trim();
+
+    definition
+        .strip_prefix("CHECK")
+        .unwrap_or(definition)
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use deadpool_postgres::GenericClient;
+    use indexmap::IndexMap;
+
+    use polars::prelude::*;
+
+    use super::{check_expression, MissingKeys};
+    use crate::postgres::conflict_behavior::ConflictBehavior;
+    use crate::postgres::copy_loader::InsertMethod;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::postgres_operator::{
+        InsertDataframePayload, PostgresOperator, UpsertDataframePayload,
+    };
+    use crate::postgres::staging_load::LoadMode;
+    use crate::postgres::table_mode::TableMode;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::test_support::{connect_to_test_db, test_pool};
+
+    #[test]
+    fn test_check_expression() {
+        assert_eq!(check_expression("CHECK ((price > 0))"), "((price > 0))");
+        assert_eq!(
+            check_expression("CHECK ((price > (0)::numeric)) NOT VALID"),
+            "((price > (0)::numeric))"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_temp_table_vanishes_after_session_is_closed() {
+        let pool = connect_to_test_db().await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        let temp_table_name = session
+            .create_temp_table(&column_data_types, &["id".to_string()], "orders")
+            .await
+            .unwrap();
+        let query =
+            format!("SELECT to_regclass('pg_temp.{temp_table_name}') IS NOT NULL AS exists");
+        let exists: bool = session
+            .client
+            .query_one(&query, &[])
+            .await
+            .unwrap()
+            .get("exists");
+        assert!(exists);
+        session.close().await.unwrap();
+
+        // The pool has a single connection, so the same connection is returned
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        let exists: bool = session
+            .client
+            .query_one(&query, &[])
+            .await
+            .unwrap()
+            .get("exists");
+        assert!(!exists);
+        session.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_temp_table_vanishes_after_failed_load() {
+        let pool = connect_to_test_db().await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        let temp_table_name = session
+            .create_temp_table(&column_data_types, &["id".to_string()], "orders")
+            .await
+            .unwrap();
+        // The load fails, since the DataFrame has a column missing in the temporary table
+        let df =
+            DataFrame::new(vec![Series::new("id", &[1]), Series::new("amount", &[10])]).unwrap();
+        let result = session
+            .insert_dataframe_in_temp_table(
+                &df,
+                &InsertDataframePayload {
+                    database_name: "mydb".to_string(),
+                    schema_name: "public".to_string(),
+                    table_name: "orders".to_string(),
+                    insert_method: InsertMethod::default(),
+                    load_mode: LoadMode::default(),
+                    copy_commit_rows: None,
+                },
+            )
+            .await;
+        assert!(session.close_with(result).await.is_err());
+
+        // The pool has a single connection, so the same connection is returned
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        let query =
+            format!("SELECT to_regclass('pg_temp.{temp_table_name}') IS NOT NULL AS exists");
+        let exists: bool = session
+            .client
+            .query_one(&query, &[])
+            .await
+            .unwrap()
+            .get("exists");
+        assert!(!exists);
+        session.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_find_check_constraint_violations() {
+        let pool = test_pool("check_constraints").await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        session
+            .client
+            .batch_execute(
+                "CREATE TABLE check_constraints.products (id int PRIMARY KEY, price int CONSTRAINT positive_price CHECK (price > 0));",
+            )
+            .await
+            .unwrap();
+
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+        column_data_types.insert("price".to_string(), "int".to_string());
+        session
+            .create_temp_table(&column_data_types, &["id".to_string()], "products")
+            .await
+            .unwrap();
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I", "I"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[1, 2]),
+            Series::new("price", &[10, -5]),
+        ])
+        .unwrap();
+        let payload = InsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "check_constraints".to_string(),
+            table_name: "products".to_string(),
+            insert_method: InsertMethod::default(),
+            load_mode: LoadMode::default(),
+            copy_commit_rows: None,
+        };
+        session
+            .insert_dataframe_in_temp_table(&df, &payload)
+            .await
+            .unwrap();
+
+        let violations = session
+            .find_check_constraint_violations("check_constraints", "products")
+            .await
+            .unwrap();
+        session.close().await.unwrap();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].constraint_name, "positive_price");
+        assert_eq!(violations[0].violating_rows, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_compare_with_partitioned_parent() {
+        let pool = test_pool("partitioned").await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        session
+            .client
+            .batch_execute(
+                "CREATE TABLE partitioned.events (id int PRIMARY KEY, amount int) PARTITION BY RANGE (id);
+                CREATE TABLE partitioned.events_low PARTITION OF partitioned.events FOR VALUES FROM (0) TO (100);
+                CREATE TABLE partitioned.events_high PARTITION OF partitioned.events FOR VALUES FROM (100) TO (200);",
+            )
+            .await
+            .unwrap();
+        session.close().await.unwrap();
+
+        // The partitions are not listed as tables of their own
+        let tables = postgres_operator
+            .get_tables_in_schema("partitioned", &[], &[], &TableMode::AllTables)
+            .await
+            .unwrap();
+        assert_eq!(tables, vec!["events".to_string()]);
+
+        // The rows are routed to the partitions through the parent
+        for insert_method in [InsertMethod::Insert, InsertMethod::Copy] {
+            let df = DataFrame::new(vec![
+                Series::new("Op", &["I", "I"]),
+                Series::new("_dms_ingestion_timestamp", &["", ""]),
+                Series::new("id", &[1, 150]),
+                Series::new("amount", &[10, 20]),
+            ])
+            .unwrap();
+            let payload = InsertDataframePayload {
+                database_name: "mydb".to_string(),
+                schema_name: "partitioned".to_string(),
+                table_name: "events".to_string(),
+                insert_method,
+                load_mode: LoadMode::default(),
+                copy_commit_rows: None,
+            };
+            let session = postgres_operator.temp_table_session().await.unwrap();
+            session
+                .client
+                .execute("TRUNCATE partitioned.events", &[])
+                .await
+                .unwrap();
+            session.close().await.unwrap();
+            postgres_operator
+                .insert_dataframe_in_target_db(&df, &payload)
+                .await
+                .unwrap();
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["U", "D"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[150, 1]),
+            Series::new("amount", &[30, 10]),
+        ])
+        .unwrap();
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "partitioned".to_string(),
+            table_name: "events".to_string(),
+            primary_key: "id".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
+        };
+        postgres_operator
+            .upsert_dataframe_in_target_db(&df, &payload)
+            .await
+            .unwrap();
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        let count = |table: &str| format!("SELECT COUNT(*) AS count FROM partitioned.{table}");
+        for (table, expected_count) in [("events", 1i64), ("events_low", 0), ("events_high", 1)] {
+            let actual_count: i64 = session
+                .client
+                .query_one(&count(table), &[])
+                .await
+                .unwrap()
+                .get("count");
+            assert_eq!(actual_count, expected_count);
+        }
+
+        // The comparison against the parent includes the rows of all the partitions
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+        column_data_types.insert("amount".to_string(), "int".to_string());
+        session
+            .create_temp_table(&column_data_types, &["id".to_string()], "events")
+            .await
+            .unwrap();
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["I"]),
+            Series::new("_dms_ingestion_timestamp", &[""]),
+            Series::new("id", &[150]),
+            Series::new("amount", &[30]),
+        ])
+        .unwrap();
+        session
+            .insert_dataframe_in_temp_table(
+                &df,
+                &InsertDataframePayload {
+                    database_name: "mydb".to_string(),
+                    schema_name: "partitioned".to_string(),
+                    table_name: "events".to_string(),
+                    insert_method: InsertMethod::default(),
+                    load_mode: LoadMode::default(),
+                    copy_commit_rows: None,
+                },
+            )
+            .await
+            .unwrap();
+        let difference_count = session
+            .count_differences(
+                "partitioned",
+                "events",
+                &["id".to_string(), "amount".to_string()],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        session.close().await.unwrap();
+
+        assert_eq!(difference_count, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_rollup_hashes_match() {
+        let pool = test_pool("rollup").await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        session
+            .client
+            .batch_execute(
+                "CREATE TABLE rollup.users (id int PRIMARY KEY, name text);
+                INSERT INTO rollup.users VALUES (1, 'a'), (2, 'b');",
+            )
+            .await
+            .unwrap();
+
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+        column_data_types.insert("name".to_string(), "text".to_string());
+        session
+            .create_temp_table(&column_data_types, &["id".to_string()], "users")
+            .await
+            .unwrap();
+        let columns = vec!["id".to_string(), "name".to_string()];
+        // The temporary table has a unique name within the session
+        let temp_table_name = session.temp_table_name("users").unwrap();
+        let rollup_hashes_match = |statement: &'static str| {
+            let session = &session;
+            let columns = &columns;
+            let statement = statement.replace("{temp_table}", &temp_table_name);
+            async move {
+                session.client.batch_execute(&statement).await.unwrap();
+                session
+                    .rollup_hashes_match("rollup", "users", columns, None, None)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // The rows are inserted in a different order
+        assert!(
+            rollup_hashes_match("INSERT INTO pg_temp.{temp_table} VALUES (2, 'b'), (1, 'a')").await
+        );
+        // A changed row changes the rollup
+        assert!(
+            !rollup_hashes_match("UPDATE pg_temp.{temp_table} SET name = 'c' WHERE id = 2").await
+        );
+        assert!(
+            rollup_hashes_match("UPDATE pg_temp.{temp_table} SET name = 'b' WHERE id = 2").await
+        );
+        // An added row changes the rollup
+        assert!(!rollup_hashes_match("INSERT INTO pg_temp.{temp_table} VALUES (3, 'c')").await);
+
+        session.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_count_differences_of_sampled_keys() {
+        let pool = test_pool("sampled").await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        session
+            .client
+            .batch_execute(
+                "CREATE TABLE sampled.users (id int PRIMARY KEY, name text);
+                INSERT INTO sampled.users VALUES (1, 'a'), (2, 'b'), (3, 'c');",
+            )
+            .await
+            .unwrap();
+
+        let mut column_data_types = IndexMap::new();
+        column_data_types.insert("id".to_string(), "int".to_string());
+        column_data_types.insert("name".to_string(), "text".to_string());
+        let temp_table_name = session
+            .create_temp_table(&column_data_types, &["id".to_string()], "users")
+            .await
+            .unwrap();
+        // Only the key 2 is sampled
+        session
+            .client
+            .batch_execute(&format!(
+                "INSERT INTO pg_temp.{temp_table_name} VALUES (2, 'b')"
+            ))
+            .await
+            .unwrap();
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let sampled_key = vec!["id".to_string()];
+
+        let sampled_difference_count = session
+            .count_differences("sampled", "users", &columns, None, Some(&sampled_key))
+            .await
+            .unwrap();
+        let difference_count = session
+            .count_differences("sampled", "users", &columns, None, None)
+            .await
+            .unwrap();
+        session.close().await.unwrap();
+
+        assert_eq!((sampled_difference_count, difference_count), (0, 2));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_find_missing_keys() {
+        let pool = test_pool("missing_keys").await;
+        let postgres_operator = PostgresOperatorImpl::new(pool);
+
+        let session = postgres_operator.temp_table_session().await.unwrap();
+        session
+            .client
+            .batch_execute(
+                "CREATE TABLE missing_keys.users (tenant_id int, id int, name text, PRIMARY KEY (tenant_id, id));
+                INSERT INTO missing_keys.users VALUES (1, 1, 'a'), (1, 2, 'b');",
+            )
+            .await
+            .unwrap();
+
+        let chunks = [
+            DataFrame::new(vec![
+                Series::new("Op", &["I", "I"]),
+                Series::new("tenant_id", &[1, 1]),
+                Series::new("id", &[1, 3]),
+                Series::new("name", &["a", "c"]),
+            ])
+            .unwrap(),
+            DataFrame::new(vec![
+                Series::new("Op", &["I", "U"]),
+                Series::new("tenant_id", &[2, 1]),
+                Series::new("id", &[1, 3]),
+                Series::new("name", &["d", "e"]),
+            ])
+            .unwrap(),
+        ];
+        let primary_key = vec!["tenant_id".to_string(), "id".to_string()];
+
+        for chunk in &chunks {
+            session
+                .stage_keys("missing_keys", "users", &primary_key, chunk)
+                .await
+                .unwrap();
+        }
+        let missing_keys = session
+            .find_missing_keys("missing_keys", "users", &primary_key)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            missing_keys,
+            MissingKeys {
+                table_name: "users".to_string(),
+                primary_keys: vec!["1,3".to_string(), "2,1".to_string()],
+            }
+        );
+        // The staged keys are dropped once checked
+        assert!(session
+            .find_missing_keys("missing_keys", "users", &primary_key)
+            .await
+            .is_err());
+        session.close().await.unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+/// Represents the key used for a table without a primary key.
+///
+/// The fallback can be one of the following:
+///
+/// * Disabled - No key is used, as if the table had no unique key.
+/// * First - The first unique key of the table, by name, is used.
+/// * Named - The unique key with the given name is used, if the table has it.
+///
+/// Only the unique keys on plain columns and without a predicate are considered.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum UniqueKeyFallback {
+    #[default]
+    Disabled,
+    First,
+    Named(String),
+}
+
+/// Represents a unique key of a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueKey {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+impl UniqueKeyFallback {
+    /// Selects the unique key used for a table without a primary key.
+    ///
+    /// # Arguments
+    ///
+    /// * `unique_keys` - The unique keys of the table, ordered by name.
+    ///
+    /// # Returns
+    ///
+    /// The selected unique key, if any.
+    pub fn select(&self, unique_keys: Vec<UniqueKey>) -> Option<UniqueKey> {
+        match self {
+            UniqueKeyFallback::Disabled => None,
+            UniqueKeyFallback::First => unique_keys.into_iter().next(),
+            UniqueKeyFallback::Named(name) => unique_keys
+                .into_iter()
+                .find(|unique_key| &unique_key.name == name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::postgres_operator::PostgresOperator;
+    use crate::postgres::postgres_operator_impl::PostgresOperatorImpl;
+    use crate::postgres::test_support::test_pool;
+
+    fn unique_keys() -> Vec<UniqueKey> {
+        vec![
+            UniqueKey {
+                name: "users_email_key".to_string(),
+                columns: vec!["email".to_string()],
+            },
+            UniqueKey {
+                name: "users_tenant_id_username_key".to_string(),
+                columns: vec!["tenant_id".to_string(), "username".to_string()],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_select_unique_key() {
+        assert_eq!(UniqueKeyFallback::Disabled.select(unique_keys()), None);
+        assert_eq!(
+            UniqueKeyFallback::First.select(unique_keys()),
+            Some(unique_keys()[0].clone())
+        );
+        assert_eq!(
+            UniqueKeyFallback::Named("users_tenant_id_username_key".to_string())
+                .select(unique_keys()),
+            Some(unique_keys()[1].clone())
+        );
+        assert_eq!(
+            UniqueKeyFallback::Named("missing_key".to_string()).select(unique_keys()),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_unique_key_is_used_without_primary_key() {
+        let pool = test_pool("unique_keys").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE unique_keys.users (
+                    tenant_id int NOT NULL,
+                    username text NOT NULL,
+                    email text UNIQUE,
+                    UNIQUE (tenant_id, username)
+                );",
+            )
+            .await
+            .unwrap();
+        drop(client);
+
+        let postgres_operator = PostgresOperatorImpl::new(pool.clone());
+        assert!(postgres_operator
+            .get_primary_key("users", "unique_keys")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let postgres_operator = PostgresOperatorImpl::new(pool.clone())
+            .with_unique_key_fallback(UniqueKeyFallback::First);
+        assert_eq!(
+            postgres_operator
+                .get_primary_key("users", "unique_keys")
+                .await
+                .unwrap(),
+            vec!["email".to_string()]
+        );
+
+        let postgres_operator = PostgresOperatorImpl::new(pool).with_unique_key_fallback(
+            UniqueKeyFallback::Named("users_tenant_id_username_key".to_string()),
+        );
+        assert_eq!(
+            postgres_operator
+                .get_primary_key("users", "unique_keys")
+                .await
+                .unwrap(),
+            vec!["tenant_id".to_string(), "username".to_string()]
+        );
+    }
+}
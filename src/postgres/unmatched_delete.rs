@@ -0,0 +1,207 @@
+use log::warn;
+use std::sync::{Arc, Mutex};
+
+/// Represents a delete that matched no row of the target table,
+/// which may signal an ordering or data problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmatchedDelete {
+    pub schema_name: String,
+    pub table_name: String,
+    pub primary_key_value: String,
+}
+
+/// Represents a shared record of the deletes that matched no row.
+#[derive(Debug, Clone, Default)]
+pub struct UnmatchedDeleteRecorder(Arc<Mutex<Vec<UnmatchedDelete>>>);
+
+impl UnmatchedDeleteRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the deletes recorded so far.
+    pub fn deletes(&self) -> Vec<UnmatchedDelete> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn record(&self, unmatched_delete: UnmatchedDelete) {
+        self.0.lock().unwrap().push(unmatched_delete);
+    }
+
+    fn extend(&self, unmatched_deletes: Vec<UnmatchedDelete>) {
+        self.0.lock().unwrap().extend(unmatched_deletes);
+    }
+}
+
+/// Represents how a delete that matched no row of the target table is handled.
+///
+/// The policy can be one of the following:
+///
+/// * Ignore - The delete is ignored.
+/// * Warn - A warning is logged.
+/// * Record - A warning is logged and the delete is recorded in the given recorder.
+#[derive(Debug, Clone, Default)]
+pub enum UnmatchedDeletePolicy {
+    #[default]
+    Ignore,
+    Warn,
+    Record(UnmatchedDeleteRecorder),
+}
+
+impl UnmatchedDeletePolicy {
+    /// Handles the result of a delete based on the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_name` - The name of the schema.
+    /// * `table_name` - The name of the table.
+    /// * `primary_key_value` - The primary key value of the deleted row.
+    /// * `rows_deleted` - The number of rows the delete affected.
+    pub fn on_delete(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        primary_key_value: &str,
+        rows_deleted: u64,
+    ) {
+        if rows_deleted > 0 {
+            return;
+        }
+
+        match self {
+            UnmatchedDeletePolicy::Ignore => {}
+            UnmatchedDeletePolicy::Warn => {
+                warn!("Delete of {primary_key_value} matched no row in table {schema_name}.{table_name}");
+            }
+            UnmatchedDeletePolicy::Record(recorder) => {
+                warn!("Delete of {primary_key_value} matched no row in table {schema_name}.{table_name}");
+                recorder.record(UnmatchedDelete {
+                    schema_name: schema_name.to_string(),
+                    table_name: table_name.to_string(),
+                    primary_key_value: primary_key_value.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Gets the policy for an attempt of a transaction that may be rolled back, which records
+    /// the deletes in a recorder of its own rather than in the recorder of this policy.
+    ///
+    /// # Returns
+    ///
+    /// The policy of the attempt.
+    pub fn for_attempt(&self) -> Self {
+        match self {
+            UnmatchedDeletePolicy::Record(_) => {
+                UnmatchedDeletePolicy::Record(UnmatchedDeleteRecorder::new())
+            }
+            policy => policy.clone(),
+        }
+    }
+
+    /// Records the deletes of an attempt in the recorder of this policy, once the transaction
+    /// of the attempt is committed.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The policy of the attempt, as returned by `for_attempt`.
+    pub fn commit(&self, attempt: &UnmatchedDeletePolicy) {
+        if let (
+            UnmatchedDeletePolicy::Record(recorder),
+            UnmatchedDeletePolicy::Record(attempt_recorder),
+        ) = (self, attempt)
+        {
+            recorder.extend(attempt_recorder.deletes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use deadpool_postgres::GenericClient;
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::postgres::conflict_behavior::ConflictBehavior;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::postgres_operator::UpsertDataframePayload;
+    use crate::postgres::postgres_operator_impl::upsert_dataframe_with_client;
+    use crate::postgres::test_support::test_pool;
+
+    #[test]
+    fn test_record_delete_matching_no_row() {
+        let recorder = UnmatchedDeleteRecorder::new();
+        let policy = UnmatchedDeletePolicy::Record(recorder.clone());
+
+        policy.on_delete("public", "users", "1", 1);
+        policy.on_delete("public", "users", "2", 0);
+
+        assert_eq!(
+            recorder.deletes(),
+            vec![UnmatchedDelete {
+                schema_name: "public".to_string(),
+                table_name: "users".to_string(),
+                primary_key_value: "2".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_deletes_of_rolled_back_attempt_are_not_recorded() {
+        let recorder = UnmatchedDeleteRecorder::new();
+        let policy = UnmatchedDeletePolicy::Record(recorder.clone());
+
+        // The first attempt is rolled back, and the retry is committed
+        let rolled_back = policy.for_attempt();
+        rolled_back.on_delete("public", "users", "2", 0);
+        assert!(recorder.deletes().is_empty());
+
+        let committed = policy.for_attempt();
+        committed.on_delete("public", "users", "2", 0);
+        policy.commit(&committed);
+
+        assert_eq!(recorder.deletes().len(), 1);
+        assert_eq!(recorder.deletes()[0].primary_key_value, "2");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the Postgres DB of docker-compose"]
+    async fn test_delete_of_non_existent_key_is_recorded() {
+        let pool = test_pool("unmatched_delete").await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "CREATE TABLE unmatched_delete.users (id int PRIMARY KEY, name text);
+                INSERT INTO unmatched_delete.users VALUES (1, 'first');",
+            )
+            .await
+            .unwrap();
+
+        let df = DataFrame::new(vec![
+            Series::new("Op", &["D", "D"]),
+            Series::new("_dms_ingestion_timestamp", &["", ""]),
+            Series::new("id", &[1, 2]),
+            Series::new("name", &["first", "missing"]),
+        ])
+        .unwrap();
+        let recorder = UnmatchedDeleteRecorder::new();
+        let payload = UpsertDataframePayload {
+            database_name: "mydb".to_string(),
+            schema_name: "unmatched_delete".to_string(),
+            table_name: "users".to_string(),
+            primary_key: "id".to_string(),
+            conflict_behavior: ConflictBehavior::default(),
+            unmatched_delete_policy: UnmatchedDeletePolicy::Record(recorder.clone()),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
+        };
+        upsert_dataframe_with_client(&client, &df, &payload)
+            .await
+            .unwrap();
+
+        let deletes = recorder.deletes();
+        assert_eq!(deletes.len(), 1);
+        assert_eq!(deletes[0].primary_key_value, "2");
+    }
+}
@@ -0,0 +1,257 @@
+use anyhow::Result;
+use log::warn;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::conflict_behavior::ConflictBehavior;
+use super::postgres_operator::{PostgresOperator, UpsertDataframePayload};
+use super::postgres_row_struct::RowStruct;
+
+/// Represents an opt-in verification of the upserts, which re-reads a sample of the upserted
+/// rows from the target and checks that each column now holds the applied value, e.g. to catch
+/// a column kept at its old value by a rule of the target.
+///
+/// Each sampled row is re-read with its own query, so the sample should be kept small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpsertVerification {
+    pub sample_size: usize,
+}
+
+/// Represents an upserted row that does not hold the applied values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnappliedUpdate {
+    pub primary_key_value: String,
+    /// The column that does not hold the applied value, or None if the row does not exist.
+    pub column: Option<String>,
+}
+
+impl UpsertVerification {
+    /// Samples the rows expected in the target after an upsert, i.e. the last operation of each
+    /// key that is not a delete. The rows are sampled evenly across the DataFrame.
+    ///
+    /// # Arguments
+    ///
+    /// * `df` - The upserted DataFrame.
+    /// * `primary_key` - The primary key columns.
+    ///
+    /// # Returns
+    ///
+    /// At most `sample_size` rows of the DataFrame.
+    pub fn sample_rows(&self, df: &DataFrame, primary_key: &[String]) -> Result<DataFrame> {
+        let df = df.unique_stable(Some(primary_key), UniqueKeepStrategy::Last, None)?;
+        let df = df.filter(&df.column("Op")?.str()?.not_equal("D"))?;
+        if self.sample_size == 0 {
+            return Ok(df.clear());
+        }
+
+        let step = df.height().div_ceil(self.sample_size).max(1);
+        let indices = (0..df.height())
+            .step_by(step)
+            .map(|row| row as IdxSize)
+            .collect::<Vec<_>>();
+
+        Ok(df.take(&IdxCa::from_vec("", indices))?)
+    }
+
+    /// Verifies that a sample of the upserted rows holds the applied values in the target.
+    ///
+    /// The upserts that leave the existing rows unchanged on conflict are not verified, as
+    /// their rows are not expected to hold the applied values.
+    ///
+    /// # Arguments
+    ///
+    /// * `postgres_operator` - The operator of the target database.
+    /// * `df` - The upserted DataFrame.
+    /// * `payload` - The payload of the upsert.
+    ///
+    /// # Returns
+    ///
+    /// The sampled rows and columns that do not hold the applied values.
+    pub async fn verify(
+        &self,
+        postgres_operator: &(impl PostgresOperator + Sync),
+        df: &DataFrame,
+        payload: &UpsertDataframePayload,
+    ) -> Result<Vec<UnappliedUpdate>> {
+        if payload.conflict_behavior == ConflictBehavior::DoNothing {
+            return Ok(Vec::new());
+        }
+
+        let primary_key = payload
+            .primary_key
+            .split(',')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let rows = self.sample_rows(df, &primary_key)?;
+        let literals = |columns: &[&Series], row: usize| {
+            columns
+                .iter()
+                .map(|column| {
+                    let value = column.get(row)?;
+                    Ok((
+                        column.name().to_string(),
+                        RowStruct::new(&value).displayed(),
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let value_columns = rows
+            .get_columns()
+            .iter()
+            .filter(|column| column.name() != "Op" && column.name() != "_dms_ingestion_timestamp")
+            .collect::<Vec<_>>();
+        let key_columns = rows.columns(&primary_key)?;
+
+        let mut unapplied_updates = Vec::new();
+        for row in 0..rows.height() {
+            let key = literals(&key_columns, row)?;
+            let primary_key_value = key
+                .iter()
+                .map(|(column, value)| format!("{column}={value}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let unapplied_columns = postgres_operator
+                .find_unapplied_columns(
+                    &payload.schema_name,
+                    &payload.table_name,
+                    &literals(&value_columns, row)?,
+                    &key,
+                )
+                .await?;
+            match unapplied_columns {
+                None => unapplied_updates.push(UnappliedUpdate {
+                    primary_key_value,
+                    column: None,
+                }),
+                Some(columns) => {
+                    unapplied_updates.extend(columns.into_iter().map(|column| UnappliedUpdate {
+                        primary_key_value: primary_key_value.clone(),
+                        column: Some(column),
+                    }))
+                }
+            }
+        }
+
+        for unapplied_update in &unapplied_updates {
+            warn!(
+                "Upsert of row ({}) of table {}.{} did not apply column {}",
+                unapplied_update.primary_key_value,
+                payload.schema_name,
+                payload.table_name,
+                unapplied_update.column.as_deref().unwrap_or("*")
+            );
+        }
+
+        Ok(unapplied_updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::incomplete_delete::IncompleteDeletePolicy;
+    use crate::postgres::postgres_operator::MockPostgresOperator;
+    use crate::postgres::unmatched_delete::UnmatchedDeletePolicy;
+
+    fn upsert_payload() -> UpsertDataframePayload {
+        UpsertDataframePayload {
+            database_name: "database_name".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "users".to_string(),
+            primary_key: "id".to_string(),
+            conflict_behavior: ConflictBehavior::DoUpdate,
+            unmatched_delete_policy: UnmatchedDeletePolicy::default(),
+            incomplete_delete_policy: IncompleteDeletePolicy::default(),
+            delete_batch_size: None,
+            deferred_constraints: false,
+        }
+    }
+
+    fn upserted_rows() -> DataFrame {
+        df!(
+            "Op" => &["I", "U", "I", "D"],
+            "_dms_ingestion_timestamp" => &[
+                "2024-01-01 00:00:01",
+                "2024-01-01 00:00:02",
+                "2024-01-01 00:00:03",
+                "2024-01-01 00:00:04"
+            ],
+            "id" => &[1i64, 1, 2, 3],
+            "name" => &["a", "b", "c", "d"]
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sample_rows_keeps_last_operation_of_each_key() {
+        let rows = UpsertVerification { sample_size: 10 }
+            .sample_rows(&upserted_rows(), &["id".to_string()])
+            .unwrap();
+
+        let names = rows.column("name").unwrap().str().unwrap();
+        assert_eq!(
+            names.into_iter().collect::<Vec<_>>(),
+            vec![Some("b"), Some("c")]
+        );
+
+        let rows = UpsertVerification { sample_size: 1 }
+            .sample_rows(&upserted_rows(), &["id".to_string()])
+            .unwrap();
+        assert_eq!(rows.height(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verified_update_passes() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_find_unapplied_columns()
+            .withf(|schema_name, table_name, values, _| {
+                schema_name == "public"
+                    && table_name == "users"
+                    && !values.iter().any(|(column, _)| column == "Op")
+            })
+            .times(2)
+            .returning(|_, _, _, _| Ok(Some(Vec::new())));
+
+        let unapplied_updates = UpsertVerification { sample_size: 10 }
+            .verify(&postgres_operator, &upserted_rows(), &upsert_payload())
+            .await
+            .unwrap();
+
+        assert!(unapplied_updates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_skipped_column_fails_verification() {
+        let mut postgres_operator = MockPostgresOperator::new();
+        postgres_operator
+            .expect_find_unapplied_columns()
+            .returning(|_, _, _, primary_key| {
+                if primary_key == [("id".to_string(), "1".to_string())] {
+                    Ok(Some(vec!["name".to_string()]))
+                } else {
+                    Ok(None)
+                }
+            });
+
+        let unapplied_updates = UpsertVerification { sample_size: 10 }
+            .verify(&postgres_operator, &upserted_rows(), &upsert_payload())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            unapplied_updates,
+            vec![
+                UnappliedUpdate {
+                    primary_key_value: "id=1".to_string(),
+                    column: Some("name".to_string()),
+                },
+                UnappliedUpdate {
+                    primary_key_value: "id=2".to_string(),
+                    column: None,
+                },
+            ]
+        );
+    }
+}
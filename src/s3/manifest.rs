@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Result};
+
+use super::s3_operator::S3ParquetFile;
+
+/// Parses a manifest of the Parquet files to validate, either a JSON array of S3 keys or
+/// a plain-text list with one S3 key per line.
+///
+/// # Arguments
+///
+/// * `content` - The content of the manifest.
+///
+/// # Returns
+///
+/// The listed keys, in order. Blank lines of a plain-text manifest are ignored.
+pub fn parse_manifest(content: &str) -> Result<Vec<String>> {
+    let content = content.trim();
+
+    if content.starts_with('[') {
+        let keys: Vec<String> = serde_json::from_str(content)
+            .map_err(|e| anyhow!("Invalid JSON manifest, expected a list of keys: {}", e))?;
+        return Ok(keys
+            .into_iter()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect());
+    }
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Selects the files of a table listed in a manifest, which may list the files of several tables,
+/// and orders them so that the LOAD files come first, since they are processed in INSERT mode,
+/// while keeping the order of the manifest otherwise.
+///
+/// # Arguments
+///
+/// * `keys` - The keys listed in the manifest.
+/// * `table_prefix` - The prefix of the keys of the table.
+///
+/// # Returns
+///
+/// The Parquet files of the table, LOAD files first.
+pub fn manifest_files(keys: Vec<String>, table_prefix: &str) -> Vec<S3ParquetFile> {
+    let (load_files, cdc_files): (Vec<_>, Vec<_>) = keys
+        .into_iter()
+        .filter(|key| key.starts_with(table_prefix))
+        .map(S3ParquetFile::new)
+        .partition(S3ParquetFile::is_load_file);
+
+    load_files.into_iter().chain(cdc_files).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain_text_manifest() {
+        let content = "
+            data/mydb/public/users/20240101-000000000.parquet
+
+            data/mydb/public/users/LOAD00000001.parquet
+        ";
+
+        assert_eq!(
+            parse_manifest(content).unwrap(),
+            vec![
+                "data/mydb/public/users/20240101-000000000.parquet",
+                "data/mydb/public/users/LOAD00000001.parquet",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_json_manifest() {
+        let content = r#"[
+            "data/mydb/public/users/20240101-000000000.parquet",
+            "data/mydb/public/users/LOAD00000001.parquet"
+        ]"#;
+
+        assert_eq!(
+            parse_manifest(content).unwrap(),
+            vec![
+                "data/mydb/public/users/20240101-000000000.parquet",
+                "data/mydb/public/users/LOAD00000001.parquet",
+            ]
+        );
+        assert!(parse_manifest(r#"[{"key": "a.parquet"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_manifest_files_are_load_first() {
+        let files = manifest_files(
+            vec![
+                "users/20240102-000000000.parquet".to_string(),
+                "users/LOAD00000001.parquet".to_string(),
+                "users/20240101-000000000.parquet".to_string(),
+                "users/LOAD00000002.parquet".to_string(),
+            ],
+            "users/",
+        );
+
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "users/LOAD00000001.parquet",
+                "users/LOAD00000002.parquet",
+                "users/20240102-000000000.parquet",
+                "users/20240101-000000000.parquet",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest_files_of_two_tables() {
+        let content = "
+            data/mydb/public/users/LOAD00000001.parquet
+            data/mydb/public/orders/LOAD00000001.parquet
+            data/mydb/public/users_archive/LOAD00000001.parquet
+            data/mydb/public/orders/20240101-000000000.parquet
+            data/mydb/public/users/20240101-000000000.parquet
+        ";
+        let keys = parse_manifest(content).unwrap();
+
+        let file_names = |table_prefix: &str| {
+            manifest_files(keys.clone(), table_prefix)
+                .into_iter()
+                .map(|file| file.file_name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            file_names("data/mydb/public/users/"),
+            vec![
+                "data/mydb/public/users/LOAD00000001.parquet",
+                "data/mydb/public/users/20240101-000000000.parquet",
+            ]
+        );
+        assert_eq!(
+            file_names("data/mydb/public/orders/"),
+            vec![
+                "data/mydb/public/orders/LOAD00000001.parquet",
+                "data/mydb/public/orders/20240101-000000000.parquet",
+            ]
+        );
+    }
+}
@@ -1,4 +1,8 @@
+pub mod manifest;
+pub mod partition_listing;
+pub mod path_style;
 pub mod s3_operator;
+pub mod server_side_encryption;
 
 #[cfg(test)]
 mod s3_operator_tests;
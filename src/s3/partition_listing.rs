@@ -0,0 +1,186 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Days, NaiveDate};
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use super::path_style::PathStyle;
+use super::s3_operator::S3ParquetFile;
+
+/// The default number of partitions listed concurrently, kept low to stay within
+/// the request rate limits of S3 and the request quotas of the account.
+pub const DEFAULT_LIST_CONCURRENCY: usize = 4;
+
+#[async_trait]
+pub trait PartitionLister {
+    /// Lists the files of a date partition.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket
+    /// * `partition_prefix` - The prefix of the partition, e.g. `prefix/2024/03/01/`
+    ///
+    /// # Returns
+    ///
+    /// The files of the partition, in the order of their keys.
+    async fn list_partition(
+        &self,
+        bucket_name: &str,
+        partition_prefix: &str,
+    ) -> Result<Vec<S3ParquetFile>>;
+}
+
+/// Gets the prefixes of the daily partitions of a table between two dates.
+///
+/// # Arguments
+///
+/// * `prefix_path` - The prefix of the table
+/// * `start_date` - The first date, inclusive
+/// * `stop_date` - The last date, inclusive
+/// * `path_style` - The style of the partition paths
+///
+/// # Returns
+///
+/// The prefixes of the partitions, e.g. `prefix/2024/03/01/`.
+pub fn day_partitions(
+    prefix_path: &str,
+    start_date: NaiveDate,
+    stop_date: NaiveDate,
+    path_style: PathStyle,
+) -> Vec<String> {
+    let mut partitions = Vec::new();
+    let mut date = start_date;
+    while date <= stop_date {
+        partitions.push(path_style.date_path(prefix_path, date));
+        date = match date.checked_add_days(Days::new(1)) {
+            Some(date) => date,
+            None => break,
+        };
+    }
+    partitions
+}
+
+/// Lists the files of several partitions, with at most `list_concurrency` list calls in flight.
+///
+/// # Arguments
+///
+/// * `partition_lister` - The lister of a single partition
+/// * `bucket_name` - The name of the S3 bucket
+/// * `partitions` - The prefixes of the partitions
+/// * `list_concurrency` - The maximum number of concurrent list calls
+///
+/// # Returns
+///
+/// The files of all the partitions, in the order of the partitions.
+pub async fn list_partitions(
+    partition_lister: &(impl PartitionLister + Sync),
+    bucket_name: &str,
+    partitions: &[String],
+    list_concurrency: usize,
+) -> Result<Vec<S3ParquetFile>> {
+    // The partitions are owned by the stream, so that its future is not higher-ranked over
+    // their lifetime when awaited inside an async trait method
+    let files = stream::iter(partitions.to_vec())
+        .map(|partition| async move {
+            partition_lister
+                .list_partition(bucket_name, partition.as_str())
+                .await
+        })
+        .buffered(list_concurrency.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(files.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// A lister that counts the list calls in flight.
+    #[derive(Default)]
+    struct CountingPartitionLister {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl PartitionLister for CountingPartitionLister {
+        async fn list_partition(
+            &self,
+            _bucket_name: &str,
+            partition_prefix: &str,
+        ) -> Result<Vec<S3ParquetFile>> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(vec![S3ParquetFile::new(format!(
+                "{partition_prefix}20240301-000000000.parquet"
+            ))])
+        }
+    }
+
+    #[test]
+    fn test_day_partitions_across_months() {
+        let partitions = day_partitions(
+            "prefix/table",
+            NaiveDate::from_ymd_opt(2024, 2, 28).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            PathStyle::Plain,
+        );
+
+        assert_eq!(
+            partitions,
+            vec![
+                "prefix/table/2024/02/28/",
+                "prefix/table/2024/02/29/",
+                "prefix/table/2024/03/01/"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_partitions_respects_list_concurrency() {
+        let partition_lister = CountingPartitionLister::default();
+        let partitions = (1..=12)
+            .map(|day| format!("prefix/table/2024/03/{day:02}/"))
+            .collect::<Vec<_>>();
+
+        let files = list_partitions(&partition_lister, "bucket", &partitions, 3)
+            .await
+            .unwrap();
+
+        assert_eq!(partition_lister.calls.load(Ordering::SeqCst), 12);
+        assert!(partition_lister.max_in_flight.load(Ordering::SeqCst) <= 3);
+        assert!(partition_lister.max_in_flight.load(Ordering::SeqCst) > 1);
+        // The files keep the order of the partitions
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.file_name.as_str())
+                .collect::<Vec<_>>(),
+            partitions
+                .iter()
+                .map(|partition| format!("{partition}20240301-000000000.parquet"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_partitions_one_at_a_time() {
+        let partition_lister = CountingPartitionLister::default();
+        let partitions = vec!["a/".to_string(), "b/".to_string()];
+
+        list_partitions(&partition_lister, "bucket", &partitions, 0)
+            .await
+            .unwrap();
+
+        assert_eq!(partition_lister.max_in_flight.load(Ordering::SeqCst), 1);
+    }
+}
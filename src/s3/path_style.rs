@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+
+/// Represents how the date partitions of a table are rendered in the S3 paths.
+///
+/// The style can be one of the following:
+///
+/// * Plain - The partitions are rendered as `2024/03/01`, as written by DMS.
+/// * Hive - The partitions are rendered as `year=2024/month=03/day=01`, as written by some
+///   downstream tools.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    #[default]
+    Plain,
+    Hive,
+}
+
+impl PathStyle {
+    /// Gets the path of the partition of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix_path` - The prefix of the table
+    /// * `date` - The date of the partition
+    ///
+    /// # Returns
+    ///
+    /// The path of the partition, with a trailing slash, e.g. `prefix/2024/03/01/`.
+    pub fn date_path(&self, prefix_path: &str, date: NaiveDate) -> String {
+        match self {
+            PathStyle::Plain => format!("{}/{}/", prefix_path, date.format("%Y/%m/%d")),
+            PathStyle::Hive => format!(
+                "{}/{}/",
+                prefix_path,
+                date.format("year=%Y/month=%m/day=%d")
+            ),
+        }
+    }
+
+    /// Checks whether the LOAD files of a table sort after the date partitions, so that
+    /// a listing that starts after the partition of a date also finds them.
+    pub fn load_files_sort_after_partitions(&self) -> bool {
+        match self {
+            PathStyle::Plain => true,
+            PathStyle::Hive => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+    }
+
+    #[test]
+    fn test_plain_date_path() {
+        assert_eq!(
+            PathStyle::Plain.date_path("prefix/db/public/table", date()),
+            "prefix/db/public/table/2024/03/01/"
+        );
+    }
+
+    #[test]
+    fn test_hive_date_path() {
+        assert_eq!(
+            PathStyle::Hive.date_path("prefix/db/public/table", date()),
+            "prefix/db/public/table/year=2024/month=03/day=01/"
+        );
+    }
+
+    #[test]
+    fn test_date_path_anchors_listing() {
+        let prefix_path = "prefix/db/public/table";
+        for path_style in [PathStyle::Plain, PathStyle::Hive] {
+            let start_date_path = path_style.date_path(prefix_path, date());
+            let previous_day =
+                path_style.date_path(prefix_path, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+            let later_month =
+                path_style.date_path(prefix_path, NaiveDate::from_ymd_opt(2024, 11, 1).unwrap());
+
+            // The listing starts after the anchor, so the files of the start date
+            // and of the later dates are listed, while those of the previous days are not
+            let file = |date_path: &str| format!("{date_path}20240301-000000000.parquet");
+            assert!(file(&previous_day) < start_date_path);
+            assert!(file(&start_date_path) > start_date_path);
+            assert!(file(&later_month) > start_date_path);
+
+            let load_file = format!("{prefix_path}/LOAD00000001.parquet");
+            assert_eq!(
+                load_file > start_date_path,
+                path_style.load_files_sort_after_partitions()
+            );
+        }
+    }
+}
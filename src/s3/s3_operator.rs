@@ -1,9 +1,15 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use aws_sdk_s3::primitives::{ByteStream, DateTime, DateTimeFormat};
+use aws_sdk_s3::types::RequestPayer;
 use aws_sdk_s3::Client as S3Client;
-use chrono::{Datelike, NaiveDate};
+use chrono::NaiveDate;
 use log::{debug, info};
+use std::collections::HashMap;
+
+use super::manifest::{manifest_files, parse_manifest};
+use super::partition_listing::{day_partitions, list_partitions, PartitionLister};
+use super::path_style::PathStyle;
 
 #[cfg(test)]
 use mockall::automock;
@@ -17,6 +23,10 @@ pub enum LoadParquetFilesPayload {
         table_name: String,
         start_date: String,
         stop_date: Option<String>,
+        include_load_outside_window: bool,
+        partitioned_listing: bool,
+        list_concurrency: usize,
+        path_style: PathStyle,
     },
     FullLoadOnly {
         bucket_name: String,
@@ -26,20 +36,84 @@ pub enum LoadParquetFilesPayload {
         table_name: String,
     },
     AbsolutePath(String),
+    Manifest {
+        bucket_name: String,
+        manifest_key: String,
+        /// The prefix of the table, e.g. `<prefix>/<database>/<schema>/<table>/`, to which
+        /// the listed keys are restricted, as a manifest may list the files of several tables.
+        table_prefix: String,
+    },
+}
+
+impl LoadParquetFilesPayload {
+    /// Gets the bucket and the prefix of the table the files are listed from.
+    ///
+    /// # Returns
+    ///
+    /// The bucket and the prefix of the table, or None for an absolute path or a manifest.
+    pub fn table_location(&self) -> Option<(&str, String)> {
+        match self {
+            LoadParquetFilesPayload::DateAware {
+                bucket_name,
+                s3_prefix,
+                database_name,
+                schema_name,
+                table_name,
+                ..
+            }
+            | LoadParquetFilesPayload::FullLoadOnly {
+                bucket_name,
+                s3_prefix,
+                database_name,
+                schema_name,
+                table_name,
+            } => Some((
+                bucket_name.as_str(),
+                format!(
+                    "{}/{}/{}/{}/",
+                    s3_prefix, database_name, schema_name, table_name
+                ),
+            )),
+            LoadParquetFilesPayload::AbsolutePath(_) | LoadParquetFilesPayload::Manifest { .. } => {
+                None
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct S3ParquetFile {
     pub file_name: String,
+    pub last_modified: Option<DateTime>,
+    pub version_id: Option<String>,
 }
 
 impl S3ParquetFile {
     pub fn new(file_name: impl Into<String>) -> Self {
         Self {
             file_name: file_name.into(),
+            last_modified: None,
+            version_id: None,
+        }
+    }
+
+    pub fn new_with_last_modified(
+        file_name: impl Into<String>,
+        last_modified: Option<DateTime>,
+    ) -> Self {
+        Self {
+            file_name: file_name.into(),
+            last_modified,
+            version_id: None,
         }
     }
 
+    /// Pins the file to a version of the object, for versioned buckets.
+    pub fn with_version_id(mut self, version_id: Option<String>) -> Self {
+        self.version_id = version_id;
+        self
+    }
+
     pub fn is_load_file(&self) -> bool {
         self.file_name.contains("LOAD")
     }
@@ -49,6 +123,102 @@ impl S3ParquetFile {
     }
 }
 
+/// Checks whether a key is returned by a listing that starts after the given key, if any.
+///
+/// S3 lists the keys in lexicographic order, so only the keys that sort after the start key
+/// are returned, whatever their last modified date.
+///
+/// # Arguments
+///
+/// * `key` - The key of the object.
+/// * `start_after` - The key the listing starts after.
+///
+/// # Returns
+///
+/// Whether the key is listed.
+pub fn is_listed_after(key: &str, start_after: Option<&str>) -> bool {
+    start_after.is_none_or(|start_after| key > start_after)
+}
+
+/// Checks whether the LOAD files of a table need a listing of their own in a DateAware listing.
+///
+/// The listing that starts after the start date partition misses the LOAD files when they sort
+/// before the partitions, while a listing of the whole prefix already returns them.
+///
+/// # Arguments
+///
+/// * `start_after` - Whether the listing starts after the start date partition.
+/// * `path_style` - The path style of the date partitions.
+///
+/// # Returns
+///
+/// Whether the LOAD files are listed separately.
+pub fn lists_load_files_separately(start_after: bool, path_style: PathStyle) -> bool {
+    start_after && !path_style.load_files_sort_after_partitions()
+}
+
+/// Moves the LOAD files before the CDC files, keeping the listing order of each.
+///
+/// # Arguments
+///
+/// * `files` - The listed files.
+pub fn load_files_first(files: &mut [S3ParquetFile]) {
+    files.sort_by_key(|file| !file.is_load_file());
+}
+
+/// Pins the listed files to the given versions of their objects.
+///
+/// # Arguments
+///
+/// * `files` - The listed files.
+/// * `version_ids` - The version IDs of the objects, by key.
+///
+/// # Returns
+///
+/// The files, along with the version ID of their object, if any.
+pub fn with_version_ids(
+    files: Vec<S3ParquetFile>,
+    version_ids: &HashMap<String, String>,
+) -> Vec<S3ParquetFile> {
+    files
+        .into_iter()
+        .map(|file| {
+            let version_id = version_ids.get(&file.file_name).cloned();
+            file.with_version_id(version_id)
+        })
+        .collect()
+}
+
+/// Checks whether a file is included in the date window of a DateAware listing.
+///
+/// # Arguments
+///
+/// * `file` - The key of the file
+/// * `last_modified` - The last modified date of the file
+/// * `start_date` - The start date to include the files, exclusive
+/// * `stop_date` - The stop date to include the files, exclusive
+/// * `include_load_outside_window` - Whether the LOAD files are included regardless of their date
+///
+/// # Returns
+///
+/// Whether the file is included.
+pub fn is_file_in_date_window(
+    file: &str,
+    last_modified: &DateTime,
+    start_date: &DateTime,
+    stop_date: Option<&DateTime>,
+    include_load_outside_window: bool,
+) -> bool {
+    if include_load_outside_window && file.contains("LOAD") {
+        return true;
+    }
+
+    match stop_date {
+        Some(stop_date) => last_modified > start_date && last_modified < stop_date,
+        None => last_modified > start_date,
+    }
+}
+
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait S3Operator {
@@ -83,10 +253,12 @@ pub trait S3Operator {
     /// * `prefix_path` - The prefix path
     /// * `start_date` - The start date to include the files
     /// * `stop_date` - The stop date to include the files
+    /// * `include_load_outside_window` - Whether the LOAD files are included regardless of their date
     ///
     /// # Returns
     ///
     /// A list of files.
+    #[allow(clippy::too_many_arguments)]
     async fn get_files_from_s3_based_on_date(
         &self,
         bucket_name: &str,
@@ -95,6 +267,7 @@ pub trait S3Operator {
         prefix_path: &str,
         start_date: &DateTime,
         stop_date: Option<DateTime>,
+        include_load_outside_window: bool,
     ) -> Result<Vec<S3ParquetFile>>;
 
     /// Gets only the full load files from S3.
@@ -114,15 +287,267 @@ pub trait S3Operator {
         table_name: &str,
         prefix_path: &str,
     ) -> Result<Vec<S3ParquetFile>>;
+
+    /// Uploads an object to S3.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket
+    /// * `key` - The key of the object
+    /// * `body` - The content of the object
+    /// * `content_type` - The content type of the object
+    ///
+    /// # Returns
+    ///
+    /// A Result indicating success or failure.
+    async fn put_object(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<()>;
+
+    /// Lists the partition folders directly under a prefix, without recursing into them,
+    /// to explore the partition structure of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket
+    /// * `prefix` - The prefix of the folder to list
+    ///
+    /// # Returns
+    ///
+    /// The common prefixes of the folder, e.g. `data/mydb/public/users/2024/`, in order.
+    async fn list_partitions(&self, bucket_name: &str, prefix: &str) -> Result<Vec<String>>;
+}
+
+/// The delimiter of the folders of the S3 keys.
+pub const FOLDER_DELIMITER: &str = "/";
+
+/// Returns the prefix of a folder, i.e. ending with the folder delimiter, so that only the
+/// partitions inside it are listed rather than the sibling keys that share the prefix.
+///
+/// # Arguments
+///
+/// * `prefix` - The prefix of the folder, with or without a trailing delimiter.
+///
+/// # Returns
+///
+/// The prefix of the folder, which is empty for the root of the bucket.
+pub fn folder_prefix(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with(FOLDER_DELIMITER) {
+        prefix.to_string()
+    } else {
+        format!("{prefix}{FOLDER_DELIMITER}")
+    }
 }
 
 pub struct S3OperatorImpl<'a> {
     s3_client: &'a S3Client,
+    request_payer: Option<RequestPayer>,
+    object_versions: bool,
+    start_after: bool,
 }
 
 impl<'a> S3OperatorImpl<'a> {
     pub fn new(s3_client: &'a S3Client) -> Self {
-        Self { s3_client }
+        Self {
+            s3_client,
+            request_payer: None,
+            object_versions: false,
+            start_after: true,
+        }
+    }
+
+    /// Sets whether the listing of the files of a date range starts after the partition of its
+    /// start date, which skips the older partitions on the S3 side. The optimization assumes that
+    /// the keys sort like their dates, which does not hold for every layout, e.g. a late file in
+    /// the partition of an earlier date. When disabled, the whole prefix of the table is listed
+    /// and the files are only filtered by their last modified date.
+    pub fn with_start_after(mut self, start_after: bool) -> Self {
+        self.start_after = start_after;
+        self
+    }
+
+    /// Sets whether the listed files are pinned to the current version of their objects,
+    /// so that a re-run against a versioned bucket reads the exact same bytes.
+    pub fn with_object_versions(mut self, object_versions: bool) -> Self {
+        self.object_versions = object_versions;
+        self
+    }
+
+    /// Gets the version IDs of the latest versions of the objects under a prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket
+    /// * `prefix_path` - The prefix of the objects
+    ///
+    /// # Returns
+    ///
+    /// The version IDs of the latest versions, by key. Deleted objects are not included.
+    async fn get_latest_version_ids(
+        &self,
+        bucket_name: &str,
+        prefix_path: &str,
+    ) -> Result<HashMap<String, String>> {
+        let mut version_ids = HashMap::new();
+        let mut key_marker = None;
+        let mut version_id_marker = None;
+
+        loop {
+            let response = self
+                .s3_client
+                .list_object_versions()
+                .bucket(bucket_name)
+                .prefix(prefix_path)
+                .set_key_marker(key_marker.clone())
+                .set_version_id_marker(version_id_marker.clone())
+                .set_request_payer(self.request_payer.clone())
+                .send()
+                .await
+                .map_err(aws_sdk_s3::Error::from)?;
+
+            for version in response.versions.unwrap_or_default() {
+                if let (Some(key), Some(version_id), Some(true)) =
+                    (version.key, version.version_id, version.is_latest)
+                {
+                    version_ids.insert(key, version_id);
+                }
+            }
+
+            if !response.is_truncated.unwrap_or_default() {
+                break;
+            }
+            key_marker = response.next_key_marker;
+            version_id_marker = response.next_version_id_marker;
+        }
+
+        debug!(
+            "Object versions under {prefix_path}: {:?}",
+            version_ids.len()
+        );
+        Ok(version_ids)
+    }
+
+    /// Sets whether the requests are sent with the requester-pays header, which is required
+    /// to list requester-pays buckets. The caller is charged for the requests and the data transfer.
+    pub fn with_requester_pays(mut self, requester_pays: bool) -> Self {
+        self.request_payer = requester_pays.then_some(RequestPayer::Requester);
+        self
+    }
+
+    /// Gets the list of files from S3 by listing the daily partitions of the date range concurrently,
+    /// along with the LOAD files.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket
+    /// * `table_name` - The name of the table
+    /// * `prefix_path` - The prefix path of the table
+    /// * `start_date` - The start date to include the files
+    /// * `stop_date` - The stop date to include the files
+    /// * `include_load_outside_window` - Whether the LOAD files are included regardless of their date
+    /// * `list_concurrency` - The maximum number of concurrent list calls
+    /// * `path_style` - The style of the partition paths
+    ///
+    /// # Returns
+    ///
+    /// A list of files, with the LOAD files last.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_files_from_s3_partitions(
+        &self,
+        bucket_name: &str,
+        table_name: &str,
+        prefix_path: &str,
+        start_date: &DateTime,
+        stop_date: &DateTime,
+        include_load_outside_window: bool,
+        list_concurrency: usize,
+        path_style: PathStyle,
+    ) -> Result<Vec<S3ParquetFile>> {
+        let to_naive_date = |date: &DateTime| -> Result<NaiveDate> {
+            Ok(NaiveDate::parse_from_str(
+                date.fmt(DateTimeFormat::DateTimeWithOffset)?.as_str(),
+                "%Y-%m-%dT%H:%M:%SZ",
+            )?)
+        };
+        let partitions = day_partitions(
+            prefix_path,
+            to_naive_date(start_date)?,
+            to_naive_date(stop_date)?,
+            path_style,
+        );
+        debug!(
+            "Partitions to list for table {table_name}: {:?}",
+            partitions
+        );
+
+        let mut files = list_partitions(self, bucket_name, &partitions, list_concurrency).await?;
+        files.extend(
+            self.get_full_load_files_from_s3(bucket_name, table_name, prefix_path)
+                .await?,
+        );
+
+        let files = files
+            .into_iter()
+            .filter(|file| {
+                file.last_modified.is_some_and(|last_modified| {
+                    is_file_in_date_window(
+                        &file.file_name,
+                        &last_modified,
+                        start_date,
+                        Some(stop_date),
+                        include_load_outside_window,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        info!("Files to process for table {table_name}: {:?}", files.len());
+
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl PartitionLister for S3OperatorImpl<'_> {
+    async fn list_partition(
+        &self,
+        bucket_name: &str,
+        partition_prefix: &str,
+    ) -> Result<Vec<S3ParquetFile>> {
+        let mut files: Vec<S3ParquetFile> = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .s3_client
+                .list_objects_v2()
+                .bucket(bucket_name)
+                .prefix(partition_prefix)
+                .set_continuation_token(next_token.clone())
+                .set_request_payer(self.request_payer.clone())
+                .send()
+                .await
+                .map_err(aws_sdk_s3::Error::from)?;
+
+            next_token.clone_from(&response.next_continuation_token);
+
+            for object in response.contents.unwrap_or_default() {
+                let file = object.key.unwrap();
+                debug!("File: {:?}", file);
+                files.push(S3ParquetFile::new_with_last_modified(
+                    file,
+                    object.last_modified,
+                ));
+            }
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(files)
     }
 }
 
@@ -141,17 +566,18 @@ impl S3Operator for S3OperatorImpl<'_> {
                 table_name,
                 start_date,
                 stop_date,
+                include_load_outside_window,
+                partitioned_listing,
+                list_concurrency,
+                path_style,
             } => {
                 let iter_start_date =
                     NaiveDate::parse_from_str(start_date.as_str(), "%Y-%m-%dT%H:%M:%SZ")?;
-                let year = iter_start_date.year();
-                let month = format!("{:02}", iter_start_date.month());
-                let day = format!("{:02}", iter_start_date.day());
                 let prefix_path = format!(
                     "{}/{}/{}/{}",
                     s3_prefix, database_name, schema_name, table_name
                 );
-                let start_date_path = format!("{}/{}/{}/{}/", prefix_path, year, month, day);
+                let start_date_path = path_style.date_path(&prefix_path, iter_start_date);
 
                 let start_date =
                     DateTime::from_str(start_date.as_str(), DateTimeFormat::DateTimeWithOffset)?;
@@ -164,21 +590,63 @@ impl S3Operator for S3OperatorImpl<'_> {
                     )?)
                 };
 
-                let mut files_list: Vec<S3ParquetFile> = self
-                    .get_files_from_s3_based_on_date(
-                        bucket_name.as_str(),
-                        table_name.as_str(),
-                        start_date_path.as_str(),
-                        format!("{}/", prefix_path).as_str(),
-                        &start_date,
-                        stop_date,
-                    )
-                    .await?;
+                let mut files_list: Vec<S3ParquetFile> = match stop_date {
+                    // The bounded date range is listed partition by partition, concurrently
+                    Some(stop_date) if *partitioned_listing => {
+                        self.get_files_from_s3_partitions(
+                            bucket_name.as_str(),
+                            table_name.as_str(),
+                            prefix_path.as_str(),
+                            &start_date,
+                            &stop_date,
+                            *include_load_outside_window,
+                            *list_concurrency,
+                            *path_style,
+                        )
+                        .await?
+                    }
+                    _ => {
+                        let mut files = self
+                            .get_files_from_s3_based_on_date(
+                                bucket_name.as_str(),
+                                table_name.as_str(),
+                                start_date_path.as_str(),
+                                format!("{}/", prefix_path).as_str(),
+                                &start_date,
+                                stop_date,
+                                *include_load_outside_window,
+                            )
+                            .await?;
 
-                // We want to process the LOAD files first in INSERT mode, so we rotate the list,
+                        // The LOAD files sort before the hive partitions, so the listing that
+                        // starts after the start date partition misses them
+                        if lists_load_files_separately(self.start_after, *path_style) {
+                            let load_files = self
+                                .get_full_load_files_from_s3(
+                                    bucket_name.as_str(),
+                                    table_name.as_str(),
+                                    prefix_path.as_str(),
+                                )
+                                .await?;
+                            files.extend(load_files.into_iter().filter(|file| {
+                                file.last_modified.is_some_and(|last_modified| {
+                                    is_file_in_date_window(
+                                        &file.file_name,
+                                        &last_modified,
+                                        &start_date,
+                                        stop_date.as_ref(),
+                                        *include_load_outside_window,
+                                    )
+                                })
+                            }));
+                        }
+                        files
+                    }
+                };
+
+                // We want to process the LOAD files first in INSERT mode,
                 // Then, we will process the rest CDC files in UPSERT mode.
-                let load_files_count = files_list.iter().filter(|s| s.is_load_file()).count();
-                files_list.rotate_right(load_files_count);
+                load_files_first(&mut files_list);
                 files_list
             }
             LoadParquetFilesPayload::FullLoadOnly {
@@ -200,9 +668,43 @@ impl S3Operator for S3OperatorImpl<'_> {
             LoadParquetFilesPayload::AbsolutePath(absolute_path) => {
                 vec![S3ParquetFile::new(absolute_path.to_string())]
             }
+            LoadParquetFilesPayload::Manifest {
+                bucket_name,
+                manifest_key,
+                table_prefix,
+            } => {
+                let response = self
+                    .s3_client
+                    .get_object()
+                    .bucket(bucket_name)
+                    .key(manifest_key)
+                    .set_request_payer(self.request_payer.clone())
+                    .send()
+                    .await
+                    .map_err(aws_sdk_s3::Error::from)?;
+                let content = response.body.collect().await?.into_bytes();
+                let keys = parse_manifest(std::str::from_utf8(&content)?)?;
+                info!(
+                    "Found {} files in manifest s3://{}/{}",
+                    keys.len(),
+                    bucket_name,
+                    manifest_key
+                );
+
+                // The LOAD files are processed first in INSERT mode, as for the other modes
+                manifest_files(keys, table_prefix)
+            }
         };
 
-        Ok(parquet_files)
+        match s3_parquet_file_load_key.table_location() {
+            Some((bucket_name, prefix_path)) if self.object_versions => {
+                let version_ids = self
+                    .get_latest_version_ids(bucket_name, prefix_path.as_str())
+                    .await?;
+                Ok(with_version_ids(parquet_files, &version_ids))
+            }
+            _ => Ok(parquet_files),
+        }
     }
 
     async fn get_files_from_s3_based_on_date(
@@ -213,8 +715,9 @@ impl S3Operator for S3OperatorImpl<'_> {
         prefix_path: &str,
         start_date: &DateTime,
         stop_date: Option<DateTime>,
+        include_load_outside_window: bool,
     ) -> Result<Vec<S3ParquetFile>> {
-        let mut files: Vec<String> = Vec::new();
+        let mut files: Vec<S3ParquetFile> = Vec::new();
         let mut next_token = None;
 
         loop {
@@ -222,8 +725,9 @@ impl S3Operator for S3OperatorImpl<'_> {
                 .s3_client
                 .list_objects_v2()
                 .bucket(bucket_name)
-                .start_after(start_date_path)
-                .prefix(prefix_path);
+                .set_start_after(self.start_after.then(|| start_date_path.to_string()))
+                .prefix(prefix_path)
+                .set_request_payer(self.request_payer.clone());
 
             let response = if next_token.is_some() {
                 builder
@@ -246,16 +750,18 @@ impl S3Operator for S3OperatorImpl<'_> {
                     let file = object.key.unwrap();
                     // Filter files based on last modified date
                     if let Some(last_modified) = object.last_modified {
-                        if let Some(stop_date) = stop_date {
-                            if (last_modified > *start_date && last_modified < stop_date)
-                                || file.contains("LOAD")
-                            {
-                                debug!("File: {:?}", file);
-                                files.push(file);
-                            }
-                        } else if last_modified > *start_date || file.contains("LOAD") {
+                        if is_file_in_date_window(
+                            &file,
+                            &last_modified,
+                            start_date,
+                            stop_date.as_ref(),
+                            include_load_outside_window,
+                        ) {
                             debug!("File: {:?}", file);
-                            files.push(file);
+                            files.push(S3ParquetFile::new_with_last_modified(
+                                file,
+                                Some(last_modified),
+                            ));
                         }
                     }
                 }
@@ -266,11 +772,6 @@ impl S3Operator for S3OperatorImpl<'_> {
             }
         }
 
-        let files = files
-            .iter()
-            .map(|f| S3ParquetFile::new(f.to_string()))
-            .collect::<Vec<_>>();
-
         Ok(files)
     }
 
@@ -280,7 +781,7 @@ impl S3Operator for S3OperatorImpl<'_> {
         table_name: &str,
         prefix_path: &str,
     ) -> Result<Vec<S3ParquetFile>> {
-        let mut files: Vec<String> = Vec::new();
+        let mut files: Vec<S3ParquetFile> = Vec::new();
 
         // The maximum no of keys returned is 1000,
         // so we don't need to paginate with next_token
@@ -290,7 +791,8 @@ impl S3Operator for S3OperatorImpl<'_> {
             .s3_client
             .list_objects_v2()
             .bucket(bucket_name)
-            .prefix(format!("{}/LOAD", prefix_path));
+            .prefix(format!("{}/LOAD", prefix_path))
+            .set_request_payer(self.request_payer.clone());
 
         let response = builder
             .to_owned()
@@ -302,15 +804,70 @@ impl S3Operator for S3OperatorImpl<'_> {
             for object in contents.clone() {
                 let file = object.key.unwrap();
                 debug!("File: {:?}", file);
-                files.push(file);
+                files.push(S3ParquetFile::new_with_last_modified(
+                    file,
+                    object.last_modified,
+                ));
             }
         }
-        let files = files
-            .iter()
-            .map(|f| S3ParquetFile::new(f.to_string()))
-            .collect::<Vec<_>>();
 
         info!("Files to process for table {table_name}: {:?}", files.len());
         Ok(files)
     }
+
+    async fn put_object(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<()> {
+        self.s3_client
+            .put_object()
+            .bucket(bucket_name)
+            .key(key)
+            .body(ByteStream::from(body))
+            .content_type(content_type)
+            .set_request_payer(self.request_payer.clone())
+            .send()
+            .await
+            .map_err(aws_sdk_s3::Error::from)?;
+
+        Ok(())
+    }
+
+    async fn list_partitions(&self, bucket_name: &str, prefix: &str) -> Result<Vec<String>> {
+        let mut partitions: Vec<String> = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .s3_client
+                .list_objects_v2()
+                .bucket(bucket_name)
+                .prefix(folder_prefix(prefix))
+                .delimiter(FOLDER_DELIMITER)
+                .set_continuation_token(next_token.clone())
+                .set_request_payer(self.request_payer.clone())
+                .send()
+                .await
+                .map_err(aws_sdk_s3::Error::from)?;
+
+            next_token.clone_from(&response.next_continuation_token);
+
+            partitions.extend(
+                response
+                    .common_prefixes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|common_prefix| common_prefix.prefix),
+            );
+            if next_token.is_none() {
+                break;
+            }
+        }
+        debug!("Partitions under {prefix}: {:?}", partitions);
+
+        Ok(partitions)
+    }
 }
@@ -1,5 +1,12 @@
 #[cfg(test)]
 mod tests {
+    use crate::s3::path_style::PathStyle;
+    use crate::s3::s3_operator::folder_prefix;
+    use crate::s3::s3_operator::is_file_in_date_window;
+    use crate::s3::s3_operator::is_listed_after;
+    use crate::s3::s3_operator::lists_load_files_separately;
+    use crate::s3::s3_operator::load_files_first;
+    use crate::s3::s3_operator::with_version_ids;
     use crate::s3::s3_operator::LoadParquetFilesPayload;
     use crate::s3::s3_operator::MockS3Operator;
     use crate::s3::s3_operator::S3Operator;
@@ -34,6 +41,10 @@ mod tests {
             table_name,
             start_date,
             stop_date,
+            include_load_outside_window: true,
+            partitioned_listing: false,
+            list_concurrency: 1,
+            path_style: PathStyle::Plain,
         };
 
         let files = s3_operator
@@ -50,7 +61,7 @@ mod tests {
 
         s3_operator
             .expect_get_files_from_s3_based_on_date()
-            .returning(|_, _, _, _, _, _| Ok(vec![S3ParquetFile::new("file1")]));
+            .returning(|_, _, _, _, _, _, _| Ok(vec![S3ParquetFile::new("file1")]));
 
         let bucket_name = "bucket_name".to_string();
         let table_name = "table_name".to_string();
@@ -70,10 +81,241 @@ mod tests {
                 &prefix_path,
                 &start_date,
                 stop_date,
+                true,
             )
             .await
             .unwrap();
 
         assert_eq!(files.len(), 1);
     }
+
+    fn date_time(date: &str) -> DateTime {
+        DateTime::from_str(date, DateTimeFormat::DateTimeWithOffset).unwrap()
+    }
+
+    #[test]
+    fn test_old_load_file_with_narrow_window() {
+        let start_date = date_time("2024-06-01T00:00:00Z");
+        let stop_date = date_time("2024-06-02T00:00:00Z");
+        let old_load = (
+            "prefix/table_name/LOAD00000001.parquet",
+            date_time("2024-01-01T00:00:00Z"),
+        );
+        let old_cdc = (
+            "prefix/table_name/2024/01/01/20240101-000000000.parquet",
+            date_time("2024-01-01T00:00:00Z"),
+        );
+        let recent_cdc = (
+            "prefix/table_name/2024/06/01/20240601-120000000.parquet",
+            date_time("2024-06-01T12:00:00Z"),
+        );
+
+        let included = |include_load_outside_window: bool| {
+            [&old_load, &old_cdc, &recent_cdc]
+                .into_iter()
+                .filter(|(file, last_modified)| {
+                    is_file_in_date_window(
+                        file,
+                        last_modified,
+                        &start_date,
+                        Some(&stop_date),
+                        include_load_outside_window,
+                    )
+                })
+                .map(|(file, _)| *file)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(included(true), vec![old_load.0, recent_cdc.0]);
+        assert_eq!(included(false), vec![recent_cdc.0]);
+    }
+
+    #[test]
+    fn test_listing_without_start_after_finds_late_file() {
+        let start_date = date_time("2024-06-01T00:00:00Z");
+        let start_date_path = "prefix/table_name/2024/06/01/";
+        // A file written late in the partition of the previous day
+        let late_cdc = (
+            "prefix/table_name/2024/05/31/20240601-000500000.parquet",
+            date_time("2024-06-01T00:05:00Z"),
+        );
+        let recent_cdc = (
+            "prefix/table_name/2024/06/01/20240601-120000000.parquet",
+            date_time("2024-06-01T12:00:00Z"),
+        );
+
+        let listed = |start_after: Option<&str>| {
+            [&late_cdc, &recent_cdc]
+                .into_iter()
+                .filter(|(file, _)| is_listed_after(file, start_after))
+                .filter(|(file, last_modified)| {
+                    is_file_in_date_window(file, last_modified, &start_date, None, true)
+                })
+                .map(|(file, _)| *file)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(listed(Some(start_date_path)), vec![recent_cdc.0]);
+        assert_eq!(listed(None), vec![late_cdc.0, recent_cdc.0]);
+    }
+
+    #[test]
+    fn test_hive_listing_without_start_after_keeps_load_files_once() {
+        let keys = [
+            "prefix/table_name/LOAD00000001.parquet",
+            "prefix/table_name/LOAD00000002.parquet",
+            "prefix/table_name/year=2024/month=06/day=01/20240601-120000000.parquet",
+            "prefix/table_name/year=2024/month=06/day=02/20240602-120000000.parquet",
+        ];
+
+        // The listing of the whole prefix already returns the LOAD files
+        let mut files = keys
+            .into_iter()
+            .filter(|key| is_listed_after(key, None))
+            .map(S3ParquetFile::new)
+            .collect::<Vec<_>>();
+        assert!(!lists_load_files_separately(false, PathStyle::Hive));
+        assert!(lists_load_files_separately(true, PathStyle::Hive));
+        assert!(!lists_load_files_separately(true, PathStyle::Plain));
+
+        load_files_first(&mut files);
+
+        let file_names = files
+            .iter()
+            .map(|file| file.file_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(file_names, keys);
+    }
+
+    #[test]
+    fn test_load_files_first() {
+        let mut files = vec![
+            S3ParquetFile::new("prefix/table_name/2024/06/01/20240601-120000000.parquet"),
+            S3ParquetFile::new("prefix/table_name/2024/06/02/20240602-120000000.parquet"),
+            S3ParquetFile::new("prefix/table_name/LOAD00000001.parquet"),
+            S3ParquetFile::new("prefix/table_name/LOAD00000002.parquet"),
+        ];
+
+        load_files_first(&mut files);
+
+        let file_names = files
+            .iter()
+            .map(|file| file.file_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            file_names,
+            vec![
+                "prefix/table_name/LOAD00000001.parquet",
+                "prefix/table_name/LOAD00000002.parquet",
+                "prefix/table_name/2024/06/01/20240601-120000000.parquet",
+                "prefix/table_name/2024/06/02/20240602-120000000.parquet",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_file_without_stop_date() {
+        let start_date = date_time("2024-06-01T00:00:00Z");
+        let old_load = date_time("2024-01-01T00:00:00Z");
+
+        assert!(is_file_in_date_window(
+            "LOAD00000001.parquet",
+            &old_load,
+            &start_date,
+            None,
+            true
+        ));
+        assert!(!is_file_in_date_window(
+            "LOAD00000001.parquet",
+            &old_load,
+            &start_date,
+            None,
+            false
+        ));
+    }
+
+    #[test]
+    fn test_with_version_ids() {
+        let files = vec![
+            S3ParquetFile::new("prefix/db/public/users/LOAD00000001.parquet"),
+            S3ParquetFile::new("prefix/db/public/users/20240101-000000000.parquet"),
+        ];
+        let version_ids = std::collections::HashMap::from([(
+            "prefix/db/public/users/LOAD00000001.parquet".to_string(),
+            "v1".to_string(),
+        )]);
+
+        let files = with_version_ids(files, &version_ids);
+
+        assert_eq!(files[0].version_id.as_deref(), Some("v1"));
+        assert_eq!(files[1].version_id, None);
+    }
+
+    #[test]
+    fn test_table_location() {
+        let payload = LoadParquetFilesPayload::FullLoadOnly {
+            bucket_name: "bucket_name".to_string(),
+            s3_prefix: "prefix".to_string(),
+            database_name: "db".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "users".to_string(),
+        };
+        assert_eq!(
+            payload.table_location(),
+            Some(("bucket_name", "prefix/db/public/users/".to_string()))
+        );
+        assert_eq!(
+            LoadParquetFilesPayload::AbsolutePath("key".to_string()).table_location(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_folder_prefix() {
+        assert_eq!(folder_prefix("data/users"), "data/users/");
+        assert_eq!(folder_prefix("data/users/"), "data/users/");
+        assert_eq!(folder_prefix(""), "");
+    }
+
+    #[tokio::test]
+    async fn test_list_partitions_of_nested_keys() {
+        let mut s3_operator = MockS3Operator::new();
+
+        s3_operator
+            .expect_list_partitions()
+            .withf(|bucket_name, prefix| {
+                bucket_name == "bucket_name" && prefix == "data/mydb/public/users/"
+            })
+            .returning(|_, prefix| Ok(vec![format!("{prefix}2024/"), format!("{prefix}2025/")]));
+        s3_operator
+            .expect_list_partitions()
+            .withf(|bucket_name, prefix| {
+                bucket_name == "bucket_name" && prefix == "data/mydb/public/users/2024/"
+            })
+            .returning(|_, prefix| Ok(vec![format!("{prefix}01/"), format!("{prefix}02/")]));
+
+        let years = s3_operator
+            .list_partitions("bucket_name", "data/mydb/public/users/")
+            .await
+            .unwrap();
+        assert_eq!(
+            years,
+            vec![
+                "data/mydb/public/users/2024/",
+                "data/mydb/public/users/2025/"
+            ]
+        );
+
+        let months = s3_operator
+            .list_partitions("bucket_name", &years[0])
+            .await
+            .unwrap();
+        assert_eq!(
+            months,
+            vec![
+                "data/mydb/public/users/2024/01/",
+                "data/mydb/public/users/2024/02/"
+            ]
+        );
+    }
 }
@@ -0,0 +1,241 @@
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the server-side encryption expected for the Parquet files of DMS.
+///
+/// The mode can be one of the following:
+///
+/// * Any - No encryption is expected.
+/// * SseS3 - The files are encrypted with keys managed by S3.
+/// * SseKms - The files are encrypted with a KMS key, whose key policy must allow the caller
+///   to decrypt them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ServerSideEncryptionMode {
+    #[default]
+    Any,
+    SseS3,
+    SseKms,
+}
+
+/// Represents a failure to read an object from S3 due to its encryption or its permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum S3ObjectError {
+    KmsAccessDenied {
+        bucket_name: String,
+        key: String,
+        code: String,
+        message: String,
+    },
+    AccessDenied {
+        bucket_name: String,
+        key: String,
+        message: String,
+    },
+}
+
+impl Display for S3ObjectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            S3ObjectError::KmsAccessDenied {
+                bucket_name,
+                key,
+                code,
+                message,
+            } => write!(
+                f,
+                "Access denied to s3://{bucket_name}/{key}, which is encrypted with SSE-KMS: \
+                the key must be enabled and its key policy must allow kms:Decrypt for the caller \
+                ({code}: {message})"
+            ),
+            S3ObjectError::AccessDenied {
+                bucket_name,
+                key,
+                message,
+            } => write!(
+                f,
+                "Access denied to s3://{bucket_name}/{key} (AccessDenied: {message})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for S3ObjectError {}
+
+impl S3ObjectError {
+    /// Maps the error code of a failed GET of an object to a descriptive error.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_name` - The name of the S3 bucket.
+    /// * `key` - The key of the object.
+    /// * `expected_encryption` - The server-side encryption expected for the object.
+    /// * `code` - The error code returned by S3.
+    /// * `message` - The error message returned by S3.
+    ///
+    /// # Returns
+    ///
+    /// The descriptive error, or None if the error is not related to encryption or permissions.
+    pub fn from_code(
+        bucket_name: &str,
+        key: &str,
+        expected_encryption: ServerSideEncryptionMode,
+        code: Option<&str>,
+        message: Option<&str>,
+    ) -> Option<Self> {
+        let code = code?;
+        let message = message.unwrap_or_default().to_string();
+
+        let is_kms_error = code.starts_with("KMS.");
+        let is_access_denied = code == "AccessDenied";
+
+        if is_kms_error
+            || (is_access_denied && expected_encryption == ServerSideEncryptionMode::SseKms)
+        {
+            Some(S3ObjectError::KmsAccessDenied {
+                bucket_name: bucket_name.to_string(),
+                key: key.to_string(),
+                code: code.to_string(),
+                message,
+            })
+        } else if is_access_denied {
+            Some(S3ObjectError::AccessDenied {
+                bucket_name: bucket_name.to_string(),
+                key: key.to_string(),
+                message,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Maps the error of a failed GET of an object, keeping the original error
+/// if it is not related to encryption or permissions.
+///
+/// # Arguments
+///
+/// * `error` - The error of the GET request.
+/// * `bucket_name` - The name of the S3 bucket.
+/// * `key` - The key of the object.
+/// * `expected_encryption` - The server-side encryption expected for the object.
+///
+/// # Returns
+///
+/// The mapped error.
+pub fn map_get_object_error<R>(
+    error: SdkError<GetObjectError, R>,
+    bucket_name: &str,
+    key: &str,
+    expected_encryption: ServerSideEncryptionMode,
+) -> anyhow::Error
+where
+    R: std::fmt::Debug + Send + Sync + 'static,
+{
+    let s3_object_error = error.as_service_error().and_then(|service_error| {
+        S3ObjectError::from_code(
+            bucket_name,
+            key,
+            expected_encryption,
+            service_error.code(),
+            service_error.message(),
+        )
+    });
+
+    match s3_object_error {
+        Some(s3_object_error) => s3_object_error.into(),
+        None => aws_sdk_s3::Error::from(error).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_code(
+        expected_encryption: ServerSideEncryptionMode,
+        code: &str,
+    ) -> Option<S3ObjectError> {
+        S3ObjectError::from_code(
+            "bucket",
+            "prefix/LOAD00000001.parquet",
+            expected_encryption,
+            Some(code),
+            Some("denied"),
+        )
+    }
+
+    #[test]
+    fn test_kms_disabled_is_mapped_regardless_of_expected_encryption() {
+        for expected_encryption in [
+            ServerSideEncryptionMode::Any,
+            ServerSideEncryptionMode::SseS3,
+            ServerSideEncryptionMode::SseKms,
+        ] {
+            assert_eq!(
+                from_code(expected_encryption, "KMS.DisabledException"),
+                Some(S3ObjectError::KmsAccessDenied {
+                    bucket_name: "bucket".to_string(),
+                    key: "prefix/LOAD00000001.parquet".to_string(),
+                    code: "KMS.DisabledException".to_string(),
+                    message: "denied".to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_access_denied_is_mapped_to_kms_when_expected() {
+        let error = from_code(ServerSideEncryptionMode::SseKms, "AccessDenied").unwrap();
+
+        assert!(matches!(error, S3ObjectError::KmsAccessDenied { .. }));
+        assert_eq!(
+            error.to_string(),
+            "Access denied to s3://bucket/prefix/LOAD00000001.parquet, which is encrypted with SSE-KMS: \
+            the key must be enabled and its key policy must allow kms:Decrypt for the caller \
+            (AccessDenied: denied)"
+        );
+    }
+
+    #[test]
+    fn test_access_denied_without_kms() {
+        assert_eq!(
+            from_code(ServerSideEncryptionMode::Any, "AccessDenied"),
+            Some(S3ObjectError::AccessDenied {
+                bucket_name: "bucket".to_string(),
+                key: "prefix/LOAD00000001.parquet".to_string(),
+                message: "denied".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_other_errors_are_not_mapped() {
+        assert_eq!(
+            from_code(ServerSideEncryptionMode::SseKms, "NoSuchKey"),
+            None
+        );
+        assert_eq!(
+            S3ObjectError::from_code(
+                "bucket",
+                "key",
+                ServerSideEncryptionMode::SseKms,
+                None,
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mapped_error_can_be_downcast() {
+        let error: anyhow::Error = from_code(ServerSideEncryptionMode::SseKms, "AccessDenied")
+            .unwrap()
+            .into();
+
+        assert!(matches!(
+            error.downcast_ref::<S3ObjectError>(),
+            Some(S3ObjectError::KmsAccessDenied { .. })
+        ));
+    }
+}
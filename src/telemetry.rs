@@ -0,0 +1,137 @@
+/// Creates the span of an operation, e.g. the read of a Parquet file, with the given attributes.
+///
+/// The spans are only created with the `otel` feature, e.g. to be exported to OpenTelemetry by a
+/// `tracing` subscriber of the application. Without it, a disabled span is returned, so that
+/// entering it or recording an attribute on it is a no-op.
+#[cfg(feature = "otel")]
+macro_rules! operation_span {
+    ($name:literal, $($fields:tt)*) => {
+        tracing::info_span!($name, $($fields)*)
+    };
+}
+
+#[cfg(not(feature = "otel"))]
+macro_rules! operation_span {
+    ($name:literal, $($fields:tt)*) => {
+        tracing::Span::none()
+    };
+}
+
+pub(crate) use operation_span;
+
+/// Records the spans created while it is the default subscriber, along with their attributes
+/// and their parent, to check the emitted spans in tests.
+#[cfg(all(test, feature = "otel"))]
+pub(crate) mod span_recorder {
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, Layer};
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Represents a recorded span.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct RecordedSpan {
+        pub name: String,
+        pub parent: Option<String>,
+        pub fields: HashMap<String, String>,
+    }
+
+    #[derive(Clone, Default)]
+    pub struct SpanRecorder(Arc<Mutex<Vec<(u64, RecordedSpan)>>>);
+
+    impl SpanRecorder {
+        /// Gets the recorded spans, in the order they were created.
+        pub fn spans(&self) -> Vec<RecordedSpan> {
+            let spans = self.0.lock().unwrap();
+            spans.iter().map(|(_, span)| span.clone()).collect()
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S> Layer<S> for SpanRecorder
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut span = RecordedSpan {
+                name: attributes.metadata().name().to_string(),
+                parent: ctx
+                    .span(id)
+                    .and_then(|span| span.parent())
+                    .map(|parent| parent.name().to_string()),
+                fields: HashMap::new(),
+            };
+            attributes.record(&mut FieldVisitor(&mut span.fields));
+            self.0.lock().unwrap().push((id.into_u64(), span));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, span)) = spans
+                .iter_mut()
+                .rev()
+                .find(|(span_id, _)| *span_id == id.into_u64())
+            {
+                values.record(&mut FieldVisitor(&mut span.fields));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "otel"))]
+    #[test]
+    fn test_operation_span_is_disabled_without_otel_feature() {
+        let span = operation_span!("apply_file", key = "key", rows = 3usize);
+
+        assert!(span.is_disabled());
+        span.record("rows", 4usize);
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_operation_span_is_emitted_with_otel_feature() {
+        use super::span_recorder::SpanRecorder;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = SpanRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let table_span = operation_span!("snapshot_table", table = "users");
+            let _entered = table_span.enter();
+            let file_span = operation_span!(
+                "apply_file",
+                key = "users/LOAD00000001.parquet",
+                rows = tracing::field::Empty
+            );
+            file_span.record("rows", 3usize);
+        });
+
+        let spans = recorder.spans();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].name, "snapshot_table");
+        assert_eq!(spans[0].fields["table"], "users");
+        assert_eq!(spans[1].name, "apply_file");
+        assert_eq!(spans[1].parent.as_deref(), Some("snapshot_table"));
+        assert_eq!(spans[1].fields["key"], "users/LOAD00000001.parquet");
+        assert_eq!(spans[1].fields["rows"], "3");
+    }
+}